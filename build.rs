@@ -0,0 +1,29 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    #[cfg(feature = "grpc")]
+    grpc_service();
+}
+
+/// Server side of the `Analyzer` service in proto/analyzer.proto, generated without protoc:
+/// the messages are written by hand in src/grpc.rs
+#[cfg(feature = "grpc")]
+fn grpc_service() {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    let method = |name: &str, route_name: &str, input_type: &str| {
+        Method::builder()
+            .name(name)
+            .route_name(route_name)
+            .input_type(input_type)
+            .output_type("super::AnalysisReply")
+            .codec_path("tonic::codec::ProstCodec")
+            .build()
+    };
+    let service = Service::builder()
+        .name("Analyzer")
+        .package("abitur.v1")
+        .method(method("analyze", "Analyze", "super::AnalyzeRequest"))
+        .method(method("get_status", "GetStatus", "super::StatusRequest"))
+        .build();
+    Builder::new().build_client(false).compile(&[service]);
+}
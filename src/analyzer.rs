@@ -1,53 +1,171 @@
-use crate::models::{StudentRecord, normalize_snils, ApplicantApplication, EagerApplicant};
-use std::collections::HashMap;
+use crate::confidence::Confidence;
+use crate::models::{StudentRecord, normalize_snils, parse_score, ApplicantApplication, CutoffTies, EagerApplicant, FundingSource, ProgramKey, TieBreaker};
+use crate::observer::{SimulationObserver, TargetTrace};
+use crate::progress;
+use crate::report_model::{ProgramReport, ReportModel, ReportRow};
+use crate::scoring;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 
-#[derive(Debug, Clone)]
+/// Competition metrics of one program-funding combination
+#[derive(Debug, Clone, Serialize)]
 pub struct ProgramPopularity {
     pub program_name: String,
-    pub program_key: String, // program_name + funding_source for uniqueness
-    pub funding_source: String,
+    pub program_key: ProgramKey,
+    // Institution of the lists, empty with a single one
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub institution: String,
+    pub funding_source: FundingSource,
     pub top_candidates_average_priority: f64,
     pub average_score: f64,
     pub available_places: u32,
+    // Places the list shows when seat_overrides set `available_places` to another number
+    pub published_places: Option<u32>,
     pub total_eager_applicants: usize,
     pub eager_applicants: Vec<StudentRecord>,
 }
 
-#[derive(Debug, Clone)]
+/// Result of the admission simulation over all programs
+#[derive(Debug, Clone, Serialize)]
 pub struct AdmissionAnalysis {
     pub program_popularities: Vec<ProgramPopularity>,
-    pub final_admission_results: HashMap<String, Vec<String>>, // program_key -> admitted SNILSes
+    pub final_admission_results: BTreeMap<ProgramKey, Vec<String>>, // admitted SNILSes in admission order
 }
+
+/// Outcome of the simulation for the target applicant in one program-funding combination
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetOutcome {
+    pub program_key: ProgramKey,
+    pub program_name: String,
+    pub institution: String, // empty with a single institution
+    pub funding_source: FundingSource,
+    pub available_places: u32,
+    pub published_places: Option<u32>, // places the list shows, if seat_overrides changed them
+    pub priority: Option<u32>, // None if target did not apply
+    pub target_score: Option<f64>,
+    pub target_bonus: f64, // achievement points included in target_score
+    pub cutoff_score: f64,
+    pub cutoff_ties: usize, // eager applicants with exactly the cutoff score, admitted or not
+    pub projected_cutoff_score: Option<f64>, // cutoff if consent-only applicants were enrolled too, with originals_only
+    pub quota_places_returned: Option<u32>, // unfilled quota places the combination receives on campaign.quota_transfer
+    pub transfer_cutoff_score: Option<f64>, // cutoff once unfilled quota places have gone to the budget competitions
+    pub position_in_admitted: Option<usize>, // 1-based position if admitted
+    pub admitted_count: usize,
+    pub status: String,
+    // How far the outcome can be trusted given the quality of the lists, once assessed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<Confidence>,
+}
+
+/// How close the target applicant is to a seat in one program-funding combination
+#[derive(Debug, Clone, Serialize)]
+pub struct AdmissionDistance {
+    pub program_key: ProgramKey,
+    pub program_name: String,
+    pub funding_source: FundingSource,
+    pub priority: u32,
+    pub available_places: u32,
+    pub published_places: Option<u32>, // places the list shows, if seat_overrides changed them
+    pub position: usize, // 1-based position among unblocked eager applicants, target included
+    pub unblocked_eager: usize, // unblocked eager applicants other than the target
+    pub seats_remaining: i64, // places left when the target's turn comes; <= 0 means no seat
+}
+
+/// Tie breakers of `cutoff_ties = "secondary"` when none are configured
+pub const DEFAULT_TIE_BREAKERS: [TieBreaker; 3] = [TieBreaker::SubjectSum, TieBreaker::Achievements, TieBreaker::Original];
     
+/// Simulates priority-based admission and reports the outcome for the target applicant
+#[derive(Debug, Clone)]
 pub struct AdmissionAnalyzer<'a> {
     pub target_snils: &'a str,
+    // Only applicants with original documents are enrolled, consent alone is not enough
+    pub originals_only: bool,
+    pub cutoff_ties: CutoffTies,
+    // Order of equal scores with CutoffTies::Secondary
+    pub tie_breakers: Vec<TieBreaker>,
+    // Program key -> places to simulate instead of those the list shows
+    pub seat_overrides: BTreeMap<String, u32>,
 }
 
 impl<'a> AdmissionAnalyzer<'a> {
     pub fn new(target_snils: &'a str) -> Self {
         Self {
             target_snils, 
+            originals_only: false,
+            cutoff_ties: CutoffTies::Strict,
+            tie_breakers: Vec::new(),
+            seat_overrides: BTreeMap::new(),
         }
     }
 
+    /// Simulate the programs in `seat_overrides`, by program key as in the reports, with
+    /// the given number of places, e.g. extra seats announced but not yet on the page
+    pub fn with_seat_overrides(mut self, seat_overrides: Option<&BTreeMap<String, u32>>) -> Self {
+        self.seat_overrides = seat_overrides.cloned().unwrap_or_default();
+        self
+    }
+
+    /// Settle equal scores at a program's last seat as `cutoff_ties` says
+    ///
+    /// `tie_breakers` default to [`DEFAULT_TIE_BREAKERS`] and only apply to `CutoffTies::Secondary`.
+    pub fn with_cutoff_ties(mut self, cutoff_ties: CutoffTies, tie_breakers: Option<&[TieBreaker]>) -> Self {
+        self.cutoff_ties = cutoff_ties;
+        self.tie_breakers = match cutoff_ties {
+            CutoffTies::Secondary => tie_breakers.unwrap_or(&DEFAULT_TIE_BREAKERS).to_vec(),
+            _ => Vec::new(),
+        };
+        self
+    }
+
+    /// Enroll only applicants who handed in original documents
+    pub fn with_originals_only(mut self, originals_only: bool) -> Self {
+        self.originals_only = originals_only;
+        self
+    }
+
+    /// Whether the application takes part in the simulation
+    pub fn is_eager(&self, record: &StudentRecord) -> bool {
+        record.has_original_document() || (!self.originals_only && record.has_consent())
+    }
+
+    /// Popularity of every program-funding combination, in the order of
+    /// [`AdmissionAnalysis::program_popularities`]
+    pub fn program_popularities(&self, all_program_records: &[(String, Vec<StudentRecord>)]) -> Vec<ProgramPopularity> {
+        self.calculate_all_program_popularities(all_program_records)
+    }
+
     /// Main analysis function following the new priority-based logic
-    pub fn analyze_all_programs(&self, all_program_records: &Vec<(String, Vec<StudentRecord>)>) -> AdmissionAnalysis {
+    pub fn analyze_all_programs(&self, all_program_records: &[(String, Vec<StudentRecord>)]) -> AdmissionAnalysis {
+        self.simulate(all_program_records, &mut TargetTrace::new(self.target_snils))
+    }
+
+    /// [`Self::analyze_all_programs`] with `observer` told every step of the simulation
+    ///
+    /// The target's debug log is written as well; pass a tuple to subscribe several observers.
+    pub fn analyze_all_programs_observed(
+        &self,
+        all_program_records: &[(String, Vec<StudentRecord>)],
+        observer: &mut dyn SimulationObserver,
+    ) -> AdmissionAnalysis {
+        self.simulate(all_program_records, &mut (TargetTrace::new(self.target_snils), observer))
+    }
+
+    fn simulate(
+        &self,
+        all_program_records: &[(String, Vec<StudentRecord>)],
+        observer: &mut dyn SimulationObserver,
+    ) -> AdmissionAnalysis {
         // Step 1: Create program-funding combinations and calculate popularity
         let program_popularities = self.calculate_all_program_popularities(all_program_records);
         
         // Step 2: Prepare eager applicants with their applications ordered by priority
         let eager_applicants = self.prepare_eager_applicants(all_program_records);
-        
-        // Step 3: Sort eager applicants by score descending then average rank ascending
-        let mut sorted_eager_applicants = eager_applicants;
-        sorted_eager_applicants.sort_by(|a, b| {
-            b.score.partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-                .then_with(|| a.average_rank.partial_cmp(&b.average_rank).unwrap_or(std::cmp::Ordering::Equal))
-        });
 
-        // Step 4: Simulate admission process using the new priority-based algorithm
-        let final_admission_results = self.simulate_priority_based_admission(&program_popularities, &sorted_eager_applicants);
+        // Step 3: Simulate admission, every program seating applicants in its own order
+        let final_admission_results =
+            self.simulate_priority_based_admission(&program_popularities, &eager_applicants, observer);
         
         AdmissionAnalysis {
             program_popularities,
@@ -60,40 +178,48 @@ impl<'a> AdmissionAnalyzer<'a> {
         let mut popularities = Vec::new();
         
         // Group by program-funding combinations
-        let mut program_funding_combinations: HashMap<String, (String, String, Vec<StudentRecord>)> = HashMap::new();
+        let mut program_funding_combinations: HashMap<ProgramKey, Vec<&StudentRecord>> = HashMap::new();
         
         for (program_name, records) in all_program_records {
             for record in records {
-                let program_key = format!("{}_{}", program_name, record.funding_source);
                 program_funding_combinations
-                    .entry(program_key.clone())
-                    .or_insert_with(|| (program_name.clone(), record.funding_source.clone(), Vec::new()))
-                    .2
-                    .push(record.clone());
+                    .entry(ProgramKey::of(program_name, record))
+                    .or_default()
+                    .push(record);
             }
         }
         
-        // Calculate popularity for each combination
-        for (program_key, (program_name, funding_source, records)) in program_funding_combinations {
-            let popularity = self.calculate_program_popularity(&program_name, &funding_source, &program_key, &records);
-            popularities.push(popularity);
-        }
+        // Calculate popularity for each combination, programs are independent of each other
+        let programs_bar = progress::bar(program_funding_combinations.len(), &t!("progress.programs"));
+        popularities.par_extend(program_funding_combinations.into_par_iter().map(|(program_key, records)| {
+            let popularity = self.calculate_program_popularity(program_key, &records);
+            programs_bar.inc(1);
+            popularity
+        }));
+        programs_bar.finish_and_clear();
         
-        // Sort by average priority (lower is more popular)
-        popularities.sort_by(|a, b| a.top_candidates_average_priority.partial_cmp(&b.top_candidates_average_priority).unwrap_or(std::cmp::Ordering::Equal));
+        // Sort by average priority (lower is more popular), equally popular ones by key so
+        // the reports list programs in one order from run to run
+        popularities.sort_by(|a, b| {
+            a.top_candidates_average_priority
+                .total_cmp(&b.top_candidates_average_priority)
+                .then_with(|| a.program_key.cmp(&b.program_key))
+        });
         
         popularities
     }
 
     /// Calculate program popularity metrics based on new criteria
-    fn calculate_program_popularity(&self, program_name: &str, funding_source: &str, program_key: &str, records: &[StudentRecord]) -> ProgramPopularity {
-        let available_places = records[0].available_places;
+    fn calculate_program_popularity(&self, program_key: ProgramKey, records: &[&StudentRecord]) -> ProgramPopularity {
+        let listed_places = records[0].available_places;
+        let available_places = self.seat_overrides.get(&program_key.to_string()).copied().unwrap_or(listed_places);
+        let published_places = (available_places != listed_places).then_some(listed_places);
         
         // Filter for eager applicants (have original document OR consent)
         let mut eager_applicants: Vec<StudentRecord> = records
             .iter()
-            .filter(|record| record.has_original_document() || record.has_consent())
-            .cloned()
+            .filter(|record| self.is_eager(record))
+            .map(|record| (*record).clone())
             .collect();
         
         // Sort eager applicants by rank (best rank first - ascending order)
@@ -118,7 +244,7 @@ impl<'a> AdmissionAnalyzer<'a> {
         // Calculate average score of all eager applicants
         let all_scores: Vec<f64> = eager_applicants
             .iter()
-            .filter_map(|record| record.get_numeric_score())
+            .filter_map(|record| record.score)
             .collect();
 
         let average_score = if all_scores.is_empty() {
@@ -128,154 +254,393 @@ impl<'a> AdmissionAnalyzer<'a> {
         };
 
         ProgramPopularity {
-            program_name: program_name.to_string(),
-            program_key: program_key.to_string(),
-            funding_source: funding_source.to_string(),
+            program_name: program_key.name.clone(),
+            institution: records[0].institution.clone(),
+            funding_source: program_key.funding.clone(),
+            program_key,
             top_candidates_average_priority,
             average_score,
             available_places,
+            published_places,
             total_eager_applicants,
             eager_applicants,
         }
     }
 
     /// Prepare eager applicants with their applications sorted by priority
+    ///
+    /// An applicant of several institutions is one eager applicant per institution, as each
+    /// admits on its own. Applicants come by institution and SNILS, so every run simulates
+    /// them in one order.
     fn prepare_eager_applicants(&self, all_program_records: &[(String, Vec<StudentRecord>)]) -> Vec<EagerApplicant> {
-        let mut applicant_map: HashMap<String, Vec<ApplicantApplication>> = HashMap::new();
+        // (institution, normalized SNILS) -> applications
+        let mut applicant_map: BTreeMap<(String, String), Vec<ApplicantApplication>> = BTreeMap::new();
 
         // Collect all applications for each applicant
         for (program_name, records) in all_program_records {
             for record in records {
                 // Only consider eager applicants
-                if record.has_original_document() || record.has_consent() {
-                    let normalized_snils = normalize_snils(&record.snils);
-                    let program_key = format!("{}_{}", program_name, record.funding_source);
-                    
-                    let application = ApplicantApplication {
-                        snils: record.snils.clone(),
-                        program_key,
-                        program_name: program_name.clone(),
-                        funding_source: record.funding_source.clone(),
-                        priority: record.priority,
-                        score: record.get_numeric_score().unwrap_or(0.0),
-                        rank: record.rank,
-                        has_consent: record.has_consent(),
-                        has_original_document: record.has_original_document(),
-                    };
-                    
+                if self.is_eager(record) {
+                    let applicant = (record.institution.clone(), normalize_snils(&record.snils));
                     applicant_map
-                        .entry(normalized_snils.clone())
-                        .or_insert_with(Vec::new)
-                        .push(application);
-
+                        .entry(applicant)
+                        .or_default()
+                        .push(self.application(program_name, record));
                 }
             }
         }
         
         // Create eager applicants with sorted applications
         let mut eager_applicants = Vec::new();
-        for (snils, mut applications) in applicant_map {
+        for (applicant, mut applications) in applicant_map {
             // Sort applications by priority (ascending - lower priority number is more preferred)
             applications.sort_by_key(|app| app.priority);
-            
-            // Calculate average rank across all applications
-            let average_rank = applications.iter().map(|app| app.rank as f64).sum::<f64>() / applications.len() as f64;
-
-            let score = applications.iter().map(|app| app.score).sum::<f64>() / applications.len() as f64;
 
+            let (institution, snils) = applicant;
             eager_applicants.push(EagerApplicant {
                 snils,
+                institution,
                 applications,
-                average_rank,
-                score,
             });
         }
         
         eager_applicants
     }
 
+    /// Application of `record` to `program_name` as the simulation compares it
+    pub(crate) fn application(&self, program_name: &str, record: &StudentRecord) -> ApplicantApplication {
+        ApplicantApplication {
+            snils: record.snils.clone(),
+            program_key: ProgramKey::of(program_name, record),
+            program_name: program_name.to_string(),
+            funding_source: record.funding_source.clone(),
+            priority: record.priority,
+            score: record.score.unwrap_or(0.0),
+            tie_breaks: self.tie_breakers.iter().map(|tie_breaker| tie_break_value(*tie_breaker, record)).collect(),
+            rank: record.rank,
+            privileged: record.privileged,
+            has_consent: record.has_consent(),
+            has_original_document: record.has_original_document(),
+        }
+    }
+
+    /// Order in which a program seats two of its applications: privileged ones first, then by
+    /// the program's score descending and tie breakers descending, then by published rank with
+    /// unparsed ranks last; SNILS last, so equal applications keep one order from run to run
+    ///
+    /// An application is privileged in its own program only, elsewhere the applicant competes.
+    pub(crate) fn admission_order(&self, a: &ApplicantApplication, b: &ApplicantApplication) -> Ordering {
+        b.privileged
+            .cmp(&a.privileged)
+            .then_with(|| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal))
+            .then_with(|| b.tie_breaks.partial_cmp(&a.tie_breaks).unwrap_or(Ordering::Equal))
+            .then_with(|| (a.rank == 0, a.rank).cmp(&(b.rank == 0, b.rank)))
+            .then_with(|| normalize_snils(&a.snils).cmp(&normalize_snils(&b.snils)))
+    }
+
     /// Simulate admission process using priority-based algorithm
+    ///
+    /// Each applicant applies to their programs by priority until one holds a seat for them.
+    /// A program holds its places for the best of those applying in its own order, see
+    /// [`Self::admission_order`], and turns away whoever falls beyond them, who then applies
+    /// to their next priority. The target is simulated like everyone else.
     fn simulate_priority_based_admission(
         &self,
         program_popularities: &[ProgramPopularity],
-        sorted_eager_applicants: &[EagerApplicant],
-    ) -> HashMap<String, Vec<String>> {
-        let mut admission_lists: HashMap<String, Vec<String>> = HashMap::new();
-        let mut admitted_applicants: std::collections::HashSet<String> = std::collections::HashSet::new();
+        eager_applicants: &[EagerApplicant],
+        observer: &mut dyn SimulationObserver,
+    ) -> BTreeMap<ProgramKey, Vec<String>> {
+        let mut admission_lists: BTreeMap<ProgramKey, Vec<String>> = BTreeMap::new();
+        // Program -> (applicant, application) of every seat held, in admission order
+        let mut seat_holders: HashMap<&ProgramKey, Vec<(usize, usize)>> = HashMap::new();
+        let places: HashMap<&ProgramKey, u32> = program_popularities
+            .iter()
+            .map(|popularity| (&popularity.program_key, popularity.available_places))
+            .collect();
+        // Applicant -> their next application to apply with
+        let mut next_application = vec![0; eager_applicants.len()];
         
         // Initialize admission lists
         for popularity in program_popularities {
             admission_lists.insert(popularity.program_key.clone(), Vec::new());
         }
+        let seat = |(applicant, application): (usize, usize)| &eager_applicants[applicant].applications[application];
         
-        // Iterate through applicants in order of average rank
-        for applicant in sorted_eager_applicants {
-            let normalized_snils = normalize_snils(&applicant.snils);
-            
-            // Skip if already admitted to any program
-            if admitted_applicants.contains(&normalized_snils) {
-                continue;
-            }
-            
-            // Try to admit to programs in order of applicant's priority
-            for application in &applicant.applications {
+        let applicants_bar = progress::bar(eager_applicants.len(), &t!("progress.applicants"));
+        for first in 0..eager_applicants.len() {
+            applicants_bar.inc(1);
+            // The applicant and everyone they turn away apply until each holds a seat or runs out
+            let mut applying = vec![first];
+            while let Some(applicant) = applying.pop() {
+                let Some(application) = eager_applicants[applicant].applications.get(next_application[applicant]) else {
+                    continue;
+                };
+                let held = (applicant, next_application[applicant]);
+                next_application[applicant] += 1;
                 let program_key = &application.program_key;
-                
-                // Find the program's available places
-                let available_places = program_popularities
-                    .iter()
-                    .find(|p| p.program_key == *program_key)
-                    .map(|p| p.available_places)
-                    .unwrap_or(0);
+                let (Some(admission_list), Some(&available_places)) =
+                    (admission_lists.get_mut(program_key), places.get(program_key))
+                else {
+                    applying.push(applicant);
+                    continue;
+                };
+                observer.on_consider(application, admission_list, available_places);
 
-                if normalized_snils == normalize_snils(self.target_snils) {
-                    println!("Processing applicant: {} for program: {}", normalized_snils, program_key);
-                }
+                let holders = seat_holders.entry(program_key).or_default();
+                let position = holders.partition_point(|other| self.admission_order(seat(*other), seat(held)).is_lt());
+                holders.insert(position, held);
+                admission_list.insert(position, application.snils.clone());
 
-                // Check if admission list is not full
-                if let Some(admission_list) = admission_lists.get_mut(program_key) {
-                    if normalized_snils == normalize_snils(self.target_snils) {
-                        println!("Admission list length: {} available: {}", admission_list.len(), available_places);
-
-                        let mut snils_str = String::new();
-                        for admitted_snils in admission_list.clone() {
-                            if !snils_str.is_empty() {
-                                snils_str.push_str(", ");
-                            }
-                            if normalize_snils(&admitted_snils) == normalized_snils {
-                                snils_str.push_str(&format!("*{}*", admitted_snils));
-                            } else {
-                                snils_str.push_str(&format!("{}", admitted_snils));
-                            }
-                        }
-                        println!("{}", snils_str);
-                    }
-                    if admission_list.len() < available_places as usize {
-                        // Admit the applicant and mark as admitted
-                        admission_list.push(application.snils.clone());
-                        admitted_applicants.insert(normalized_snils.clone());
-
-
-                        if normalized_snils != normalize_snils(self.target_snils) {
-                            // Move to next applicant if not the target applicant
-                            break;
-                        } else {
-                            println!("Admitted target applicant: {}", normalized_snils);
-                        }
+                // Those beyond the places are turned away; with AdmitAll not those tied with the last seat
+                let mut admitted = true;
+                while holders.len() > available_places as usize {
+                    let last = *holders.last().expect("more holders than places");
+                    let tied_with_last_seat = self.cutoff_ties == CutoffTies::AdmitAll
+                        && available_places > 0
+                        && {
+                            let (cutoff, last) = (seat(holders[available_places as usize - 1]), seat(last));
+                            // Privileged seats have no cutoff to tie with
+                            !cutoff.privileged && !last.privileged && (cutoff.score - last.score).abs() < SCORE_EPSILON
+                        };
+                    if tied_with_last_seat {
+                        break;
                     }
+                    holders.pop();
+                    admission_list.pop();
+                    observer.on_reject(seat(last), admission_list, available_places);
+                    admitted &= last != held;
+                    applying.push(last.0);
+                }
+                if admitted {
+                    observer.on_admit(application, position + 1);
                 }
             }
         }
-        
+        applicants_bar.finish_and_clear();
+
+        // Anywhere but the first priority, the seats there went to applicants ahead
+        let admitted_to: HashMap<usize, usize> = seat_holders.values().flatten().copied().collect();
+        for (index, applicant) in eager_applicants.iter().enumerate() {
+            let admitted_to = admitted_to.get(&index).map(|application| &applicant.applications[*application]);
+            let first_priority = applicant.applications.first().map(|application| application.priority);
+            if admitted_to.map(|application| application.priority) != first_priority {
+                observer.on_displace(applicant, admitted_to);
+            }
+        }
+
         admission_lists
     }
 
+    /// Rows, cutoffs and target outcome of every program-funding combination in popularity order
+    pub fn report_model(
+        &self,
+        analysis: &AdmissionAnalysis,
+        all_program_records: &[(String, Vec<StudentRecord>)],
+    ) -> ReportModel {
+        let normalized_target = normalize_snils(self.target_snils);
+
+        let programs = analysis.program_popularities.par_iter().map(|popularity| {
+            let admitted_snils_list = analysis
+                .final_admission_results
+                .get(&popularity.program_key)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            // Applicant -> 1-based position in the admission order
+            let admission_positions: HashMap<String, usize> = admitted_snils_list
+                .iter()
+                .enumerate()
+                .map(|(index, snils)| (normalize_snils(snils), index + 1))
+                .collect();
+
+            let mut rows: Vec<ReportRow> = all_program_records
+                .iter()
+                .flat_map(|(program_name, records)| {
+                    records.iter().filter(|record| popularity.program_key.matches(program_name, record))
+                })
+                .map(|record| {
+                    let normalized_snils = normalize_snils(&record.snils);
+                    let admission_position = admission_positions.get(&normalized_snils).copied();
+                    ReportRow {
+                        record: record.clone(),
+                        eager: self.is_eager(record),
+                        admitted: admission_position.is_some(),
+                        admission_position,
+                        target: normalized_snils == normalized_target,
+                    }
+                })
+                .collect();
+            rows.sort_by_key(|row| row.record.rank);
+
+            // Lowest score among admitted applicants, privileged ones are seated whatever their score
+            let cutoff_score = rows
+                .iter()
+                .filter(|row| row.admitted && !row.record.privileged)
+                .filter_map(|row| row.record.score)
+                .fold(f64::MAX, f64::min);
+            let cutoff_score = if cutoff_score == f64::MAX { 0.0 } else { cutoff_score };
+            let cutoff_ties = if cutoff_score > 0.0 {
+                rows.iter()
+                    .filter(|row| row.eager && !row.record.privileged)
+                    .filter(|row| row.record.score.is_some_and(|score| (score - cutoff_score).abs() < SCORE_EPSILON))
+                    .count()
+            } else {
+                0
+            };
+
+            let target_record = rows.iter().find(|row| row.target).map(|row| &row.record);
+            let position_in_admitted = admission_positions.get(&normalized_target).copied();
+            let target_score = target_record.map(|record| record.score.unwrap_or(0.0));
+
+            let status = match (target_record, position_in_admitted, target_score) {
+                (None, _, _) => "Not_Applied",
+                (Some(_), Some(_), _) => "Admitted",
+                // Nobody is admitted without places, the other statuses would say nothing about the target
+                (Some(_), None, _) if popularity.available_places == 0 => "No_Places",
+                (Some(_), None, Some(score)) if score > cutoff_score && cutoff_score > 0.0 => "Admitted_ByScore_NotByPriority",
+                _ => "Not_Admitted",
+            };
+
+            let target = TargetOutcome {
+                program_key: popularity.program_key.clone(),
+                program_name: popularity.program_name.clone(),
+                institution: popularity.institution.clone(),
+                funding_source: popularity.funding_source.clone(),
+                available_places: popularity.available_places,
+                published_places: popularity.published_places,
+                priority: target_record.map(|record| record.priority),
+                target_score,
+                target_bonus: target_record.map_or(0.0, |record| record.bonus),
+                cutoff_score,
+                cutoff_ties,
+                projected_cutoff_score: None,
+                quota_places_returned: None,
+                transfer_cutoff_score: None,
+                position_in_admitted,
+                admitted_count: admitted_snils_list.len(),
+                status: status.to_string(),
+                confidence: None,
+            };
+
+            ProgramReport {
+                program_key: popularity.program_key.clone(),
+                program_name: popularity.program_name.clone(),
+                institution: popularity.institution.clone(),
+                funding_source: popularity.funding_source.clone(),
+                available_places: popularity.available_places,
+                published_places: popularity.published_places,
+                total_eager_applicants: popularity.total_eager_applicants,
+                cutoff_score,
+                cutoff_ties,
+                projected_cutoff_score: None,
+                quota_places_returned: None,
+                transfer_cutoff_score: None,
+                admitted_count: admitted_snils_list.len(),
+                rows,
+                target,
+            }
+        }).collect();
+
+        ReportModel {
+            target_snils: self.target_snils.to_string(),
+            programs,
+        }
+    }
+
+    /// Rank the target's programs by seats remaining when the target's turn comes
+    ///
+    /// Eager applicants admitted by the simulation to a program they prefer are blocked and
+    /// do not compete; the target counts as eager so the list shows where handing in the
+    /// original document pays off most. Closest to admission first.
+    pub fn admission_distances(
+        &self,
+        analysis: &AdmissionAnalysis,
+        all_program_records: &[(String, Vec<StudentRecord>)],
+    ) -> Vec<AdmissionDistance> {
+        let normalized_target = normalize_snils(self.target_snils);
+
+        // (institution, applicant) -> program the simulation admitted them to
+        let admitted_to: HashMap<(&str, String), &ProgramKey> = analysis
+            .program_popularities
+            .iter()
+            .flat_map(|popularity| {
+                let admitted = analysis.final_admission_results.get(&popularity.program_key).map(Vec::as_slice).unwrap_or_default();
+                admitted
+                    .iter()
+                    .map(move |snils| ((popularity.institution.as_str(), normalize_snils(snils)), &popularity.program_key))
+            })
+            .collect();
+        // (applicant, program) -> priority
+        let priorities: HashMap<(String, &ProgramKey), u32> = analysis
+            .program_popularities
+            .iter()
+            .flat_map(|popularity| {
+                popularity.eager_applicants.iter().map(move |record| {
+                    ((normalize_snils(&record.snils), &popularity.program_key), record.priority)
+                })
+            })
+            .collect();
+
+        let mut distances: Vec<AdmissionDistance> = analysis.program_popularities.par_iter().filter_map(|popularity| {
+            let target_record = all_program_records
+                .iter()
+                .flat_map(|(program_name, records)| {
+                    records.iter().filter(|record| popularity.program_key.matches(program_name, record))
+                })
+                .find(|record| normalize_snils(&record.snils) == normalized_target)?;
+
+            let unblocked: Vec<&StudentRecord> = popularity
+                .eager_applicants
+                .iter()
+                .filter(|record| {
+                    let snils = normalize_snils(&record.snils);
+                    if snils == normalized_target {
+                        return false;
+                    }
+                    match admitted_to.get(&(popularity.institution.as_str(), snils.clone())) {
+                        Some(program_key) if **program_key != popularity.program_key => priorities
+                            .get(&(snils, *program_key))
+                            .is_none_or(|admitted_priority| *admitted_priority > record.priority),
+                        _ => true,
+                    }
+                })
+                .collect();
+            // Privileged applicants are seated first, the others in list order
+            let ahead = unblocked
+                .iter()
+                .filter(|record| match (record.privileged, target_record.privileged) {
+                    (true, false) => true,
+                    (false, true) => false,
+                    _ => record.rank < target_record.rank,
+                })
+                .count();
+
+            Some(AdmissionDistance {
+                program_key: popularity.program_key.clone(),
+                program_name: popularity.program_name.clone(),
+                funding_source: popularity.funding_source.clone(),
+                priority: target_record.priority,
+                available_places: popularity.available_places,
+                published_places: popularity.published_places,
+                position: ahead + 1,
+                unblocked_eager: unblocked.len(),
+                seats_remaining: popularity.available_places as i64 - ahead as i64,
+            })
+        }).collect();
+
+        distances.sort_by(|a, b| {
+            b.seats_remaining
+                .cmp(&a.seats_remaining)
+                .then_with(|| a.priority.cmp(&b.priority))
+        });
+        distances
+    }
+
     /// Public method to group records by program and funding type (for reporting)
-    pub fn group_by_program_and_funding_public(
+    pub fn group_by_program_and_funding_public<'r>(
         &self,
-        all_program_records: Vec<(String, Vec<StudentRecord>)>,
-    ) -> HashMap<String, HashMap<String, Vec<StudentRecord>>> {
-        let mut grouped: HashMap<String, HashMap<String, Vec<StudentRecord>>> = HashMap::new();
+        all_program_records: &'r [(String, Vec<StudentRecord>)],
+    ) -> BTreeMap<String, BTreeMap<FundingSource, Vec<&'r StudentRecord>>> {
+        let mut grouped: BTreeMap<String, BTreeMap<FundingSource, Vec<&StudentRecord>>> = BTreeMap::new();
         
         for (program_name, records) in all_program_records {
             if records.is_empty() {
@@ -287,9 +652,9 @@ impl<'a> AdmissionAnalyzer<'a> {
                 let funding_type = record.funding_source.clone();
                 grouped
                     .entry(program_name.clone())
-                    .or_insert_with(HashMap::new)
+                    .or_default()
                     .entry(funding_type)
-                    .or_insert_with(Vec::new)
+                    .or_default()
                     .push(record);
             }
         }
@@ -297,3 +662,38 @@ impl<'a> AdmissionAnalyzer<'a> {
         grouped
     }
 }
+
+/// The target's best program across all institutions: the most preferred one the simulation
+/// admits to, the widest margin over the cutoff between equal priorities
+///
+/// Each institution admits separately, so the target may be admitted by several of them
+/// and has to choose. None when nothing admits the target.
+pub fn best_option(outcomes: &[TargetOutcome]) -> Option<&TargetOutcome> {
+    let margin = |outcome: &TargetOutcome| outcome.target_score.unwrap_or(0.0) - outcome.cutoff_score;
+    outcomes
+        .iter()
+        .filter(|outcome| outcome.status == "Admitted")
+        .min_by(|a, b| a.priority.cmp(&b.priority).then_with(|| margin(b).total_cmp(&margin(a))))
+}
+
+/// Scores closer than this are the same score
+const SCORE_EPSILON: f64 = 1e-6;
+
+/// Value of `tie_breaker` for one application, higher wins
+fn tie_break_value(tie_breaker: TieBreaker, record: &StudentRecord) -> f64 {
+    match tie_breaker {
+        TieBreaker::SubjectSum => scoring::subject_sum(record).unwrap_or(0.0),
+        TieBreaker::Achievements => parse_score(&record.achievements).unwrap_or(0.0),
+        TieBreaker::Original => {
+            if record.has_original_document() {
+                1.0
+            } else {
+                0.0
+            }
+        }
+        // Earlier is higher; an application without a time comes after those with one
+        TieBreaker::Submitted => record
+            .submitted_at
+            .map_or(f64::NEG_INFINITY, |submitted_at| -(submitted_at.and_utc().timestamp() as f64)),
+    }
+}
@@ -0,0 +1,42 @@
+use crate::models::{normalize_snils, StudentRecord};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Replaces SNILS with stable pseudonymous IDs derived from a salted hash
+pub struct Anonymizer {
+    salt: String,
+}
+
+impl Anonymizer {
+    /// Use the configured salt so IDs stay the same between runs, or a random one for this run only
+    pub fn new(salt: Option<&str>) -> Self {
+        let salt = match salt {
+            Some(salt) if !salt.is_empty() => salt.to_string(),
+            _ => {
+                let mut bytes = [0u8; 16];
+                rand::thread_rng().fill_bytes(&mut bytes);
+                bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+            }
+        };
+        Self { salt }
+    }
+
+    /// Pseudonym like `ID-3F9A1C27B0E4`; different spellings of the same SNILS map to the same ID
+    pub fn pseudonym(&self, snils: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update(normalize_snils(snils).as_bytes());
+        let digest = hasher.finalize();
+        let hex: String = digest.iter().take(6).map(|byte| format!("{:02X}", byte)).collect();
+        format!("ID-{}", hex)
+    }
+
+    /// Replace SNILS in all parsed records
+    pub fn apply(&self, all_program_records: &mut [(String, Vec<StudentRecord>)]) {
+        for (_, records) in all_program_records.iter_mut() {
+            for record in records.iter_mut() {
+                record.snils = self.pseudonym(&record.snils);
+            }
+        }
+    }
+}
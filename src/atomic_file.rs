@@ -0,0 +1,29 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Write `contents` to `path` so readers see either the old file or the whole new one
+///
+/// The contents go to a hidden temporary file next to `path` first, which then replaces
+/// it by a rename. Tools polling the output directory during a run never read a half-written report.
+pub fn write(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref();
+    let staged = staged_path(path);
+    let result = write_staged(&staged, contents.as_ref()).and_then(|()| fs::rename(&staged, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&staged);
+    }
+    result
+}
+
+fn write_staged(staged: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = fs::File::create(staged)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// `.<name>.tmp` in the same directory, so the rename never crosses file systems
+fn staged_path(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+    path.with_file_name(format!(".{}.tmp", name))
+}
@@ -0,0 +1,139 @@
+use crate::enrollment_order::{self, Validation};
+use crate::models::FundingSource;
+use crate::{analyze_records, cli, console_table, csv_report, load_enrollments, load_snapshot, output_dirs, overrides, run_summary, snapshot};
+use anyhow::{bail, Result};
+use clap::ArgMatches;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Scores of one configuration variant over every archived run
+pub struct VariantScore {
+    // Overrides as given with --variant, or the localized name of the configuration itself
+    pub name: String,
+    pub runs: Vec<RunScore>,
+}
+
+impl VariantScore {
+    /// Mean share of predicted seats the orders confirmed, over the runs that predicted any
+    pub fn accuracy(&self) -> Option<f64> {
+        mean(self.runs.iter().filter_map(|run| run.accuracy))
+    }
+
+    pub fn target_correct(&self) -> usize {
+        self.runs.iter().filter(|run| run.target_correct).count()
+    }
+
+    /// Mean absolute cutoff error over all programs of all runs
+    pub fn cutoff_error(&self) -> Option<f64> {
+        mean(self.runs.iter().flat_map(|run| run.cutoffs.iter().map(CutoffError::error)))
+    }
+}
+
+/// How the simulation of one archived run compares to the orders
+pub struct RunScore {
+    // Name of the run directory
+    pub run: String,
+    pub accuracy: Option<f64>,
+    // The simulation put the target where the orders enrolled them, or nowhere for both
+    pub target_correct: bool,
+    pub cutoffs: Vec<CutoffError>,
+}
+
+impl RunScore {
+    fn of(run: String, validation: &Validation) -> Self {
+        Self {
+            run,
+            accuracy: validation.accuracy(),
+            target_correct: validation.target_predicted == validation.target_enrolled,
+            cutoffs: validation
+                .programs
+                .iter()
+                .filter_map(|program| {
+                    Some(CutoffError {
+                        program_name: program.program_name.clone(),
+                        funding_source: program.funding_source.clone(),
+                        predicted_cutoff: program.predicted_cutoff,
+                        actual_cutoff: program.actual_cutoff?,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    pub fn cutoff_error(&self) -> Option<f64> {
+        mean(self.cutoffs.iter().map(CutoffError::error))
+    }
+}
+
+/// Simulated and actual cutoff of a program the orders cover
+pub struct CutoffError {
+    pub program_name: String,
+    pub funding_source: FundingSource,
+    pub predicted_cutoff: f64,
+    pub actual_cutoff: f64,
+}
+
+impl CutoffError {
+    pub fn error(&self) -> f64 {
+        (self.predicted_cutoff - self.actual_cutoff).abs()
+    }
+}
+
+/// Simulate every archived run of a campaign with the configuration and each `--variant`,
+/// then print and write how well each predicted the enrollment orders
+pub fn run(settings: &cli::RunSettings, matches: &ArgMatches) -> Result<()> {
+    let runs_root = matches.get_one::<String>("runs").unwrap_or(&settings.output_root);
+    if !Path::new(runs_root).is_dir() {
+        bail!("{}", t!("backtest.no_runs", runs_root));
+    }
+    let mut run_dirs: Vec<PathBuf> = output_dirs::list_run_directories(Path::new(runs_root))?
+        .into_iter()
+        .filter(|run_dir| run_dir.join(snapshot::SNAPSHOT_FILE).exists())
+        .collect();
+    if run_dirs.is_empty() {
+        bail!("{}", t!("backtest.no_runs", runs_root));
+    }
+    // Timestamp names sort in time order
+    run_dirs.sort();
+    let snapshots = run_dirs
+        .iter()
+        .map(|run_dir| {
+            let run = run_dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+            Ok((run, load_snapshot(&run_dir.join(snapshot::SNAPSHOT_FILE))?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let orders: Vec<&String> = matches.get_many::<String>("orders").into_iter().flatten().collect();
+
+    let mut variants = vec![(t!("backtest.configured"), Vec::new())];
+    for variant in matches.get_many::<String>("variant").into_iter().flatten() {
+        let assignments = variant
+            .split(';')
+            .filter(|assignment| !assignment.trim().is_empty())
+            .map(|assignment| overrides::parse_assignment(assignment.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        variants.push((variant.trim().to_string(), assignments));
+    }
+
+    let mut scores = Vec::new();
+    for (name, assignments) in variants {
+        info!("{}", t!("backtest.scoring", name, snapshots.len()));
+        let variant_settings = cli::RunSettings::new(settings.config.with_overrides(&assignments)?)?;
+        let enrollments = load_enrollments(&variant_settings, orders.iter().copied())?;
+        let mut runs = Vec::new();
+        for (run, records) in &snapshots {
+            let analyzed = analyze_records(&variant_settings, records.clone(), &mut run_summary::RunSummary::default())?;
+            runs.push(RunScore::of(run.clone(), &enrollment_order::validate(&analyzed.report, &enrollments)));
+        }
+        scores.push(VariantScore { name, runs });
+    }
+
+    console_table::print_backtest_table(&scores, settings.color);
+    csv_report::generate_backtest_csvs(&settings.csv_dialect, &scores, runs_root)?;
+    info!("{}", t!("backtest.written", runs_root));
+    Ok(())
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
@@ -0,0 +1,78 @@
+use crate::models::CampaignOptions;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// Whether the lists can still change before enrollment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CampaignMode {
+    // Originals can still be handed in, results are projections
+    Projection,
+    // The originals deadline has passed, only applicants with originals are enrolled
+    Locked,
+}
+
+/// A campaign date still ahead
+#[derive(Debug, Clone, Serialize)]
+pub struct CampaignDate {
+    // i18n key of the date's name, e.g. "campaign.originals_deadline"
+    pub label: String,
+    pub date: NaiveDate,
+    // 0 on the day itself
+    pub days_remaining: i64,
+}
+
+/// Where the campaign stands on a given day
+#[derive(Debug, Clone, Serialize)]
+pub struct CampaignStatus {
+    pub today: NaiveDate,
+    pub mode: CampaignMode,
+    // Dates not passed yet, soonest first
+    pub upcoming: Vec<CampaignDate>,
+}
+
+impl CampaignStatus {
+    /// Status of the campaign on `today`, `None` if no dates are configured
+    ///
+    /// The lists lock the day after the originals deadline, or after the enrollment order
+    /// without one.
+    pub fn new(options: &CampaignOptions, today: NaiveDate) -> Option<Self> {
+        let mut dates: Vec<(&str, NaiveDate)> = Vec::new();
+        dates.extend(options.originals_deadline.map(|date| ("campaign.originals_deadline", date)));
+        dates.extend(options.enrollment_order.map(|date| ("campaign.enrollment_order", date)));
+        dates.extend(options.waves.iter().flatten().map(|date| ("campaign.wave_end", *date)));
+        dates.extend(options.quota_transfer.map(|date| ("campaign.quota_transfer", date)));
+        if dates.is_empty() {
+            return None;
+        }
+
+        let lock_date = options.originals_deadline.or(options.enrollment_order);
+        let mode = match lock_date {
+            Some(date) if today > date => CampaignMode::Locked,
+            _ => CampaignMode::Projection,
+        };
+        let mut upcoming: Vec<CampaignDate> = dates
+            .into_iter()
+            .filter(|(_, date)| *date >= today)
+            .map(|(label, date)| CampaignDate {
+                label: label.to_string(),
+                date,
+                days_remaining: (date - today).num_days(),
+            })
+            .collect();
+        upcoming.sort_by_key(|date| date.date);
+        Some(Self { today, mode, upcoming })
+    }
+
+    /// One line with the mode and the next date, for consoles and text reports
+    pub fn describe(&self) -> String {
+        let mode = match self.mode {
+            CampaignMode::Projection => t!("campaign.projection"),
+            CampaignMode::Locked => t!("campaign.locked"),
+        };
+        match self.upcoming.first() {
+            Some(next) => t!("campaign.next_date", mode, next.days_remaining, t!(&next.label), next.date),
+            None => mode,
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::analyzer::{AdmissionAnalysis, AdmissionAnalyzer};
+use crate::models::{normalize_snils, ProgramKey, StudentRecord};
+use rayon::prelude::*;
+use serde::Serialize;
+
+/// The target's seats with the places of one program changed by `delta`
+#[derive(Debug, Clone, Serialize)]
+pub struct CapacityStep {
+    pub delta: i64,
+    pub places: u32,
+    // Programs the target is admitted to, one per institution; empty without a seat
+    pub seats: Vec<ProgramKey>,
+}
+
+/// How the target's seats depend on the places of one program-funding combination
+#[derive(Debug, Clone, Serialize)]
+pub struct CapacitySensitivity {
+    pub program_key: ProgramKey,
+    pub available_places: u32,
+    // Only the steps where the target's seats differ from those with the current places
+    pub changes: Vec<CapacityStep>,
+}
+
+impl CapacitySensitivity {
+    /// Smallest change of places that moves the target, None if none in the range does
+    pub fn closest_change(&self) -> Option<i64> {
+        self.changes.iter().map(|step| step.delta).min_by_key(|delta| delta.abs())
+    }
+}
+
+/// Simulate every program-funding combination with `range` places fewer up to `range` more,
+/// one at a time, most sensitive first
+///
+/// A program is more sensitive the smaller the change of its places that gives the target
+/// another seat, then the more of the changes tried do. Programs whose places do not
+/// matter in the range come last. Places never go below 0.
+pub fn sweep(
+    analyzer: &AdmissionAnalyzer,
+    analysis: &AdmissionAnalysis,
+    all_program_records: &[(String, Vec<StudentRecord>)],
+    range: u32,
+) -> Vec<CapacitySensitivity> {
+    let current = target_seats(analyzer, analysis);
+    let mut sensitivities: Vec<CapacitySensitivity> = analysis
+        .program_popularities
+        .par_iter()
+        .map(|popularity| {
+            let places = popularity.available_places;
+            let changes = (-i64::from(range)..=i64::from(range))
+                .filter(|delta| *delta != 0 && i64::from(places) + delta >= 0)
+                .filter_map(|delta| {
+                    let changed_places = (i64::from(places) + delta) as u32;
+                    let mut changed = analyzer.clone();
+                    changed.seat_overrides.insert(popularity.program_key.to_string(), changed_places);
+                    let seats = target_seats(&changed, &changed.analyze_all_programs(all_program_records));
+                    (seats != current).then_some(CapacityStep { delta, places: changed_places, seats })
+                })
+                .collect();
+            CapacitySensitivity {
+                program_key: popularity.program_key.clone(),
+                available_places: places,
+                changes,
+            }
+        })
+        .collect();
+    sensitivities.sort_by(|a, b| {
+        let closest = |sensitivity: &CapacitySensitivity| sensitivity.closest_change().map_or(i64::MAX, i64::abs);
+        closest(a).cmp(&closest(b)).then(b.changes.len().cmp(&a.changes.len()))
+    });
+    sensitivities
+}
+
+/// Programs the simulation admits the target to, in program order
+pub fn target_seats(analyzer: &AdmissionAnalyzer, analysis: &AdmissionAnalysis) -> Vec<ProgramKey> {
+    let normalized_target = normalize_snils(analyzer.target_snils);
+    analysis
+        .final_admission_results
+        .iter()
+        .filter(|(_, admitted)| admitted.iter().any(|snils| normalize_snils(snils) == normalized_target))
+        .map(|(program_key, _)| program_key.clone())
+        .collect()
+}
@@ -0,0 +1,259 @@
+use crate::{atomic_file, program_dirs};
+use crate::models::StudentRecord;
+use crate::report_model::{ProgramReport, ReportModel};
+use anyhow::Result;
+use plotters::prelude::*;
+use crate::storage::Storage;
+use std::fs;
+use std::path::Path;
+
+const CHART_SIZE: (u32, u32) = (900, 500);
+const SCORE_BIN: f64 = 0.1;
+const TREND_COLORS: [RGBColor; 6] = [
+    RGBColor(0x1F, 0x77, 0xB4),
+    RGBColor(0xFF, 0x7F, 0x0E),
+    RGBColor(0x2C, 0xA0, 0x2C),
+    RGBColor(0xD6, 0x27, 0x28),
+    RGBColor(0x94, 0x67, 0xBD),
+    RGBColor(0x8C, 0x56, 0x4B),
+];
+
+/// Render SVG charts: score and priority histograms into the directory of each program
+/// and the cutoff trend over runs stored in analysis.db (if present) into output/charts
+pub fn generate_charts(
+    report: &ReportModel,
+    program_dirs: &program_dirs::ProgramDirectories,
+    output_dir: &str,
+    database_path: &Path,
+) -> Result<()> {
+
+    for program in &report.programs {
+        if program.rows.is_empty() {
+            continue;
+        }
+        let records: Vec<&StudentRecord> = program.rows.iter().map(|row| &row.record).collect();
+        let title = format!("{} ({})", program.program_name, program.funding_source);
+        let program_dir = program_dirs.create(output_dir, &program.program_key)?;
+
+        if let Some(svg) = score_histogram_svg(&title, program)? {
+            atomic_file::write(program_dir.join("scores.svg"), svg)?;
+        }
+        let svg = priority_histogram_svg(&title, &records)?;
+        atomic_file::write(program_dir.join("priorities.svg"), svg)?;
+    }
+
+    if let Some(svg) = cutoff_trend_svg(database_path)? {
+        let charts_dir = Path::new(output_dir).join("charts");
+        fs::create_dir_all(&charts_dir)?;
+        atomic_file::write(charts_dir.join("cutoff_trend.svg"), svg)?;
+    }
+    Ok(())
+}
+
+/// Histogram of scores for all and eager applicants with cutoff and target score markers
+///
+/// `None` when no application in the program has a score.
+fn score_histogram_svg(title: &str, program: &ProgramReport) -> Result<Option<String>> {
+    let bin_of = |score: f64| (score / SCORE_BIN).floor() as i64;
+    let scores: Vec<(f64, bool)> = program
+        .rows
+        .iter()
+        .filter_map(|row| row.record.score.map(|score| (score, row.eager)))
+        .collect();
+    if scores.is_empty() {
+        return Ok(None);
+    }
+
+    let min_bin = scores.iter().map(|(score, _)| bin_of(*score)).min().unwrap_or(0);
+    let max_bin = scores.iter().map(|(score, _)| bin_of(*score)).max().unwrap_or(0);
+    let mut all_counts = vec![0u32; (max_bin - min_bin + 1) as usize];
+    let mut eager_counts = all_counts.clone();
+    for (score, is_eager) in &scores {
+        let index = (bin_of(*score) - min_bin) as usize;
+        all_counts[index] += 1;
+        if *is_eager {
+            eager_counts[index] += 1;
+        }
+    }
+    let max_count = all_counts.iter().copied().max().unwrap_or(1);
+    let x_range = (min_bin as f64 * SCORE_BIN)..((max_bin + 1) as f64 * SCORE_BIN);
+
+    let mut svg = String::new();
+    let root = SVGBackend::with_string(&mut svg, CHART_SIZE).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(t!("chart.scores_title", title), ("sans-serif", 18))
+        .margin(15)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(x_range, 0u32..max_count + 1)?;
+    chart
+        .configure_mesh()
+        .x_desc(t!("report.score"))
+        .y_desc(t!("chart.applicants"))
+        .x_label_formatter(&|x| format!("{:.1}", x))
+        .draw()?;
+
+    let bar = |counts: &[u32], style: ShapeStyle| {
+        counts
+            .iter()
+            .enumerate()
+            .map(move |(index, count)| {
+                let x0 = (min_bin + index as i64) as f64 * SCORE_BIN;
+                Rectangle::new([(x0, 0), (x0 + SCORE_BIN, *count)], style)
+            })
+            .collect::<Vec<_>>()
+    };
+    chart
+        .draw_series(bar(&all_counts, RGBColor(0x9D, 0xB4, 0xD6).filled()))?
+        .label(t!("chart.all_applicants"))
+        .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], RGBColor(0x9D, 0xB4, 0xD6).filled()));
+    chart
+        .draw_series(bar(&eager_counts, RGBColor(0x3A, 0x5F, 0x9E).filled()))?
+        .label(t!("chart.eager_applicants"))
+        .legend(|(x, y)| Rectangle::new([(x, y - 5), (x + 10, y + 5)], RGBColor(0x3A, 0x5F, 0x9E).filled()));
+
+    if program.cutoff_score > 0.0 {
+        chart
+            .draw_series(LineSeries::new(
+                [(program.cutoff_score, 0), (program.cutoff_score, max_count + 1)],
+                RED.stroke_width(2),
+            ))?
+            .label(t!("report.cutoff"))
+            .legend(|(x, y)| PathElement::new([(x, y), (x + 15, y)], RED.stroke_width(2)));
+    }
+    if let Some(target_score) = program.target.target_score {
+        let target_color = RGBColor(0xE8, 0xA3, 0x3D);
+        chart
+            .draw_series(LineSeries::new(
+                [(target_score, 0), (target_score, max_count + 1)],
+                target_color.stroke_width(2),
+            ))?
+            .label(t!("report.target_legend"))
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 15, y)], target_color.stroke_width(2)));
+    }
+
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .position(SeriesLabelPosition::UpperLeft)
+        .draw()?;
+    root.present()?;
+    drop(chart);
+    drop(root);
+    Ok(Some(svg))
+}
+
+/// Histogram of the priority applicants gave to this program
+fn priority_histogram_svg(title: &str, records: &[&StudentRecord]) -> Result<String> {
+    let max_priority = records.iter().map(|record| record.priority).max().unwrap_or(1).max(1);
+    let mut counts = vec![0u32; max_priority as usize + 1];
+    for record in records {
+        counts[record.priority as usize] += 1;
+    }
+    let max_count = counts.iter().copied().max().unwrap_or(1);
+
+    let mut svg = String::new();
+    let root = SVGBackend::with_string(&mut svg, CHART_SIZE).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(t!("chart.priorities_title", title), ("sans-serif", 18))
+        .margin(15)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d((1u32..max_priority + 1).into_segmented(), 0u32..max_count + 1)?;
+    chart
+        .configure_mesh()
+        .x_desc(t!("report.priority"))
+        .y_desc(t!("chart.applicants"))
+        .draw()?;
+    chart.draw_series(
+        Histogram::vertical(&chart)
+            .style(RGBColor(0x3A, 0x5F, 0x9E).filled())
+            .margin(10)
+            .data(records.iter().map(|record| (record.priority, 1))),
+    )?;
+    root.present()?;
+    drop(chart);
+    drop(root);
+    Ok(svg)
+}
+
+/// Line per program with the cutoff score of every run stored in analysis.db
+///
+/// `None` when there is no database or fewer than two runs to compare.
+pub fn cutoff_trend_svg(database_path: &Path) -> Result<Option<String>> {
+    if !database_path.exists() {
+        return Ok(None);
+    }
+    let storage = Storage::open(database_path)?;
+    let runs = storage.runs()?;
+    if runs.len() < 2 {
+        return Ok(None);
+    }
+
+    let programs: Vec<(String, Vec<(usize, f64)>)> = storage
+        .program_histories()?
+        .into_iter()
+        .map(|history| {
+            let points = history
+                .points
+                .iter()
+                .filter_map(|point| {
+                    let run_index = runs.iter().position(|run| run.id == point.run_id)?;
+                    Some((run_index, point.cutoff_score))
+                })
+                .collect();
+            (format!("{} ({})", history.program_name, history.funding_source), points)
+        })
+        .collect();
+
+    let max_score = programs
+        .iter()
+        .flat_map(|(_, points)| points.iter().map(|(_, score)| *score))
+        .fold(0.0_f64, f64::max);
+    let min_score = programs
+        .iter()
+        .flat_map(|(_, points)| points.iter().map(|(_, score)| *score))
+        .fold(max_score, f64::min);
+
+    let mut svg = String::new();
+    let root = SVGBackend::with_string(&mut svg, (CHART_SIZE.0, CHART_SIZE.1 + 100)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(&root)
+        .caption(t!("chart.trend_title"), ("sans-serif", 18))
+        .margin(15)
+        .x_label_area_size(60)
+        .y_label_area_size(50)
+        .build_cartesian_2d(0..runs.len() - 1, (min_score - 0.1).max(0.0)..max_score + 0.1)?;
+    chart
+        .configure_mesh()
+        .y_desc(t!("report.cutoff_score"))
+        .x_labels(runs.len().min(12))
+        .x_label_formatter(&|index| {
+            runs.get(*index)
+                .map(|run| run.created_at.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default()
+        })
+        .draw()?;
+
+    for (i, (program, points)) in programs.iter().enumerate() {
+        let color = TREND_COLORS[i % TREND_COLORS.len()];
+        chart
+            .draw_series(LineSeries::new(points.iter().copied(), color.stroke_width(2)).point_size(3))?
+            .label(program.as_str())
+            .legend(move |(x, y)| PathElement::new([(x, y), (x + 15, y)], color.stroke_width(2)));
+    }
+    chart
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .position(SeriesLabelPosition::LowerRight)
+        .draw()?;
+    root.present()?;
+    // The backend borrows `svg` until the chart and drawing area are gone
+    drop(chart);
+    drop(root);
+    Ok(Some(svg))
+}
@@ -0,0 +1,56 @@
+use crate::atomic_file;
+use crate::error::StorageError;
+use crate::models::{ProgramInfo, StudentRecord};
+use chrono::{DateTime, Local, Utc};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// File in the output root with the source checksums of the last completed run
+pub const CHECKSUMS_FILE: &str = "checksums.json";
+
+/// Checksum of every loaded source, by file path or URL
+pub type SourceChecksums = BTreeMap<String, String>;
+
+/// SHA-256 of the program lists parsed from one source
+///
+/// Hashing what was parsed rather than the page itself ignores markup-only changes,
+/// such as a "generated at" time in the page footer. The time each record was parsed at
+/// is left out for the same reason.
+pub fn of_programs(programs: &[(ProgramInfo, Vec<StudentRecord>)]) -> String {
+    let mut hasher = Sha256::new();
+    for (program_info, records) in programs {
+        // Serializing plain data into memory cannot fail
+        hasher.update(serde_json::to_vec(program_info).unwrap_or_default());
+        hash_records(&mut hasher, records);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Feed `records` to `hasher`, leaving out the time they were parsed at
+pub fn hash_records(hasher: &mut Sha256, records: &[StudentRecord]) {
+    let records: Vec<StudentRecord> = records
+        .iter()
+        .cloned()
+        .map(|mut record| {
+            if let Some(provenance) = &mut record.provenance {
+                provenance.scraped_at = DateTime::<Utc>::UNIX_EPOCH.with_timezone(&Local);
+            }
+            record
+        })
+        .collect();
+    hasher.update(serde_json::to_vec(&records).unwrap_or_default());
+}
+
+/// Checksums saved by the last completed run, `None` if there are none or they cannot be read
+pub fn load(output_root: &Path) -> Option<SourceChecksums> {
+    let content = fs::read_to_string(output_root.join(CHECKSUMS_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub fn save(output_root: &Path, checksums: &SourceChecksums) -> Result<(), StorageError> {
+    let path = output_root.join(CHECKSUMS_FILE);
+    let content = serde_json::to_string_pretty(checksums)?;
+    atomic_file::write(&path, content).map_err(|cause| StorageError::Write { path: path.display().to_string(), cause })
+}
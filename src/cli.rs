@@ -0,0 +1,457 @@
+use crate::csv_output::CsvDialect;
+use crate::models::{Config, DataSourceMode, OutputFormat};
+use crate::overrides;
+use abitur_analyzer::fixture;
+use abitur_analyzer::identity::IdentityResolver;
+use abitur_analyzer::scoring::ScoringRules;
+use anyhow::Result;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Command line with global options and one subcommand per pipeline stage
+pub fn command() -> Command {
+    let command = Command::new("abitur-analyzer")
+        .version("1.0")
+        .about("Simultes admission process")
+        .arg(
+            Arg::new("config")
+                .short('c')
+                .long("config")
+                .value_name("FILE")
+                .help("Configuration file path")
+                .default_value("config.toml")
+                .global(true)
+        )
+        .arg(
+            Arg::new("snils")
+                .short('s')
+                .long("snils")
+                .value_name("SNILS")
+                .help("target applicant id")
+                .global(true)
+        )
+        .arg(
+            Arg::new("data_source_mode")
+                .short('d')
+                .long("data_source_mode")
+                .value_name("DATA_SOURCE_MODE")
+                .help("data source mode 'local'/'internet")
+                .default_value("")
+                .global(true)
+        )
+        .arg(
+            Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_name("FORMAT")
+                .help("output formats 'csv'/'json'/'html'/'xlsx'/'markdown'/'sqlite'/'charts'/'pdf'/'heatmap', comma separated (overrides config)")
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .global(true)
+        )
+        .arg(
+            Arg::new("profile")
+                .short('P')
+                .long("profile")
+                .value_name("NAME")
+                .help("use a profile from the configuration file or the profiles/ directory")
+                .global(true)
+        )
+        .arg(
+            Arg::new("set")
+                .long("set")
+                .value_name("KEY=VALUE")
+                .help("override any config key, e.g. csv.delimiter=';' (repeatable, overrides ABIT_* variables)")
+                .action(ArgAction::Append)
+                .global(true)
+        )
+        .arg(
+            Arg::new("lang")
+                .short('l')
+                .long("lang")
+                .value_name("LANG")
+                .help("language of console output and reports 'en'/'ru' (overrides config)")
+                .global(true)
+        )
+        .arg(
+            Arg::new("funding")
+                .long("funding")
+                .value_name("FUNDING")
+                .help("analyze only 'budget' (with targeted and quota places) or 'commercial' lists, or 'all' (overrides config)")
+                .value_parser(["budget", "commercial", "all"])
+                .global(true)
+        )
+        .arg(
+            Arg::new("anonymize")
+                .long("anonymize")
+                .help("replace SNILS with pseudonymous IDs in all outputs (overrides config)")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("no_color")
+                .long("no-color")
+                .help("disable colors in console tables")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .help("fetch and parse the sources and show what would be analyzed, without writing any output (run and scrape)")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("resume")
+                .long("resume")
+                .help("continue an interrupted scrape, fetching only the pages it did not get (run and scrape)")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("from_snapshot")
+                .global(true)
+        )
+        .arg(
+            Arg::new("from_snapshot")
+                .long("from-snapshot")
+                .value_name("SNAPSHOT")
+                .help("analyze a snapshot file, run directory or @ID of a stored run instead of the sources (run and scrape)")
+                .global(true)
+        )
+        .arg(
+            Arg::new("save_snapshot")
+                .long("save-snapshot")
+                .value_name("FILE")
+                .help("also save the parsed lists to this snapshot file, to analyze them again later (run and scrape)")
+                .global(true)
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("more detailed console output, repeat for trace level")
+                .action(ArgAction::Count)
+                .conflicts_with("quiet")
+                .global(true)
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("only print warnings, errors and the final summary")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("log_file")
+                .long("log-file")
+                .value_name("FILE")
+                .help("write structured JSON log to a file (overrides config)")
+                .global(true)
+        )
+        .subcommand(Command::new("run").about("scrape, analyze and write reports (default)"))
+        .subcommand(Command::new("scrape").about("fetch and parse sources into a snapshot without analysis"))
+        .subcommand(
+            Command::new("analyze")
+                .about("simulate admission from a snapshot and print the results")
+                .arg(snapshot_arg()),
+        )
+        .subcommand(
+            Command::new("optimize")
+                .about("find the order of the target's priorities that gets the best seat")
+                .arg(snapshot_arg()),
+        )
+        .subcommand(
+            Command::new("stress")
+                .about("show how the target's outcome changes with the places of each program")
+                .arg(snapshot_arg())
+                .arg(
+                    Arg::new("range")
+                        .long("range")
+                        .value_name("K")
+                        .help("places to try fewer and more than each program has")
+                        .value_parser(clap::value_parser!(u32).range(1..=50))
+                        .default_value("3"),
+                ),
+        )
+        .subcommand(
+            Command::new("report")
+                .about("regenerate reports next to a snapshot, or in a new run directory for one outside the output directory")
+                .arg(snapshot_arg()),
+        )
+        .subcommand(
+            Command::new("compare")
+                .about("show how the target's outcome changed between two snapshots")
+                .arg(Arg::new("before").value_name("BEFORE").help("older snapshot file, run directory or @ID of a stored run").required(true))
+                .arg(Arg::new("after").value_name("AFTER").help("newer snapshot file, run directory or @ID of a stored run").required(true)),
+        )
+        .subcommand(
+            Command::new("validate")
+                .about("compare the simulation of a snapshot with official enrollment orders")
+                .arg(
+                    Arg::new("orders")
+                        .value_name("ORDER")
+                        .help("enrollment order saved as an HTML page or CSV file")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .arg(snapshot_arg().long("snapshot")),
+        )
+        .subcommand(
+            Command::new("backtest")
+                .about("score the simulation of every archived run against enrollment orders, per config variant")
+                .arg(
+                    Arg::new("orders")
+                        .value_name("ORDER")
+                        .help("enrollment order saved as an HTML page or CSV file")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .arg(
+                    Arg::new("runs")
+                        .long("runs")
+                        .value_name("DIR")
+                        .help("directory with the timestamped run directories of the campaign (default: output directory)"),
+                )
+                .arg(
+                    Arg::new("variant")
+                        .long("variant")
+                        .value_name("KEY=VALUE;...")
+                        .help("config overrides to score besides the configuration itself, e.g. \"cutoff_ties=admit_all\"")
+                        .action(ArgAction::Append),
+                ),
+        )
+        .subcommand(
+            Command::new("watch")
+                .about("run periodically and print changes of the target's outcome")
+                .arg(
+                    Arg::new("interval")
+                        .short('i')
+                        .long("interval")
+                        .value_name("MINUTES")
+                        .help("minutes between runs (default: 30, or the [schedule] from the config)")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .conflicts_with("schedule"),
+                )
+                .arg(
+                    Arg::new("schedule")
+                        .long("schedule")
+                        .value_name("CRON")
+                        .help("run at cron times instead, e.g. \"0 8-20 * * *\" for every hour from 8:00 to 20:00"),
+                )
+                .arg(
+                    Arg::new("timezone")
+                        .long("timezone")
+                        .value_name("TZ")
+                        .help("time zone of the schedule, e.g. Europe/Moscow (default: system time zone)"),
+                ),
+        )
+        .subcommand(
+            Command::new("history")
+                .about("list runs stored in the history database or show a program's cutoff over time")
+                .arg(
+                    Arg::new("program")
+                        .short('p')
+                        .long("program")
+                        .value_name("TEXT")
+                        .help("show the cutoff history of programs whose name or funding contains TEXT"),
+                ),
+        )
+        .subcommand(
+            Command::new("generate-fixture")
+                .about("write randomized admission list pages without personal data, for testing")
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("DIR")
+                        .help("directory to write the pages to")
+                        .default_value("fixture"),
+                )
+                .arg(
+                    Arg::new("programs")
+                        .long("programs")
+                        .value_name("N")
+                        .help("programs, each with a budget and a commercial list")
+                        .value_parser(clap::value_parser!(u64).range(1..=fixture::PROGRAM_NAMES.len() as u64))
+                        .default_value("4"),
+                )
+                .arg(
+                    Arg::new("applicants")
+                        .long("applicants")
+                        .value_name("N")
+                        .help("applicants, each applying to one to five lists")
+                        .value_parser(clap::value_parser!(u64).range(1..=100_000))
+                        .default_value("300"),
+                )
+                .arg(
+                    Arg::new("pages")
+                        .long("pages")
+                        .value_name("N")
+                        .help("HTML pages to spread the lists over")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .default_value("2"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .long("seed")
+                        .value_name("N")
+                        .help("random seed; the same seed gives the same pages")
+                        .value_parser(clap::value_parser!(u64))
+                        .default_value("1"),
+                ),
+        )
+        .subcommand(Command::new("init").about("create the configuration interactively, checking the sources with a test scrape"))
+        .subcommand(
+            Command::new("config")
+                .about("inspect the configuration")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("show")
+                        .about("print the effective configuration after profile, ABIT_* variables and flags"),
+                ),
+        )
+        .subcommand(
+            Command::new("serve")
+                .about("serve a local web dashboard for the latest run")
+                .arg(
+                    Arg::new("bind")
+                        .long("bind")
+                        .value_name("ADDR")
+                        .help("address to listen on")
+                        .default_value("127.0.0.1:8080"),
+                ),
+        );
+    #[cfg(feature = "grpc")]
+    let command = command.subcommand(
+        Command::new("grpc")
+            .about("serve the Analyze and GetStatus RPCs of proto/analyzer.proto for the latest run")
+            .arg(
+                Arg::new("bind")
+                    .long("bind")
+                    .value_name("ADDR")
+                    .help("address to listen on")
+                    .default_value("127.0.0.1:50051"),
+            ),
+    );
+    command
+}
+
+fn snapshot_arg() -> Arg {
+    Arg::new("snapshot")
+        .value_name("SNAPSHOT")
+        .help("snapshot file, run directory or @ID of a stored run (default: latest run)")
+}
+
+/// Config overrides from the command line: `--set KEY=VALUE` in order, then the dedicated flags
+pub fn config_overrides(matches: &ArgMatches) -> Result<Vec<(String, String)>> {
+    let mut overrides = Vec::new();
+    for assignment in matches.get_many::<String>("set").into_iter().flatten() {
+        overrides.push(overrides::parse_assignment(assignment)?);
+    }
+
+    let mut set = |key: &str, value: String| overrides.push((key.to_string(), value));
+    if let Some(snils) = matches.get_one::<String>("snils") {
+        set("target_snils", snils.clone());
+    }
+    if let Some(mode) = matches.get_one::<String>("data_source_mode").filter(|mode| !mode.is_empty()) {
+        set("data_source_mode", mode.clone());
+    }
+    if let Some(formats) = matches.get_many::<String>("format") {
+        let formats: Vec<OutputFormat> = formats.map(|value| value.parse()).collect::<Result<_, _>>()?;
+        let names: Vec<&str> = formats.iter().map(OutputFormat::name).collect();
+        set("output_formats", names.join(","));
+    }
+    if let Some(lang) = matches.get_one::<String>("lang") {
+        set("language", lang.clone());
+    }
+    if let Some(funding) = matches.get_one::<String>("funding") {
+        set("funding", funding.clone());
+    }
+    if matches.get_flag("anonymize") {
+        set("anonymize", "true".to_string());
+    }
+    if matches.get_flag("no_color") {
+        set("console_color", "false".to_string());
+    }
+    if let Some(log_file) = matches.get_one::<String>("log_file") {
+        set("log_file", log_file.clone());
+    }
+    Ok(overrides)
+}
+
+/// Options shared by all subcommands, merged from the config file, environment and command line
+#[derive(Clone)]
+pub struct RunSettings {
+    pub config: Config,
+    pub target_snils: String,
+    pub data_source_mode: DataSourceMode,
+    pub output_formats: Vec<OutputFormat>,
+    pub anonymize: bool,
+    pub color: bool,
+    pub output_root: String,
+    pub output_retention: usize,
+    pub csv_dialect: CsvDialect,
+    pub identities: IdentityResolver,
+    pub scoring: ScoringRules,
+    // Snapshot analyzed instead of the sources, from --from-snapshot
+    pub from_snapshot: Option<String>,
+    // Copy of the run's snapshot to keep, from --save-snapshot
+    pub save_snapshot: Option<PathBuf>,
+    // Reuse the pages of an interrupted scrape, from --resume
+    pub resume: bool,
+}
+
+impl RunSettings {
+    pub fn new(config: Config) -> Result<Self> {
+        debug!("{}", t!("config.mode_from_config", format!("{:?}", config.data_source_mode)));
+        Ok(Self {
+            target_snils: config.target_snils.clone(),
+            data_source_mode: config.data_source_mode.clone(),
+            output_formats: config.output_formats.clone().unwrap_or_else(|| vec![OutputFormat::Csv]),
+            anonymize: config.anonymize.unwrap_or(false),
+            color: config.console_color.unwrap_or(true),
+            output_root: config.output_directory.clone().unwrap_or_else(|| "output".to_string()),
+            output_retention: config.output_retention.unwrap_or(10),
+            csv_dialect: CsvDialect::from_options(&config.csv.clone().unwrap_or_default(), &config.reports.clone().unwrap_or_default())?,
+            identities: IdentityResolver::new(config.identity.as_ref())?,
+            scoring: ScoringRules::new(config.scoring.as_deref())?,
+            from_snapshot: None,
+            save_snapshot: None,
+            resume: false,
+            config,
+        })
+    }
+
+    /// Take the snapshot to analyze, the one to save and whether to resume from the command line
+    pub fn with_snapshot_options(mut self, matches: &ArgMatches) -> Self {
+        self.from_snapshot = matches.get_one::<String>("from_snapshot").cloned();
+        self.save_snapshot = matches.get_one::<String>("save_snapshot").map(PathBuf::from);
+        self.resume = matches.get_flag("resume");
+        self
+    }
+
+    /// Directory of the most recent run
+    pub fn latest_dir(&self) -> PathBuf {
+        if self.output_retention > 0 {
+            Path::new(&self.output_root).join("latest")
+        } else {
+            PathBuf::from(&self.output_root)
+        }
+    }
+
+    /// History database shared by all runs
+    pub fn database_path(&self) -> PathBuf {
+        Path::new(&self.output_root).join(abitur_analyzer::storage::DATABASE_FILE)
+    }
+
+    /// Snapshot file given as a file or run directory, or the latest run's snapshot
+    pub fn snapshot_path(&self, arg: Option<&String>) -> PathBuf {
+        let path = arg.map(PathBuf::from).unwrap_or_else(|| self.latest_dir());
+        if path.is_dir() {
+            path.join(abitur_analyzer::snapshot::SNAPSHOT_FILE)
+        } else {
+            path
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use crate::analyzer::TargetOutcome;
+use crate::models::{FundingSource, ProgramKey};
+use serde::Serialize;
+
+/// The target applicant's outcome in one program before and after
+#[derive(Debug, Clone, Serialize)]
+pub struct OutcomeChange {
+    pub program_key: ProgramKey,
+    pub program_name: String,
+    pub funding_source: FundingSource,
+    pub before: Option<TargetOutcome>,
+    pub after: Option<TargetOutcome>,
+}
+
+impl OutcomeChange {
+    /// Status, cutoff, position or seats differ between the two runs
+    pub fn is_changed(&self) -> bool {
+        match (&self.before, &self.after) {
+            (Some(before), Some(after)) => {
+                before.status != after.status
+                    || (before.cutoff_score - after.cutoff_score).abs() > f64::EPSILON
+                    || before.position_in_admitted != after.position_in_admitted
+                    || before.available_places != after.available_places
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Change worth a notification, detected by [`significant_events`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ChangeEvent {
+    /// The target was admitted before and is not anymore
+    DroppedOut {
+        program_name: String,
+        funding_source: FundingSource,
+    },
+    /// The cutoff score rose above the target's score
+    CutoffAboveTarget {
+        program_name: String,
+        funding_source: FundingSource,
+        cutoff_score: f64,
+        target_score: f64,
+    },
+    /// The program got more places
+    SeatsOpened {
+        program_name: String,
+        funding_source: FundingSource,
+        places_before: u32,
+        places_after: u32,
+    },
+}
+
+/// Events for the target's programs that deserve an alert
+pub fn significant_events(changes: &[OutcomeChange]) -> Vec<ChangeEvent> {
+    let mut events = Vec::new();
+    for change in changes {
+        let program_name = change.program_name.clone();
+        let funding_source = change.funding_source.clone();
+        let was_admitted = change.before.as_ref().is_some_and(|before| before.status == "Admitted");
+        let is_admitted = change.after.as_ref().is_some_and(|after| after.status == "Admitted");
+        if was_admitted && !is_admitted {
+            events.push(ChangeEvent::DroppedOut {
+                program_name: program_name.clone(),
+                funding_source: funding_source.clone(),
+            });
+        }
+
+        let (Some(before), Some(after)) = (&change.before, &change.after) else {
+            continue;
+        };
+        if let Some(target_score) = after.target_score {
+            if before.cutoff_score <= target_score && after.cutoff_score > target_score {
+                events.push(ChangeEvent::CutoffAboveTarget {
+                    program_name: program_name.clone(),
+                    funding_source: funding_source.clone(),
+                    cutoff_score: after.cutoff_score,
+                    target_score,
+                });
+            }
+        }
+        if after.available_places > before.available_places {
+            events.push(ChangeEvent::SeatsOpened {
+                program_name,
+                funding_source,
+                places_before: before.available_places,
+                places_after: after.available_places,
+            });
+        }
+    }
+    events
+}
+
+/// Pair outcomes of programs the target applied to in either run, in the order of `after`
+pub fn compare_outcomes(before: &[TargetOutcome], after: &[TargetOutcome]) -> Vec<OutcomeChange> {
+    let applied = |outcome: &&TargetOutcome| outcome.status != "Not_Applied";
+    let mut changes: Vec<OutcomeChange> = after
+        .iter()
+        .filter(applied)
+        .map(|outcome| OutcomeChange {
+            program_key: outcome.program_key.clone(),
+            program_name: outcome.program_name.clone(),
+            funding_source: outcome.funding_source.clone(),
+            before: before
+                .iter()
+                .filter(applied)
+                .find(|old| old.program_key == outcome.program_key)
+                .cloned(),
+            after: Some(outcome.clone()),
+        })
+        .collect();
+
+    for outcome in before.iter().filter(applied) {
+        if !changes.iter().any(|change| change.program_key == outcome.program_key) {
+            changes.push(OutcomeChange {
+                program_key: outcome.program_key.clone(),
+                program_name: outcome.program_name.clone(),
+                funding_source: outcome.funding_source.clone(),
+                before: Some(outcome.clone()),
+                after: None,
+            });
+        }
+    }
+    changes
+}
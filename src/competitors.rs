@@ -0,0 +1,192 @@
+use crate::models::{normalize_snils, CompetitorOptions, ProgramKey};
+use crate::report_model::{ReportModel, ReportRow};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Applicants listed on each side of the cutoff when `boundary` is not set
+pub const DEFAULT_BOUNDARY: usize = 5;
+
+/// Another application of a competitor
+#[derive(Debug, Clone, Serialize)]
+pub struct OtherApplication {
+    pub program_key: ProgramKey,
+    pub priority: u32,
+}
+
+/// An applicant in one of the target's programs scoring at least as high as the target
+#[derive(Debug, Clone, Serialize)]
+pub struct Competitor {
+    // Program the competitor meets the target in
+    pub program_key: ProgramKey,
+    pub snils: String,
+    pub rank: u32,
+    pub priority: u32,
+    pub score: Option<f64>,
+    pub target_priority: u32,
+    pub target_score: f64,
+    pub eager: bool,
+    pub privileged: bool,
+    // Handed in the original after the target did, for lists that show when
+    pub original_after_target: bool,
+    // Applications to the other programs, by priority
+    pub other_applications: Vec<OtherApplication>,
+    // Program the simulation seats the competitor in, None without a seat
+    pub admitted_to: Option<ProgramKey>,
+}
+
+impl Competitor {
+    /// The simulation seats the competitor in another program, leaving the seat here
+    pub fn admitted_elsewhere(&self) -> bool {
+        self.admitted_to.as_ref().is_some_and(|admitted_to| *admitted_to != self.program_key)
+    }
+}
+
+/// An applicant close to the cutoff of a program of interest
+#[derive(Debug, Clone, Serialize)]
+pub struct BoundaryApplicant {
+    pub program_key: ProgramKey,
+    // Projected cutoff of the program, or the simulated one without a projection
+    pub cutoff_score: f64,
+    // Scores at or above the cutoff
+    pub above: bool,
+    pub snils: String,
+    pub rank: u32,
+    pub priority: u32,
+    pub score: f64,
+    pub consent: bool,
+    pub original: bool,
+    pub eager: bool,
+    pub target: bool,
+    // Program the simulation seats the applicant in, None without a seat
+    pub admitted_to: Option<ProgramKey>,
+}
+
+/// Applicants who apply to any of the target's programs with a score at or above the
+/// target's there, in popularity order of the programs and rank order within them
+///
+/// Privileged applicants are seated ahead of everyone, so they are listed whatever their
+/// score. `options` narrow the list down to eager applicants, some programs or those the
+/// simulation does not seat elsewhere.
+pub fn find(report: &ReportModel, options: &CompetitorOptions) -> Vec<Competitor> {
+    let programs: Vec<String> = options.programs.iter().flatten().map(|program| program.to_lowercase()).collect();
+
+    // Every application of every applicant, to annotate the competitors
+    let mut applications: HashMap<String, Vec<(&ProgramKey, &ReportRow)>> = HashMap::new();
+    for program in &report.programs {
+        for row in &program.rows {
+            applications.entry(normalize_snils(&row.record.snils)).or_default().push((&program.program_key, row));
+        }
+    }
+
+    let mut competitors = Vec::new();
+    for program in &report.programs {
+        let Some((target_priority, target_score, target_original_at)) =
+            program.target_row().and_then(|row| Some((row.record.priority, row.record.score?, row.record.original_at)))
+        else {
+            continue;
+        };
+        let name = program.program_name.to_lowercase();
+        if !programs.is_empty() && !programs.iter().any(|fragment| name.contains(fragment.as_str())) {
+            continue;
+        }
+
+        for row in program.rows.iter().filter(|row| !row.target) {
+            let record = &row.record;
+            if !(record.privileged || record.score.is_some_and(|score| score >= target_score)) {
+                continue;
+            }
+            if options.eager_only.unwrap_or(false) && !row.eager {
+                continue;
+            }
+
+            let own = applications.get(&normalize_snils(&record.snils)).map_or(&[][..], Vec::as_slice);
+            let mut other_applications: Vec<OtherApplication> = own
+                .iter()
+                .filter(|(program_key, _)| **program_key != program.program_key)
+                .map(|(program_key, row)| OtherApplication {
+                    program_key: (*program_key).clone(),
+                    priority: row.record.priority,
+                })
+                .collect();
+            other_applications.sort_by(|a, b| a.priority.cmp(&b.priority).then_with(|| a.program_key.cmp(&b.program_key)));
+            let admitted_to = own.iter().find(|(_, row)| row.admitted).map(|(program_key, _)| (*program_key).clone());
+
+            let competitor = Competitor {
+                program_key: program.program_key.clone(),
+                snils: record.snils.clone(),
+                rank: record.rank,
+                priority: record.priority,
+                score: record.score,
+                target_priority,
+                target_score,
+                eager: row.eager,
+                privileged: record.privileged,
+                original_after_target: record.original_at.zip(target_original_at).is_some_and(|(original_at, target)| original_at > target),
+                other_applications,
+                admitted_to,
+            };
+            if options.staying_only.unwrap_or(false) && competitor.admitted_elsewhere() {
+                continue;
+            }
+            competitors.push(competitor);
+        }
+    }
+    competitors
+}
+
+/// The `boundary` applicants scoring closest above the cutoff and those closest below it
+/// in every program of interest, best rank first
+///
+/// Programs of interest are those in `programs_of_interest`, or the target's programs if
+/// it is not set. Privileged applicants are seated whatever their score and left out.
+pub fn boundary(report: &ReportModel, programs_of_interest: Option<&[String]>, options: &CompetitorOptions) -> Vec<BoundaryApplicant> {
+    let size = options.boundary.unwrap_or(DEFAULT_BOUNDARY);
+    let mut admitted_to: HashMap<String, &ProgramKey> = HashMap::new();
+    for program in &report.programs {
+        for row in program.admitted_rows() {
+            admitted_to.insert(normalize_snils(&row.record.snils), &program.program_key);
+        }
+    }
+
+    let mut applicants = Vec::new();
+    for program in &report.programs {
+        let of_interest = match programs_of_interest {
+            Some(programs) => programs.contains(&program.program_name),
+            None => program.target_row().is_some(),
+        };
+        let cutoff_score = program.projected_cutoff_score.unwrap_or(program.cutoff_score);
+        if !of_interest || size == 0 || cutoff_score <= 0.0 {
+            continue;
+        }
+
+        // Highest score first, published rank among equal scores
+        let mut scored: Vec<(&ReportRow, f64)> = program
+            .rows
+            .iter()
+            .filter(|row| !row.record.privileged)
+            .filter_map(|row| Some((row, row.record.score?)))
+            .collect();
+        scored.sort_by(|(a, a_score), (b, b_score)| b_score.total_cmp(a_score).then(a.record.rank.cmp(&b.record.rank)));
+        let split = scored.partition_point(|(_, score)| *score >= cutoff_score);
+        let closest = split.saturating_sub(size)..(split + size).min(scored.len());
+
+        for (index, &(row, score)) in scored.iter().enumerate().take(closest.end).skip(closest.start) {
+            let record = &row.record;
+            applicants.push(BoundaryApplicant {
+                program_key: program.program_key.clone(),
+                cutoff_score,
+                above: index < split,
+                snils: record.snils.clone(),
+                rank: record.rank,
+                priority: record.priority,
+                score,
+                consent: record.has_consent(),
+                original: record.has_original_document(),
+                eager: row.eager,
+                target: row.target,
+                admitted_to: admitted_to.get(&normalize_snils(&record.snils)).map(|program_key| (*program_key).clone()),
+            });
+        }
+    }
+    applicants
+}
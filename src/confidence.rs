@@ -0,0 +1,147 @@
+use crate::identity;
+use crate::models::{ProgramKey, StudentRecord};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+// Shares of applications with an unreadable score that lower the grade to medium and low
+const UNPARSED_SCORES_MEDIUM: f64 = 0.05;
+const UNPARSED_SCORES_LOW: f64 = 0.2;
+
+// Shares of applications with a masked SNILS that lower the grade to medium and low
+const MASKED_SNILS_MEDIUM: f64 = 0.1;
+const MASKED_SNILS_LOW: f64 = 0.5;
+
+// Age in hours of the oldest list of a program that lowers the grade to medium and low
+const STALE_HOURS_MEDIUM: i64 = 24;
+const STALE_HOURS_LOW: i64 = 72;
+
+/// How far the simulated outcome of a program can be trusted, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Grade {
+    High,
+    Medium,
+    Low,
+}
+
+impl Grade {
+    /// Name as written in JSON and used by templates
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::High => "high",
+            Self::Medium => "medium",
+            Self::Low => "low",
+        }
+    }
+
+    /// Name in the selected language, as reports write it
+    pub fn label(self) -> String {
+        match self {
+            Self::High => t!("confidence.high"),
+            Self::Medium => t!("confidence.medium"),
+            Self::Low => t!("confidence.low"),
+        }
+    }
+}
+
+/// Grade of `confidence` in the selected language, "-" if it was not assessed
+pub fn label(confidence: Option<&Confidence>) -> String {
+    confidence.map_or_else(|| "-".to_string(), |confidence| confidence.grade.label())
+}
+
+/// Confidence in the simulation of one program-funding combination and what lowered it
+#[derive(Debug, Clone, Serialize)]
+pub struct Confidence {
+    pub grade: Grade,
+    // Share of applications whose score could not be read, privileged ones aside
+    pub unparsed_scores: f64,
+    // Share of applications identified by a masked SNILS only
+    pub masked_snils: f64,
+    // Hours since the oldest list of the combination was read, if the records tell
+    pub data_age_hours: Option<i64>,
+    // A source of the combination changed its table columns since the last run
+    pub layout_changed: bool,
+    // Why the grade is not high, in the selected language
+    pub reasons: Vec<String>,
+}
+
+/// Share of the applications of every program-funding combination identified by a masked
+/// SNILS only
+///
+/// Taken before anonymizing, which turns masked and full SNILS alike into pseudonyms.
+pub fn masked_shares(all_program_records: &[(String, Vec<StudentRecord>)]) -> BTreeMap<ProgramKey, f64> {
+    by_program(all_program_records)
+        .into_iter()
+        .map(|(program_key, records)| {
+            let masked = records.iter().filter(|record| identity::is_masked(&record.snils)).count();
+            (program_key, masked as f64 / records.len() as f64)
+        })
+        .collect()
+}
+
+/// Grade every program-funding combination of `all_program_records` from the quality of its data
+///
+/// Unreadable scores and masked SNILS make applicants compete with the wrong score or as
+/// someone else, old lists miss the latest consents, and a changed table layout may have
+/// shifted columns. The grade is the lowest any of them gives; `masked_snils` are the shares
+/// from [`masked_shares`] and `layout_changes` the sources whose columns changed in this run.
+pub fn assess(
+    all_program_records: &[(String, Vec<StudentRecord>)],
+    masked_snils: &BTreeMap<ProgramKey, f64>,
+    layout_changes: &BTreeSet<String>,
+    now: DateTime<Local>,
+) -> BTreeMap<ProgramKey, Confidence> {
+    by_program(all_program_records)
+        .into_iter()
+        .map(|(program_key, records)| {
+            let unparsed = records.iter().filter(|record| record.score.is_none() && !record.privileged).count();
+            let unparsed_scores = unparsed as f64 / records.len() as f64;
+            let masked_snils = masked_snils.get(&program_key).copied().unwrap_or_default();
+            let data_age_hours = records
+                .iter()
+                .filter_map(|record| record.provenance.as_ref())
+                .map(|provenance| (now - provenance.scraped_at).num_hours())
+                .max();
+            let layout_changed = records
+                .iter()
+                .filter_map(|record| record.provenance.as_ref())
+                .any(|provenance| layout_changes.contains(&provenance.source));
+
+            let mut grade = Grade::High;
+            let mut reasons = Vec::new();
+            let mut lower = |to: Grade, reason: String| {
+                grade = grade.max(to);
+                reasons.push(reason);
+            };
+            if unparsed_scores > UNPARSED_SCORES_MEDIUM {
+                let to = if unparsed_scores > UNPARSED_SCORES_LOW { Grade::Low } else { Grade::Medium };
+                lower(to, t!("confidence.unparsed_scores", format!("{:.0}", unparsed_scores * 100.0)));
+            }
+            if masked_snils > MASKED_SNILS_MEDIUM {
+                let to = if masked_snils > MASKED_SNILS_LOW { Grade::Low } else { Grade::Medium };
+                lower(to, t!("confidence.masked_snils", format!("{:.0}", masked_snils * 100.0)));
+            }
+            if let Some(hours) = data_age_hours.filter(|hours| *hours >= STALE_HOURS_MEDIUM) {
+                let to = if hours >= STALE_HOURS_LOW { Grade::Low } else { Grade::Medium };
+                lower(to, t!("confidence.stale", hours));
+            }
+            if layout_changed {
+                lower(Grade::Medium, t!("confidence.layout_changed"));
+            }
+
+            let confidence = Confidence { grade, unparsed_scores, masked_snils, data_age_hours, layout_changed, reasons };
+            (program_key, confidence)
+        })
+        .collect()
+}
+
+fn by_program(all_program_records: &[(String, Vec<StudentRecord>)]) -> BTreeMap<ProgramKey, Vec<&StudentRecord>> {
+    let mut programs: BTreeMap<ProgramKey, Vec<&StudentRecord>> = BTreeMap::new();
+    for (program_name, records) in all_program_records {
+        for record in records {
+            programs.entry(ProgramKey::of(program_name, record)).or_default().push(record);
+        }
+    }
+    programs
+}
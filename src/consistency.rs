@@ -0,0 +1,141 @@
+use crate::models::{normalize_snils, ProgramKey, Provenance, ScoreResolution, StudentRecord};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Field of an applicant's record that differs between their lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Score,
+    PsychologicalTest,
+}
+
+/// Value of the field in one list
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedValue {
+    pub program_key: ProgramKey,
+    pub value: String,
+    // Where the list's record was read from
+    pub provenance: Option<Provenance>,
+}
+
+/// One applicant's field with different values in different lists
+#[derive(Debug, Clone, Serialize)]
+pub struct Inconsistency {
+    pub snils: String,
+    pub field: Field,
+    pub values: Vec<ListedValue>,
+    // Value the simulation used in all lists, `None` if each list keeps its own
+    pub resolved: Option<String>,
+}
+
+/// Compare every applicant's score and test result across their lists and settle score
+/// differences with `resolution`
+///
+/// Run after identity resolution, so applicants listed under different identifiers are
+/// compared too. Test results are only reported; a missing one ("-") is not a difference.
+pub fn reconcile(
+    all_program_records: &mut [(String, Vec<StudentRecord>)],
+    resolution: ScoreResolution,
+) -> Vec<Inconsistency> {
+    // Applicant -> (list index, record index) of all their records
+    let mut applicants: BTreeMap<String, Vec<(usize, usize)>> = BTreeMap::new();
+    for (list_index, (_, records)) in all_program_records.iter().enumerate() {
+        for (record_index, record) in records.iter().enumerate() {
+            applicants
+                .entry(normalize_snils(&record.snils))
+                .or_default()
+                .push((list_index, record_index));
+        }
+    }
+
+    let mut inconsistencies = Vec::new();
+    for positions in applicants.values().filter(|positions| positions.len() > 1) {
+        let (first_list, first_record) = positions[0];
+        let snils = all_program_records[first_list].1[first_record].snils.clone();
+
+        let scores = listed(all_program_records, positions, |record| Some(record.average_score.clone()));
+        let tests = listed(all_program_records, positions, |record| {
+            let result = record.psychological_test.trim();
+            (!result.is_empty() && result != "-").then(|| result.to_string())
+        });
+        let parsed: Vec<Option<f64>> = positions
+            .iter()
+            .map(|&(list_index, record_index)| all_program_records[list_index].1[record_index].score)
+            .collect();
+        if parsed.iter().any(|score| !same_score(*score, parsed[0])) {
+            let resolved = resolve(&parsed, resolution);
+            if let Some(score) = resolved {
+                for &(list_index, record_index) in positions {
+                    let record = &mut all_program_records[list_index].1[record_index];
+                    if record.score.is_some() {
+                        record.set_numeric_score(score);
+                    }
+                }
+            }
+            inconsistencies.push(Inconsistency {
+                snils: snils.clone(),
+                field: Field::Score,
+                values: scores,
+                resolved: resolved.map(|score| format!("{:.4}", score)),
+            });
+        }
+
+        if tests.iter().any(|test| test.value != tests[0].value) {
+            inconsistencies.push(Inconsistency {
+                snils,
+                field: Field::PsychologicalTest,
+                values: tests,
+                resolved: None,
+            });
+        }
+    }
+    inconsistencies
+}
+
+/// `value` of the records at `positions` with their lists, records without one are left out
+fn listed(
+    all_program_records: &[(String, Vec<StudentRecord>)],
+    positions: &[(usize, usize)],
+    value: impl Fn(&StudentRecord) -> Option<String>,
+) -> Vec<ListedValue> {
+    positions
+        .iter()
+        .filter_map(|&(list_index, record_index)| {
+            let (program_name, records) = &all_program_records[list_index];
+            let record = &records[record_index];
+            value(record).map(|value| ListedValue {
+                program_key: ProgramKey::of(program_name, record),
+                value,
+                provenance: record.provenance.clone(),
+            })
+        })
+        .collect()
+}
+
+fn same_score(a: Option<f64>, b: Option<f64>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => (a - b).abs() < 1e-6,
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
+
+/// Score to use in all lists; scores that are not numbers are left out
+fn resolve(scores: &[Option<f64>], resolution: ScoreResolution) -> Option<f64> {
+    let scores: Vec<f64> = scores.iter().flatten().copied().collect();
+    match resolution {
+        ScoreResolution::Keep => None,
+        ScoreResolution::Highest => scores.iter().copied().reduce(f64::max),
+        ScoreResolution::Lowest => scores.iter().copied().reduce(f64::min),
+        ScoreResolution::MostCommon => {
+            let count = |score: f64| scores.iter().filter(|other| same_score(Some(**other), Some(score))).count();
+            scores.iter().copied().reduce(|best, score| {
+                match count(score).cmp(&count(best)) {
+                    std::cmp::Ordering::Greater => score,
+                    std::cmp::Ordering::Equal if score > best => score,
+                    _ => best,
+                }
+            })
+        }
+    }
+}
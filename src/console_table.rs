@@ -0,0 +1,661 @@
+use crate::analyzer::{self, AdmissionAnalysis, AdmissionDistance, TargetOutcome};
+use crate::backtest::VariantScore;
+use crate::capacity::CapacitySensitivity;
+use crate::compare::OutcomeChange;
+use crate::confidence::{self, Confidence, Grade};
+use crate::enrollment_order::Validation;
+use crate::i18n;
+use crate::models::{normalize_snils, ProgramKey, StudentRecord};
+use crate::priorities::PriorityOption;
+use crate::single_program::ProgramEstimate;
+use crate::storage::{ProgramHistory, StoredRun};
+use crate::verdict;
+use comfy_table::presets::UTF8_FULL_CONDENSED;
+use comfy_table::{Cell, CellAlignment, Color, ContentArrangement, Table};
+
+/// Print an aligned table with the target applicant's outcome per program
+///
+/// Rows are colored green/yellow/red for admitted/borderline/not admitted; with `color`
+/// disabled (or when stdout is not a terminal) no ANSI codes are emitted.
+pub fn print_final_cutoff_table(
+    target_snils: &str,
+    analysis: &AdmissionAnalysis,
+    target_outcomes: &[TargetOutcome],
+    color: bool,
+) {
+    // Projected cutoffs are only computed with originals_only
+    let projected = target_outcomes.iter().any(|outcome| outcome.projected_cutoff_score.is_some());
+    // Only until campaign.quota_transfer, when quota places are left unfilled
+    let transfer = target_outcomes.iter().any(|outcome| outcome.transfer_cutoff_score.is_some());
+    let mut header = vec![
+        String::new(),
+        t!("report.program"),
+        t!("report.funding"),
+        t!("report.priority"),
+        t!("report.places"),
+        t!("report.cutoff_score"),
+    ];
+    if projected {
+        header.push(t!("report.projected_short"));
+    }
+    if transfer {
+        header.push(t!("report.transfer_short"));
+    }
+    header.extend([
+        t!("report.target_score"),
+        t!("report.per_place"),
+        t!("report.avg_priority"),
+        t!("report.position"),
+        t!("report.status"),
+        t!("confidence.header"),
+    ]);
+    let columns = header.len();
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(header);
+    if !color {
+        table.force_no_tty();
+    }
+
+    for outcome in target_outcomes.iter().filter(|outcome| outcome.status != "Not_Applied") {
+        let Some(popularity) = analysis
+            .program_popularities
+            .iter()
+            .find(|popularity| popularity.program_key == outcome.program_key)
+        else {
+            continue;
+        };
+        let (icon, status_color) = match outcome.status.as_str() {
+            "Admitted" => ("✅", Color::Green),
+            "Admitted_ByScore_NotByPriority" => ("🟡", Color::Yellow),
+            "No_Places" => ("❔", Color::DarkGrey),
+            _ => ("❌", Color::Red),
+        };
+        let eager_per_place = match popularity.available_places {
+            0 => "-".to_string(),
+            places => format!("{:.1}", popularity.eager_applicants.len() as f64 / places as f64),
+        };
+        let position = outcome
+            .position_in_admitted
+            .map(|position| t!("report.of", position, outcome.admitted_count))
+            .unwrap_or_else(|| "-".to_string());
+
+        let mut row = vec![
+            Cell::new(icon),
+            Cell::new(&outcome.program_name),
+            Cell::new(&outcome.funding_source),
+            Cell::new(outcome.priority.unwrap_or(0)),
+            Cell::new(i18n::places_label(outcome.available_places, outcome.published_places)),
+            Cell::new(format!("{:.4}", outcome.cutoff_score)),
+        ];
+        if projected {
+            row.push(Cell::new(
+                outcome.projected_cutoff_score.map_or_else(|| "-".to_string(), |score| format!("{:.4}", score)),
+            ));
+        }
+        if transfer {
+            row.push(Cell::new(match (outcome.transfer_cutoff_score, outcome.quota_places_returned) {
+                (Some(score), Some(places)) => format!("{:.4} (+{})", score, places),
+                (Some(score), None) => format!("{:.4}", score),
+                _ => "-".to_string(),
+            }));
+        }
+        row.extend([
+            Cell::new(format!("{:.4}", outcome.target_score.unwrap_or(0.0))).fg(status_color),
+            Cell::new(eager_per_place),
+            Cell::new(format!("{:.2}", popularity.top_candidates_average_priority)),
+            Cell::new(position),
+            Cell::new(i18n::status_label(&outcome.status)).fg(status_color),
+            Cell::new(confidence::label(outcome.confidence.as_ref())).fg(confidence_color(outcome.confidence.as_ref())),
+        ]);
+        table.add_row(row);
+    }
+    for column in 3..columns - 2 {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+
+    println!("{}", t!("final.header", target_snils));
+    println!("{}", table);
+    if let Some(line) = best_option_line(target_outcomes) {
+        println!("{}", line);
+    }
+}
+
+fn confidence_color(confidence: Option<&Confidence>) -> Color {
+    match confidence.map(|confidence| confidence.grade) {
+        Some(Grade::High) => Color::Green,
+        Some(Grade::Medium) => Color::Yellow,
+        Some(Grade::Low) => Color::Red,
+        None => Color::DarkGrey,
+    }
+}
+
+/// Best overall option below the table, only when the outcomes span several institutions
+pub fn best_option_line(target_outcomes: &[TargetOutcome]) -> Option<String> {
+    let mut institutions: Vec<&str> = target_outcomes.iter().map(|outcome| outcome.institution.as_str()).collect();
+    institutions.sort_unstable();
+    institutions.dedup();
+    if institutions.len() < 2 {
+        return None;
+    }
+    Some(match analyzer::best_option(target_outcomes) {
+        Some(best) => t!(
+            "report.best_option",
+            best.program_name,
+            best.funding_source,
+            best.priority.unwrap_or(0),
+            format!("{:.4}", best.target_score.unwrap_or(0.0) - best.cutoff_score)
+        ),
+        None => t!("report.no_best_option"),
+    })
+}
+
+/// Print the target's programs ranked by seats remaining among unblocked eager applicants
+pub fn print_admission_distance_table(admission_distances: &[AdmissionDistance], color: bool) {
+    if admission_distances.is_empty() {
+        return;
+    }
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            "#".to_string(),
+            t!("report.program"),
+            t!("report.funding"),
+            t!("report.priority"),
+            t!("report.places"),
+            t!("distance.position"),
+            t!("distance.seats_remaining"),
+        ]);
+    if !color {
+        table.force_no_tty();
+    }
+
+    for (index, distance) in admission_distances.iter().enumerate() {
+        let seats_color = if distance.seats_remaining > 0 { Color::Green } else { Color::Red };
+        table.add_row(vec![
+            Cell::new(index + 1),
+            Cell::new(&distance.program_name),
+            Cell::new(&distance.funding_source),
+            Cell::new(distance.priority),
+            Cell::new(i18n::places_label(distance.available_places, distance.published_places)),
+            Cell::new(t!("report.of", distance.position, distance.unblocked_eager + 1)),
+            Cell::new(distance.seats_remaining).fg(seats_color),
+        ]);
+    }
+    for column in [0, 3, 4, 5, 6] {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+
+    println!("{}", t!("distance.header"));
+    println!("{}", table);
+}
+
+/// Print programs where the target's outcome changed as `before → after` columns
+pub fn print_changes_table(target_snils: &str, changes: &[OutcomeChange], color: bool) {
+    println!("{}", t!("compare.header", target_snils));
+    let changed: Vec<&OutcomeChange> = changes.iter().filter(|change| change.is_changed()).collect();
+    if changed.is_empty() {
+        println!("{}", t!("compare.no_changes"));
+        return;
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            t!("report.program"),
+            t!("report.funding"),
+            t!("report.places"),
+            t!("report.cutoff_score"),
+            t!("report.position"),
+            t!("report.status"),
+        ]);
+    if !color {
+        table.force_no_tty();
+    }
+
+    let transition = |value: fn(&TargetOutcome) -> String, change: &OutcomeChange| {
+        let before = change.before.as_ref().map(value).unwrap_or_else(|| "-".to_string());
+        let after = change.after.as_ref().map(value).unwrap_or_else(|| "-".to_string());
+        if before == after {
+            after
+        } else {
+            format!("{} → {}", before, after)
+        }
+    };
+    for change in changed {
+        let status_color = match change.after.as_ref().map(|outcome| outcome.status.as_str()) {
+            Some("Admitted") => Color::Green,
+            Some("Admitted_ByScore_NotByPriority") => Color::Yellow,
+            Some("No_Places") => Color::DarkGrey,
+            _ => Color::Red,
+        };
+        table.add_row(vec![
+            Cell::new(&change.program_name),
+            Cell::new(&change.funding_source),
+            Cell::new(transition(|outcome| i18n::places_label(outcome.available_places, outcome.published_places), change)),
+            Cell::new(transition(|outcome| format!("{:.4}", outcome.cutoff_score), change)),
+            Cell::new(transition(
+                |outcome| outcome.position_in_admitted.map(|position| position.to_string()).unwrap_or_else(|| "-".to_string()),
+                change,
+            )),
+            Cell::new(transition(|outcome| i18n::status_label(&outcome.status), change)).fg(status_color),
+        ]);
+    }
+    println!("{}", table);
+}
+
+/// Print every parsed list with its size and the target's priority in it, for `--dry-run`
+pub fn print_sources_table(target_snils: &str, all_program_records: &[(String, Vec<StudentRecord>)], color: bool) {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            t!("report.program"),
+            t!("report.funding"),
+            t!("dry_run.study_form"),
+            t!("report.places"),
+            t!("dry_run.applications"),
+            t!("dry_run.withdrawn"),
+            t!("dry_run.target_priority"),
+        ]);
+    if !color {
+        table.force_no_tty();
+    }
+
+    let target = normalize_snils(target_snils);
+    for (program_name, records) in all_program_records {
+        let Some(first) = records.first() else {
+            continue;
+        };
+        let target_record = records.iter().find(|record| !target.is_empty() && normalize_snils(&record.snils) == target);
+        table.add_row(vec![
+            Cell::new(program_name),
+            Cell::new(&first.funding_source),
+            Cell::new(&first.study_form),
+            Cell::new(first.available_places).set_alignment(CellAlignment::Right),
+            Cell::new(records.len()).set_alignment(CellAlignment::Right),
+            Cell::new(records.iter().filter(|record| record.withdrawn).count()).set_alignment(CellAlignment::Right),
+            match target_record {
+                Some(record) => Cell::new(record.priority).fg(Color::Green).set_alignment(CellAlignment::Right),
+                None => Cell::new("-").set_alignment(CellAlignment::Right),
+            },
+        ]);
+    }
+    println!("{}", table);
+}
+
+/// Print the runs stored in the history database
+pub fn print_stored_runs_table(runs: &[StoredRun], color: bool) {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            t!("history.run"),
+            t!("history.time"),
+            t!("history.target"),
+            t!("history.programs"),
+            t!("history.lists"),
+        ]);
+    if !color {
+        table.force_no_tty();
+    }
+    for run in runs {
+        table.add_row(vec![
+            Cell::new(format!("@{}", run.id)),
+            Cell::new(run.created_at.format("%Y-%m-%d %H:%M")),
+            Cell::new(&run.target_snils),
+            Cell::new(run.programs).set_alignment(CellAlignment::Right),
+            Cell::new(if run.has_snapshot { "✓" } else { "-" }),
+        ]);
+    }
+    println!("{}", table);
+}
+
+/// Print places, eager applicants and cutoff of one program in every stored run
+pub fn print_cutoff_history_table(history: &ProgramHistory, color: bool) {
+    println!("{}", t!("history.cutoff_header", history.program_name, history.funding_source));
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            t!("history.run"),
+            t!("history.time"),
+            t!("report.places"),
+            t!("report.total_eager"),
+            t!("report.cutoff_score"),
+        ]);
+    if !color {
+        table.force_no_tty();
+    }
+    let mut previous_cutoff: Option<f64> = None;
+    for point in &history.points {
+        let cutoff_color = match previous_cutoff {
+            Some(previous) if point.cutoff_score > previous => Color::Red,
+            Some(previous) if point.cutoff_score < previous => Color::Green,
+            _ => Color::Reset,
+        };
+        table.add_row(vec![
+            Cell::new(format!("@{}", point.run_id)),
+            Cell::new(point.created_at.format("%Y-%m-%d %H:%M")),
+            Cell::new(point.available_places).set_alignment(CellAlignment::Right),
+            Cell::new(point.total_eager_applicants).set_alignment(CellAlignment::Right),
+            Cell::new(format!("{:.4}", point.cutoff_score))
+                .set_alignment(CellAlignment::Right)
+                .fg(cutoff_color),
+        ]);
+        previous_cutoff = Some(point.cutoff_score);
+    }
+    println!("{}", table);
+}
+
+/// Print the predicted and enrolled applicants of every program the orders cover, the
+/// share of predictions they confirmed and what the mismatches come from
+pub fn print_validation_table(target_snils: &str, validation: &Validation, color: bool) {
+    println!("{}", t!("validation.header", target_snils));
+    if validation.programs.is_empty() {
+        println!("{}", t!("validation.no_programs"));
+        return;
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            t!("report.program"),
+            t!("report.funding"),
+            t!("report.places"),
+            t!("validation.predicted"),
+            t!("validation.enrolled"),
+            t!("validation.confirmed"),
+            t!("report.cutoff_score"),
+            t!("validation.actual_cutoff"),
+        ]);
+    if !color {
+        table.force_no_tty();
+    }
+
+    for program in &validation.programs {
+        let confirmed_color = if program.confirmed == program.predicted.max(program.enrolled) { Color::Green } else { Color::Yellow };
+        table.add_row(vec![
+            Cell::new(&program.program_name),
+            Cell::new(&program.funding_source),
+            Cell::new(program.available_places),
+            Cell::new(program.predicted),
+            Cell::new(program.enrolled),
+            Cell::new(program.confirmed).fg(confirmed_color),
+            Cell::new(format!("{:.4}", program.predicted_cutoff)),
+            Cell::new(program.actual_cutoff.map_or_else(|| "-".to_string(), |cutoff| format!("{:.4}", cutoff))),
+        ]);
+    }
+    for column in 2..8 {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    println!("{}", table);
+
+    if let Some(accuracy) = validation.accuracy() {
+        let predicted: usize = validation.programs.iter().map(|program| program.predicted).sum();
+        let confirmed: usize = validation.programs.iter().map(|program| program.confirmed).sum();
+        println!("{}", t!("validation.accuracy", confirmed, predicted, format!("{:.1}", accuracy * 100.0)));
+    }
+    let placement = |program_key: Option<&crate::models::ProgramKey>| {
+        program_key.map_or_else(|| t!("validation.nowhere"), |program_key| program_key.to_string())
+    };
+    println!(
+        "{}",
+        t!("validation.target", placement(validation.target_predicted.as_ref()), placement(validation.target_enrolled.as_ref()))
+    );
+
+    if !validation.mismatches.is_empty() {
+        println!("{}", t!("validation.reasons"));
+        let mut reasons: Vec<(&str, usize)> = Vec::new();
+        for mismatch in &validation.mismatches {
+            match reasons.iter_mut().find(|(reason, _)| *reason == mismatch.reason.as_str()) {
+                Some((_, count)) => *count += 1,
+                None => reasons.push((mismatch.reason.as_str(), 1)),
+            }
+        }
+        for (reason, count) in reasons {
+            println!("   {}: {}", t!(&format!("validation.reason.{}", reason)), count);
+        }
+    }
+}
+
+/// Print each configuration variant's mean scores over the archived runs
+pub fn print_backtest_table(scores: &[VariantScore], color: bool) {
+    let runs = scores.first().map_or(0, |score| score.runs.len());
+    println!("{}", t!("backtest.header", runs));
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            t!("backtest.variant"),
+            t!("backtest.accuracy"),
+            t!("backtest.target_correct"),
+            t!("backtest.cutoff_error"),
+        ]);
+    if !color {
+        table.force_no_tty();
+    }
+
+    // The first of equally good variants, so the configuration wins ties
+    let mut best: Option<&VariantScore> = None;
+    for score in scores.iter().filter(|score| score.accuracy().is_some()) {
+        if best.is_none_or(|best| score.accuracy() > best.accuracy()) {
+            best = Some(score);
+        }
+    }
+    for score in scores {
+        let accuracy = Cell::new(score.accuracy().map_or_else(|| "-".to_string(), |accuracy| format!("{:.1}%", accuracy * 100.0)));
+        let is_best = best.is_some_and(|best| std::ptr::eq(best, score));
+        table.add_row(vec![
+            Cell::new(&score.name),
+            if is_best { accuracy.fg(Color::Green) } else { accuracy },
+            Cell::new(t!("report.of", score.target_correct(), score.runs.len())),
+            Cell::new(score.cutoff_error().map_or_else(|| "-".to_string(), |error| format!("{:.4}", error))),
+        ]);
+    }
+    for column in 1..4 {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    println!("{}", table);
+    if let Some(best) = best.filter(|_| scores.len() > 1) {
+        println!("{}", t!("backtest.best", best.name));
+    }
+}
+
+/// Print the seats reordering the target's priorities gets, best first per institution,
+/// and whether to keep the current order
+pub fn print_priority_options_table(target_snils: &str, options: &[PriorityOption], color: bool) {
+    println!("{}", t!("priorities.header", target_snils));
+    if options.is_empty() {
+        println!("{}", t!("report.not_found_anywhere"));
+        return;
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            t!("priorities.ordering"),
+            t!("priorities.seat"),
+            t!("priorities.chance"),
+            t!("priorities.margin"),
+            t!("priorities.moved"),
+        ]);
+    if !color {
+        table.force_no_tty();
+    }
+
+    for option in options {
+        let ordering: Vec<String> = option
+            .ordering
+            .iter()
+            .enumerate()
+            .map(|(index, program_key)| format!("{}. {} ({})", index + 1, program_key.name, program_key.funding))
+            .collect();
+        let seat = match &option.admitted_to {
+            Some(program_key) => format!("{} ({})", program_key.name, program_key.funding),
+            None => i18n::status_label("Not_Admitted"),
+        };
+        let chance_color = match option.chance {
+            Some(verdict::Chance::Safe) => Color::Green,
+            Some(verdict::Chance::Match) => Color::Yellow,
+            _ => Color::Red,
+        };
+        table.add_row(vec![
+            Cell::new(ordering.join("\n")),
+            Cell::new(seat),
+            Cell::new(option.chance.map_or_else(|| "-".to_string(), |chance| t!(chance.label_key()))).fg(chance_color),
+            Cell::new(option.margin.map_or_else(|| "-".to_string(), |margin| format!("{:+.4}", margin))),
+            Cell::new(if option.current { t!("priorities.current") } else { option.moved.to_string() }),
+        ]);
+    }
+    println!("{}", table);
+
+    // Options come best first within each institution
+    let mut institutions: Vec<&str> = options.iter().map(|option| option.institution.as_str()).collect();
+    institutions.dedup();
+    for institution in institutions {
+        let Some(best) = options.iter().find(|option| option.institution == institution) else {
+            continue;
+        };
+        let advice = if best.current { t!("priorities.keep") } else { t!("priorities.reorder", best.moved) };
+        if institution.is_empty() {
+            println!("{}", advice);
+        } else {
+            println!("{}: {}", institution, advice);
+        }
+    }
+}
+
+/// Print the target's seat with the places of every program changed in turn, most
+/// sensitive program first
+pub fn print_capacity_table(target_snils: &str, current: &[ProgramKey], sensitivities: &[CapacitySensitivity], color: bool) {
+    let seats_label = |seats: &[ProgramKey]| {
+        if seats.is_empty() {
+            i18n::status_label("Not_Admitted")
+        } else {
+            seats.iter().map(|seat| format!("{} ({})", seat.name, seat.funding)).collect::<Vec<_>>().join(", ")
+        }
+    };
+    println!("{}", t!("capacity.header", target_snils));
+    println!("{}", t!("capacity.current", seats_label(current)));
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            t!("report.program"),
+            t!("report.funding"),
+            t!("report.places"),
+            t!("capacity.changes"),
+            t!("capacity.closest"),
+        ]);
+    if !color {
+        table.force_no_tty();
+    }
+
+    for sensitivity in sensitivities {
+        let changes: Vec<String> = sensitivity
+            .changes
+            .iter()
+            .map(|step| format!("{:+} ({}) → {}", step.delta, step.places, seats_label(&step.seats)))
+            .collect();
+        let closest = sensitivity.closest_change();
+        let closest_color = match closest.map(i64::abs) {
+            Some(1) => Color::Red,
+            Some(_) => Color::Yellow,
+            None => Color::Green,
+        };
+        table.add_row(vec![
+            Cell::new(&sensitivity.program_key.name),
+            Cell::new(sensitivity.program_key.funding.as_str()),
+            Cell::new(sensitivity.available_places).set_alignment(CellAlignment::Right),
+            Cell::new(if changes.is_empty() { "-".to_string() } else { changes.join("\n") }),
+            Cell::new(closest.map_or_else(|| "-".to_string(), |delta| format!("{:+}", delta))).fg(closest_color),
+        ]);
+    }
+    println!("{}", table);
+
+    match sensitivities.first().and_then(|sensitivity| Some((sensitivity, sensitivity.closest_change()?))) {
+        Some((sensitivity, delta)) => println!(
+            "{}",
+            t!("capacity.most_sensitive", format!("{} ({})", sensitivity.program_key.name, sensitivity.program_key.funding), format!("{:+}", delta))
+        ),
+        None => println!("{}", t!("capacity.stable")),
+    }
+}
+
+/// Print the target's estimated chance of a seat in each competition of the one program of interest
+pub fn print_estimate_table(target_snils: &str, estimates: &[ProgramEstimate], color: bool) {
+    println!("{}", t!("estimate.header", target_snils));
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            t!("report.program"),
+            t!("report.funding"),
+            t!("report.priority"),
+            t!("report.places"),
+            t!("distance.position"),
+            t!("estimate.expected_ahead"),
+            t!("estimate.seat_chance"),
+            t!("report.status"),
+        ]);
+    if !color {
+        table.force_no_tty();
+    }
+
+    for estimate in estimates {
+        let status_color = match estimate.status.as_str() {
+            "Admitted" => Color::Green,
+            "Not_Applied" => Color::DarkGrey,
+            _ => Color::Red,
+        };
+        let position = estimate
+            .position
+            .map(|position| t!("report.of", position, estimate.eager_applicants.max(position)))
+            .unwrap_or_else(|| "-".to_string());
+        table.add_row(vec![
+            Cell::new(&estimate.program_key.name),
+            Cell::new(&estimate.funding_source),
+            Cell::new(estimate.priority.map_or_else(|| "-".to_string(), |priority| priority.to_string())),
+            Cell::new(estimate.available_places),
+            Cell::new(position),
+            Cell::new(format!("{:.1}", estimate.expected_ahead)),
+            Cell::new(format!("{:.0}%", estimate.seat_chance * 100.0)).fg(status_color),
+            Cell::new(i18n::status_label(&estimate.status)).fg(status_color),
+        ]);
+    }
+    for column in [2, 3, 4, 5, 6] {
+        if let Some(column) = table.column_mut(column) {
+            column.set_cell_alignment(CellAlignment::Right);
+        }
+    }
+    println!("{}", table);
+    println!("{}", t!("estimate.approximate"));
+}
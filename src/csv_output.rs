@@ -0,0 +1,128 @@
+use crate::atomic_file;
+use crate::models::{CsvOptions, ReportOptions};
+use anyhow::{anyhow, bail, Result};
+use encoding_rs::{Encoding, UTF_8};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Columns written only when `[reports.columns]` names them
+const OPTIONAL_COLUMNS: &[&str] = &["Normalized_SNILS"];
+
+/// CSV dialect shared by all CSV writers: delimiter, decimal separator, encoding, BOM
+/// and the columns chosen for each file
+#[derive(Debug, Clone)]
+pub struct CsvDialect {
+    delimiter: u8,
+    decimal_comma: bool,
+    encoding: &'static Encoding,
+    bom: bool,
+    // File name -> its columns in order, from [reports.columns]
+    columns: Arc<BTreeMap<String, Vec<String>>>,
+}
+
+impl CsvDialect {
+    pub fn from_options(options: &CsvOptions, reports: &ReportOptions) -> Result<Self> {
+        let delimiter = match options.delimiter.as_deref().unwrap_or(",") {
+            "\\t" | "tab" => b'\t',
+            value if value.len() == 1 && value.is_ascii() => value.as_bytes()[0],
+            value => return Err(anyhow!("CSV delimiter must be a single ASCII character: {:?}", value)),
+        };
+        let encoding_label = options.encoding.as_deref().unwrap_or("utf-8");
+        let encoding = Encoding::for_label(encoding_label.as_bytes())
+            .ok_or_else(|| anyhow!("Unknown CSV encoding: {}", encoding_label))?;
+
+        Ok(Self {
+            delimiter,
+            decimal_comma: options.decimal_comma.unwrap_or(false),
+            encoding,
+            bom: options.bom.unwrap_or(false),
+            columns: Arc::new(reports.columns.clone().unwrap_or_default()),
+        })
+    }
+
+    /// Start a CSV file; the content is encoded and written on `flush`
+    pub fn writer<P: AsRef<Path>>(&self, path: P) -> CsvFile {
+        CsvFile {
+            writer: csv::WriterBuilder::new().delimiter(self.delimiter).from_writer(Vec::new()),
+            path: path.as_ref().to_path_buf(),
+            dialect: self.clone(),
+            selection: None,
+        }
+    }
+
+    /// Normalize the decimal separator of a numeric value
+    pub fn decimal(&self, value: &str) -> String {
+        if self.decimal_comma {
+            value.replace('.', ",")
+        } else {
+            value.replace(',', ".")
+        }
+    }
+}
+
+/// CSV file being written with a configured dialect
+///
+/// The first record is the header; it decides which of the columns of every record
+/// are written and in which order.
+pub struct CsvFile {
+    writer: csv::Writer<Vec<u8>>,
+    path: PathBuf,
+    dialect: CsvDialect,
+    // Indices of the written columns, set by the header
+    selection: Option<Vec<usize>>,
+}
+
+impl CsvFile {
+    pub fn write_record<I, T>(&mut self, record: I) -> Result<()>
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        let record: Vec<T> = record.into_iter().collect();
+        if self.selection.is_none() {
+            let header: Vec<String> = record.iter().map(|field| String::from_utf8_lossy(field.as_ref()).into_owned()).collect();
+            self.selection = Some(self.select(&header)?);
+        }
+        let selection = self.selection.as_deref().unwrap_or_default();
+        self.writer.write_record(selection.iter().filter_map(|&index| record.get(index)))?;
+        Ok(())
+    }
+
+    /// Indices of the columns in `header` configured for this file, all but the optional
+    /// ones without a configuration
+    fn select(&self, header: &[String]) -> Result<Vec<usize>> {
+        // "Document Type" and "document_type" name the same column
+        let key = |name: &str| name.to_lowercase().replace([' ', '_'], "");
+        let file_name = self.path.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        let Some(columns) = self.dialect.columns.get(file_name.as_ref()) else {
+            return Ok((0..header.len())
+                .filter(|&index| !OPTIONAL_COLUMNS.iter().any(|optional| key(optional) == key(&header[index])))
+                .collect());
+        };
+        columns
+            .iter()
+            .map(|column| match header.iter().position(|name| key(name) == key(column)) {
+                Some(index) => Ok(index),
+                None => bail!("Unknown column {:?} for {} in [reports.columns], it has: {}", column, file_name, header.join(", ")),
+            })
+            .collect()
+    }
+
+    /// Encode the buffered records and write them to the file
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        let content = String::from_utf8(self.writer.get_ref().clone())?;
+        let (encoded, _, _) = self.dialect.encoding.encode(&content);
+
+        let mut bytes = Vec::with_capacity(encoded.len() + UTF8_BOM.len());
+        if self.dialect.bom && self.dialect.encoding == UTF_8 {
+            bytes.extend_from_slice(UTF8_BOM);
+        }
+        bytes.extend_from_slice(&encoded);
+        atomic_file::write(&self.path, bytes)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,783 @@
+use crate::{analyzer, atomic_file, backtest, campaign, competitors, confidence, consistency, console_table, csv_output, enrollment_order, models, program_aliases, program_dirs, ranks, report_model, strategies, templates, AnalyzedRun};
+use anyhow::Result;
+use rayon::prelude::*;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::info;
+
+/// Columns every per-applicant CSV starts with, in the order of [`record_fields`];
+/// Normalized_SNILS is written only when `[reports.columns]` asks for it
+const RECORD_HEADERS: [&str; 22] = [
+    "Rank", "Computed_Rank", "SNILS", "Normalized_SNILS", "Priority", "Consent", "Document_Type", "Average_Score", "Achievements",
+    "Subject_Scores", "Psychological_Test", "Funding_Source", "Study_Form", "Available_Places", "Submitted_At", "Consent_At",
+    "Original_At", "Application_ID", "Source", "Source_Table", "Source_Row", "Scraped_At",
+];
+
+fn format_time(time: Option<chrono::NaiveDateTime>) -> String {
+    time.map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default()
+}
+
+/// Values of [`RECORD_HEADERS`] for one record
+fn record_fields(csv_dialect: &csv_output::CsvDialect, record: &models::StudentRecord) -> Vec<String> {
+    let provenance = record.provenance.as_ref();
+    vec![
+        record.rank.to_string(),
+        record.computed_rank.map(|rank| rank.to_string()).unwrap_or_default(),
+        record.snils.clone(),
+        models::normalize_snils(&record.snils),
+        record.priority.to_string(),
+        record.consent.clone(),
+        record.document_type.clone(),
+        csv_dialect.decimal(&record.average_score),
+        csv_dialect.decimal(&record.achievements),
+        record.subject_scores.clone(),
+        record.psychological_test.clone(),
+        record.funding_source.to_string(),
+        record.study_form.to_string(),
+        record.available_places.to_string(),
+        format_time(record.submitted_at),
+        format_time(record.consent_at),
+        format_time(record.original_at),
+        record.application_id.clone().unwrap_or_default(),
+        provenance.map(|provenance| provenance.source.clone()).unwrap_or_default(),
+        provenance.map(|provenance| provenance.table.to_string()).unwrap_or_default(),
+        provenance.map(|provenance| provenance.row.to_string()).unwrap_or_default(),
+        provenance
+            .map(|provenance| provenance.scraped_at.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default(),
+    ]
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "Да" } else { "Нет" }.to_string()
+}
+
+/// Write the CSV and text reports
+pub(crate) fn generate_csv_reports(
+    csv_dialect: &csv_output::CsvDialect,
+    templates: &templates::ReportTemplates,
+    analyzed: &AnalyzedRun,
+    output_dir: &str,
+) -> Result<()> {
+    let AnalyzedRun {
+        all_program_records,
+        analysis,
+        admission_distances,
+        ..
+    } = analyzed;
+    let program_dirs = program_dirs::ProgramDirectories::of(analyzed);
+    generate_program_popularity_report(templates, analysis, output_dir)?;
+    generate_detailed_csv(csv_dialect, all_program_records, output_dir)?;
+    generate_individual_program_csvs(csv_dialect, &program_dirs, all_program_records, output_dir)?;
+    generate_filtered_eager_csvs(csv_dialect, &program_dirs, analyzed, output_dir)?;
+    generate_available_places_csvs(csv_dialect, &program_dirs, &analyzed.report, output_dir)?;
+    generate_final_cutoff_analysis(csv_dialect, templates, analyzed, output_dir)?;
+    generate_admission_distance_csv(csv_dialect, admission_distances, output_dir)?;
+    generate_competitors_csv(csv_dialect, &analyzed.competitors, output_dir)?;
+    generate_boundary_csv(csv_dialect, &analyzed.boundary, output_dir)?;
+    generate_strategies_csv(csv_dialect, &analyzed.strategies, output_dir)?;
+    generate_flows_csv(csv_dialect, &analyzed.flows, output_dir)
+}
+
+fn generate_program_popularity_report(
+    templates: &templates::ReportTemplates,
+    analysis: &analyzer::AdmissionAnalysis,
+    output_dir: &str,
+) -> Result<()> {
+    #[derive(serde::Serialize)]
+    struct PopularityRow<'a> {
+        #[serde(flatten)]
+        popularity: &'a analyzer::ProgramPopularity,
+        eager_per_place: f64,
+    }
+
+    let popularities: Vec<PopularityRow> = analysis
+        .program_popularities
+        .iter()
+        .map(|popularity| PopularityRow {
+            popularity,
+            eager_per_place: popularity.total_eager_applicants as f64 / popularity.available_places as f64,
+        })
+        .collect();
+
+    let content = templates.render(
+        "program_popularity.txt.tera",
+        &serde_json::json!({ "popularities": popularities }),
+    )?;
+    atomic_file::write(Path::new(output_dir).join("program_popularity.txt"), content)?;
+    Ok(())
+}
+
+fn generate_detailed_csv(
+    csv_dialect: &csv_output::CsvDialect,
+    all_program_records: &[(String, Vec<models::StudentRecord>)],
+    output_dir: &str,
+) -> Result<()> {
+
+    let csv_path = Path::new(output_dir).join("all_applicants.csv");
+    let mut writer = csv_dialect.writer(csv_path);
+
+    // Write headers
+    writer.write_record([
+        "Program",
+        "Rank",
+        "Computed Rank",
+        "SNILS",
+        "Normalized SNILS",
+        "Priority",
+        "Consent",
+        "Document Type",
+        "Average Score",
+        "Achievements",
+        "Subject Scores",
+        "Psychological Test",
+        "Funding Source",
+        "Study Form",
+        "Available Places",
+        "Submitted At",
+        "Consent At",
+        "Original At",
+        "Application ID",
+        "Source",
+        "Source Table",
+        "Source Row",
+        "Scraped At",
+    ])?;
+
+    // Write data
+    for (program_name, records) in all_program_records {
+        for record in records {
+            writer.write_record(std::iter::once(program_name.clone()).chain(record_fields(csv_dialect, record)))?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+// 2. Generate programs/<program>/<funding>/<form>/applicants.csv for each competition
+fn generate_individual_program_csvs(
+    csv_dialect: &csv_output::CsvDialect,
+    program_dirs: &program_dirs::ProgramDirectories,
+    all_program_records: &[(String, Vec<models::StudentRecord>)],
+    output_dir: &str,
+) -> Result<()> {
+    // A list may hold several funding sources and study forms
+    let mut competitions: BTreeMap<models::ProgramKey, Vec<&models::StudentRecord>> = BTreeMap::new();
+    for (program_name, records) in all_program_records {
+        for record in records {
+            competitions.entry(models::ProgramKey::of(program_name, record)).or_default().push(record);
+        }
+    }
+
+    for (program_key, records) in competitions {
+        let csv_path = program_dirs.create(output_dir, &program_key)?.join("applicants.csv");
+        let mut writer = csv_dialect.writer(csv_path);
+
+        // Write headers
+        writer.write_record(RECORD_HEADERS)?;
+
+        // Write data
+        for record in records {
+            writer.write_record(record_fields(csv_dialect, record))?;
+        }
+
+        writer.flush()?;
+    }
+
+    Ok(())
+}
+
+// 3. Generate filtered eager applicants with exclusion marks
+fn generate_filtered_eager_csvs(
+    csv_dialect: &csv_output::CsvDialect,
+    program_dirs: &program_dirs::ProgramDirectories,
+    analyzed: &AnalyzedRun,
+    output_dir: &str,
+) -> Result<()> {
+    use crate::models::normalize_snils;
+    let AnalyzedRun {
+        target_snils,
+        all_program_records,
+        analysis,
+        originals_only,
+        funding_order,
+        ..
+    } = analyzed;
+
+    // Create exclusion tracker based on admission simulation
+    let analyzer_instance = analyzer::AdmissionAnalyzer::new(target_snils).with_originals_only(*originals_only);
+    let program_funding_groups = analyzer_instance.group_by_program_and_funding_public(all_program_records);
+    let mut excluded_normalized_snils = std::collections::HashSet::new();
+
+    // Process programs in popularity order
+    for popularity in &analysis.program_popularities {
+        let program_name = &popularity.program_name;
+        // One file per competition of the list, opened at its first record
+        let mut writers = BTreeMap::new();
+
+        if let Some(funding_groups) = program_funding_groups.get(program_name) {
+            // Configured funding types first, then budget, targeted, commercial and other funding
+            let mut funding_groups: Vec<_> = funding_groups.iter().collect();
+            funding_groups.sort_by_key(|(funding_source, _)| funding_source.order_key(funding_order));
+            for (_, funding_records) in funding_groups {
+                for record in funding_records {
+                    let is_eager = analyzer_instance.is_eager(record);
+                    let normalized_snils = normalize_snils(&record.snils);
+                    let is_excluded = excluded_normalized_snils.contains(&normalized_snils);
+
+                    let writer = match writers.entry(models::ProgramKey::of(program_name, record)) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => {
+                            let csv_path = program_dirs.create(output_dir, entry.key())?.join("filtered_eager.csv");
+                            let mut writer = csv_dialect.writer(csv_path);
+                            writer.write_record(RECORD_HEADERS.iter().chain(&["Is_Eager", "Excluded_By_Higher_Priority"]))?;
+                            entry.insert(writer)
+                        }
+                    };
+                    writer.write_record(
+                        record_fields(csv_dialect, record).into_iter().chain([yes_no(is_eager), yes_no(is_excluded)]),
+                    )?;
+                }
+                
+                // Mark as excluded those who get admitted
+                let program_key = models::ProgramKey::of(program_name, funding_records[0]);
+                let available_places = analysis
+                    .program_popularities
+                    .iter()
+                    .find(|popularity| popularity.program_key == program_key)
+                    .map_or(funding_records[0].available_places, |popularity| popularity.available_places)
+                    as usize;
+                let to_exclude: Vec<String> = funding_records
+                    .iter()
+                    .filter(|r| analyzer_instance.is_eager(r) && !excluded_normalized_snils.contains(&normalize_snils(&r.snils)))
+                    .take(available_places)
+                    .map(|r| normalize_snils(&r.snils))
+                    .collect();
+                
+                for snils in to_exclude {
+                    excluded_normalized_snils.insert(snils);
+                }
+            }
+        }
+
+        for writer in writers.values_mut() {
+            writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+// 4. Generate available places CSV files (only admitted students)
+fn generate_available_places_csvs(
+    csv_dialect: &csv_output::CsvDialect,
+    program_dirs: &program_dirs::ProgramDirectories,
+    report: &report_model::ReportModel,
+    output_dir: &str,
+) -> Result<()> {
+    use crate::models::FundingSource;
+
+    // Process each program-funding combination, each is written to its own file
+    report.programs.par_iter().try_for_each(|program| -> Result<()> {
+        let csv_path = program_dirs.create(output_dir, &program.program_key)?.join("admitted.csv");
+        let mut writer = csv_dialect.writer(csv_path);
+
+        // Write headers
+        writer.write_record(RECORD_HEADERS.iter().chain(&["Admission_Status"]))?;
+
+        // Admitted applicants and the target applicant in rank order
+        let mut admission_position = 0;
+        for row in program.rows.iter().filter(|row| row.admitted || row.target) {
+            let record = &row.record;
+            if row.admitted {
+                admission_position += 1;
+            }
+
+            let admission_status = if row.target {
+                // For target applicant, determine status based on score vs cutoff
+                let target_score = record.score.unwrap_or(0.0);
+
+                if row.admitted {
+                    // Target was actually admitted
+                    if admission_position <= program.available_places as usize {
+                        match record.funding_source {
+                            FundingSource::Budget => "Admitted_Budget+",
+                            FundingSource::Commercial => "Admitted_Commercial+",
+                            FundingSource::Targeted => "Admitted_Targeted+",
+                            FundingSource::SpecialQuota => "Admitted_SpecialQuota+",
+                            FundingSource::Other(_) => "Admitted_Other+",
+                        }
+                    } else {
+                        match record.funding_source {
+                            FundingSource::Budget => "Admitted_Budget-",
+                            FundingSource::Commercial => "Admitted_Commercial-",
+                            FundingSource::Targeted => "Admitted_Targeted-",
+                            FundingSource::SpecialQuota => "Admitted_SpecialQuota-",
+                            FundingSource::Other(_) => "Admitted_Other-",
+                        }
+                    }
+                } else {
+                    // Target was not admitted - check if their score is above cutoff
+                    if target_score > program.cutoff_score && program.cutoff_score > 0.0 {
+                        "Target_NotAdmitted+"  // Score above cutoff but not admitted due to priority
+                    } else {
+                        "Target_NotAdmitted-"  // Score below cutoff or no cutoff available
+                    }
+                }
+            } else {
+                // Regular admitted student
+                match record.funding_source {
+                    FundingSource::Budget => "Admitted_Budget",
+                    FundingSource::Commercial => "Admitted_Commercial",
+                    FundingSource::Targeted => "Admitted_Targeted",
+                    FundingSource::SpecialQuota => "Admitted_SpecialQuota",
+                    FundingSource::Other(_) => "Admitted_Other",
+                }
+            };
+
+            writer.write_record(record_fields(csv_dialect, record).into_iter().chain([admission_status.to_string()]))?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    })
+}
+
+/// Configured institution of lists attached to `name`; without `attached` lists in the run,
+/// a single configured institution covers all of them
+fn configured_institution<'a>(
+    institutions: &'a [models::InstitutionOptions],
+    name: &str,
+    attached: bool,
+) -> Option<&'a models::InstitutionOptions> {
+    match institutions {
+        [single] if name.is_empty() && !attached => Some(single),
+        _ => institutions.iter().find(|institution| institution.name == name),
+    }
+}
+
+/// Target outcomes by institution in order of first appearance, for per-institution sections
+///
+/// Sections are named after the configured institution, with its city and site as `details`.
+fn institution_sections(
+    outcomes: &[analyzer::TargetOutcome],
+    institutions: &[models::InstitutionOptions],
+) -> Vec<serde_json::Value> {
+    let mut sections: Vec<(&str, Vec<&analyzer::TargetOutcome>)> = Vec::new();
+    for outcome in outcomes {
+        match sections.iter_mut().find(|(name, _)| *name == outcome.institution) {
+            Some((_, section)) => section.push(outcome),
+            None => sections.push((&outcome.institution, vec![outcome])),
+        }
+    }
+    let attached = sections.iter().any(|(name, _)| !name.is_empty());
+    sections
+        .into_iter()
+        .map(|(name, outcomes)| {
+            let institution = configured_institution(institutions, name, attached);
+            serde_json::json!({
+                "name": match institution {
+                    Some(institution) => institution.name.clone(),
+                    None if name.is_empty() && attached => t!("report.no_institution"),
+                    None => name.to_string(),
+                },
+                "details": institution.map(models::InstitutionOptions::describe).unwrap_or_default(),
+                "outcomes": outcomes,
+            })
+        })
+        .collect()
+}
+
+// 5. Generate final cutoff analysis for programs by popularity of interest with target applicant position
+fn generate_final_cutoff_analysis(
+    csv_dialect: &csv_output::CsvDialect,
+    templates: &templates::ReportTemplates,
+    analyzed: &AnalyzedRun,
+    output_dir: &str,
+) -> Result<()> {
+    let final_path = Path::new(output_dir).join("final_cutoff_analysis.txt");
+    let final_csv_path = Path::new(output_dir).join("final_cutoff_analysis.csv");
+
+    let content = templates.render(
+        "final_cutoff_analysis.txt.tera",
+        &serde_json::json!({
+            "target_snils": analyzed.target_snils,
+            "campaign": analyzed.campaign.as_ref().map(campaign::CampaignStatus::describe),
+            "outcomes": analyzed.target_outcomes,
+            "institutions": institution_sections(&analyzed.target_outcomes, &analyzed.institutions),
+            "best_option": console_table::best_option_line(&analyzed.target_outcomes),
+        }),
+    )?;
+
+    let mut csv_writer = csv_dialect.writer(final_csv_path);
+    csv_writer.write_record([
+        "Program", "Funding_Type", "Position_In_Admitted", "Available_Places", 
+        "Target_Score", "Cutoff_Score", "Projected_Cutoff_Score", "Admission_Position", "Admission_Status",
+        "Applicants_On_Cutoff", "Published_Places", "Quota_Places_Returned", "Transfer_Cutoff_Score", "Institution",
+        "Confidence", "Confidence_Reasons"
+    ])?;
+
+    let attached = analyzed.report.programs.iter().any(|program| !program.institution.is_empty());
+    // Every program-funding combination the target applied to, in order of popularity
+    for program in &analyzed.report.programs {
+        let outcome = &program.target;
+        if program.target_row().is_none() {
+            continue;
+        }
+
+        // Set only with originals_only
+        let projected_cutoff = program
+            .projected_cutoff_score
+            .map(|score| csv_dialect.decimal(&format!("{:.4}", score)))
+            .unwrap_or_default();
+
+        let position_csv = match outcome.position_in_admitted {
+            Some(position) => format!("Position {} of {}", position, program.admitted_count),
+            None => "Not in list".to_string(),
+        };
+
+        csv_writer.write_record([
+            &program.program_name,
+            &program.funding_source.to_string(),
+            &position_csv,
+            &program.available_places.to_string(),
+            &csv_dialect.decimal(&format!("{:.4}", outcome.target_score.unwrap_or(0.0))),
+            &csv_dialect.decimal(&format!("{:.4}", program.cutoff_score)),
+            &projected_cutoff,
+            &position_csv,
+            &outcome.status,
+            &program.cutoff_ties.to_string(),
+            &program.published_places.map(|places| places.to_string()).unwrap_or_default(),
+            &program.quota_places_returned.map(|places| places.to_string()).unwrap_or_default(),
+            &program
+                .transfer_cutoff_score
+                .map(|score| csv_dialect.decimal(&format!("{:.4}", score)))
+                .unwrap_or_default(),
+            configured_institution(&analyzed.institutions, &program.institution, attached)
+                .map_or(&program.institution, |institution| &institution.name),
+            &confidence::label(outcome.confidence.as_ref()),
+            &outcome.confidence.as_ref().map(|confidence| confidence.reasons.join("; ")).unwrap_or_default(),
+        ])?;
+    }
+
+    atomic_file::write(final_path, content)?;
+    csv_writer.flush()?;
+    Ok(())
+}
+
+// Leaderboard of the target's programs by seats remaining
+fn generate_admission_distance_csv(
+    csv_dialect: &csv_output::CsvDialect,
+    admission_distances: &[analyzer::AdmissionDistance],
+    output_dir: &str,
+) -> Result<()> {
+    let mut writer = csv_dialect.writer(Path::new(output_dir).join("admission_distance.csv"));
+    writer.write_record([
+        "Program", "Funding_Type", "Priority", "Available_Places",
+        "Position_Among_Unblocked_Eager", "Unblocked_Eager_Applicants", "Seats_Remaining", "Published_Places",
+    ])?;
+    for distance in admission_distances {
+        writer.write_record([
+            &distance.program_name,
+            &distance.funding_source.to_string(),
+            &distance.priority.to_string(),
+            &distance.available_places.to_string(),
+            &distance.position.to_string(),
+            &distance.unblocked_eager.to_string(),
+            &distance.seats_remaining.to_string(),
+            &distance.published_places.map(|places| places.to_string()).unwrap_or_default(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Applicants closest above and below the cutoff of every program of interest
+fn generate_boundary_csv(
+    csv_dialect: &csv_output::CsvDialect,
+    boundary: &[competitors::BoundaryApplicant],
+    output_dir: &str,
+) -> Result<()> {
+    let mut writer = csv_dialect.writer(Path::new(output_dir).join("boundary.csv"));
+    writer.write_record([
+        "Program", "Funding_Type", "Study_Form", "Cutoff", "Side", "Rank", "SNILS", "Priority", "Score", "Score_Gap",
+        "Consent", "Original", "Is_Eager", "Is_Target", "Admitted_To",
+    ])?;
+    for applicant in boundary {
+        writer.write_record([
+            applicant.program_key.name.as_str(),
+            applicant.program_key.funding.as_str(),
+            applicant.program_key.study_form.as_str(),
+            &csv_dialect.decimal(&format!("{:.4}", applicant.cutoff_score)),
+            if applicant.above { "above" } else { "below" },
+            &applicant.rank.to_string(),
+            &applicant.snils,
+            &applicant.priority.to_string(),
+            &csv_dialect.decimal(&format!("{:.4}", applicant.score)),
+            &csv_dialect.decimal(&format!("{:+.4}", applicant.score - applicant.cutoff_score)),
+            &yes_no(applicant.consent),
+            &yes_no(applicant.original),
+            &yes_no(applicant.eager),
+            &yes_no(applicant.target),
+            &applicant.admitted_to.as_ref().map(ToString::to_string).unwrap_or_default(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Application strategies of the eager applicants, most common first, with where the
+/// simulation seats those following them
+fn generate_strategies_csv(csv_dialect: &csv_output::CsvDialect, strategies: &[strategies::Strategy], output_dir: &str) -> Result<()> {
+    let total: usize = strategies.iter().map(|strategy| strategy.applicants).sum();
+    let mut writer = csv_dialect.writer(Path::new(output_dir).join("application_strategies.csv"));
+    writer.write_record(["Strategy", "Programs", "Applicants", "Share", "Seated_In", "Unseated", "Is_Target"])?;
+    for strategy in strategies {
+        let seated: Vec<String> = strategy.seated.iter().map(|(program_key, count)| format!("{}: {}", program_key, count)).collect();
+        let share = if total > 0 { strategy.applicants as f64 * 100.0 / total as f64 } else { 0.0 };
+        writer.write_record([
+            &strategy.describe(),
+            &strategy.programs.len().to_string(),
+            &strategy.applicants.to_string(),
+            &csv_dialect.decimal(&format!("{:.1}", share)),
+            &seated.join("; "),
+            &strategy.unseated().to_string(),
+            &yes_no(strategy.target),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Source, destination and count of every flow, as Sankey diagram tools take them
+fn generate_flows_csv(csv_dialect: &csv_output::CsvDialect, flows: &[strategies::Flow], output_dir: &str) -> Result<()> {
+    let mut writer = csv_dialect.writer(Path::new(output_dir).join("applicant_flows.csv"));
+    writer.write_record(["Source", "Destination", "Count"])?;
+    for flow in flows {
+        writer.write_record([
+            flow.source.to_string(),
+            flow.destination.as_ref().map_or_else(|| t!("flows.no_seat"), ToString::to_string),
+            flow.applicants.to_string(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Applicants competing with the target, with their other priorities and where the simulation seats them
+fn generate_competitors_csv(
+    csv_dialect: &csv_output::CsvDialect,
+    competitors: &[competitors::Competitor],
+    output_dir: &str,
+) -> Result<()> {
+    let mut writer = csv_dialect.writer(Path::new(output_dir).join("competitors.csv"));
+    writer.write_record([
+        "Program", "Funding_Type", "Study_Form", "Rank", "SNILS", "Priority", "Score", "Target_Priority",
+        "Target_Score", "Is_Eager", "Privileged", "Original_After_Target", "Other_Priorities", "Admitted_To", "Admitted_Elsewhere",
+    ])?;
+    for competitor in competitors {
+        let other_priorities: Vec<String> = competitor
+            .other_applications
+            .iter()
+            .map(|application| format!("{}: {}", application.priority, application.program_key))
+            .collect();
+        writer.write_record([
+            &competitor.program_key.name,
+            competitor.program_key.funding.as_str(),
+            competitor.program_key.study_form.as_str(),
+            &competitor.rank.to_string(),
+            &competitor.snils,
+            &competitor.priority.to_string(),
+            &competitor.score.map(|score| csv_dialect.decimal(&format!("{:.4}", score))).unwrap_or_default(),
+            &competitor.target_priority.to_string(),
+            &csv_dialect.decimal(&format!("{:.4}", competitor.target_score)),
+            &yes_no(competitor.eager),
+            &yes_no(competitor.privileged),
+            &yes_no(competitor.original_after_target),
+            &other_priorities.join("; "),
+            &competitor.admitted_to.as_ref().map(ToString::to_string).unwrap_or_default(),
+            &yes_no(competitor.admitted_elsewhere()),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// One row per list of every applicant whose score or test result differs between lists
+/// Applications whose published rank does not fit the rank their score gives
+pub(crate) fn generate_rank_discrepancies_csv(
+    csv_dialect: &csv_output::CsvDialect,
+    discrepancies: &[ranks::RankDiscrepancy],
+    output_dir: &str,
+) -> Result<()> {
+    let path = Path::new(output_dir).join("rank_discrepancies.csv");
+    let mut writer = csv_dialect.writer(&path);
+    writer.write_record(["Program", "Funding_Type", "Study_Form", "SNILS", "Published_Rank", "Computed_Rank", "Issue", "Source"])?;
+    for discrepancy in discrepancies {
+        let issue = match discrepancy.issue {
+            ranks::RankIssue::Duplicate => "Duplicate",
+            ranks::RankIssue::OutOfOrder => "Out_Of_Order",
+            ranks::RankIssue::Gap => "Gap",
+        };
+        writer.write_record([
+            &discrepancy.program_key.name,
+            discrepancy.program_key.funding.as_str(),
+            discrepancy.program_key.study_form.as_str(),
+            &discrepancy.snils,
+            &discrepancy.published_rank.to_string(),
+            &discrepancy.computed_rank.to_string(),
+            issue,
+            &discrepancy.provenance.as_ref().map(|provenance| provenance.to_string()).unwrap_or_default(),
+        ])?;
+    }
+    writer.flush()?;
+    info!("{}", t!("ranks.report", path.display()));
+    Ok(())
+}
+
+/// Lists renamed by `program_aliases` and how many of their applications were duplicates
+pub(crate) fn generate_program_merges_csv(
+    csv_dialect: &csv_output::CsvDialect,
+    merges: &[program_aliases::ProgramMerge],
+    output_dir: &str,
+) -> Result<()> {
+    let path = Path::new(output_dir).join("program_merges.csv");
+    let mut writer = csv_dialect.writer(&path);
+    writer.write_record(["Alias", "Program", "Funding_Type", "Study_Form", "Applications", "Duplicates_Removed"])?;
+    for merge in merges {
+        writer.write_record([
+            &merge.alias,
+            &merge.program_key.name,
+            merge.program_key.funding.as_str(),
+            merge.program_key.study_form.as_str(),
+            &merge.applications.to_string(),
+            &merge.duplicates_removed.to_string(),
+        ])?;
+    }
+    writer.flush()?;
+    info!("{}", t!("aliases.report", path.display()));
+    Ok(())
+}
+
+pub(crate) fn generate_reconciliation_csv(
+    csv_dialect: &csv_output::CsvDialect,
+    inconsistencies: &[consistency::Inconsistency],
+    output_dir: &str,
+) -> Result<()> {
+    let path = Path::new(output_dir).join("reconciliation.csv");
+    let mut writer = csv_dialect.writer(&path);
+    writer.write_record(["SNILS", "Field", "Program", "Funding_Type", "Study_Form", "Value", "Resolved_Value", "Source"])?;
+    for inconsistency in inconsistencies {
+        let field = match inconsistency.field {
+            consistency::Field::Score => "Score",
+            consistency::Field::PsychologicalTest => "Psychological_Test",
+        };
+        for listed in &inconsistency.values {
+            let value = match inconsistency.field {
+                consistency::Field::Score => csv_dialect.decimal(&listed.value),
+                consistency::Field::PsychologicalTest => listed.value.clone(),
+            };
+            writer.write_record([
+                &inconsistency.snils,
+                field,
+                &listed.program_key.name,
+                listed.program_key.funding.as_str(),
+                listed.program_key.study_form.as_str(),
+                &value,
+                &inconsistency.resolved.as_deref().map(|value| csv_dialect.decimal(value)).unwrap_or_default(),
+                &listed.provenance.as_ref().map(|provenance| provenance.to_string()).unwrap_or_default(),
+            ])?;
+        }
+    }
+    writer.flush()?;
+    info!("{}", t!("consistency.report", path.display()));
+    Ok(())
+}
+
+/// Predicted against enrolled per program, and every applicant the two disagree about
+pub fn generate_validation_csvs(
+    csv_dialect: &csv_output::CsvDialect,
+    validation: &enrollment_order::Validation,
+    output_dir: &str,
+) -> Result<()> {
+    let mut writer = csv_dialect.writer(Path::new(output_dir).join("validation.csv"));
+    writer.write_record([
+        "Program", "Funding_Type", "Available_Places", "Predicted", "Enrolled", "Confirmed",
+        "Predicted_Cutoff", "Actual_Cutoff",
+    ])?;
+    for program in &validation.programs {
+        writer.write_record([
+            &program.program_name,
+            &program.funding_source.to_string(),
+            &program.available_places.to_string(),
+            &program.predicted.to_string(),
+            &program.enrolled.to_string(),
+            &program.confirmed.to_string(),
+            &csv_dialect.decimal(&format!("{:.4}", program.predicted_cutoff)),
+            &program.actual_cutoff.map(|cutoff| csv_dialect.decimal(&format!("{:.4}", cutoff))).unwrap_or_default(),
+        ])?;
+    }
+    writer.flush()?;
+
+    let mut writer = csv_dialect.writer(Path::new(output_dir).join("validation_mismatches.csv"));
+    writer.write_record(["Program", "SNILS", "Score", "Priority", "Enrolled", "Reason", "Other_Program"])?;
+    for mismatch in &validation.mismatches {
+        writer.write_record([
+            mismatch.program_key.to_string(),
+            mismatch.snils.clone(),
+            mismatch.score.map(|score| csv_dialect.decimal(&format!("{:.4}", score))).unwrap_or_default(),
+            mismatch.priority.map(|priority| priority.to_string()).unwrap_or_default(),
+            yes_no(mismatch.enrolled),
+            mismatch.reason.as_str().to_string(),
+            mismatch.other_program.as_ref().map(ToString::to_string).unwrap_or_default(),
+        ])?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Scores of every variant per archived run, and the cutoff errors behind them
+pub fn generate_backtest_csvs(
+    csv_dialect: &csv_output::CsvDialect,
+    scores: &[backtest::VariantScore],
+    output_dir: &str,
+) -> Result<()> {
+    let decimal = |value: Option<f64>| value.map(|value| csv_dialect.decimal(&format!("{:.4}", value))).unwrap_or_default();
+
+    let mut writer = csv_dialect.writer(Path::new(output_dir).join("backtest.csv"));
+    writer.write_record(["Variant", "Run", "Seats_Confirmed", "Target_Correct", "Mean_Cutoff_Error"])?;
+    for score in scores {
+        for run in &score.runs {
+            writer.write_record([
+                score.name.clone(),
+                run.run.clone(),
+                decimal(run.accuracy),
+                yes_no(run.target_correct),
+                decimal(run.cutoff_error()),
+            ])?;
+        }
+    }
+    writer.flush()?;
+
+    let mut writer = csv_dialect.writer(Path::new(output_dir).join("backtest_cutoffs.csv"));
+    writer.write_record(["Variant", "Run", "Program", "Funding_Type", "Predicted_Cutoff", "Actual_Cutoff", "Cutoff_Error"])?;
+    for score in scores {
+        for run in &score.runs {
+            for cutoff in &run.cutoffs {
+                writer.write_record([
+                    score.name.clone(),
+                    run.run.clone(),
+                    cutoff.program_name.clone(),
+                    cutoff.funding_source.to_string(),
+                    decimal(Some(cutoff.predicted_cutoff)),
+                    decimal(Some(cutoff.actual_cutoff)),
+                    decimal(Some(cutoff.error())),
+                ])?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use crate::atomic_file;
+use crate::error::StorageError;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// File in the output root with the list links found on every index page last time
+pub const DISCOVERED_FILE: &str = "discovered_lists.json";
+
+/// List URLs by the URL of the index page linking to them, in the order of the page
+pub type DiscoveredLists = BTreeMap<String, Vec<String>>;
+
+/// Links found by the last run, empty if there are none or they cannot be read
+pub fn load(output_root: &Path) -> DiscoveredLists {
+    fs::read_to_string(output_root.join(DISCOVERED_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(output_root: &Path, discovered: &DiscoveredLists) -> Result<(), StorageError> {
+    let path = output_root.join(DISCOVERED_FILE);
+    let content = serde_json::to_string_pretty(discovered)?;
+    atomic_file::write(&path, content).map_err(|cause| StorageError::Write { path: path.display().to_string(), cause })
+}
+
+/// Links of `current` missing from `previous`, and links of `previous` gone from `current`
+pub fn changes<'a>(previous: &'a [String], current: &'a [String]) -> (Vec<&'a String>, Vec<&'a String>) {
+    let appeared = current.iter().filter(|url| !previous.contains(url)).collect();
+    let disappeared = previous.iter().filter(|url| !current.contains(url)).collect();
+    (appeared, disappeared)
+}
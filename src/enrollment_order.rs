@@ -0,0 +1,387 @@
+use crate::error::{ParseError, Result, ScrapeError};
+use crate::models::{normalize_snils, FundingSource, ProgramKey, StudyForm};
+use crate::report_model::{ProgramReport, ReportModel, ReportRow};
+use encoding_rs::WINDOWS_1251;
+use scraper::{ElementRef, Html, Selector};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+// Elements whose text before a table says which program and funding the table is about
+const CONTEXT_ELEMENTS: &[&str] = &["p", "h1", "h2", "h3", "h4", "h5", "h6", "li"];
+
+/// An applicant enrolled by an official enrollment order (приказ о зачислении)
+#[derive(Debug, Clone, Serialize)]
+pub struct Enrollment {
+    pub snils: String,
+    pub program_name: String,
+    // Empty when the order does not say; any funding of the program then matches
+    pub funding_source: FundingSource,
+    pub study_form: StudyForm,
+}
+
+/// Enrollments of an order saved as CSV (by the `.csv` extension) or an HTML page
+///
+/// Columns are found by their headers: SNILS or applicant code, and optionally program,
+/// funding and study form. HTML tables without program or funding columns take them from
+/// the text above the table, e.g. "ОП СПО Фармация" and "Источник финансирования: Бюджет".
+/// CSV files may be in UTF-8 or Windows-1251, as spreadsheets export them.
+pub fn load(path: &str) -> Result<Vec<Enrollment>> {
+    let bytes = fs::read(path).map_err(|cause| ScrapeError::Read { path: path.to_string(), cause })?;
+    let content = match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(error) => WINDOWS_1251.decode(error.as_bytes()).0.into_owned(),
+    };
+    let is_csv = Path::new(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    let enrollments = if is_csv { parse_csv(&content, path)? } else { parse_html(&content) };
+    if enrollments.is_empty() {
+        return Err(ParseError::new(path, "no enrolled applicants found; is there a SNILS column?").into());
+    }
+    Ok(enrollments)
+}
+
+/// Positions of the columns an order table has
+#[derive(Debug, Default)]
+struct Columns {
+    snils: usize,
+    program: Option<usize>,
+    funding: Option<usize>,
+    study_form: Option<usize>,
+}
+
+impl Columns {
+    /// Columns named in `header`, `None` if it has no SNILS column
+    fn of_header(header: &[String]) -> Option<Self> {
+        let find = |markers: &[&str]| {
+            header.iter().position(|cell| {
+                let cell = cell.to_lowercase();
+                markers.iter().any(|marker| cell.contains(marker))
+            })
+        };
+        Some(Self {
+            snils: find(&["снилс", "snils"]).or_else(|| find(&["код", "идентификатор", "code"]))?,
+            program: find(&["программ", "специальност", "направлени", "program"]),
+            funding: find(&["финансир", "основа", "funding"]),
+            study_form: find(&["форма", "study_form", "form"]),
+        })
+    }
+
+    fn enrollment(&self, cells: &[String], context: &Enrollment) -> Option<Enrollment> {
+        let cell = |column: Option<usize>| column.and_then(|column| cells.get(column)).map(|cell| cell.trim());
+        let snils = cell(Some(self.snils)).and_then(|cell| cell.lines().next()).unwrap_or_default().trim();
+        if normalize_snils(snils).is_empty() {
+            return None;
+        }
+        Some(Enrollment {
+            snils: snils.to_string(),
+            program_name: cell(self.program).unwrap_or(&context.program_name).to_string(),
+            funding_source: cell(self.funding).map_or_else(|| context.funding_source.clone(), FundingSource::parse),
+            study_form: cell(self.study_form).map_or_else(|| context.study_form.clone(), StudyForm::parse),
+        })
+    }
+}
+
+fn parse_csv(content: &str, path: &str) -> Result<Vec<Enrollment>> {
+    // Title lines above the header have no delimiters, the first rows of the table decide
+    let head: String = content.lines().take(20).collect();
+    let delimiter = [b',', b'\t', b';']
+        .into_iter()
+        .max_by_key(|delimiter| head.bytes().filter(|byte| byte == delimiter).count())
+        .unwrap_or(b',');
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let mut columns = None;
+    let mut enrollments = Vec::new();
+    for (index, row) in reader.records().enumerate() {
+        let row = row.map_err(|error| ParseError::new(path, error).at_row(index + 1))?;
+        let cells: Vec<String> = row.iter().map(str::to_string).collect();
+        match &columns {
+            None => columns = Columns::of_header(&cells),
+            Some(columns) => enrollments.extend(columns.enrollment(&cells, &empty_context())),
+        }
+    }
+    Ok(enrollments)
+}
+
+fn parse_html(content: &str) -> Vec<Enrollment> {
+    let document = Html::parse_document(content);
+    let row_selector = Selector::parse("tr").unwrap();
+    let cell_selector = Selector::parse("th, td").unwrap();
+
+    let mut context = empty_context();
+    let mut enrollments = Vec::new();
+    for element in document.root_element().descendants().filter_map(ElementRef::wrap) {
+        let name = element.value().name();
+        let in_table = element.ancestors().filter_map(ElementRef::wrap).any(|ancestor| ancestor.value().name() == "table");
+        if in_table {
+            continue;
+        }
+        if CONTEXT_ELEMENTS.contains(&name) {
+            update_context(&mut context, &element.text().collect::<String>());
+            continue;
+        }
+        if name != "table" {
+            continue;
+        }
+
+        let mut columns = None;
+        for row in element.select(&row_selector) {
+            let cells: Vec<String> = row.select(&cell_selector).map(|cell| cell.text().collect()).collect();
+            match &columns {
+                None => columns = Columns::of_header(&cells),
+                Some(columns) => enrollments.extend(columns.enrollment(&cells, &context)),
+            }
+        }
+    }
+    enrollments
+}
+
+fn empty_context() -> Enrollment {
+    Enrollment {
+        snils: String::new(),
+        program_name: String::new(),
+        funding_source: FundingSource::Other(String::new()),
+        study_form: StudyForm::Other(String::new()),
+    }
+}
+
+/// Take the program, funding or study form a line of text above a table names
+fn update_context(context: &mut Enrollment, text: &str) {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.starts_with("ОП СПО") {
+        context.program_name = text;
+        return;
+    }
+    let Some((label, value)) = text.split_once(':') else {
+        return;
+    };
+    let label = label.to_lowercase();
+    let value = value.trim();
+    if label.contains("источник финансирования") {
+        context.funding_source = FundingSource::parse(value);
+    } else if label.contains("форма обучения") {
+        context.study_form = StudyForm::parse(value);
+    } else if label.contains("специальность") || label.contains("программа") {
+        context.program_name = value.to_string();
+    }
+}
+
+/// Why the simulation and the order disagree about an applicant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MismatchReason {
+    /// Enrolled, but the program's list did not have the application
+    NotInList,
+    /// Enrolled, but the list showed neither an original nor a consent
+    NotEager,
+    /// Enrolled, but the simulation gave them a seat in another program
+    AdmittedElsewhere,
+    /// Enrolled, but the simulation ranked them below the last seat
+    BelowCutoff,
+    /// Predicted, but the order enrolled them in another program
+    EnrolledElsewhere,
+    /// Predicted, but no order enrolled them
+    NotEnrolled,
+}
+
+impl MismatchReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NotInList => "not_in_list",
+            Self::NotEager => "not_eager",
+            Self::AdmittedElsewhere => "admitted_elsewhere",
+            Self::BelowCutoff => "below_cutoff",
+            Self::EnrolledElsewhere => "enrolled_elsewhere",
+            Self::NotEnrolled => "not_enrolled",
+        }
+    }
+}
+
+/// An applicant the simulation and the order place differently in one program
+#[derive(Debug, Clone, Serialize)]
+pub struct Mismatch {
+    pub program_key: ProgramKey,
+    pub snils: String,
+    // Score and priority in the program's list, if it had the application
+    pub score: Option<f64>,
+    pub priority: Option<u32>,
+    // Enrolled by the order; otherwise only predicted
+    pub enrolled: bool,
+    pub reason: MismatchReason,
+    // Program of AdmittedElsewhere and EnrolledElsewhere
+    pub other_program: Option<ProgramKey>,
+}
+
+/// Prediction against the order for one program-funding combination
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramValidation {
+    pub program_key: ProgramKey,
+    pub program_name: String,
+    pub funding_source: FundingSource,
+    pub available_places: u32,
+    pub predicted: usize,
+    pub enrolled: usize,
+    // Predicted and enrolled
+    pub confirmed: usize,
+    pub predicted_cutoff: f64,
+    // Lowest listed score among the enrolled applicants, `None` if the list had none of them
+    pub actual_cutoff: Option<f64>,
+}
+
+/// How the simulation compares to the enrollment orders
+///
+/// Only programs with at least one enrollment are compared, so orders published for
+/// some programs validate those without counting the others as wrong.
+#[derive(Debug, Clone, Serialize)]
+pub struct Validation {
+    pub programs: Vec<ProgramValidation>,
+    pub mismatches: Vec<Mismatch>,
+    // Programs named in the orders that are not in the lists, with their enrollments
+    pub unknown_programs: BTreeMap<String, usize>,
+    // Where the simulation and the orders put the target, `None` for nowhere
+    pub target_predicted: Option<ProgramKey>,
+    pub target_enrolled: Option<ProgramKey>,
+}
+
+impl Validation {
+    /// Share of the predicted seats in compared programs the orders confirmed
+    pub fn accuracy(&self) -> Option<f64> {
+        let predicted: usize = self.programs.iter().map(|program| program.predicted).sum();
+        let confirmed: usize = self.programs.iter().map(|program| program.confirmed).sum();
+        (predicted > 0).then(|| confirmed as f64 / predicted as f64)
+    }
+}
+
+/// Compare the simulated admission in `report` with the applicants the orders enrolled
+///
+/// Enrollments are matched to a program by name, and by funding and study form where the
+/// order gives them. Identifiers must already be spelled as in the lists (resolved, and
+/// pseudonyms when the lists are anonymized).
+pub fn validate(report: &ReportModel, enrollments: &[Enrollment]) -> Validation {
+    let mut enrolled: HashMap<String, ProgramKey> = HashMap::new();
+    let mut unknown_programs = BTreeMap::new();
+    for enrollment in enrollments {
+        let snils = normalize_snils(&enrollment.snils);
+        let matching: Vec<&ProgramReport> = report
+            .programs
+            .iter()
+            .filter(|program| {
+                program.program_name.trim().to_lowercase() == enrollment.program_name.trim().to_lowercase()
+                    && (enrollment.funding_source.is_empty() || enrollment.funding_source == program.funding_source)
+                    && (enrollment.study_form.is_empty() || enrollment.study_form == program.program_key.study_form)
+            })
+            .collect();
+        // Without funding in the order, the combination the applicant applied to
+        let program = matching
+            .iter()
+            .find(|program| row_of(program, &snils).is_some())
+            .or(matching.first());
+        match program {
+            Some(program) => {
+                enrolled.insert(snils, program.program_key.clone());
+            }
+            None => *unknown_programs.entry(enrollment.program_name.clone()).or_insert(0) += 1,
+        }
+    }
+
+    // The target is simulated in every program; their seat is the one of the best priority
+    let mut predicted: HashMap<String, (u32, ProgramKey)> = HashMap::new();
+    for program in &report.programs {
+        for row in program.admitted_rows() {
+            let placement = (row.record.priority, program.program_key.clone());
+            predicted
+                .entry(normalize_snils(&row.record.snils))
+                .and_modify(|current| {
+                    if placement.0 < current.0 {
+                        *current = placement.clone();
+                    }
+                })
+                .or_insert(placement);
+        }
+    }
+    let predicted: HashMap<String, ProgramKey> =
+        predicted.into_iter().map(|(snils, (_, program_key))| (snils, program_key)).collect();
+
+    let mut programs = Vec::new();
+    let mut mismatches = Vec::new();
+    for program in &report.programs {
+        let in_program = |placements: &HashMap<String, ProgramKey>| -> Vec<String> {
+            let mut applicants: Vec<String> = placements
+                .iter()
+                .filter(|(_, program_key)| **program_key == program.program_key)
+                .map(|(snils, _)| snils.clone())
+                .collect();
+            applicants.sort();
+            applicants
+        };
+        let enrolled_here = in_program(&enrolled);
+        if enrolled_here.is_empty() {
+            continue;
+        }
+        let predicted_here = in_program(&predicted);
+
+        let mismatch = |snils: &str, enrolled: bool, reason: MismatchReason, other_program: Option<&ProgramKey>| {
+            let row = row_of(program, snils);
+            Mismatch {
+                program_key: program.program_key.clone(),
+                snils: row.map_or_else(|| snils.to_string(), |row| row.record.snils.clone()),
+                score: row.and_then(|row| row.record.score),
+                priority: row.map(|row| row.record.priority),
+                enrolled,
+                reason,
+                other_program: other_program.cloned(),
+            }
+        };
+        for snils in enrolled_here.iter().filter(|snils| !predicted_here.contains(snils)) {
+            let other_program = predicted.get(snils);
+            let reason = match row_of(program, snils) {
+                None => MismatchReason::NotInList,
+                Some(row) if !row.eager => MismatchReason::NotEager,
+                Some(_) if other_program.is_some() => MismatchReason::AdmittedElsewhere,
+                Some(_) => MismatchReason::BelowCutoff,
+            };
+            mismatches.push(mismatch(snils, true, reason, other_program));
+        }
+        for snils in predicted_here.iter().filter(|snils| !enrolled_here.contains(snils)) {
+            let other_program = enrolled.get(snils);
+            let reason = match other_program {
+                Some(_) => MismatchReason::EnrolledElsewhere,
+                None => MismatchReason::NotEnrolled,
+            };
+            mismatches.push(mismatch(snils, false, reason, other_program));
+        }
+
+        programs.push(ProgramValidation {
+            program_key: program.program_key.clone(),
+            program_name: program.program_name.clone(),
+            funding_source: program.funding_source.clone(),
+            available_places: program.available_places,
+            predicted: predicted_here.len(),
+            enrolled: enrolled_here.len(),
+            confirmed: predicted_here.iter().filter(|snils| enrolled_here.contains(snils)).count(),
+            predicted_cutoff: program.cutoff_score,
+            actual_cutoff: enrolled_here
+                .iter()
+                .filter_map(|snils| row_of(program, snils)?.record.score)
+                .min_by(f64::total_cmp),
+        });
+    }
+
+    let target = normalize_snils(&report.target_snils);
+    Validation {
+        programs,
+        mismatches,
+        unknown_programs,
+        target_predicted: predicted.get(&target).cloned(),
+        target_enrolled: enrolled.get(&target).cloned(),
+    }
+}
+
+fn row_of<'a>(program: &'a ProgramReport, snils: &str) -> Option<&'a ReportRow> {
+    program.rows.iter().find(|row| normalize_snils(&row.record.snils) == snils)
+}
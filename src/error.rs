@@ -0,0 +1,197 @@
+use thiserror::Error;
+
+/// Error of the library, by what went wrong
+///
+/// Each category keeps the file, URL, program or row it is about and the underlying
+/// error as its source, so `{:#}` with anyhow or walking `source()` shows the whole chain.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Scrape(#[from] ScrapeError),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+    #[error(transparent)]
+    Simulation(#[from] SimulationError),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The error and its causes, as `{:#}` shows them with anyhow
+#[cfg(any(feature = "ffi", feature = "python"))]
+pub(crate) fn error_chain(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    message
+}
+
+/// A source could not be read or fetched
+#[derive(Debug, Error)]
+pub enum ScrapeError {
+    #[error("Failed to read {path}")]
+    Read {
+        path: String,
+        #[source]
+        cause: std::io::Error,
+    },
+    #[cfg(feature = "network")]
+    #[error("Failed to fetch {url}")]
+    Fetch {
+        url: String,
+        #[source]
+        cause: reqwest::Error,
+    },
+    #[cfg(feature = "network")]
+    #[error("Request to {url} failed with status {status}")]
+    Status { url: String, status: reqwest::StatusCode },
+    #[error("Response from {url} is larger than {limit_mb} MB, raise max_response_mb to fetch it")]
+    TooLarge { url: String, limit_mb: u64 },
+    #[error("Response from {url} uses the unsupported content encoding {encoding}")]
+    Encoding { url: String, encoding: String },
+    #[error("Response from {url} is cut short or damaged")]
+    Damaged {
+        url: String,
+        #[source]
+        cause: std::io::Error,
+    },
+    #[error("Failed to write the large response to {path}")]
+    Spool {
+        path: String,
+        #[source]
+        cause: std::io::Error,
+    },
+}
+
+/// Content of a source or snapshot that could not be understood
+#[derive(Debug, Error)]
+#[error("{}: {message}", self.location())]
+pub struct ParseError {
+    // File path, URL or "snapshot" for one passed as text
+    pub input: String,
+    pub program: Option<String>,
+    // 1-based position of the program's table on the page
+    pub table: Option<usize>,
+    // 1-based position of the record in its program list
+    pub row: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(input: &str, message: impl ToString) -> Self {
+        Self {
+            input: input.to_string(),
+            program: None,
+            table: None,
+            row: None,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn in_program(mut self, program: &str) -> Self {
+        self.program = Some(program.to_string());
+        self
+    }
+
+    pub fn in_table(mut self, table: usize) -> Self {
+        self.table = Some(table);
+        self
+    }
+
+    pub fn at_row(mut self, row: usize) -> Self {
+        self.row = Some(row);
+        self
+    }
+
+    fn location(&self) -> String {
+        let mut location = self.input.clone();
+        if let Some(program) = &self.program {
+            location.push_str(&format!(", program {}", program));
+        }
+        if let Some(table) = self.table {
+            location.push_str(&format!(", table {}", table));
+        }
+        if let Some(row) = self.row {
+            location.push_str(&format!(", row {}", row));
+        }
+        location
+    }
+}
+
+/// Configuration that could not be loaded, saved or applied
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("Failed to read {path}")]
+    Read {
+        path: String,
+        #[source]
+        cause: std::io::Error,
+    },
+    #[error("Failed to write {path}")]
+    Write {
+        path: String,
+        #[source]
+        cause: std::io::Error,
+    },
+    #[error("Invalid TOML in {path}")]
+    Syntax {
+        path: String,
+        #[source]
+        cause: toml::de::Error,
+    },
+    #[error("Invalid settings")]
+    Settings(#[source] toml::de::Error),
+    #[error("Failed to encode the configuration")]
+    Encode(#[from] toml::ser::Error),
+    #[error("Target {target} matches several applicants, give more of it: {}", .candidates.join("; "))]
+    AmbiguousTarget { target: String, candidates: Vec<String> },
+    #[error("{0}")]
+    Invalid(String),
+}
+
+/// The simulation cannot run on what the sources provided
+#[derive(Debug, Error)]
+pub enum SimulationError {
+    #[error("{}", t!("sources.aborted", source_name))]
+    Aborted {
+        source_name: String,
+        #[source]
+        cause: Box<Error>,
+    },
+    #[error("{}", t!("sources.too_few", loaded, failed, required))]
+    TooFewSources { loaded: usize, failed: usize, required: usize },
+}
+
+/// Runs, snapshots or summaries could not be stored or looked up
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[cfg(feature = "storage")]
+    #[error("History database error")]
+    Database(#[from] rusqlite::Error),
+    #[error("Failed to write {path}")]
+    Write {
+        path: String,
+        #[source]
+        cause: std::io::Error,
+    },
+    #[error("Failed to encode the snapshot")]
+    Encode(#[from] serde_json::Error),
+    #[error("No run {0} in the history database")]
+    NoRun(i64),
+    #[error("Run {0} was stored without its applicant lists")]
+    NoSnapshot(i64),
+}
+
+#[cfg(feature = "storage")]
+impl From<rusqlite::Error> for Error {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Storage(error.into())
+    }
+}
@@ -0,0 +1,102 @@
+use crate::analyzer::TargetOutcome;
+use crate::atomic_file;
+use crate::error::StorageError;
+use crate::models::{FundingSource, ProgramKey};
+use crate::report_model::{ReportModel, ReportRow};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::path::Path;
+
+/// File in the run directory with one JSON event per line
+pub const EVENTS_FILE: &str = "events.jsonl";
+
+/// Something that happened during a run, for tools building their own analytics
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A file or URL was read and parsed
+    SourceFetched {
+        source: String,
+        // Modification time of a file, fetch time of a page
+        data_time: DateTime<Local>,
+        programs: usize,
+    },
+    /// A program list of a source, after deduplication
+    ProgramParsed {
+        source: String,
+        program_name: String,
+        // Empty with a single institution
+        institution: String,
+        applications: usize,
+        duplicates_removed: usize,
+    },
+    /// The simulation gave an applicant a seat
+    ApplicantAdmitted {
+        program_key: ProgramKey,
+        program_name: String,
+        funding_source: FundingSource,
+        snils: String,
+        // 1-based position in the admission order
+        position: Option<usize>,
+        priority: u32,
+        score: Option<f64>,
+    },
+    /// The target's outcome in one program, as in analysis.json
+    TargetStatus(TargetOutcome),
+}
+
+#[derive(Serialize)]
+struct LoggedEvent<'a> {
+    at: DateTime<Local>,
+    #[serde(flatten)]
+    event: &'a Event,
+}
+
+/// Events of a run in the order they happened
+#[derive(Debug, Default)]
+pub struct EventLog {
+    events: Vec<(DateTime<Local>, Event)>,
+}
+
+impl EventLog {
+    pub fn push(&mut self, event: Event) {
+        self.events.push((Local::now(), event));
+    }
+
+    /// Every admitted applicant in admission order, then the target's outcome per program
+    pub fn push_analysis(&mut self, report: &ReportModel, target_outcomes: &[TargetOutcome]) {
+        for program in &report.programs {
+            let mut admitted: Vec<&ReportRow> = program.admitted_rows().collect();
+            admitted.sort_by_key(|row| row.admission_position);
+            for row in admitted {
+                self.push(Event::ApplicantAdmitted {
+                    program_key: program.program_key.clone(),
+                    program_name: program.program_name.clone(),
+                    funding_source: program.funding_source.clone(),
+                    snils: row.record.snils.clone(),
+                    position: row.admission_position,
+                    priority: row.record.priority,
+                    score: row.record.score,
+                });
+            }
+        }
+        for outcome in target_outcomes {
+            self.push(Event::TargetStatus(outcome.clone()));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Write events.jsonl into `output_dir`
+    pub fn write(&self, output_dir: &Path) -> Result<(), StorageError> {
+        let mut content = String::new();
+        for (at, event) in &self.events {
+            content.push_str(&serde_json::to_string(&LoggedEvent { at: *at, event })?);
+            content.push('\n');
+        }
+        let path = output_dir.join(EVENTS_FILE);
+        atomic_file::write(&path, content).map_err(|cause| StorageError::Write { path: path.display().to_string(), cause })
+    }
+}
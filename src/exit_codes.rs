@@ -0,0 +1,35 @@
+use abitur_analyzer::error::{ConfigError, Error, SimulationError};
+use abitur_analyzer::run_summary::RunSummary;
+use std::process::ExitCode;
+
+/// Unexpected error
+pub const ERROR: u8 = 1;
+/// Invalid configuration; clap uses it for invalid arguments too
+pub const CONFIG: u8 = 2;
+/// Run completed, but some sources failed
+pub const PARTIAL: u8 = 3;
+/// Sources failed beyond what failure_policy allows, or no program lists were found
+pub const SOURCES: u8 = 4;
+
+/// Exit code of a completed run
+pub fn of_run(summary: &RunSummary) -> ExitCode {
+    if summary.sources_failed > 0 {
+        ExitCode::from(PARTIAL)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Exit code of a run that ended with `error`, by the category of the library error behind it
+pub fn of_error(error: &anyhow::Error) -> ExitCode {
+    for cause in error.chain() {
+        match cause.downcast_ref::<Error>() {
+            Some(Error::Simulation(_)) => return ExitCode::from(SOURCES),
+            Some(Error::Config(_)) => return ExitCode::from(CONFIG),
+            _ if cause.is::<SimulationError>() => return ExitCode::from(SOURCES),
+            _ if cause.is::<ConfigError>() => return ExitCode::from(CONFIG),
+            _ => {}
+        }
+    }
+    ExitCode::from(ERROR)
+}
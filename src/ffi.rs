@@ -0,0 +1,127 @@
+//! C interface to the analysis, for tools that embed it instead of running the CLI
+//!
+//! Requests and responses are JSON strings, so any language with a C FFI can call it
+//! without mirroring the library's types. With the `wasm` feature the same analysis is
+//! exported to JavaScript as `analyze`.
+
+use crate::analyzer::{AdmissionDistance, TargetOutcome};
+use crate::error::{error_chain, ConfigError};
+use crate::models::Config;
+use crate::identity::IdentityResolver;
+use crate::scoring::ScoringRules;
+use crate::scraper::AdmissionScraper;
+use crate::{analyze_prepared, prepare_records, sources, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::ffi::{c_char, CStr, CString};
+use std::panic;
+
+/// What [`abitur_analyze`] is asked to analyze
+#[derive(Debug, Deserialize)]
+struct Request {
+    // config.toml contents, built-in defaults for the keys left out
+    #[serde(default)]
+    config: String,
+    // Target of the analysis instead of the configuration's target_snils
+    target_snils: Option<String>,
+    pages: Vec<Page>,
+}
+
+/// An admission list page as pasted or downloaded by the caller
+#[derive(Debug, Deserialize)]
+struct Page {
+    // Name of the page in records and messages, e.g. its URL
+    source: String,
+    html: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Response {
+    // As the lists spell it
+    target_snils: String,
+    target_outcomes: Vec<TargetOutcome>,
+    admission_distances: Vec<AdmissionDistance>,
+}
+
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Analyze the pages of a JSON request and return the target's outcomes as JSON
+///
+/// The request is `{"config": "<config.toml>", "target_snils": "...", "pages": [{"source":
+/// "...", "html": "..."}]}`, where `config` and `target_snils` are optional. The response is
+/// `{"target_snils", "target_outcomes", "admission_distances"}` or `{"error": "..."}`, and
+/// must be released with [`abitur_free_string`]. Null only if `request` is null.
+///
+/// # Safety
+///
+/// `request` must be null or a NUL-terminated string valid for the duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn abitur_analyze(request: *const c_char) -> *mut c_char {
+    if request.is_null() {
+        return std::ptr::null_mut();
+    }
+    let request = CStr::from_ptr(request).to_string_lossy().into_owned();
+    // Serialized JSON escapes NUL characters, so it always makes a C string
+    CString::new(respond(&request)).unwrap_or_default().into_raw()
+}
+
+/// [`abitur_analyze`] for JavaScript, taking and returning the same JSON
+#[cfg(feature = "wasm")]
+#[wasm_bindgen::prelude::wasm_bindgen(js_name = analyze)]
+pub fn analyze_js(request: &str) -> String {
+    respond(request)
+}
+
+/// Release a string returned by [`abitur_analyze`]
+///
+/// # Safety
+///
+/// `response` must be null or a pointer [`abitur_analyze`] returned, released only once.
+#[no_mangle]
+pub unsafe extern "C" fn abitur_free_string(response: *mut c_char) {
+    if !response.is_null() {
+        drop(CString::from_raw(response));
+    }
+}
+
+/// JSON response to a JSON request, the error instead of the outcomes if it fails
+fn respond(request: &str) -> String {
+    let response = match panic::catch_unwind(|| analyze(request)) {
+        Ok(Ok(response)) => serde_json::to_string(&response),
+        Ok(Err(e)) => serde_json::to_string(&ErrorResponse { error: error_chain(&e) }),
+        Err(_) => serde_json::to_string(&ErrorResponse { error: "The analysis panicked".to_string() }),
+    };
+    response.unwrap_or_default()
+}
+
+fn analyze(request: &str) -> Result<Response> {
+    let request: Request =
+        serde_json::from_str(request).map_err(|e| ConfigError::Invalid(format!("Invalid request: {}", e)))?;
+    let mut config = Config::parse(&request.config)?;
+    if let Some(target_snils) = request.target_snils {
+        config.target_snils = target_snils;
+    }
+
+    let scraper = AdmissionScraper::new().with_row_merge(config.row_merge.unwrap_or_default());
+    let mut all_program_records = Vec::new();
+    for page in &request.pages {
+        for (program_info, records) in scraper.parse_html_content(&page.html, &page.source)? {
+            all_program_records.push((program_info.name, sources::deduplicate_records_by_snils(records)));
+        }
+    }
+
+    let identities = IdentityResolver::new(config.identity.as_ref())?;
+    let scoring = ScoringRules::new(config.scoring.as_deref())?;
+    let prepared = prepare_records(&config, &identities, &scoring, all_program_records)?;
+    let analyzed = analyze_prepared(&config, prepared, &BTreeSet::new(), |analyzer, all_program_records| {
+        analyzer.analyze_all_programs(all_program_records)
+    });
+    Ok(Response {
+        target_snils: analyzed.target_snils,
+        target_outcomes: analyzed.target_outcomes,
+        admission_distances: analyzed.admission_distances,
+    })
+}
@@ -0,0 +1,211 @@
+use crate::models::{FundingSource, ProgramInfo, StudentRecord, StudyForm};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+/// Programs of the generated campaign, in order of `FixtureOptions::programs`
+pub const PROGRAM_NAMES: &[&str] = &[
+    "ОП СПО Лечебное дело",
+    "ОП СПО Сестринское дело",
+    "ОП СПО Фармация",
+    "ОП СПО Акушерское дело",
+    "ОП СПО Лабораторная диагностика",
+    "ОП СПО Стоматология ортопедическая",
+    "ОП СПО Стоматология профилактическая",
+    "ОП СПО Медицинский массаж",
+];
+
+/// Size of a generated campaign; the same options and seed always give the same lists
+#[derive(Debug, Clone)]
+pub struct FixtureOptions {
+    // Programs taken from PROGRAM_NAMES, each with a budget and a commercial competition
+    pub programs: usize,
+    pub applicants: usize,
+    // Pages the competitions are spread over, like the lists of several sites
+    pub pages: usize,
+    pub seed: u64,
+}
+
+impl Default for FixtureOptions {
+    fn default() -> Self {
+        Self {
+            programs: 4,
+            applicants: 300,
+            pages: 2,
+            seed: 1,
+        }
+    }
+}
+
+/// Randomized admission lists with plausible places, scores, priorities and consents
+///
+/// Applicants get codes like `С25-04217` instead of SNILS, so the lists hold no personal
+/// data. Scores are normally distributed around 4.1; an applicant applies to one to five
+/// competitions, popular programs more often, and most hand in a consent or an original
+/// for one of them. A few applications are withdrawn.
+pub fn generate(options: &FixtureOptions) -> Vec<(ProgramInfo, Vec<StudentRecord>)> {
+    let mut rng = StdRng::seed_from_u64(options.seed);
+
+    let mut competitions: Vec<(ProgramInfo, f64)> = Vec::new();
+    for name in PROGRAM_NAMES.iter().take(options.programs.clamp(1, PROGRAM_NAMES.len())) {
+        let popularity = rng.gen_range(0.5..2.0);
+        for (funding_source, places) in [
+            (FundingSource::Budget, rng.gen_range(2..=10) * 5),
+            (FundingSource::Commercial, rng.gen_range(1..=6) * 5),
+        ] {
+            let info = ProgramInfo {
+                name: name.to_string(),
+                institution: String::new(),
+                funding_source,
+                study_form: StudyForm::FullTime,
+                available_places: places,
+                columns: Vec::new(),
+            };
+            competitions.push((info, popularity));
+        }
+    }
+
+    let mut codes: Vec<u32> = (0..100_000).collect();
+    codes.shuffle(&mut rng);
+    let mut lists: Vec<Vec<StudentRecord>> = vec![Vec::new(); competitions.len()];
+    for code in codes.into_iter().take(options.applicants) {
+        let snils = format!("С25-{:05}", code);
+        let average = (4.1 + 0.45 * standard_normal(&mut rng)).clamp(3.0, 5.0);
+        let marks: Vec<String> = (0..3)
+            .map(|_| (average + rng.gen_range(-0.6..0.6)).round().clamp(3.0, 5.0).to_string())
+            .collect();
+        let achievements = match rng.gen_range(0..10) {
+            0 => "1",
+            1 => "0,5",
+            _ => "",
+        };
+
+        // Competitions by priority, drawn by popularity without repeats
+        let applications = rng.gen_range(1..=5.min(competitions.len()));
+        let mut remaining: Vec<usize> = (0..competitions.len()).collect();
+        let mut chosen = Vec::new();
+        for _ in 0..applications {
+            let total: f64 = remaining.iter().map(|&index| competitions[index].1).sum();
+            let mut pick = rng.gen_range(0.0..total);
+            let position = remaining
+                .iter()
+                .position(|&index| {
+                    pick -= competitions[index].1;
+                    pick < 0.0
+                })
+                .unwrap_or(remaining.len() - 1);
+            chosen.push(remaining.remove(position));
+        }
+
+        // Most applicants settle on one application, usually the first priority
+        let decided = rng.gen_bool(0.6).then(|| if rng.gen_bool(0.7) { 0 } else { rng.gen_range(0..chosen.len()) });
+        let original_without_consent = decided.is_none() && rng.gen_bool(0.15);
+        for (index, &competition) in chosen.iter().enumerate() {
+            let info = &competitions[competition].0;
+            let consent = decided == Some(index);
+            let original = (consent && rng.gen_bool(0.7)) || (original_without_consent && index == 0);
+            lists[competition].push(StudentRecord {
+                rank: 0,
+                snils: snils.clone(),
+                priority: index as u32 + 1,
+                consent: yes_no(consent),
+                document_type: yes_no(original),
+                score: Some((average * 10_000.0).round() / 10_000.0),
+                average_score: format!("{:.4}", average).replace('.', ","),
+                achievements: achievements.to_string(),
+                bonus: 0.0,
+                subject_scores: marks.join(" "),
+                psychological_test: "-".to_string(),
+                program_name: info.name.clone(),
+                institution: String::new(),
+                funding_source: info.funding_source.clone(),
+                study_form: info.study_form.clone(),
+                available_places: info.available_places,
+                withdrawn: rng.gen_bool(0.02),
+                privileged: false,
+                computed_rank: None,
+                provenance: None,
+                submitted_at: None,
+                consent_at: None,
+                original_at: None,
+                recent_consent: false,
+                application_id: None,
+            });
+        }
+    }
+
+    competitions
+        .into_iter()
+        .zip(lists)
+        .filter(|(_, records)| !records.is_empty())
+        .map(|((info, _), mut records)| {
+            records.sort_by(|a, b| b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0)));
+            for (index, record) in records.iter_mut().enumerate() {
+                record.rank = index as u32 + 1;
+            }
+            (info, records)
+        })
+        .collect()
+}
+
+/// Spread the competitions round-robin over `pages` pages in the layout the scraper reads
+pub fn render_pages(programs: &[(ProgramInfo, Vec<StudentRecord>)], pages: usize) -> Vec<String> {
+    let pages = pages.clamp(1, programs.len().max(1));
+    (0..pages)
+        .map(|page| {
+            let mut html = String::from("<html><head><meta charset=\"utf-8\"></head><body><div class=\"data-wrap\">\n");
+            for (info, records) in programs.iter().skip(page).step_by(pages) {
+                html.push_str(&render_program(info, records));
+            }
+            html.push_str("</div></body></html>\n");
+            html
+        })
+        .collect()
+}
+
+fn render_program(info: &ProgramInfo, records: &[StudentRecord]) -> String {
+    let mut html = format!(
+        "<div><p><strong>{}</strong></p><p>Источник финансирования: <i>{}</i></p><p>Форма обучения: <i>{}</i></p><p>Количество мест: <i>{}</i></p></div>\n",
+        info.name,
+        info.funding_source.as_str(),
+        info.study_form.as_str(),
+        info.available_places
+    );
+    html.push_str("<table class=\"table-bordered\"><thead><tr>");
+    for header in [
+        "№", "ФИО", "СНИЛС / Код", "Приоритет", "Согласие", "Оригинал", "Средний балл", "Оценки",
+        "Вступительное испытание", "Индивидуальные достижения",
+    ] {
+        html.push_str(&format!("<th>{}</th>", header));
+    }
+    html.push_str("</tr></thead><tbody>\n");
+    for record in records {
+        let style = if record.withdrawn { " style=\"text-decoration: line-through\"" } else { "" };
+        html.push_str(&format!(
+            "<tr class=\"srt\"{}><td>{}</td><td></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            style,
+            record.rank,
+            record.snils,
+            record.priority,
+            record.consent,
+            record.document_type,
+            record.average_score,
+            record.subject_scores,
+            record.psychological_test,
+            record.achievements,
+        ));
+    }
+    html.push_str("</tbody></table>\n");
+    html
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "Да" } else { "Нет" }.to_string()
+}
+
+/// Box-Muller transform, rand has no normal distribution without rand_distr
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
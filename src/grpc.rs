@@ -0,0 +1,222 @@
+use crate::analyzer;
+use crate::cli::RunSettings;
+use crate::serve::{Dashboard, SNAPSHOT_BODY_LIMIT};
+use crate::snapshot::Snapshot;
+use crate::AnalyzedRun;
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use tonic::{Request, Response, Status};
+use tracing::info;
+
+/// Messages of proto/analyzer.proto and the generated `Analyzer` service
+pub mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct AnalyzeRequest {
+        #[prost(string, tag = "1")]
+        pub target_snils: String,
+        #[prost(string, tag = "2")]
+        pub snapshot_json: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct StatusRequest {
+        #[prost(string, tag = "1")]
+        pub target_snils: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct AnalysisReply {
+        #[prost(string, tag = "1")]
+        pub target_snils: String,
+        #[prost(message, repeated, tag = "2")]
+        pub target_outcomes: Vec<TargetOutcome>,
+        #[prost(message, repeated, tag = "3")]
+        pub admission_distances: Vec<AdmissionDistance>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TargetOutcome {
+        #[prost(string, tag = "1")]
+        pub program_key: String,
+        #[prost(string, tag = "2")]
+        pub program_name: String,
+        #[prost(string, tag = "3")]
+        pub institution: String,
+        #[prost(string, tag = "4")]
+        pub funding_source: String,
+        #[prost(uint32, tag = "5")]
+        pub available_places: u32,
+        #[prost(uint32, optional, tag = "6")]
+        pub published_places: Option<u32>,
+        #[prost(uint32, optional, tag = "7")]
+        pub priority: Option<u32>,
+        #[prost(double, optional, tag = "8")]
+        pub target_score: Option<f64>,
+        #[prost(double, tag = "9")]
+        pub target_bonus: f64,
+        #[prost(double, tag = "10")]
+        pub cutoff_score: f64,
+        #[prost(uint64, tag = "11")]
+        pub cutoff_ties: u64,
+        #[prost(double, optional, tag = "12")]
+        pub projected_cutoff_score: Option<f64>,
+        #[prost(uint32, optional, tag = "13")]
+        pub quota_places_returned: Option<u32>,
+        #[prost(double, optional, tag = "14")]
+        pub transfer_cutoff_score: Option<f64>,
+        #[prost(uint64, optional, tag = "15")]
+        pub position_in_admitted: Option<u64>,
+        #[prost(uint64, tag = "16")]
+        pub admitted_count: u64,
+        #[prost(string, tag = "17")]
+        pub status: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct AdmissionDistance {
+        #[prost(string, tag = "1")]
+        pub program_key: String,
+        #[prost(string, tag = "2")]
+        pub program_name: String,
+        #[prost(string, tag = "3")]
+        pub funding_source: String,
+        #[prost(uint32, tag = "4")]
+        pub priority: u32,
+        #[prost(uint32, tag = "5")]
+        pub available_places: u32,
+        #[prost(uint32, optional, tag = "6")]
+        pub published_places: Option<u32>,
+        #[prost(uint64, tag = "7")]
+        pub position: u64,
+        #[prost(uint64, tag = "8")]
+        pub unblocked_eager: u64,
+        #[prost(int64, tag = "9")]
+        pub seats_remaining: i64,
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/abitur.v1.Analyzer.rs"));
+}
+
+use proto::analyzer_server::{Analyzer, AnalyzerServer};
+use proto::{AnalysisReply, AnalyzeRequest, StatusRequest};
+
+impl From<&analyzer::TargetOutcome> for proto::TargetOutcome {
+    fn from(outcome: &analyzer::TargetOutcome) -> Self {
+        Self {
+            program_key: outcome.program_key.to_string(),
+            program_name: outcome.program_name.clone(),
+            institution: outcome.institution.clone(),
+            funding_source: outcome.funding_source.to_string(),
+            available_places: outcome.available_places,
+            published_places: outcome.published_places,
+            priority: outcome.priority,
+            target_score: outcome.target_score,
+            target_bonus: outcome.target_bonus,
+            cutoff_score: outcome.cutoff_score,
+            cutoff_ties: outcome.cutoff_ties as u64,
+            projected_cutoff_score: outcome.projected_cutoff_score,
+            quota_places_returned: outcome.quota_places_returned,
+            transfer_cutoff_score: outcome.transfer_cutoff_score,
+            position_in_admitted: outcome.position_in_admitted.map(|position| position as u64),
+            admitted_count: outcome.admitted_count as u64,
+            status: outcome.status.clone(),
+        }
+    }
+}
+
+impl From<&analyzer::AdmissionDistance> for proto::AdmissionDistance {
+    fn from(distance: &analyzer::AdmissionDistance) -> Self {
+        Self {
+            program_key: distance.program_key.to_string(),
+            program_name: distance.program_name.clone(),
+            funding_source: distance.funding_source.to_string(),
+            priority: distance.priority,
+            available_places: distance.available_places,
+            published_places: distance.published_places,
+            position: distance.position as u64,
+            unblocked_eager: distance.unblocked_eager as u64,
+            seats_remaining: distance.seats_remaining,
+        }
+    }
+}
+
+impl From<&AnalyzedRun> for AnalysisReply {
+    fn from(analyzed: &AnalyzedRun) -> Self {
+        Self {
+            target_snils: analyzed.target_snils.clone(),
+            target_outcomes: analyzed.target_outcomes.iter().map(Into::into).collect(),
+            admission_distances: analyzed.admission_distances.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+fn internal_error(e: anyhow::Error) -> Status {
+    Status::internal(format!("{:#}", e))
+}
+
+/// The RPCs share the dashboard's cached analysis of the latest snapshot
+struct AnalyzerService {
+    dashboard: Dashboard,
+}
+
+impl AnalyzerService {
+    /// The configured target unless the request names one
+    fn target_snils(&self, requested: String) -> String {
+        if requested.trim().is_empty() {
+            self.dashboard.settings.target_snils.clone()
+        } else {
+            requested
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Analyzer for AnalyzerService {
+    async fn analyze(&self, request: Request<AnalyzeRequest>) -> Result<Response<AnalysisReply>, Status> {
+        let request = request.into_inner();
+        let target_snils = self.target_snils(request.target_snils);
+        let analyzed = if request.snapshot_json.is_empty() {
+            self.dashboard.status_of(&target_snils).await.map_err(internal_error)?
+        } else {
+            let snapshot = Snapshot::parse(&request.snapshot_json).map_err(|e| Status::invalid_argument(format!("{:#}", e)))?;
+            Arc::new(
+                self.dashboard
+                    .analyze_for(target_snils, snapshot.into_program_records())
+                    .await
+                    .map_err(internal_error)?,
+            )
+        };
+        Ok(Response::new(AnalysisReply::from(analyzed.as_ref())))
+    }
+
+    async fn get_status(&self, request: Request<StatusRequest>) -> Result<Response<AnalysisReply>, Status> {
+        let target_snils = self.target_snils(request.into_inner().target_snils);
+        let analyzed = self.dashboard.status_of(&target_snils).await.map_err(internal_error)?;
+        if analyzed.target_outcomes.iter().all(|outcome| outcome.priority.is_none()) {
+            return Err(Status::not_found(t!("serve.applicant_not_found", target_snils)));
+        }
+        Ok(Response::new(AnalysisReply::from(analyzed.as_ref())))
+    }
+}
+
+/// Serve the `Analyzer` gRPC service for the latest run until Ctrl-C
+pub async fn serve(settings: RunSettings, bind: &str) -> Result<()> {
+    let address = tokio::net::lookup_host(bind)
+        .await?
+        .next()
+        .with_context(|| format!("cannot resolve {}", bind))?;
+    let service = AnalyzerServer::new(AnalyzerService {
+        dashboard: Dashboard::new(settings),
+    })
+    .max_decoding_message_size(SNAPSHOT_BODY_LIMIT);
+
+    info!("{}", t!("grpc.listening", address));
+    tonic::transport::Server::builder()
+        .add_service(service)
+        .serve_with_shutdown(address, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    info!("{}", t!("grpc.stopped"));
+    Ok(())
+}
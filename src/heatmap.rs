@@ -0,0 +1,152 @@
+use crate::atomic_file;
+use crate::csv_output;
+use crate::html_report::{escape, page_end, page_start};
+use crate::i18n;
+use crate::report_model::{ProgramReport, ReportModel};
+use anyhow::Result;
+use std::path::Path;
+
+/// One column of the heatmap and whether higher values mean more room for the target
+struct Metric {
+    header: &'static str,
+    label_key: &'static str,
+    higher_is_better: bool,
+    value: fn(&ProgramReport) -> Option<f64>,
+}
+
+const METRICS: [Metric; 6] = [
+    Metric { header: "Places", label_key: "report.places", higher_is_better: true, value: places },
+    Metric { header: "Eager_Applicants", label_key: "report.total_eager", higher_is_better: false, value: eager },
+    Metric { header: "Eager_Per_Place", label_key: "report.per_place", higher_is_better: false, value: eager_per_place },
+    Metric { header: "Cutoff", label_key: "report.cutoff_short", higher_is_better: false, value: cutoff },
+    Metric { header: "Projected_Cutoff", label_key: "report.projected_short", higher_is_better: false, value: projected_cutoff },
+    Metric { header: "Unclaimed_By_Originals", label_key: "heatmap.unclaimed", higher_is_better: true, value: unclaimed },
+];
+
+fn places(program: &ProgramReport) -> Option<f64> {
+    Some(program.available_places as f64)
+}
+
+fn eager(program: &ProgramReport) -> Option<f64> {
+    Some(program.total_eager_applicants as f64)
+}
+
+fn eager_per_place(program: &ProgramReport) -> Option<f64> {
+    (program.available_places > 0).then(|| program.total_eager_applicants as f64 / program.available_places as f64)
+}
+
+// No cutoff while nobody is admitted
+fn cutoff(program: &ProgramReport) -> Option<f64> {
+    (program.admitted_count > 0).then_some(program.cutoff_score)
+}
+
+fn projected_cutoff(program: &ProgramReport) -> Option<f64> {
+    program.projected_cutoff_score.filter(|score| *score > 0.0)
+}
+
+/// Places left once every applicant with the original document in the list takes one
+fn unclaimed(program: &ProgramReport) -> Option<f64> {
+    let originals = program.rows.iter().filter(|row| row.record.has_original_document()).count() as u32;
+    Some(program.available_places.saturating_sub(originals) as f64)
+}
+
+/// Integers without decimals, ratios and scores with them
+fn format_value(label_key: &str, value: f64) -> String {
+    match label_key {
+        "report.per_place" => format!("{:.2}", value),
+        "report.cutoff_short" | "report.projected_short" => format!("{:.4}", value),
+        _ => format!("{:.0}", value),
+    }
+}
+
+/// Write seat_heatmap.csv and seat_heatmap.html, every competition against the metrics
+/// telling where places remain
+pub fn generate_seat_heatmap(csv_dialect: &csv_output::CsvDialect, report: &ReportModel, output_dir: &str) -> Result<()> {
+    let mut writer = csv_dialect.writer(Path::new(output_dir).join("seat_heatmap.csv"));
+    let mut headers = vec!["Program", "Institution", "Funding_Type", "Study_Form"];
+    headers.extend(METRICS.iter().map(|metric| metric.header));
+    writer.write_record(headers)?;
+    for program in &report.programs {
+        let mut fields = vec![
+            program.program_name.clone(),
+            program.institution.clone(),
+            program.funding_source.to_string(),
+            program.program_key.study_form.as_str().to_string(),
+        ];
+        fields.extend(METRICS.iter().map(|metric| {
+            (metric.value)(program)
+                .map(|value| csv_dialect.decimal(&format_value(metric.label_key, value)))
+                .unwrap_or_default()
+        }));
+        writer.write_record(fields)?;
+    }
+    writer.flush()?;
+
+    atomic_file::write(Path::new(output_dir).join("seat_heatmap.html"), heatmap_html(report))?;
+    Ok(())
+}
+
+/// Sortable table with every metric cell shaded from red (crowded) to green (room left)
+/// relative to the other competitions
+fn heatmap_html(report: &ReportModel) -> String {
+    let ranges: Vec<(f64, f64)> = METRICS
+        .iter()
+        .map(|metric| {
+            report
+                .programs
+                .iter()
+                .filter_map(|program| (metric.value)(program))
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| (low.min(value), high.max(value)))
+        })
+        .collect();
+
+    let mut html = page_start(&t!("heatmap.title"));
+    html.push_str(&format!("<p>{}</p>\n", t!("heatmap.legend")));
+    html.push_str("<table class=\"sortable\">\n<thead><tr>");
+    html.push_str(&format!("<th>{}</th><th>{}</th><th>{}</th>", t!("report.program"), t!("report.funding"), t!("dry_run.study_form")));
+    for metric in &METRICS {
+        html.push_str(&format!("<th>{}</th>", t!(metric.label_key)));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+
+    for program in &report.programs {
+        let name = if program.institution.is_empty() {
+            escape(&program.program_name)
+        } else {
+            format!("{} ({})", escape(&program.program_name), escape(&program.institution))
+        };
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td>",
+            name,
+            escape(program.funding_source.as_str()),
+            escape(program.program_key.study_form.as_str())
+        ));
+        for (metric, &(low, high)) in METRICS.iter().zip(&ranges) {
+            match (metric.value)(program) {
+                Some(value) => {
+                    let text = match metric.label_key {
+                        "report.places" => i18n::places_label(program.available_places, program.published_places),
+                        _ => format_value(metric.label_key, value),
+                    };
+                    html.push_str(&format!("<td style=\"background: {}\">{}</td>", shade(value, low, high, metric.higher_is_better), text));
+                }
+                None => html.push_str("<td>-</td>"),
+            }
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html.push_str(&page_end());
+    html
+}
+
+/// Light red for the least favourable value of a column through yellow to light green
+/// for the most favourable one
+fn shade(value: f64, low: f64, high: f64, higher_is_better: bool) -> String {
+    let mut room = if high - low > f64::EPSILON { (value - low) / (high - low) } else { 0.5 };
+    if !higher_is_better {
+        room = 1.0 - room;
+    }
+    let hue = room * 120.0;
+    format!("hsl({:.0}, 70%, 80%)", hue)
+}
@@ -0,0 +1,398 @@
+use crate::analyzer::TargetOutcome;
+use crate::atomic_file;
+use crate::campaign::CampaignStatus;
+use crate::confidence::Confidence;
+use crate::i18n::{self, Language};
+use crate::report_model::{ProgramReport, ReportModel, ReportRow};
+use crate::strategies::Flow;
+use anyhow::Result;
+use std::path::Path;
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h1 { font-size: 1.6em; }
+h2 { font-size: 1.2em; margin-top: 2em; }
+table { border-collapse: collapse; margin: 0.5em 0; }
+th, td { border: 1px solid #ccc; padding: 3px 8px; text-align: left; }
+th { background: #f0f0f0; cursor: pointer; user-select: none; }
+tr.admitted td { background: #eef8ee; }
+tr.target td { background: #ffe9a8; font-weight: bold; }
+.verdict { padding: 1em; border-radius: 6px; font-size: 1.1em; }
+.verdict.ok { background: #dff3df; }
+.verdict.fail { background: #f8dddd; }
+.campaign { color: #555; }
+.status-Admitted { color: #1a7f1a; }
+.status-Not_Admitted { color: #b22222; }
+.status-Admitted_ByScore_NotByPriority { color: #b8860b; }
+.status-No_Places { color: #777; }
+.confidence-medium { color: #b8860b; }
+.confidence-low { color: #b22222; }
+"#;
+
+const SORT_SCRIPT: &str = r#"
+document.querySelectorAll('table.sortable th').forEach(function (th) {
+  th.addEventListener('click', function () {
+    var table = th.closest('table');
+    var body = table.tBodies[0];
+    var index = Array.prototype.indexOf.call(th.parentNode.children, th);
+    var asc = th.dataset.order !== 'asc';
+    th.dataset.order = asc ? 'asc' : 'desc';
+    var rows = Array.prototype.slice.call(body.rows);
+    rows.sort(function (a, b) {
+      var x = a.cells[index].textContent.trim().replace(',', '.');
+      var y = b.cells[index].textContent.trim().replace(',', '.');
+      var nx = parseFloat(x), ny = parseFloat(y);
+      var cmp = (!isNaN(nx) && !isNaN(ny)) ? nx - ny : x.localeCompare(y);
+      return asc ? cmp : -cmp;
+    });
+    rows.forEach(function (row) { body.appendChild(row); });
+  });
+});
+"#;
+
+/// Generate a single self-contained report.html with verdict, charts and sortable tables
+pub fn generate_html_report(
+    target_snils: &str,
+    campaign: Option<&CampaignStatus>,
+    report: &ReportModel,
+    target_outcomes: &[TargetOutcome],
+    flows: &[Flow],
+    output_dir: &str,
+) -> Result<()> {
+    let mut html = page_start(&t!("report.title", escape(target_snils)));
+    html.push_str(&verdict(target_outcomes));
+    if let Some(campaign) = campaign {
+        html.push_str(&format!("<p class=\"campaign\">{}</p>\n", escape(&t!("campaign.status", campaign.describe()))));
+    }
+
+    // Summary table of target outcomes
+    html.push_str(&format!("<h2>{}</h2>\n", t!("report.summary")));
+    html.push_str(&summary_table(target_outcomes));
+
+    // Chart comparing target score with cutoff per program
+    html.push_str(&format!("<h2>{}</h2>\n", t!("report.cutoff_vs_target")));
+    html.push_str(&cutoff_chart_svg(target_outcomes));
+
+    html.push_str(&format!("<h2>{}</h2>\n", t!("flows.title")));
+    html.push_str(&flow_chart_svg(flows));
+
+    // Sortable table per program-funding combination
+    for program in &report.programs {
+        html.push_str(&format!("<h2>{}</h2>\n", program_header(program)));
+        html.push_str(&program_table(program.rows.iter()));
+    }
+
+    html.push_str(&page_end());
+
+    atomic_file::write(Path::new(output_dir).join("report.html"), html)?;
+    Ok(())
+}
+
+/// Document head with styles and the page title as heading
+pub(crate) fn page_start(title: &str) -> String {
+    let lang = match i18n::language() {
+        Language::En => "en",
+        Language::Ru => "ru",
+    };
+    let mut html = format!("<!DOCTYPE html>\n<html lang=\"{}\">\n<head>\n<meta charset=\"utf-8\">\n", lang);
+    html.push_str(&format!("<title>{}</title>\n", title));
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+    html.push_str(&format!("<h1>{}</h1>\n", title));
+    html
+}
+
+/// Sorting script and closing tags
+pub(crate) fn page_end() -> String {
+    format!("<script>{}</script>\n</body>\n</html>\n", SORT_SCRIPT)
+}
+
+/// Final verdict: the first program (by target priority) the target was admitted to
+pub(crate) fn verdict(target_outcomes: &[TargetOutcome]) -> String {
+    let admitted_outcome = target_outcomes
+        .iter()
+        .filter(|outcome| outcome.status == "Admitted")
+        .min_by_key(|outcome| outcome.priority.unwrap_or(u32::MAX));
+    match admitted_outcome {
+        Some(outcome) => format!(
+            "<div class=\"verdict ok\">{}</div>\n",
+            t!(
+                "report.projected_admission",
+                escape(&outcome.program_name),
+                escape(outcome.funding_source.as_str()),
+                outcome.priority.unwrap_or(0)
+            )
+        ),
+        None => format!(
+            "<div class=\"verdict fail\">{}</div>\n",
+            t!("report.not_admitted_anywhere")
+        ),
+    }
+}
+
+/// Sortable table of the target's outcome in every program applied to
+pub(crate) fn summary_table(target_outcomes: &[TargetOutcome]) -> String {
+    let mut html = String::from("<table class=\"sortable\">\n<thead><tr>");
+    for key in [
+        "report.program",
+        "report.funding",
+        "report.priority",
+        "report.places",
+        "report.target_score",
+        "report.cutoff_score",
+        "report.on_cutoff",
+        "report.position",
+        "report.status",
+        "confidence.header",
+    ] {
+        html.push_str(&format!("<th>{}</th>", t!(key)));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for outcome in target_outcomes.iter().filter(|outcome| outcome.status != "Not_Applied") {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.4}</td><td>{}</td><td>{}</td><td class=\"status-{}\">{}</td>{}</tr>\n",
+            escape(&outcome.program_name),
+            escape(outcome.funding_source.as_str()),
+            outcome.priority.map(|p| p.to_string()).unwrap_or_default(),
+            i18n::places_label(outcome.available_places, outcome.published_places),
+            outcome.target_score.map(|s| format!("{:.4}", s)).unwrap_or_default(),
+            outcome.cutoff_score,
+            outcome.cutoff_ties,
+            outcome.position_in_admitted
+                .map(|p| t!("report.of", p, outcome.admitted_count))
+                .unwrap_or_else(|| "-".to_string()),
+            outcome.status,
+            escape(&i18n::status_label(&outcome.status)),
+            confidence_cell(outcome.confidence.as_ref()),
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+/// Grade cell with the reasons it is not high as a tooltip
+fn confidence_cell(confidence: Option<&Confidence>) -> String {
+    match confidence {
+        Some(confidence) => format!(
+            "<td class=\"confidence-{}\" title=\"{}\">{}</td>",
+            confidence.grade.as_str(),
+            escape(&confidence.reasons.join("; ")),
+            escape(&confidence.grade.label()),
+        ),
+        None => "<td>-</td>".to_string(),
+    }
+}
+
+/// Heading text with program, funding, places and eager applicants
+pub(crate) fn program_header(program: &ProgramReport) -> String {
+    t!(
+        "report.program_header",
+        escape(&program.program_name),
+        escape(program.funding_source.as_str()),
+        i18n::places_label(program.available_places, program.published_places),
+        program.total_eager_applicants
+    )
+}
+
+/// Sortable applicant table with admitted and target rows highlighted
+pub(crate) fn program_table<'a>(rows: impl Iterator<Item = &'a ReportRow>) -> String {
+    let mut html = String::from("<table class=\"sortable\">\n<thead><tr>");
+    for key in [
+        "report.rank",
+        "report.snils",
+        "report.priority",
+        "report.consent",
+        "report.original",
+        "report.score",
+        "report.achievements",
+        "report.admitted",
+    ] {
+        html.push_str(&format!("<th>{}</th>", t!(key)));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+
+    for row in rows {
+        let record = &row.record;
+        let class = if row.target {
+            "target"
+        } else if row.admitted {
+            "admitted"
+        } else {
+            ""
+        };
+        html.push_str(&format!(
+            "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            class,
+            record.rank,
+            escape(&record.snils),
+            record.priority,
+            escape(&record.consent),
+            escape(&record.document_type),
+            escape(&record.average_score),
+            escape(&record.achievements),
+            if row.admitted { "Да" } else { "Нет" },
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html
+}
+
+/// Render an inline SVG bar chart with cutoff and target score for each applied program
+pub(crate) fn cutoff_chart_svg(target_outcomes: &[TargetOutcome]) -> String {
+    let applied: Vec<&TargetOutcome> = target_outcomes
+        .iter()
+        .filter(|outcome| outcome.status != "Not_Applied")
+        .collect();
+
+    let max_score = applied
+        .iter()
+        .flat_map(|outcome| [outcome.cutoff_score, outcome.target_score.unwrap_or(0.0)])
+        .fold(0.0_f64, f64::max);
+    if applied.is_empty() || max_score <= 0.0 {
+        return format!("<p>{}</p>\n", t!("report.no_chart_data"));
+    }
+
+    let label_width = 360.0;
+    let bar_area = 400.0;
+    let row_height = 34.0;
+    let height = applied.len() as f64 * row_height + 30.0;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-size=\"12\">\n",
+        label_width + bar_area + 80.0,
+        height
+    );
+    for (i, outcome) in applied.iter().enumerate() {
+        let y = i as f64 * row_height + 10.0;
+        let cutoff_width = outcome.cutoff_score / max_score * bar_area;
+        let target_width = outcome.target_score.unwrap_or(0.0) / max_score * bar_area;
+        svg.push_str(&format!(
+            "<text x=\"0\" y=\"{:.1}\">{} ({})</text>\n",
+            y + 14.0,
+            escape(&outcome.program_name),
+            escape(outcome.funding_source.as_str())
+        ));
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{:.1}\" width=\"{:.1}\" height=\"12\" fill=\"#9db4d6\"/><text x=\"{:.1}\" y=\"{:.1}\">{:.2}</text>\n",
+            label_width, y, cutoff_width, label_width + cutoff_width + 4.0, y + 10.0, outcome.cutoff_score
+        ));
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{:.1}\" width=\"{:.1}\" height=\"12\" fill=\"#e8a33d\"/><text x=\"{:.1}\" y=\"{:.1}\">{:.2}</text>\n",
+            label_width, y + 14.0, target_width, label_width + target_width + 4.0, y + 24.0, outcome.target_score.unwrap_or(0.0)
+        ));
+    }
+    svg.push_str(&format!(
+        "<rect x=\"{}\" y=\"{:.1}\" width=\"10\" height=\"10\" fill=\"#9db4d6\"/><text x=\"{}\" y=\"{:.1}\">{}</text>\n",
+        label_width, height - 14.0, label_width + 14.0, height - 5.0, t!("report.cutoff")
+    ));
+    svg.push_str(&format!(
+        "<rect x=\"{}\" y=\"{:.1}\" width=\"10\" height=\"10\" fill=\"#e8a33d\"/><text x=\"{}\" y=\"{:.1}\">{}</text>\n",
+        label_width + 90.0, height - 14.0, label_width + 104.0, height - 5.0, t!("report.target_legend")
+    ));
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Sankey diagram of the flows: first priorities on the left, seats on the right, each band
+/// as wide as the applicants taking it
+pub(crate) fn flow_chart_svg(flows: &[Flow]) -> String {
+    let total: usize = flows.iter().map(|flow| flow.applicants).sum();
+    if total == 0 {
+        return format!("<p>{}</p>\n", t!("report.no_chart_data"));
+    }
+
+    // Nodes with their applicants, largest first, no seat last
+    let mut sources: Vec<(String, usize)> = Vec::new();
+    let mut destinations: Vec<(String, usize)> = Vec::new();
+    let no_seat = t!("flows.no_seat");
+    for flow in flows {
+        let source = flow.source.to_string();
+        let destination = flow.destination.as_ref().map_or_else(|| no_seat.clone(), ToString::to_string);
+        for (nodes, name) in [(&mut sources, source), (&mut destinations, destination)] {
+            match nodes.iter_mut().find(|(node, _)| *node == name) {
+                Some((_, applicants)) => *applicants += flow.applicants,
+                None => nodes.push((name, flow.applicants)),
+            }
+        }
+    }
+    sources.sort_by_key(|(_, applicants)| std::cmp::Reverse(*applicants));
+    destinations.sort_by_key(|(name, applicants)| (*name == no_seat, std::cmp::Reverse(*applicants)));
+
+    let label_width = 400.0;
+    let band_area = 300.0;
+    let node_width = 12.0;
+    let gap = 10.0;
+    let scale = 500.0 / total as f64;
+    let height = total as f64 * scale + gap * sources.len().max(destinations.len()) as f64 + 20.0;
+    let left = label_width;
+    let right = label_width + node_width + band_area;
+
+    // Top of every node, and how much of it the bands drawn so far take
+    let tops = |nodes: &[(String, usize)]| -> Vec<f64> {
+        let mut y = 10.0;
+        nodes
+            .iter()
+            .map(|(_, applicants)| {
+                let top = y;
+                y += *applicants as f64 * scale + gap;
+                top
+            })
+            .collect()
+    };
+    let source_tops = tops(&sources);
+    let destination_tops = tops(&destinations);
+    let mut source_used = vec![0.0; sources.len()];
+    let mut destination_used = vec![0.0; destinations.len()];
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{:.0}\" font-size=\"12\">\n",
+        right + node_width + label_width,
+        height
+    );
+    for flow in flows {
+        let destination = flow.destination.as_ref().map_or_else(|| no_seat.clone(), ToString::to_string);
+        let Some(s) = sources.iter().position(|(name, _)| *name == flow.source.to_string()) else {
+            continue;
+        };
+        let Some(d) = destinations.iter().position(|(name, _)| *name == destination) else {
+            continue;
+        };
+        let width = flow.applicants as f64 * scale;
+        let y0 = source_tops[s] + source_used[s] + width / 2.0;
+        let y1 = destination_tops[d] + destination_used[d] + width / 2.0;
+        source_used[s] += width;
+        destination_used[d] += width;
+        let (x0, x1) = (left + node_width, right);
+        let middle = (x0 + x1) / 2.0;
+        svg.push_str(&format!(
+            "<path d=\"M{:.1},{:.1} C{:.1},{:.1} {:.1},{:.1} {:.1},{:.1}\" fill=\"none\" stroke=\"{}\" stroke-opacity=\"0.4\" stroke-width=\"{:.1}\"><title>{} → {}: {}</title></path>\n",
+            x0, y0, middle, y0, middle, y1, x1, y1,
+            if flow.destination.is_none() { "#b22222" } else { "#9db4d6" },
+            width.max(1.0),
+            escape(&flow.source.to_string()),
+            escape(&destination),
+            flow.applicants
+        ));
+    }
+    for (nodes, node_tops, x, anchor, label_x) in [
+        (&sources, &source_tops, left, "end", left - 4.0),
+        (&destinations, &destination_tops, right, "start", right + node_width + 4.0),
+    ] {
+        for ((name, applicants), top) in nodes.iter().zip(node_tops) {
+            let node_height = *applicants as f64 * scale;
+            svg.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{}\" height=\"{:.1}\" fill=\"#555\"/><text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\" dominant-baseline=\"middle\">{} ({})</text>\n",
+                x, top, node_width, node_height.max(1.0), label_x, top + node_height / 2.0, anchor, escape(name), applicants
+            ));
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Escape text for safe inclusion in HTML
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,44 @@
+use crate::atomic_file;
+use crate::error::StorageError;
+use crate::models::{ProgramInfo, StudentRecord};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// File in the output root with the validators and lists of the pages fetched last
+pub const HTTP_CACHE_FILE: &str = "http_cache.json";
+
+/// A fetched page: what the server said identifies its version, and the lists parsed from it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedPage {
+    // ETag response header, sent back as If-None-Match
+    pub etag: Option<String>,
+    // Last-Modified response header, sent back as If-Modified-Since
+    pub last_modified: Option<String>,
+    pub programs: Vec<(ProgramInfo, Vec<StudentRecord>)>,
+}
+
+impl CachedPage {
+    /// Whether the server sent anything a conditional request can be made with
+    pub fn has_validators(&self) -> bool {
+        self.etag.is_some() || self.last_modified.is_some()
+    }
+}
+
+/// Cached pages by URL
+pub type HttpCache = BTreeMap<String, CachedPage>;
+
+/// Pages saved by the last run, empty if there are none or they cannot be read
+pub fn load(output_root: &Path) -> HttpCache {
+    fs::read_to_string(output_root.join(HTTP_CACHE_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(output_root: &Path, cache: &HttpCache) -> Result<(), StorageError> {
+    let path = output_root.join(HTTP_CACHE_FILE);
+    let content = serde_json::to_string(cache)?;
+    atomic_file::write(&path, content).map_err(|cause| StorageError::Write { path: path.display().to_string(), cause })
+}
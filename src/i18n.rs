@@ -0,0 +1,465 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "ru")]
+    Ru,
+}
+
+impl std::str::FromStr for Language {
+    type Err = crate::error::ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "en" => Ok(Language::En),
+            "ru" => Ok(Language::Ru),
+            other => Err(crate::error::ConfigError::Invalid(format!("Unknown language: {}", other))),
+        }
+    }
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// Select the language for all further messages; only the first call takes effect
+pub fn set_language(language: Language) {
+    let _ = LANGUAGE.set(language);
+}
+
+pub fn language() -> Language {
+    LANGUAGE.get().copied().unwrap_or(Language::En)
+}
+
+/// Translate a message by key and substitute `{}` placeholders in order: `t!("key", arg1, arg2)`
+#[macro_export]
+macro_rules! t {
+    ($key:expr) => {
+        $crate::i18n::translate($key).to_string()
+    };
+    ($key:expr, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format_message($crate::i18n::translate($key), &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+
+/// Look up the message for the current language, falling back to English and then to the key itself
+pub fn translate(key: &str) -> &str {
+    match MESSAGES.iter().find(|(k, _, _)| *k == key) {
+        Some((_, en, ru)) => match language() {
+            Language::En => en,
+            Language::Ru => ru,
+        },
+        None => key,
+    }
+}
+
+pub fn format_message(template: &str, args: &[&dyn Display]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() {
+        result.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            result.push_str(&arg.to_string());
+        }
+        result.push_str(part);
+    }
+    result
+}
+
+/// Human readable label for an admission status code
+pub fn status_label(status: &str) -> String {
+    match status {
+        "Admitted" => t!("status.admitted"),
+        "Admitted_ByScore_NotByPriority" => t!("status.admitted_by_score"),
+        "Not_Admitted" => t!("status.not_admitted"),
+        "Not_Applied" => t!("status.not_applied"),
+        "No_Places" => t!("status.no_places"),
+        other => other.to_string(),
+    }
+}
+
+/// Places of a program, with the published number when `seat_overrides` changed it
+pub fn places_label(available_places: u32, published_places: Option<u32>) -> String {
+    match published_places {
+        Some(published_places) => t!("report.places_overridden", available_places, published_places),
+        None => available_places.to_string(),
+    }
+}
+
+/// Message catalog: (key, English, Russian)
+const MESSAGES: &[(&str, &str, &str)] = &[
+    // Console: configuration
+    ("config.loading", "📋 Loading configuration from: {}", "📋 Загрузка конфигурации из: {}"),
+    ("config.profile", "🏷️ Using profile: {}", "🏷️ Используется профиль: {}"),
+    ("config.from_environment", "📋 No configuration file, using defaults and ABIT_* environment variables", "📋 Файла конфигурации нет, используются значения по умолчанию и переменные окружения ABIT_*"),
+    ("config.creating_default", "📝 Creating default configuration file: {}", "📝 Создание файла конфигурации по умолчанию: {}"),
+    ("config.edit_and_rerun", "⚠️  Please edit {} and set your target SNILS, then run the program again (or run abitur-analyzer init).", "⚠️  Отредактируйте {}, укажите СНИЛС абитуриента и запустите программу снова (или выполните abitur-analyzer init)."),
+    ("config.empty_snils", "❌ Error: target_snils is empty in configuration file and no argument provided", "❌ Ошибка: target_snils не задан ни в конфигурации, ни в аргументах"),
+    ("config.set_snils_hint", "   Please edit {} and set the target SNILS or pass it as a command-line argument", "   Укажите СНИЛС в {} или передайте его аргументом командной строки"),
+    ("config.mode_from_config", "Data source mode: {}", "Режим источников: {}"),
+    ("init.overwrite", "{} already exists, replace it?", "{} уже существует, заменить?"),
+    ("init.cancelled", "Configuration left unchanged", "Конфигурация не изменена"),
+    ("init.welcome", "🧭 Setting up abitur-analyzer, press Enter to accept a suggested value", "🧭 Настройка abitur-analyzer, Enter принимает предложенное значение"),
+    ("init.snils", "SNILS or applicant code of the target applicant", "СНИЛС или код абитуриента"),
+    ("init.snils_empty", "Enter a SNILS, e.g. 151-249-600 41", "Введите СНИЛС, например 151-249-600 41"),
+    ("init.source_mode", "Where are the applicant lists?", "Где находятся списки абитуриентов?"),
+    ("init.source_internet", "On the institution's website", "На сайте учебного заведения"),
+    ("init.source_local", "In saved HTML files", "В сохранённых HTML-файлах"),
+    ("init.urls", "URLs of the lists, comma separated", "Адреса списков через запятую"),
+    ("init.urls_invalid", "Enter at least one URL starting with http:// or https://", "Введите хотя бы один адрес, начинающийся с http:// или https://"),
+    ("init.data_directory", "Directory with the HTML files", "Каталог с HTML-файлами"),
+    ("init.directory_missing", "Directory {} does not exist", "Каталог {} не существует"),
+    ("init.testing", "🔎 Checking the sources with a test scrape...", "🔎 Проверка источников пробной загрузкой..."),
+    ("init.nothing_found", "⚠️  No applicant lists found in these sources", "⚠️  В этих источниках не найдено списков абитуриентов"),
+    ("init.retry", "Change the sources?", "Изменить источники?"),
+    ("init.found", "✅ Found {} lists with {} applications", "✅ Найдено списков: {}, заявлений: {}"),
+    ("init.target_found", "✅ The target applicant is in {} lists", "✅ Абитуриент найден в списках: {}"),
+    ("init.target_missing", "⚠️  {} is in none of the lists yet, check the SNILS", "⚠️  {} пока нет ни в одном списке, проверьте СНИЛС"),
+    ("init.programs", "Programs of interest (Space to toggle, none selected = all)", "Интересующие программы (пробел — выбрать, ничего не выбрано — все)"),
+    ("init.preset", "Admission rules of the institution", "Правила приёма учебного заведения"),
+    ("init.preset_spo_default", "spo_default: college, enrolled with the original certificate", "spo_default: колледж (СПО), зачисление по оригиналу аттестата"),
+    ("init.preset_spo_moscow", "spo_moscow: Moscow college, equal averages decided by achievements", "spo_moscow: колледж Москвы, при равных баллах решают достижения"),
+    ("init.preset_vo_2024", "vo_2024: university, enrolled with a consent, achievements added to the score", "vo_2024: вуз (ВО), зачисление по согласию, достижения добавляются к баллам"),
+    ("init.no_preset", "None: the built-in defaults, to set the rules in the file", "Без пресета: настройки по умолчанию, правила задаются в файле"),
+    ("init.formats", "Report formats (Space to toggle)", "Форматы отчётов (пробел — выбрать)"),
+    ("init.output_directory", "Output directory", "Каталог результатов"),
+    ("init.language", "Language of console output and reports", "Язык вывода и отчётов"),
+    ("init.saved", "💾 Configuration saved to {}, run abitur-analyzer to analyze", "💾 Конфигурация сохранена в {}, запустите abitur-analyzer для анализа"),
+    ("config.custom_template", "📝 Using custom template: {}", "📝 Используется пользовательский шаблон: {}"),
+    // Console: run
+    ("run.analyzing_snils", "🔍 Analyzing admission data for SNILS: {}", "🔍 Анализ данных о поступлении для СНИЛС: {}"),
+    ("run.output_directory", "📁 Output directory: {}", "📁 Каталог результатов: {}"),
+    ("run.data_source_mode", "🌐 Data source mode: {}", "🌐 Режим источников данных: {}"),
+    ("run.output_formats", "📑 Output formats: {}", "📑 Форматы отчётов: {}"),
+    ("run.processing_local", "📂 Processing local files from: {}", "📂 Обработка локальных файлов из: {}"),
+    ("run.processing_file", "📄 Processing local file: {}", "📄 Обработка локального файла: {}"),
+    ("run.found_applicants", "   ✅ Found {} applicants for program: {}", "   ✅ Найдено {} абитуриентов на программу: {}"),
+    ("run.duplicates_removed", "   🔄 Removed {} duplicate SNILS records", "   🔄 Удалено дубликатов СНИЛС: {}"),
+    ("run.file_error", "   ❌ Error processing local file: {}", "   ❌ Ошибка обработки локального файла: {}"),
+    ("run.missing_data_dir", "   ⚠️  Local data directory '{}' does not exist", "   ⚠️  Локальный каталог данных '{}' не существует"),
+    ("run.processing_internet", "🌐 Processing internet sources ({} URLs)", "🌐 Обработка интернет-источников ({} URL)"),
+    ("run.url_error", "   ❌ Error processing URL {}: {}", "   ❌ Ошибка обработки URL {}: {}"),
+    ("discovery.found", "🔎 {} program lists linked from {}", "🔎 Найдено {} списков по ссылкам со страницы {}"),
+    ("discovery.appeared", "🆕 New program list since the last run: {}", "🆕 Новый список с прошлого запуска: {}"),
+    ("discovery.disappeared", "⚠️  Program list no longer linked since the last run: {}", "⚠️  Ссылки на список больше нет на странице: {}"),
+    ("discovery.failed", "   ❌ Error reading index page {}: {}; using the {} lists found last time", "   ❌ Ошибка чтения страницы со списками {}: {}; используются найденные в прошлый раз списки: {}"),
+    ("layout.changed", "⚠️  LAYOUT CHANGED in {}: the table columns are not those of the last run ({}); please verify that the lists are still read correctly", "⚠️  ИЗМЕНИЛАСЬ СТРУКТУРА {}: столбцы таблиц не совпадают с прошлым запуском ({}); проверьте, что списки по-прежнему читаются правильно"),
+    ("run.no_urls", "   ⚠️  No internet URLs configured", "   ⚠️  Интернет-источники не настроены"),
+    ("sources.aborted", "Source {} failed and failure_policy is fail_fast", "Источник {} завершился ошибкой, а failure_policy = fail_fast"),
+    ("sources.too_few", "Only {} sources loaded ({} failed), at least {} required", "Загружено источников: {} (с ошибкой: {}), требуется не меньше {}"),
+    ("dry_run.start", "🔍 Dry run: checking the sources, no output is written", "🔍 Пробный запуск: проверка источников, результаты не записываются"),
+    ("dry_run.study_form", "Study form", "Форма обучения"),
+    ("dry_run.applications", "Applications", "Заявлений"),
+    ("dry_run.withdrawn", "Withdrawn", "Отозвано"),
+    ("dry_run.target_priority", "Target priority", "Приоритет абитуриента"),
+    ("dry_run.target_lists", "🎯 Target applicant {} found in {} of {} lists", "🎯 Абитуриент {} найден в {} из {} списков"),
+    ("dry_run.target_missing", "⚠️  Target applicant {} is not in any list", "⚠️  Абитуриента {} нет ни в одном списке"),
+    ("dry_run.inconsistencies", "⚠️  {} differences in scores or test results of the same applicant between lists", "⚠️  Расхождений в баллах или результатах испытаний одного абитуриента между списками: {}"),
+    ("dry_run.done", "✅ Dry run finished, a run would analyze the lists above", "✅ Пробный запуск завершён, анализироваться будут списки выше"),
+    ("dry_run.unsupported", "--dry-run only applies to run and scrape", "--dry-run применим только к run и scrape"),
+    ("run.no_sources", "❌ No valid data sources found or all sources failed", "❌ Нет доступных источников данных или все источники завершились ошибкой"),
+    ("run.analyzing", "\n🎯 Analyzing admission chances using priority-based algorithm...", "\n🎯 Анализ шансов на поступление по приоритетному алгоритму..."),
+    ("consistency.found", "⚠️  {} differences in scores or test results of the same applicant between lists, see reconciliation.csv", "⚠️  Расхождений в баллах или результатах испытаний одного абитуриента между списками: {}, см. reconciliation.csv"),
+    ("aliases.merged", "🔀 {} lists published under another program name merged into their program, {} duplicate applications dropped, see program_merges.csv", "🔀 Списков под другим названием программы объединено с программой: {}, повторных заявлений отброшено: {}, см. program_merges.csv"),
+    ("aliases.report", "📝 Program merges saved to: {}", "📝 Отчёт об объединении программ сохранён: {}"),
+    ("consistency.report", "📝 Reconciliation report saved to: {}", "📝 Отчёт о расхождениях сохранён: {}"),
+    ("ranks.found", "⚠️  {} published ranks do not fit the scores, see rank_discrepancies.csv", "⚠️  Опубликованных мест, не согласующихся с баллами: {}, см. rank_discrepancies.csv"),
+    ("ranks.report", "📝 Rank discrepancies saved to: {}", "📝 Расхождения в местах сохранены: {}"),
+    ("run.applicants_excluded", "🚫 {} applications of {} excluded applicants left out of the simulation", "🚫 Заявлений исключённых абитуриентов не учтено: {} (абитуриентов в списке исключений: {})"),
+    ("run.target_excluded", "⚠️  The target {} is in excluded_applicants and will not be found in the lists", "⚠️  Целевой абитуриент {} указан в excluded_applicants и не будет найден в списках"),
+    ("run.interest_missing", "⚠️  programs_of_interest: {} was configured but not found in any source", "⚠️  programs_of_interest: программа {} указана, но не найдена ни в одном источнике"),
+    ("run.interest_missing_closest", "⚠️  programs_of_interest: {} was configured but not found in any source — closest matches: {}", "⚠️  programs_of_interest: программа {} указана, но не найдена ни в одном источнике — похожие: {}"),
+    ("run.simulation_cached", "♻️  Lists and simulation settings unchanged, reusing the cached simulation", "♻️  Списки и настройки моделирования не изменились, используется сохранённый результат моделирования"),
+    ("run.simulation_cache_failed", "⚠️  Could not save the simulation cache: {}", "⚠️  Не удалось сохранить кэш моделирования: {}"),
+    ("run.seat_override_unknown", "⚠️  seat_overrides: no program with the key {}", "⚠️  seat_overrides: нет программы с ключом {}"),
+    ("run.no_places", "⚠️  {} shows no places: nobody is admitted there and its {} eager applicants compete on their other applications; set seat_overrides.\"{}\" to simulate it", "⚠️  {}: количество мест не указано, на программу никто не зачисляется, а её {} заявлений с согласием или оригиналом участвуют в конкурсе по другим приоритетам; укажите seat_overrides.\"{}\", чтобы смоделировать её"),
+    ("run.privileged_flagged", "⭐ {} applications admitted without entrance tests, seated ahead of the competition", "⭐ Заявлений без вступительных испытаний, зачисляемых вне конкурса: {}"),
+    ("flows.no_seat", "No seat", "Без места"),
+    ("flows.title", "Applicant flow from the first priority to the seat", "Движение абитуриентов от первого приоритета к месту"),
+    ("strategies.target", "🧭 {} other eager applicants applied exactly like the target: {}", "🧭 Так же, как целевой абитуриент, подали заявления ещё {} абитуриентов с оригиналом или согласием: {}"),
+    ("run.recent_consents", "⏳ {} consents given within {} days of the list not counted yet", "⏳ Не учитываются {} согласий, поданных менее чем за {} дн. до получения списка"),
+    ("run.funding_excluded", "🚫 {} applications to lists outside funding = {} excluded from the simulation", "🚫 Исключено из моделирования {} заявлений в списки вне funding = {}"),
+    ("run.late_excluded", "🚫 {} applications submitted after {} excluded from the simulation", "🚫 Исключено из моделирования {} заявлений, поданных позже {}"),
+    ("run.withdrawn_excluded", "🚫 {} withdrawn applications excluded from the simulation", "🚫 Отозванных заявлений исключено из моделирования: {}"),
+    ("identity.target_matched", "🔗 Target {} matched to {} in the lists", "🔗 Абитуриент {} найден в списках как {}"),
+    ("campaign.status", "📅 Campaign: {}", "📅 Приёмная кампания: {}"),
+    ("campaign.projection", "projection, originals can still be handed in", "прогноз, оригиналы ещё принимаются"),
+    ("campaign.locked", "locked, the originals deadline has passed and only applicants with originals are enrolled", "списки зафиксированы: приём оригиналов завершён, зачисляются только абитуриенты с оригиналами"),
+    ("campaign.next_date", "{}; {} days to {} ({})", "{}; {} дн. до {} ({})"),
+    ("campaign.originals_deadline", "the originals deadline", "окончания приёма оригиналов"),
+    ("campaign.enrollment_order", "the enrollment order", "приказа о зачислении"),
+    ("campaign.wave_end", "the end of the enrollment wave", "окончания волны зачисления"),
+    ("campaign.quota_transfer", "the transfer of unfilled quota places", "передачи незаполненных мест квот"),
+    ("quota.transfer", "↪️  {}: {} unfilled quota places expected on the transfer date", "↪️  {}: ожидается передача незаполненных мест квот: {}"),
+    ("run.rescored", "📐 {} program lists scored by their own strategy", "📐 Списков программ со своим способом подсчёта балла: {}"),
+    ("run.identities_linked", "🔗 {} applications linked to applicants listed under another identifier", "🔗 Заявлений, связанных с абитуриентами под другим идентификатором: {}"),
+    ("run.anonymized", "🕶️  SNILS replaced with pseudonymous IDs, target applicant is {}", "🕶️  СНИЛС заменены псевдонимами, абитуриент — {}"),
+    ("run.locked", "Another run is writing to {}, try again after it finishes", "В {} уже пишет другой запуск, повторите после его завершения"),
+    ("run.loading_stored_run", "📥 Loading run @{} from {}", "📥 Загрузка запуска @{} из {}"),
+    ("run.unchanged", "💤 No source changed since the last run, nothing to analyze", "💤 Источники не изменились с прошлого запуска, анализ не нужен"),
+    ("run.snapshot_saved", "💾 Parsed data saved to {}", "💾 Разобранные данные сохранены в {}"),
+    ("report.stored_run_unsupported", "Reports are written next to a snapshot file, {} is a stored run; use analyze instead", "Отчёты записываются рядом с файлом снимка, а {} — сохранённый запуск; используйте analyze"),
+    ("run.snapshot_options_unsupported", "--from-snapshot, --save-snapshot and --resume only apply to run and scrape", "--from-snapshot, --save-snapshot и --resume применимы только к run и scrape"),
+    ("run.loading_snapshot", "📥 Loading parsed data from {}", "📥 Загрузка разобранных данных из {}"),
+    ("run.complete", "✅ Priority-based analysis complete!", "✅ Анализ завершён!"),
+    ("run.results", "📂 Results: {}", "📂 Результаты: {}"),
+    ("run.check_output", "Check the output directory for detailed reports.", "Подробные отчёты находятся в каталоге результатов."),
+    ("run.cleaning", "🧹 Cleaning previous results...", "🧹 Удаление предыдущих результатов..."),
+    ("run.removed_file", "   🗑️  Removed file: {}", "   🗑️  Удалён файл: {}"),
+    ("run.removed_directory", "   🗑️  Removed directory: {}", "   🗑️  Удалён каталог: {}"),
+    ("run.cleaned", "   ✅ Output directory cleaned", "   ✅ Каталог результатов очищен"),
+    // Console: watch mode
+    ("watch.started", "👀 Watching sources every {} min, press Ctrl-C to stop", "👀 Отслеживание источников каждые {} мин, Ctrl-C для остановки"),
+    ("watch.started_schedule", "👀 Watching sources on schedule \"{}\" ({}), press Ctrl-C to stop", "👀 Отслеживание источников по расписанию \"{}\" ({}), Ctrl-C для остановки"),
+    ("watch.local_time", "local time", "местное время"),
+    ("watch.next_run", "⏳ Next run at {}", "⏳ Следующий запуск в {}"),
+    ("watch.finishing_run", "🛑 Stopping after the current run", "🛑 Остановка после завершения текущего запуска"),
+    ("watch.schedule_exhausted", "Schedule has no upcoming runs", "В расписании нет предстоящих запусков"),
+    ("watch.run_failed", "❌ Run failed: {}", "❌ Ошибка запуска: {}"),
+    ("watch.stopped", "🛑 Watch stopped", "🛑 Отслеживание остановлено"),
+    // Notifications
+    ("notify.title", "🔔 Admission changes for {}", "🔔 Изменения в поступлении для {}"),
+    ("notify.dropped_out", "❌ {} ({}): no longer admitted", "❌ {} ({}): больше не проходит"),
+    ("notify.cutoff_above_target", "📈 {} ({}): cutoff {} is above your score {}", "📈 {} ({}): проходной балл {} выше вашего {}"),
+    ("notify.seats_opened", "🪑 {} ({}): places {} → {}", "🪑 {} ({}): мест {} → {}"),
+    ("notify.summary_title", "📊 Admission analysis for {}", "📊 Анализ поступления для {}"),
+    ("notify.summary_line", "{} ({}): {}, cutoff {}, score {}", "{} ({}): {}, проходной {}, балл {}"),
+    ("notify.sent", "🔔 Notification sent via {}", "🔔 Уведомление отправлено через {}"),
+    ("notify.failed", "⚠️  Failed to send notification via {}: {}", "⚠️  Не удалось отправить уведомление через {}: {}"),
+    // Console: scraper
+    ("scraper.fetching", "🌐 Fetching data from: {}", "🌐 Загрузка данных с: {}"),
+    ("resume.resuming", "⏯️  Resuming the interrupted scrape with {} pages already fetched", "⏯️  Продолжение прерванной загрузки, уже получено страниц: {}"),
+    ("resume.nothing", "No interrupted scrape to resume, fetching every source", "Прерванной загрузки нет, загружаются все источники"),
+    ("resume.reused", "⏯️  Fetched before the interruption: {}", "⏯️  Получено до прерывания: {}"),
+    ("resume.save_failed", "Failed to save the scrape progress: {}", "Не удалось сохранить ход загрузки: {}"),
+    ("resume.hint", "{} sources failed; run again with --resume to fetch only the pages still missing", "Источников с ошибкой: {}; запустите снова с --resume, чтобы загрузить только недостающие страницы"),
+    ("scraper.not_modified", "♻️  Not modified since the last fetch, reusing its lists: {}", "♻️  Не изменилось с прошлой загрузки, используются прежние списки: {}"),
+    ("scraper.data_wrap_found", "   ✅ Found data-wrap section ({} chars)", "   ✅ Найден блок data-wrap ({} символов)"),
+    ("scraper.data_wrap_missing", "   ⚠️  No data-wrap section found, parsing entire document", "   ⚠️  Блок data-wrap не найден, разбирается весь документ"),
+    ("scraper.streaming", "   📜 Large page, parsing {} as a stream", "   📜 Большая страница, {} разбирается потоком"),
+    ("scraper.row_skipped", "   Skipping row: {}", "   Строка пропущена: {}"),
+    ("scraper.section", "   {}: from row {} on, {} with {} places", "   {}: со строки {} — {}, мест: {}"),
+    ("scraper.separator_skipped", "   {}: row {} is a subtotal or note, skipped", "   {}: строка {} — итог или примечание, пропущена"),
+    ("scraper.no_programs", "   ⚠️  Warning: No programs found in {}", "   ⚠️  Внимание: программы не найдены в {}"),
+    // Console: progress bars
+    ("progress.files", "Files", "Файлы"),
+    ("progress.urls", "URLs", "Ссылки"),
+    ("progress.programs", "Programs", "Программы"),
+    ("progress.applicants", "Simulation", "Моделирование"),
+    // Run summary
+    ("summary.title", "📈 Run summary", "📈 Сводка запуска"),
+    ("summary.sources", "Sources: {} processed, {} failed", "Источники: обработано {}, с ошибкой {}"),
+    ("summary.programs", "Programs found: {}", "Найдено программ: {}"),
+    ("summary.applicants", "Applications: {} total, {} unique applicants", "Заявления: всего {}, уникальных абитуриентов {}"),
+    ("summary.duplicates", "Duplicates removed: {}", "Удалено дубликатов: {}"),
+    ("summary.withdrawn", "Withdrawn applications (not simulated): {}", "Отозванные заявления (не учитываются): {}"),
+    ("summary.data_range", "Data timestamps: {}", "Время данных: {}"),
+    ("summary.data_range_value", "{} — {}", "{} — {}"),
+    ("summary.phases", "Wall-clock time per phase:", "Время выполнения этапов:"),
+    ("summary.phase_sources", "sources", "источники"),
+    ("summary.phase_analysis", "analysis", "анализ"),
+    ("summary.phase_reports", "reports", "отчёты"),
+    // Charts
+    ("chart.scores_title", "{}: score distribution", "{}: распределение баллов"),
+    ("chart.priorities_title", "{}: priorities of applications", "{}: приоритеты заявлений"),
+    ("chart.trend_title", "Cutoff score by run", "Проходной балл по запускам"),
+    ("chart.applicants", "Applicants", "Абитуриенты"),
+    ("chart.all_applicants", "All applicants", "Все абитуриенты"),
+    ("chart.eager_applicants", "Eager applicants", "Активные абитуриенты"),
+    // Console: final analysis
+    ("final.header", "📊 UNIFIED PRIORITY-BASED ADMISSION ANALYSIS for target SNILS: {}", "📊 ИТОГОВЫЙ АНАЛИЗ ПОСТУПЛЕНИЯ для СНИЛС: {}"),
+    // Console: compare
+    ("compare.header", "🔄 CHANGES for target SNILS: {}", "🔄 ИЗМЕНЕНИЯ для СНИЛС: {}"),
+    ("compare.no_changes", "No changes in the target's programs", "Изменений по программам абитуриента нет"),
+    // Console: validation against enrollment orders
+    ("validation.order_loaded", "📜 {} enrolled applicants in {}", "📜 Зачисленных абитуриентов: {} в {}"),
+    ("validation.unknown_program", "⚠️  Enrollment order program \"{}\" is not in the lists, enrollments not compared: {}", "⚠️  Программы \"{}\" из приказа нет в списках, зачислений не сравнено: {}"),
+    ("validation.header", "📜 SIMULATION vs ENROLLMENT ORDERS for target SNILS: {}", "📜 МОДЕЛИРОВАНИЕ и ПРИКАЗЫ О ЗАЧИСЛЕНИИ для СНИЛС: {}"),
+    ("validation.no_programs", "No program of the orders is in the lists", "Ни одной программы из приказов нет в списках"),
+    ("validation.predicted", "Predicted", "Прогноз"),
+    ("validation.enrolled", "Enrolled", "Зачислено"),
+    ("validation.confirmed", "Confirmed", "Подтверждено"),
+    ("validation.actual_cutoff", "Actual cutoff", "Фактический проходной"),
+    ("validation.accuracy", "🎯 {} of {} predicted seats confirmed ({}%)", "🎯 Подтверждено мест из прогноза: {} из {} ({}%)"),
+    ("validation.target", "Target: predicted {}, enrolled {}", "Абитуриент: прогноз — {}, зачислен — {}"),
+    ("validation.nowhere", "nowhere", "никуда"),
+    ("validation.reasons", "Mismatches by reason:", "Расхождения по причинам:"),
+    ("validation.reason.not_in_list", "enrolled, not in the program's list", "зачислен, но не было в списке программы"),
+    ("validation.reason.not_eager", "enrolled without an original or consent in the list", "зачислен без оригинала и согласия в списке"),
+    ("validation.reason.admitted_elsewhere", "enrolled, predicted for another program", "зачислен, по прогнозу — на другую программу"),
+    ("validation.reason.below_cutoff", "enrolled, predicted below the cutoff", "зачислен, по прогнозу — ниже проходного"),
+    ("validation.reason.enrolled_elsewhere", "predicted, enrolled in another program", "по прогнозу зачислен, но зачислен на другую программу"),
+    ("validation.reason.not_enrolled", "predicted, not in any order", "по прогнозу зачислен, но нет ни в одном приказе"),
+    ("validation.written", "📁 Validation report written to {}", "📁 Отчёт о проверке записан в {}"),
+    // Console: generate-fixture
+    ("fixture.written", "🧪 {} pages with {} lists and {} applications written to {}", "🧪 Страниц: {}, списков: {}, заявлений: {} записано в {}"),
+    ("invariants.found", "⚠️  The simulation result breaks {} invariants, see simulation_warnings.txt", "⚠️  Результат моделирования нарушает инварианты: {}, см. simulation_warnings.txt"),
+    ("invariants.report", "📝 Simulation warnings saved to: {}", "📝 Предупреждения моделирования сохранены: {}"),
+    ("invariants.title", "Simulation warnings", "Предупреждения моделирования"),
+    ("invariants.over_capacity", "{}: {} admitted to {} places", "{}: зачислено {} на {} мест"),
+    ("invariants.admitted_twice", "{} admitted to several programs: {}", "{} зачислен на несколько программ: {}"),
+    ("invariants.priority_bypassed", "{} admitted to {} while {}, a higher priority, has a free place", "{} зачислен на {}, хотя на {} с более высоким приоритетом есть свободное место"),
+    ("invariants.not_eager", "{} admitted to {} without the consent or original required", "{} зачислен на {} без требуемого согласия или оригинала"),
+    ("invariants.lost_seat", "{} loses their seat when {} gets {} places", "{} теряет место, если у {} будет мест: {}"),
+    ("fixture.usage", "   Analyze them with: abitur-analyzer --set data_source_mode=local --set data_directory={} --snils {}", "   Анализ: abitur-analyzer --set data_source_mode=local --set data_directory={} --snils {}"),
+    // Console: backtest
+    ("backtest.no_runs", "No run directories with a snapshot in {}", "В {} нет каталогов запусков со снимком"),
+    ("backtest.configured", "configured", "как настроено"),
+    ("backtest.scoring", "🧪 Scoring variant {} over {} runs", "🧪 Проверка варианта {} на запусках: {}"),
+    ("backtest.header", "🧪 BACKTEST against enrollment orders over {} runs", "🧪 ПРОВЕРКА по приказам о зачислении на запусках: {}"),
+    ("backtest.variant", "Variant", "Вариант"),
+    ("backtest.accuracy", "Seats confirmed", "Подтверждено мест"),
+    ("backtest.target_correct", "Target outcome right", "Исход абитуриента верен"),
+    ("backtest.cutoff_error", "Mean cutoff error", "Средняя ошибка проходного"),
+    ("backtest.best", "🏆 Most seats confirmed: {}", "🏆 Больше всего подтверждённых мест: {}"),
+    ("backtest.written", "📁 Backtest results written to {}", "📁 Результаты проверки записаны в {}"),
+    // Priority order optimizer
+    ("priorities.header", "🔀 PRIORITY ORDERS for target SNILS: {}", "🔀 ПОРЯДОК ПРИОРИТЕТОВ для СНИЛС: {}"),
+    ("priorities.ordering", "Priorities", "Приоритеты"),
+    ("priorities.seat", "Seat", "Место"),
+    ("priorities.chance", "Chance", "Шансы"),
+    ("priorities.margin", "Above cutoff", "Запас над проходным"),
+    ("priorities.moved", "Changed", "Изменено"),
+    ("priorities.current", "current", "текущий"),
+    ("priorities.keep", "✅ The current order of priorities already gets the best seat", "✅ Текущий порядок приоритетов уже даёт лучшее место"),
+    ("priorities.reorder", "🔀 The first order gets a better seat, changing {} priorities", "🔀 Первый порядок даёт место лучше, изменив приоритетов: {}"),
+    // Capacity stress test
+    ("capacity.sweeping", "📐 Simulating {} programs with up to {} places fewer and more", "📐 Моделирование {} программ с количеством мест меньше и больше текущего на {}"),
+    ("capacity.header", "📐 CAPACITY STRESS TEST for target SNILS: {}", "📐 ЧУВСТВИТЕЛЬНОСТЬ К КОЛИЧЕСТВУ МЕСТ для СНИЛС: {}"),
+    ("capacity.current", "Seat with the current places: {}", "Место при текущем количестве мест: {}"),
+    ("capacity.changes", "Places → seat", "Мест → место"),
+    ("capacity.closest", "Closest change", "Ближайшее изменение"),
+    ("capacity.stable", "✅ No change of places in the range tried moves the target", "✅ Ни одно из проверенных изменений количества мест не меняет результат"),
+    ("capacity.most_sensitive", "⚠️ Most sensitive: {}, {} places already change the seat", "⚠️ Наиболее чувствительна: {}, изменение на {} мест уже меняет место"),
+    // Confidence
+    ("confidence.header", "Confidence", "Достоверность"),
+    ("confidence.short", "Conf.", "Дост."),
+    ("confidence.high", "high", "высокая"),
+    ("confidence.medium", "medium", "средняя"),
+    ("confidence.low", "low", "низкая"),
+    ("confidence.unparsed_scores", "{}% of scores unreadable", "баллы не распознаны у {}%"),
+    ("confidence.masked_snils", "{}% of SNILS masked", "СНИЛС скрыт у {}%"),
+    ("confidence.stale", "lists {} hours old", "спискам {} ч"),
+    ("confidence.layout_changed", "table columns changed since the last run", "столбцы таблицы изменились с прошлого запуска"),
+    ("confidence.lowered", "⚠️  Low confidence in {} of {} programs: {}", "⚠️  Низкая достоверность в {} из {} программ: {}"),
+    // Single program estimate
+    ("estimate.running", "⚡ Estimating {} alone, the other programs as chances of its applicants leaving", "⚡ Оценка только программы {}, остальные программы учитываются как вероятность ухода абитуриентов"),
+    ("estimate.needs_one_program", "⚠️  single_program_estimate needs exactly one program in programs_of_interest, simulating all programs", "⚠️  Для single_program_estimate в programs_of_interest должна быть указана ровно одна программа, моделируются все программы"),
+    ("estimate.header", "⚡ SINGLE PROGRAM ESTIMATE for target SNILS: {}", "⚡ ОЦЕНКА ПО ОДНОЙ ПРОГРАММЕ для СНИЛС: {}"),
+    ("estimate.expected_ahead", "Expected ahead", "Ожидается впереди"),
+    ("estimate.seat_chance", "Seat chance", "Вероятность места"),
+    ("estimate.approximate", "ℹ️  An approximation; run without single_program_estimate for the full simulation", "ℹ️  Это приближённая оценка; для полного моделирования отключите single_program_estimate"),
+    // History database
+    ("history.empty", "No runs stored yet: enable the sqlite output format to keep the campaign history", "Сохранённых запусков пока нет: включите формат вывода sqlite, чтобы вести историю кампании"),
+    ("history.no_program", "No stored program matches \"{}\"", "Нет сохранённых программ, подходящих под \"{}\""),
+    ("history.cutoff_header", "📈 Cutoff history: {} ({})", "📈 История проходного балла: {} ({})"),
+    ("history.run", "Run", "Запуск"),
+    ("history.time", "Time", "Время"),
+    ("history.target", "Target", "Абитуриент"),
+    ("history.programs", "Programs", "Программы"),
+    ("history.lists", "Lists stored", "Списки сохранены"),
+    // Web dashboard
+    ("serve.listening", "Dashboard available at http://{}, press Ctrl-C to stop", "Панель доступна по адресу http://{}, для остановки нажмите Ctrl-C"),
+    ("serve.stopped", "Dashboard stopped", "Панель остановлена"),
+    ("grpc.listening", "gRPC service listening on {}, press Ctrl-C to stop", "gRPC-сервис слушает {}, для остановки нажмите Ctrl-C"),
+    ("grpc.stopped", "gRPC service stopped", "gRPC-сервис остановлен"),
+    ("serve.data_time", "Data of the latest run from {}", "Данные последнего запуска от {}"),
+    ("serve.programs", "Programs", "Программы"),
+    ("serve.no_history", "No history yet: enable the sqlite output format and run at least twice", "Истории пока нет: включите формат вывода sqlite и выполните хотя бы два запуска"),
+    ("serve.program_not_found", "Program {} not found", "Программа {} не найдена"),
+    ("serve.applicant_not_found", "Applicant {} is not in any list", "Абитуриент {} не найден ни в одном списке"),
+    ("serve.back", "All programs", "Все программы"),
+    ("serve.filter_all", "All applicants", "Все абитуриенты"),
+    ("serve.filter_eager", "With original or consent", "С оригиналом или согласием"),
+    ("serve.filter_admitted", "Admitted by simulation", "Зачисленные по симуляции"),
+    // Status labels (English keeps the status codes used in CSV/JSON)
+    ("status.admitted", "Admitted", "Зачислен"),
+    ("status.admitted_by_score", "Admitted_ByScore_NotByPriority", "Проходит по баллу, но не по приоритету"),
+    ("status.not_admitted", "Not_Admitted", "Не зачислен"),
+    ("status.not_applied", "Not_Applied", "Заявление не подано"),
+    ("status.no_places", "No_Places", "Нет данных о местах"),
+    // Report labels
+    ("distance.header", "🎯 DISTANCE TO ADMISSION (where the original document matters most)", "🎯 РАССТОЯНИЕ ДО ЗАЧИСЛЕНИЯ (куда выгоднее подать оригинал)"),
+    ("distance.position", "Position among eager", "Место среди активных"),
+    ("distance.seats_remaining", "Seats remaining", "Осталось мест"),
+    ("report.title", "Admission analysis for SNILS {}", "Анализ поступления для СНИЛС {}"),
+    ("report.summary", "Summary", "Итоги"),
+    ("report.program", "Program", "Программа"),
+    ("report.funding", "Funding", "Финансирование"),
+    ("report.priority", "Priority", "Приоритет"),
+    ("report.places", "Places", "Мест"),
+    ("report.target_score", "Target score", "Балл абитуриента"),
+    ("report.cutoff_score", "Cutoff score", "Проходной балл"),
+    ("report.cutoff_ties", "Applicants with exactly the cutoff score: {}", "Абитуриентов ровно с проходным баллом: {}"),
+    ("report.on_cutoff", "On cutoff", "С проходным"),
+    ("report.places_overridden", "{} (set manually, {} published)", "{} (задано вручную, опубликовано {})"),
+    ("report.projected_cutoff", "Projected cutoff (consent-only applicants enrolled too)", "Прогнозный проходной балл (с учётом подавших только согласие)"),
+    ("report.transfer_cutoff", "Cutoff after the quota transfer", "Проходной балл после передачи мест квот"),
+    ("report.quota_places_returned", "Unfilled quota places returned: {}", "Возвращается незаполненных мест квот: {}"),
+    ("report.position", "Position", "Место"),
+    ("report.status", "Status", "Статус"),
+    ("report.per_place", "Per place", "На место"),
+    ("report.avg_priority", "Avg priority", "Ср. приоритет"),
+    ("report.rank", "Rank", "№"),
+    ("report.snils", "SNILS", "СНИЛС"),
+    ("report.consent", "Consent", "Согласие"),
+    ("report.original", "Original", "Оригинал"),
+    ("report.score", "Score", "Балл"),
+    ("report.achievements", "Achievements", "Инд. достижения"),
+    ("report.admitted", "Admitted", "Зачислен"),
+    ("report.target", "Target", "Абитуриент"),
+    ("report.yes", "Yes", "Да"),
+    ("report.no", "No", "Нет"),
+    ("report.of", "{} of {}", "{} из {}"),
+    ("report.projected_admission", "✅ Projected admission: <b>{}</b> ({}), priority {}", "✅ Прогноз зачисления: <b>{}</b> ({}), приоритет {}"),
+    ("report.not_admitted_anywhere", "❌ Target applicant is not admitted to any program in the simulation", "❌ По результатам моделирования абитуриент не зачислен ни на одну программу"),
+    ("report.cutoff_vs_target", "Cutoff vs target score", "Проходной балл и балл абитуриента"),
+    ("report.no_chart_data", "No data for chart", "Нет данных для диаграммы"),
+    ("report.program_header", "{} ({}) — {} places, {} eager applicants", "{} ({}) — мест: {}, активных абитуриентов: {}"),
+    ("report.cutoff", "cutoff", "проходной"),
+    ("report.target_legend", "target", "абитуриент"),
+    ("report.popularity_title", "Program Popularity Analysis", "Анализ популярности программ"),
+    ("report.eager_per_place", "Eager applicants per place", "Активных абитуриентов на место"),
+    ("report.top_avg_priority", "Top candidates average priority", "Средний приоритет лидеров списка"),
+    ("report.average_score", "Average score", "Средний балл"),
+    ("report.available_places", "Available places", "Количество мест"),
+    ("report.total_eager", "Total eager applicants", "Всего активных абитуриентов"),
+    ("report.final_title", "Final Cutoff Analysis for SNILS: {}", "Итоговый анализ проходных баллов для СНИЛС: {}"),
+    ("report.target_not_found", "Target applicant not found", "Абитуриент не найден"),
+    ("report.position_in_admitted", "Position in admitted list: {} (of {} admitted)", "Место в списке зачисленных: {} (из {})"),
+    ("report.hypothetical", "Hypothetical: Cannot determine (target did not apply)", "Гипотетически: невозможно определить (заявление не подано)"),
+    ("report.priority_not_selected", "would qualify by score but priority {} not selected", "проходит по баллу, но приоритет {} не выбран"),
+    ("report.chances_title", "Admission chances: {}", "Шансы на поступление: {}"),
+    ("report.not_found_anywhere", "Target applicant was not found in any list.", "Абитуриент не найден ни в одном списке."),
+    ("report.cutoff_short", "Cutoff", "Проходной"),
+    ("report.projected_short", "Projected cutoff", "Прогноз проходного"),
+    ("report.transfer_short", "After quota transfer", "После передачи квот"),
+    ("report.institution", "Institution: {}", "Учебное заведение: {}"),
+    ("report.no_institution", "Other lists", "Прочие списки"),
+    ("report.best_option", "🏆 Best overall option: {} ({}), priority {}, {} above the cutoff", "🏆 Лучший вариант: {} ({}), приоритет {}, на {} выше проходного"),
+    ("report.no_best_option", "No institution admits the target in the simulation", "Ни одно учебное заведение не зачисляет абитуриента по результатам моделирования"),
+    ("report.legend", "✅ admitted · 🟡 passes by score, not by priority · ❌ not admitted", "✅ проходит · 🟡 проходит по баллу, но не по приоритету · ❌ не проходит"),
+    // verdict.pdf
+    ("pdf.title", "Admission verdict for SNILS {}", "Итоги поступления для СНИЛС {}"),
+    ("pdf.generated", "Generated {}", "Сформировано {}"),
+    ("pdf.campaign", "Campaign: {}", "Приёмная кампания: {}"),
+    ("pdf.dossier", "Applicant", "Абитуриент"),
+    ("pdf.applications", "Applications: {}, score: {}", "Заявлений: {}, балл: {}"),
+    ("pdf.documents", "Original documents: {}, consent to enrollment: {}", "Оригинал документа: {}, согласие на зачисление: {}"),
+    ("pdf.best_option", "Best option: {} ({}), priority {}", "Лучший вариант: {} ({}), приоритет {}"),
+    ("pdf.no_option", "The simulation admits the applicant to none of the programs", "По результатам моделирования абитуриент не зачислен ни на одну программу"),
+    ("pdf.more", "… and {} more programs, see final_cutoff_analysis.txt", "… и ещё программ: {}, см. final_cutoff_analysis.txt"),
+    ("pdf.legend", "Safe: admitted within the first {}% of the places. Match: admitted on the last places or passing by score on another priority. Reach: not admitted.", "Надёжно: зачисление в пределах первых {}% мест. Реально: зачисление на последние места или проходит по баллу на другом приоритете. Маловероятно: не зачислен."),
+    ("chance.safe", "Safe", "Надёжно"),
+    ("chance.match", "Match", "Реально"),
+    ("chance.reach", "Reach", "Маловероятно"),
+    // seat_heatmap.html
+    ("heatmap.title", "Seat availability", "Свободные места"),
+    ("heatmap.unclaimed", "Not claimed by originals", "Не занято оригиналами"),
+    ("heatmap.legend", "Every column is shaded from red, the most crowded competition, to green, the one with the most room left. Places not claimed by originals are the places minus the applicants who handed in the original document.", "Каждый столбец окрашен от красного (наибольший конкурс) до зелёного (больше всего свободных мест). Не занято оригиналами: места за вычетом абитуриентов, сдавших оригинал документа."),
+];
@@ -0,0 +1,189 @@
+use crate::error::ConfigError;
+use crate::models::{normalize_snils, IdentityOptions, StudentRecord};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use tracing::info;
+
+/// Links one applicant listed under different identifiers across lists
+///
+/// Some lists show the SNILS, others an internal applicant ID or a masked SNILS like
+/// `***-***-600 41`. Records are rewritten to one identifier per applicant before the
+/// analysis, so the simulation sees all their applications as one person's.
+#[derive(Debug, Clone, Default)]
+pub struct IdentityResolver {
+    // Normalized identifier -> identifier the applicant is reported under
+    aliases: HashMap<String, String>,
+    masked_snils: bool,
+    partial_target: bool,
+}
+
+impl IdentityResolver {
+    /// Resolver for the `[identity]` table; without one, identifiers only match exactly
+    pub fn new(options: Option<&IdentityOptions>) -> Result<Self, ConfigError> {
+        let mut resolver = Self::default();
+        let Some(options) = options else {
+            return Ok(resolver);
+        };
+        resolver.masked_snils = options.masked_snils.unwrap_or(false);
+        resolver.partial_target = options.partial_target.unwrap_or(false);
+
+        if let Some(path) = &options.cross_reference {
+            let content = fs::read_to_string(path).map_err(|cause| ConfigError::Read { path: path.clone(), cause })?;
+            for line in content.lines().map(str::trim) {
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let mut identifiers = line.split([',', ';', '\t']).map(str::trim).filter(|id| !id.is_empty());
+                let Some(canonical) = identifiers.next() else {
+                    continue;
+                };
+                for identifier in identifiers {
+                    resolver.aliases.insert(normalize_snils(identifier), canonical.to_string());
+                }
+            }
+        }
+        Ok(resolver)
+    }
+
+    /// Identifier `identifier` is reported under, itself if it has no alias
+    pub fn resolve(&self, identifier: &str) -> String {
+        self.aliases
+            .get(&normalize_snils(identifier))
+            .cloned()
+            .unwrap_or_else(|| identifier.to_string())
+    }
+
+    /// Identifier of the target applicant in the resolved lists
+    ///
+    /// With `partial_target` a target not listed as given is matched against every identifier:
+    /// hidden digits on either side match any digit, and a target without hidden digits matches
+    /// identifiers containing it. More than one applicant matching is an error naming them.
+    pub fn resolve_target(
+        &self,
+        target: &str,
+        all_program_records: &[(String, Vec<StudentRecord>)],
+    ) -> Result<String, ConfigError> {
+        let target = self.resolve(target);
+        let normalized_target = normalize_snils(&target);
+        let records = || all_program_records.iter().flat_map(|(_, records)| records.iter());
+        if !self.partial_target
+            || normalized_target.is_empty()
+            || records().any(|record| normalize_snils(&record.snils) == normalized_target)
+        {
+            return Ok(target);
+        }
+
+        // Matching identifier -> programs it is listed in
+        let mut candidates: BTreeMap<String, Vec<&str>> = BTreeMap::new();
+        for (program_name, records) in all_program_records {
+            for record in records.iter().filter(|record| matches_partially(&target, &record.snils)) {
+                let programs = candidates.entry(record.snils.clone()).or_default();
+                if !programs.contains(&program_name.as_str()) {
+                    programs.push(program_name);
+                }
+            }
+        }
+        let mut matches = candidates.keys();
+        match (matches.next(), matches.next()) {
+            (None, _) => Ok(target),
+            (Some(identifier), None) => {
+                info!("{}", t!("identity.target_matched", target, identifier));
+                Ok(identifier.clone())
+            }
+            _ => Err(ConfigError::AmbiguousTarget {
+                target,
+                candidates: candidates
+                    .iter()
+                    .map(|(identifier, programs)| format!("{} ({})", identifier, programs.join(", ")))
+                    .collect(),
+            }),
+        }
+    }
+
+    /// Rewrite the SNILS of every record to the applicant's identifier, returns how many changed
+    ///
+    /// A masked SNILS is linked only if exactly one full SNILS in the lists fits it.
+    pub fn resolve_records(&self, all_program_records: &mut [(String, Vec<StudentRecord>)]) -> usize {
+        let full_snils: Vec<String> = if self.masked_snils {
+            let mut full_snils: Vec<String> = all_program_records
+                .iter()
+                .flat_map(|(_, records)| records.iter())
+                .map(|record| self.resolve(&record.snils))
+                .filter(|snils| mask_pattern(snils).is_none() && snils_digits(snils).len() == SNILS_DIGITS)
+                .collect();
+            full_snils.sort();
+            full_snils.dedup();
+            full_snils
+        } else {
+            Vec::new()
+        };
+
+        let mut linked = 0;
+        for record in all_program_records.iter_mut().flat_map(|(_, records)| records.iter_mut()) {
+            let mut identifier = self.resolve(&record.snils);
+            if self.masked_snils {
+                if let Some(pattern) = mask_pattern(&identifier) {
+                    let mut candidates = full_snils.iter().filter(|snils| fits(&pattern, &snils_digits(snils)));
+                    if let (Some(snils), None) = (candidates.next(), candidates.next()) {
+                        identifier = snils.clone();
+                    }
+                }
+            }
+            if identifier != record.snils {
+                record.snils = identifier;
+                linked += 1;
+            }
+        }
+        linked
+    }
+}
+
+const SNILS_DIGITS: usize = 11;
+// Digits or characters a partial target has to share with an identifier
+const MIN_PARTIAL_MATCH: usize = 4;
+
+fn snils_digits(snils: &str) -> Vec<char> {
+    snils.chars().filter(|c| c.is_ascii_digit()).collect()
+}
+
+/// Digits of an identifier with `None` for hidden ones
+fn digit_pattern(identifier: &str) -> Vec<Option<char>> {
+    identifier
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '*')
+        .map(|c| (c != '*').then_some(c))
+        .collect()
+}
+
+/// Whether `snils` hides some of its digits, like "***-***-600 41"
+pub fn is_masked(snils: &str) -> bool {
+    mask_pattern(snils).is_some()
+}
+
+/// Digits of a masked SNILS with `None` for hidden ones, `None` if it is not masked
+fn mask_pattern(snils: &str) -> Option<Vec<Option<char>>> {
+    let pattern = digit_pattern(snils);
+    (pattern.len() == SNILS_DIGITS && pattern.contains(&None)).then_some(pattern)
+}
+
+/// Whether `identifier` may be `target` with digits hidden on either side or only part given
+fn matches_partially(target: &str, identifier: &str) -> bool {
+    let target_pattern = digit_pattern(target);
+    let identifier_pattern = digit_pattern(identifier);
+    if target_pattern.contains(&None) || identifier_pattern.contains(&None) {
+        let digits = target_pattern.iter().zip(&identifier_pattern);
+        return target_pattern.len() == identifier_pattern.len()
+            && digits.clone().filter(|(a, b)| a.is_some() && b.is_some()).count() >= MIN_PARTIAL_MATCH
+            && digits.clone().all(|(a, b)| a.is_none() || b.is_none() || a == b);
+    }
+    let target = normalize_snils(target);
+    target.chars().count() >= MIN_PARTIAL_MATCH && normalize_snils(identifier).contains(&target)
+}
+
+fn fits(pattern: &[Option<char>], digits: &[char]) -> bool {
+    pattern.len() == digits.len()
+        && pattern
+            .iter()
+            .zip(digits)
+            .all(|(expected, digit)| expected.is_none_or(|expected| expected == *digit))
+}
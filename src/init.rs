@@ -0,0 +1,235 @@
+use abitur_analyzer::i18n::{self, Language};
+use abitur_analyzer::models::{normalize_snils, Config, DataSourceMode, OutputFormat, StudentRecord};
+use abitur_analyzer::presets;
+use abitur_analyzer::run_summary::RunSummary;
+use abitur_analyzer::sources;
+use anyhow::Result;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Input, MultiSelect, Select};
+use std::path::Path;
+use tracing::{info, warn};
+
+const ALL_FORMATS: [OutputFormat; 9] = [
+    OutputFormat::Csv,
+    OutputFormat::Json,
+    OutputFormat::Html,
+    OutputFormat::Xlsx,
+    OutputFormat::Markdown,
+    OutputFormat::Sqlite,
+    OutputFormat::Charts,
+    OutputFormat::Pdf,
+    OutputFormat::Heatmap,
+];
+
+/// Ask for the settings interactively, check the sources with a test scrape and write `config_file`
+///
+/// An existing file provides the defaults; its profiles are kept.
+pub async fn run(config_file: &str) -> Result<()> {
+    let theme = ColorfulTheme::default();
+    let existing = Path::new(config_file).exists();
+    if existing
+        && !Confirm::with_theme(&theme)
+            .with_prompt(t!("init.overwrite", config_file))
+            .default(false)
+            .interact()?
+    {
+        info!("{}", t!("init.cancelled"));
+        return Ok(());
+    }
+    let mut config = if existing { Config::load_from_file(config_file)? } else { Config::default() };
+    info!("{}", t!("init.welcome"));
+
+    config.target_snils = Input::with_theme(&theme)
+        .with_prompt(t!("init.snils"))
+        .with_initial_text(config.target_snils.clone())
+        .validate_with(|snils: &String| {
+            if normalize_snils(snils).is_empty() {
+                Err(t!("init.snils_empty"))
+            } else {
+                Ok(())
+            }
+        })
+        .interact_text()?;
+
+    let all_program_records = loop {
+        ask_sources(&theme, &mut config)?;
+        info!("{}", t!("init.testing"));
+        let records =
+            sources::load_program_records(&config, &config.data_source_mode, &mut RunSummary::default()).await?;
+        if !records.is_empty() {
+            break records;
+        }
+        warn!("{}", t!("init.nothing_found"));
+        if !Confirm::with_theme(&theme).with_prompt(t!("init.retry")).default(true).interact()? {
+            break records;
+        }
+    };
+    report_target(&config.target_snils, &all_program_records);
+
+    let mut program_names: Vec<String> = all_program_records.iter().map(|(name, _)| name.clone()).collect();
+    program_names.sort();
+    program_names.dedup();
+    if !program_names.is_empty() {
+        let selected = config.programs_of_interest.clone().unwrap_or_default();
+        let items: Vec<(String, bool)> =
+            program_names.iter().map(|name| (name.clone(), selected.contains(name))).collect();
+        let chosen = MultiSelect::with_theme(&theme)
+            .with_prompt(t!("init.programs"))
+            .items_checked(&items)
+            .interact()?;
+        config.programs_of_interest = if chosen.is_empty() || chosen.len() == program_names.len() {
+            None
+        } else {
+            Some(chosen.into_iter().map(|index| program_names[index].clone()).collect())
+        };
+    }
+
+    config = ask_preset(&theme, config, config_file)?;
+
+    let formats = config.output_formats.clone().unwrap_or_else(|| vec![OutputFormat::Csv]);
+    let items: Vec<(&str, bool)> = ALL_FORMATS.iter().map(|format| (format.name(), formats.contains(format))).collect();
+    let chosen = MultiSelect::with_theme(&theme)
+        .with_prompt(t!("init.formats"))
+        .items_checked(&items)
+        .interact()?;
+    config.output_formats = Some(chosen.into_iter().map(|index| ALL_FORMATS[index]).collect());
+
+    config.output_directory = Some(
+        Input::with_theme(&theme)
+            .with_prompt(t!("init.output_directory"))
+            .default(config.output_directory.clone().unwrap_or_else(|| "output".to_string()))
+            .interact_text()?,
+    );
+
+    let languages = [Language::En, Language::Ru];
+    let current = config.language.unwrap_or_else(i18n::language);
+    let language = Select::with_theme(&theme)
+        .with_prompt(t!("init.language"))
+        .items(&["English", "Русский"])
+        .default(languages.iter().position(|language| *language == current).unwrap_or(0))
+        .interact()?;
+    config.language = Some(languages[language]);
+
+    save(&config, config_file)?;
+    info!("{}", t!("init.saved", config_file));
+    Ok(())
+}
+
+/// Campaign rules preset; choosing another one puts its settings in place of the current ones
+fn ask_preset(theme: &ColorfulTheme, config: Config, config_file: &str) -> Result<Config> {
+    let names = presets::names(Some(Path::new(config_file)));
+    let mut items: Vec<String> = names
+        .iter()
+        .map(|name| match presets::PRESETS.iter().any(|(preset, _)| preset == name) {
+            true => t!(&format!("init.preset_{}", name)),
+            false => name.clone(),
+        })
+        .collect();
+    items.push(t!("init.no_preset"));
+    let current = config.preset.as_ref().and_then(|preset| names.iter().position(|name| name == preset));
+    let chosen = Select::with_theme(theme)
+        .with_prompt(t!("init.preset"))
+        .items(&items)
+        .default(current.unwrap_or(names.len()))
+        .interact()?;
+    let preset = names.get(chosen).cloned();
+    if preset == config.preset {
+        return Ok(config);
+    }
+    let mut table = toml::Table::try_from(&config)?;
+    match &preset {
+        Some(name) => presets::overlay(&mut table, name, Some(Path::new(config_file)))?,
+        None => {
+            table.remove("preset");
+        }
+    }
+    Ok(toml::Value::Table(table).try_into()?)
+}
+
+/// Data source mode and the URLs or directory it reads
+fn ask_sources(theme: &ColorfulTheme, config: &mut Config) -> Result<()> {
+    let mode = Select::with_theme(theme)
+        .with_prompt(t!("init.source_mode"))
+        .items(&[t!("init.source_internet"), t!("init.source_local")])
+        .default(usize::from(matches!(config.data_source_mode, DataSourceMode::Local)))
+        .interact()?;
+
+    if mode == 0 {
+        config.data_source_mode = DataSourceMode::Internet;
+        // The generated default points at example.com, do not offer it
+        let urls = config
+            .internet_urls
+            .iter()
+            .flatten()
+            .filter(|url| !url.contains("example.com"))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        let urls: String = Input::with_theme(theme)
+            .with_prompt(t!("init.urls"))
+            .with_initial_text(urls)
+            .validate_with(|urls: &String| {
+                let urls = split_urls(urls);
+                if urls.is_empty() || urls.iter().any(|url| !url.starts_with("http://") && !url.starts_with("https://")) {
+                    Err(t!("init.urls_invalid"))
+                } else {
+                    Ok(())
+                }
+            })
+            .interact_text()?;
+        config.internet_urls = Some(split_urls(&urls));
+    } else {
+        config.data_source_mode = DataSourceMode::Local;
+        let directory: String = Input::with_theme(theme)
+            .with_prompt(t!("init.data_directory"))
+            .default(config.data_directory.clone().unwrap_or_else(|| "data-source".to_string()))
+            .validate_with(|directory: &String| {
+                if Path::new(directory).is_dir() {
+                    Ok(())
+                } else {
+                    Err(t!("init.directory_missing", directory))
+                }
+            })
+            .interact_text()?;
+        config.data_directory = Some(directory);
+    }
+    Ok(())
+}
+
+fn split_urls(urls: &str) -> Vec<String> {
+    urls.split([',', ' '])
+        .map(str::trim)
+        .filter(|url| !url.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Found lists and whether the target applicant is in any of them
+fn report_target(target_snils: &str, all_program_records: &[(String, Vec<StudentRecord>)]) {
+    let applications = all_program_records.iter().map(|(_, records)| records.len()).sum::<usize>();
+    info!("{}", t!("init.found", all_program_records.len(), applications));
+    let target = normalize_snils(target_snils);
+    let target_programs = all_program_records
+        .iter()
+        .filter(|(_, records)| records.iter().any(|record| normalize_snils(&record.snils) == target))
+        .count();
+    if target_programs > 0 {
+        info!("{}", t!("init.target_found", target_programs));
+    } else if !all_program_records.is_empty() {
+        warn!("{}", t!("init.target_missing", target_snils));
+    }
+}
+
+/// Write the config, keeping `[profiles]` of the file it replaces and leaving the settings of
+/// its preset to the preset
+fn save(config: &Config, config_file: &str) -> Result<()> {
+    let mut table = toml::Table::try_from(config)?;
+    presets::strip(&mut table, Some(Path::new(config_file)))?;
+    if let Ok(content) = std::fs::read_to_string(config_file) {
+        if let Some(profiles) = toml::from_str::<toml::Table>(&content)?.remove("profiles") {
+            table.insert("profiles".to_string(), profiles);
+        }
+    }
+    std::fs::write(config_file, toml::to_string_pretty(&table)?)?;
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use crate::models::StudentRecord;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// Program names at least this similar to a missing program of interest are suggested
+const MIN_SIMILARITY: f64 = 0.5;
+
+/// At most this many suggestions per missing program
+const MAX_SUGGESTIONS: usize = 3;
+
+/// A program of interest no source lists, with the listed names that look like it
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingProgram {
+    pub name: String,
+    // Most similar first
+    pub closest: Vec<String>,
+}
+
+/// Programs of interest missing from the loaded lists, which would otherwise silently drop
+/// out of every report filtered by them
+///
+/// Names are compared exactly, as the reports do; suggestions ignore case and spacing and
+/// include the listed names containing the configured one.
+pub fn missing(programs_of_interest: &[String], all_program_records: &[(String, Vec<StudentRecord>)]) -> Vec<MissingProgram> {
+    let listed: BTreeSet<&str> = all_program_records.iter().map(|(program_name, _)| program_name.as_str()).collect();
+    programs_of_interest
+        .iter()
+        .filter(|name| !listed.contains(name.as_str()))
+        .map(|name| {
+            let wanted = normalize(name);
+            let mut scored: Vec<(f64, &str)> = listed
+                .iter()
+                .map(|program_name| (similarity(&wanted, &normalize(program_name)), *program_name))
+                .filter(|(score, _)| *score >= MIN_SIMILARITY)
+                .collect();
+            scored.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(b.1)));
+            MissingProgram {
+                name: name.clone(),
+                closest: scored.into_iter().take(MAX_SUGGESTIONS).map(|(_, program_name)| program_name.to_string()).collect(),
+            }
+        })
+        .collect()
+}
+
+fn normalize(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// 1 for equal names down to 0, a name containing the other counting as nearly equal
+fn similarity(wanted: &str, listed: &str) -> f64 {
+    if wanted == listed {
+        return 1.0;
+    }
+    if !wanted.is_empty() && (listed.contains(wanted) || wanted.contains(listed)) {
+        return 0.9;
+    }
+    strsim::normalized_levenshtein(wanted, listed)
+}
@@ -0,0 +1,390 @@
+use crate::analyzer::{AdmissionAnalysis, AdmissionAnalyzer};
+use crate::models::{normalize_snils, CutoffTies, ProgramKey, StudentRecord};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+// Same tolerance as the simulation's comparison of equal scores
+const SCORE_EPSILON: f64 = 1e-6;
+
+/// A property every simulation result must have, broken
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum Violation {
+    // More admitted than places, not explained by CutoffTies::AdmitAll
+    OverCapacity { program_key: ProgramKey, admitted: usize, places: u32 },
+    AdmittedTwice { snils: String, programs: Vec<ProgramKey> },
+    // Admitted to `program_key` while `free_program`, a higher priority, kept a free place
+    PriorityBypassed { snils: String, program_key: ProgramKey, free_program: ProgramKey },
+    // Admitted without a consent or original the analyzer requires
+    NotEager { snils: String, program_key: ProgramKey },
+    // One more place in `program_key` left an applicant admitted before without a seat
+    LostSeat { snils: String, program_key: ProgramKey, places: u32 },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            Violation::OverCapacity { program_key, admitted, places } => {
+                t!("invariants.over_capacity", program_key, admitted, places)
+            }
+            Violation::AdmittedTwice { snils, programs } => t!("invariants.admitted_twice", snils, join(programs)),
+            Violation::PriorityBypassed { snils, program_key, free_program } => {
+                t!("invariants.priority_bypassed", snils, program_key, free_program)
+            }
+            Violation::NotEager { snils, program_key } => t!("invariants.not_eager", snils, program_key),
+            Violation::LostSeat { snils, program_key, places } => {
+                t!("invariants.lost_seat", snils, program_key, places)
+            }
+        };
+        f.write_str(&message)
+    }
+}
+
+/// Programs admit no more applicants than their places, except those tied with the last
+/// seat when the analyzer admits all ties
+pub fn capacity(
+    analyzer: &AdmissionAnalyzer,
+    analysis: &AdmissionAnalysis,
+    all_program_records: &[(String, Vec<StudentRecord>)],
+) -> Vec<Violation> {
+    let scores = simulated_scores(analyzer, all_program_records);
+    analysis
+        .program_popularities
+        .iter()
+        .filter_map(|popularity| {
+            let score = |snils: &String| {
+                scores.get(&(popularity.program_key.clone(), normalize_snils(snils))).copied().unwrap_or(0.0)
+            };
+            let places = popularity.available_places;
+            let admitted = analysis.final_admission_results.get(&popularity.program_key)?;
+            if admitted.len() <= places as usize {
+                return None;
+            }
+            let tied = analyzer.cutoff_ties == CutoffTies::AdmitAll
+                && places > 0
+                && admitted[places as usize..]
+                    .iter()
+                    .all(|snils| (score(snils) - score(&admitted[places as usize - 1])).abs() < SCORE_EPSILON);
+            (!tied).then(|| Violation::OverCapacity {
+                program_key: popularity.program_key.clone(),
+                admitted: admitted.len(),
+                places,
+            })
+        })
+        .collect()
+}
+
+/// Nobody is admitted to more than one program of an institution, or twice to one
+pub fn single_admission(analysis: &AdmissionAnalysis) -> Vec<Violation> {
+    let institutions = institutions(analysis);
+    let mut admissions: BTreeMap<(&str, String), Vec<ProgramKey>> = BTreeMap::new();
+    for (program_key, admitted) in &analysis.final_admission_results {
+        let institution = institutions.get(program_key).copied().unwrap_or_default();
+        for snils in admitted {
+            admissions.entry((institution, normalize_snils(snils))).or_default().push(program_key.clone());
+        }
+    }
+    admissions
+        .into_iter()
+        .filter(|(_, programs)| programs.len() > 1)
+        .map(|((_, snils), mut programs)| {
+            programs.sort();
+            Violation::AdmittedTwice { snils, programs }
+        })
+        .collect()
+}
+
+/// Nobody is admitted to a program while one of their higher priorities has a free place
+///
+/// The simulation gives each applicant the first place free by priority and places only
+/// fill up, so a place still free at the end was free on their turn too.
+pub fn priority_consistency(
+    analyzer: &AdmissionAnalyzer,
+    analysis: &AdmissionAnalysis,
+    all_program_records: &[(String, Vec<StudentRecord>)],
+) -> Vec<Violation> {
+    let places: HashMap<&ProgramKey, u32> = analysis
+        .program_popularities
+        .iter()
+        .map(|popularity| (&popularity.program_key, popularity.available_places))
+        .collect();
+    let has_free_place = |program_key: &ProgramKey| {
+        let admitted = analysis.final_admission_results.get(program_key).map_or(0, Vec::len);
+        places.get(program_key).is_some_and(|&places| admitted < places as usize)
+    };
+    let institutions = institutions(analysis);
+    // (institution, applicant) -> eager applications as (priority, program)
+    let mut applications: HashMap<(String, String), Vec<(u32, ProgramKey)>> = HashMap::new();
+    for (program_name, records) in all_program_records {
+        for record in records.iter().filter(|record| analyzer.is_eager(record)) {
+            applications
+                .entry((record.institution.clone(), normalize_snils(&record.snils)))
+                .or_default()
+                .push((record.priority, ProgramKey::of(program_name, record)));
+        }
+    }
+
+    let mut violations = Vec::new();
+    for (program_key, admitted) in &analysis.final_admission_results {
+        let institution = institutions.get(program_key).copied().unwrap_or_default();
+        for snils in admitted.iter().map(|snils| normalize_snils(snils)) {
+            let Some(applications) = applications.get(&(institution.to_string(), snils.clone())) else {
+                continue;
+            };
+            let Some(priority) = applications
+                .iter()
+                .filter(|(_, applied)| applied == program_key)
+                .map(|(priority, _)| *priority)
+                .min()
+            else {
+                continue;
+            };
+            if let Some((_, free_program)) = applications
+                .iter()
+                .filter(|(higher, free_program)| *higher < priority && has_free_place(free_program))
+                .min_by_key(|(higher, _)| *higher)
+            {
+                violations.push(Violation::PriorityBypassed {
+                    snils,
+                    program_key: program_key.clone(),
+                    free_program: free_program.clone(),
+                });
+            }
+        }
+    }
+    violations.sort_by_cached_key(Violation::to_string);
+    violations
+}
+
+/// Everyone admitted to a program applied to it with a consent or original the analyzer
+/// counts, an original alone with `originals_only`
+pub fn eagerness(
+    analyzer: &AdmissionAnalyzer,
+    analysis: &AdmissionAnalysis,
+    all_program_records: &[(String, Vec<StudentRecord>)],
+) -> Vec<Violation> {
+    let eager: HashSet<(String, ProgramKey)> = all_program_records
+        .iter()
+        .flat_map(|(program_name, records)| {
+            records
+                .iter()
+                .filter(|record| analyzer.is_eager(record))
+                .map(move |record| (normalize_snils(&record.snils), ProgramKey::of(program_name, record)))
+        })
+        .collect();
+    let mut violations: Vec<Violation> = analysis
+        .final_admission_results
+        .iter()
+        .flat_map(|(program_key, admitted)| admitted.iter().map(move |snils| (normalize_snils(snils), program_key)))
+        .filter(|(snils, program_key)| !eager.contains(&(snils.clone(), (*program_key).clone())))
+        .map(|(snils, program_key)| Violation::NotEager { snils, program_key: program_key.clone() })
+        .collect();
+    violations.sort_by_cached_key(Violation::to_string);
+    violations
+}
+
+/// One more place in any program keeps everyone admitted somewhere
+///
+/// Simulates the records again once per program, with that program's places raised
+/// through `seat_overrides`.
+pub fn seat_monotonicity(
+    analyzer: &AdmissionAnalyzer,
+    analysis: &AdmissionAnalysis,
+    all_program_records: &[(String, Vec<StudentRecord>)],
+) -> Vec<Violation> {
+    let admitted = admitted_applicants(analysis);
+    let mut violations = Vec::new();
+    for popularity in &analysis.program_popularities {
+        let places = popularity.available_places + 1;
+        let mut more_seats = analyzer.clone();
+        more_seats.seat_overrides.insert(popularity.program_key.to_string(), places);
+        let still_admitted = admitted_applicants(&more_seats.analyze_all_programs(all_program_records));
+        let mut lost: Vec<&(String, String)> = admitted.difference(&still_admitted).collect();
+        lost.sort();
+        violations.extend(lost.into_iter().map(|(_, snils)| Violation::LostSeat {
+            snils: snils.clone(),
+            program_key: popularity.program_key.clone(),
+            places,
+        }));
+    }
+    violations
+}
+
+/// Check a simulation result against every invariant that needs no further simulation
+pub fn verify(
+    analyzer: &AdmissionAnalyzer,
+    analysis: &AdmissionAnalysis,
+    all_program_records: &[(String, Vec<StudentRecord>)],
+) -> Vec<Violation> {
+    let mut violations = capacity(analyzer, analysis, all_program_records);
+    violations.extend(single_admission(analysis));
+    violations.extend(priority_consistency(analyzer, analysis, all_program_records));
+    violations.extend(eagerness(analyzer, analysis, all_program_records));
+    violations
+}
+
+/// Simulate the records with `analyzer` and check every invariant of the result
+pub fn check_all(analyzer: &AdmissionAnalyzer, all_program_records: &[(String, Vec<StudentRecord>)]) -> Vec<Violation> {
+    let analysis = analyzer.analyze_all_programs(all_program_records);
+    let mut violations = verify(analyzer, &analysis, all_program_records);
+    violations.extend(seat_monotonicity(analyzer, &analysis, all_program_records));
+    violations
+}
+
+/// Institution and normalized SNILS of everyone admitted anywhere
+fn admitted_applicants(analysis: &AdmissionAnalysis) -> HashSet<(String, String)> {
+    let institutions = institutions(analysis);
+    analysis
+        .final_admission_results
+        .iter()
+        .flat_map(|(program_key, admitted)| {
+            let institution = institutions.get(program_key).copied().unwrap_or_default();
+            admitted.iter().map(move |snils| (institution.to_string(), normalize_snils(snils)))
+        })
+        .collect()
+}
+
+/// Institution of every simulated program
+fn institutions(analysis: &AdmissionAnalysis) -> HashMap<&ProgramKey, &str> {
+    analysis
+        .program_popularities
+        .iter()
+        .map(|popularity| (&popularity.program_key, popularity.institution.as_str()))
+        .collect()
+}
+
+/// (program, applicant) -> the score the program seats the applicant by
+fn simulated_scores(
+    analyzer: &AdmissionAnalyzer,
+    all_program_records: &[(String, Vec<StudentRecord>)],
+) -> HashMap<(ProgramKey, String), f64> {
+    all_program_records
+        .iter()
+        .flat_map(|(program_name, records)| records.iter().map(move |record| (program_name, record)))
+        .filter(|(_, record)| analyzer.is_eager(record))
+        .map(|(program_name, record)| {
+            ((ProgramKey::of(program_name, record), normalize_snils(&record.snils)), record.score.unwrap_or(0.0))
+        })
+        .collect()
+}
+
+fn join(programs: &[ProgramKey]) -> String {
+    programs.iter().map(ProgramKey::to_string).collect::<Vec<_>>().join(", ")
+}
+
+/// Generators of small random campaigns for property tests of the invariants
+///
+/// Lists are short and scores come from a handful of values, so places run out and
+/// ties at the last seat are common. The first applicant of every campaign is [`TARGET`].
+///
+/// ```
+/// use abitur_analyzer::invariants::{self, strategies};
+/// use proptest::test_runner::TestRunner;
+///
+/// TestRunner::default()
+///     .run(&(strategies::analyzer(), strategies::campaign()), |(analyzer, records)| {
+///         let violations = invariants::check_all(&analyzer, &records);
+///         proptest::prop_assert!(violations.is_empty(), "{:?}", violations);
+///         Ok(())
+///     })
+///     .unwrap();
+/// ```
+#[cfg(feature = "proptest")]
+pub mod strategies {
+    use crate::analyzer::AdmissionAnalyzer;
+    use crate::fixture::PROGRAM_NAMES;
+    use crate::models::{CutoffTies, FundingSource, StudentRecord, StudyForm};
+    use proptest::prelude::*;
+
+    pub const TARGET: &str = "С25-00000";
+
+    const SCORES: &[f64] = &[3.0, 3.5, 4.0, 4.5, 5.0];
+
+    /// Institution of the programs not run by the unnamed one
+    const SECOND_INSTITUTION: &str = "Второй колледж";
+
+    /// Analyzer of [`TARGET`] with any cutoff tie rule, enrolling on consent or originals only
+    pub fn analyzer() -> impl Strategy<Value = AdmissionAnalyzer<'static>> {
+        let cutoff_ties = prop_oneof![Just(CutoffTies::Strict), Just(CutoffTies::AdmitAll), Just(CutoffTies::Secondary)];
+        (cutoff_ties, any::<bool>()).prop_map(|(cutoff_ties, originals_only)| {
+            AdmissionAnalyzer::new(TARGET)
+                .with_cutoff_ties(cutoff_ties, None)
+                .with_originals_only(originals_only)
+        })
+    }
+
+    /// One to four programs with up to four places and one to twelve applicants, each
+    /// applying to some of them in a random priority order; a few are privileged and some
+    /// programs belong to a second institution
+    pub fn campaign() -> impl Strategy<Value = Vec<(String, Vec<StudentRecord>)>> {
+        prop::collection::vec((0u32..=4, prop::bool::weighted(0.25)), 1..=4)
+            .prop_flat_map(|programs| {
+                let (places, second): (Vec<u32>, Vec<bool>) = programs.into_iter().unzip();
+                let programs = places.len();
+                let applicant = (
+                    prop::sample::select(SCORES),
+                    Just((0..programs).collect::<Vec<usize>>()).prop_shuffle(),
+                    1..=programs,
+                    any::<bool>(),
+                    any::<bool>(),
+                    prop::bool::weighted(0.1),
+                );
+                (Just(places), Just(second), prop::collection::vec(applicant, 1..=12))
+            })
+            .prop_map(|(places, second, applicants)| {
+                let institution = |program: usize| if second[program] { SECOND_INSTITUTION } else { "" };
+                let name = |program: usize| match institution(program) {
+                    "" => PROGRAM_NAMES[program].to_string(),
+                    institution => format!("{} ({})", PROGRAM_NAMES[program], institution),
+                };
+                let mut lists: Vec<Vec<StudentRecord>> = vec![Vec::new(); places.len()];
+                for (index, (score, order, applications, consent, original, privileged)) in applicants.into_iter().enumerate() {
+                    for (priority, &program) in order.iter().take(applications).enumerate() {
+                        lists[program].push(StudentRecord {
+                            rank: 0,
+                            snils: format!("С25-{:05}", index),
+                            priority: priority as u32 + 1,
+                            consent: yes_no(consent),
+                            document_type: yes_no(original),
+                            average_score: score.to_string(),
+                            score: Some(score),
+                            achievements: String::new(),
+                            bonus: 0.0,
+                            subject_scores: String::new(),
+                            psychological_test: String::new(),
+                            program_name: name(program),
+                            institution: institution(program).to_string(),
+                            funding_source: FundingSource::Budget,
+                            study_form: StudyForm::FullTime,
+                            available_places: places[program],
+                            withdrawn: false,
+                            privileged,
+                            computed_rank: None,
+                            provenance: None,
+                            submitted_at: None,
+                            consent_at: None,
+                            original_at: None,
+                            recent_consent: false,
+                            application_id: None,
+                        });
+                    }
+                }
+                lists
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, records)| !records.is_empty())
+                    .map(|(program, mut records)| {
+                        records.sort_by(|a, b| b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0)));
+                        for (index, record) in records.iter_mut().enumerate() {
+                            record.rank = index as u32 + 1;
+                        }
+                        (name(program), records)
+                    })
+                    .collect()
+            })
+    }
+
+    fn yes_no(value: bool) -> String {
+        if value { "Да" } else { "Нет" }.to_string()
+    }
+}
@@ -0,0 +1,52 @@
+use crate::atomic_file;
+use crate::error::StorageError;
+use crate::models::{ProgramInfo, StudentRecord};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// File in the output root with the table columns every source had last time
+pub const LAYOUTS_FILE: &str = "layouts.json";
+
+/// Column headers of the tables of every source, by file path or URL
+pub type SourceLayouts = BTreeMap<String, Vec<String>>;
+
+/// Distinct column headers of the program tables of one source, in the order they appear
+pub fn of_programs(programs: &[(ProgramInfo, Vec<StudentRecord>)]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for column in programs.iter().flat_map(|(program_info, _)| &program_info.columns) {
+        if !columns.contains(column) {
+            columns.push(column.clone());
+        }
+    }
+    columns
+}
+
+/// What changed from `baseline` to `current`: "+ header" for new columns, "- header" for
+/// columns gone, or the new order when only the order changed
+pub fn diff(baseline: &[String], current: &[String]) -> Vec<String> {
+    let mut changes: Vec<String> = current
+        .iter()
+        .filter(|column| !baseline.contains(column))
+        .map(|column| format!("+ {}", column))
+        .collect();
+    changes.extend(baseline.iter().filter(|column| !current.contains(column)).map(|column| format!("- {}", column)));
+    if changes.is_empty() && baseline != current {
+        changes.push(format!("order: {}", current.join(" | ")));
+    }
+    changes
+}
+
+/// Layouts saved by the last run, empty if there are none or they cannot be read
+pub fn load(output_root: &Path) -> SourceLayouts {
+    fs::read_to_string(output_root.join(LAYOUTS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(output_root: &Path, layouts: &SourceLayouts) -> Result<(), StorageError> {
+    let path = output_root.join(LAYOUTS_FILE);
+    let content = serde_json::to_string_pretty(layouts)?;
+    atomic_file::write(&path, content).map_err(|cause| StorageError::Write { path: path.display().to_string(), cause })
+}
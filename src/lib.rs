@@ -0,0 +1,416 @@
+//! Admission list analysis: parse applicant lists, simulate priority-based admission
+//! and find out where a target applicant gets in.
+//!
+//! The `abitur-analyzer` binary is a CLI around this library. To embed the analysis
+//! in another program, load a [`Config`] and call `analyze`, which needs the default
+//! `network` feature:
+//!
+#![cfg_attr(feature = "network", doc = "```no_run")]
+#![cfg_attr(not(feature = "network"), doc = "```ignore")]
+//! # async fn run() -> anyhow::Result<()> {
+//! let config = abitur_analyzer::Config::load_from_file("config.toml")?;
+//! let run = abitur_analyzer::analyze(&config).await?;
+//! for popularity in &run.analysis.program_popularities {
+//!     let admitted = &run.analysis.final_admission_results[&popularity.program_key];
+//!     println!("{}: {} of {} places", popularity.program_key, admitted.len(), popularity.available_places);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! For per-program details about the target use [`AdmissionAnalyzer::report_model`]
+//! and [`AdmissionAnalyzer::admission_distances`] with the records from
+//! [`sources::load_program_records`].
+//!
+//! Errors are an [`Error`] whose variant tells what went wrong: reading a source, parsing
+//! it, the configuration, the simulation input or storing results.
+
+#[macro_use]
+pub mod i18n;
+pub mod error;
+pub mod atomic_file;
+pub mod models;
+pub mod scoring;
+pub mod checksums;
+pub mod http_cache;
+pub mod simulation_cache;
+pub mod resume;
+pub mod discovery;
+pub mod layout;
+pub mod campaign;
+pub mod identity;
+pub mod consistency;
+pub mod anonymize;
+pub mod program_aliases;
+pub mod interest;
+pub mod overrides;
+pub mod progress;
+pub mod events;
+pub mod run_summary;
+pub mod scraper;
+pub mod privileged;
+pub mod ranks;
+pub mod sources;
+pub mod snapshot;
+pub mod fixture;
+pub mod observer;
+pub mod analyzer;
+pub mod invariants;
+pub mod quota;
+pub mod report_model;
+pub mod confidence;
+pub mod presets;
+pub mod competitors;
+pub mod strategies;
+pub mod verdict;
+pub mod priorities;
+pub mod capacity;
+pub mod single_program;
+pub mod compare;
+pub mod enrollment_order;
+#[cfg(feature = "storage")]
+pub mod storage;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "python")]
+mod python;
+
+pub use analyzer::{AdmissionAnalysis, AdmissionAnalyzer, AdmissionDistance, TargetOutcome};
+pub use error::{Error, Result};
+pub use report_model::ReportModel;
+pub use models::Config;
+
+#[cfg(feature = "network")]
+use run_summary::RunSummary;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use tracing::{info, warn};
+
+/// Load all sources configured in `config` and simulate admission for `config.target_snils`,
+/// as the `abitur-analyzer` binary does for its reports
+///
+/// Sources that fail to load are logged and skipped as far as `config.failure_policy` allows;
+/// beyond that the error is an [`Error::Simulation`]. Once `config.campaign` is past its
+/// originals deadline only applicants with originals are enrolled, as with `originals_only`.
+#[cfg(feature = "network")]
+pub async fn analyze(config: &Config) -> Result<AnalyzedRun> {
+    let mut summary = RunSummary::default();
+    let all_program_records = sources::load_program_records(config, &config.data_source_mode, &mut summary).await?;
+    sources::check_failure_policy(config, &summary)?;
+    let identities = identity::IdentityResolver::new(config.identity.as_ref())?;
+    let scoring = scoring::ScoringRules::new(config.scoring.as_deref())?;
+    let prepared = prepare_records(config, &identities, &scoring, all_program_records)?;
+    Ok(analyze_prepared(config, prepared, &summary.layout_changes, |analyzer, all_program_records| {
+        analyzer.analyze_all_programs(all_program_records)
+    }))
+}
+
+/// Simulation of the target applicant's admission with everything the reports derive from it
+pub struct AnalyzedRun {
+    pub target_snils: String,
+    pub all_program_records: Vec<(String, Vec<models::StudentRecord>)>,
+    pub analysis: AdmissionAnalysis,
+    // Rows, cutoffs and target outcomes of every program, for all reports
+    pub report: ReportModel,
+    pub target_outcomes: Vec<TargetOutcome>,
+    pub admission_distances: Vec<AdmissionDistance>,
+    // Applicants scoring at least as high as the target in its programs, as configured
+    pub competitors: Vec<competitors::Competitor>,
+    // Applicants closest to the cutoff on each side in the programs of interest
+    pub boundary: Vec<competitors::BoundaryApplicant>,
+    // Eager applicants grouped by the programs they applied to in priority order
+    pub strategies: Vec<strategies::Strategy>,
+    // How many of them go from their first priority to each seat
+    pub flows: Vec<strategies::Flow>,
+    pub inconsistencies: Vec<consistency::Inconsistency>,
+    // Published ranks that do not fit the scores, written to rank_discrepancies.csv
+    pub rank_discrepancies: Vec<ranks::RankDiscrepancy>,
+    pub program_merges: Vec<program_aliases::ProgramMerge>,
+    // Invariants the simulation result breaks, written to simulation_warnings.txt
+    pub simulation_warnings: Vec<invariants::Violation>,
+    pub campaign: Option<campaign::CampaignStatus>,
+    // Configured institutions, naming and describing those of the lists in reports
+    pub institutions: Vec<models::InstitutionOptions>,
+    // Only applicants with originals were enrolled, by configuration or a locked campaign
+    pub originals_only: bool,
+    // Funding types filling their places first in filtered_eager.csv, from target_funding_types
+    pub funding_order: Vec<models::FundingSource>,
+}
+
+/// Loaded records with every configured filter, identity, alias and scoring rule applied
+pub struct PreparedRecords {
+    // Target's identifier as the lists spell it, its pseudonym with `anonymize`
+    pub target_snils: String,
+    pub all_program_records: Vec<(String, Vec<models::StudentRecord>)>,
+    pub inconsistencies: Vec<consistency::Inconsistency>,
+    pub rank_discrepancies: Vec<ranks::RankDiscrepancy>,
+    pub program_merges: Vec<program_aliases::ProgramMerge>,
+    // Share of each combination's applications under a masked SNILS, taken before anonymizing
+    pub masked_snils: BTreeMap<models::ProgramKey, f64>,
+}
+
+/// Apply the configured filters, identities, aliases, anonymization and scoring to loaded
+/// records, as [`analyze`] and the `abitur-analyzer` binary do before simulating
+pub fn prepare_records(
+    config: &Config,
+    identities: &identity::IdentityResolver,
+    scoring: &scoring::ScoringRules,
+    mut all_program_records: Vec<(String, Vec<models::StudentRecord>)>,
+) -> Result<PreparedRecords> {
+    let withdrawn = sources::remove_withdrawn(&mut all_program_records);
+    if withdrawn > 0 {
+        info!("{}", t!("run.withdrawn_excluded", withdrawn));
+    }
+    let funding = config.funding.unwrap_or_default();
+    if funding != models::FundingScope::All {
+        let removed = sources::remove_other_funding(&mut all_program_records, funding);
+        info!("{}", t!("run.funding_excluded", removed, funding.name()));
+    }
+    if let Some(until) = config.submitted_until {
+        let late = sources::remove_submitted_after(&mut all_program_records, until);
+        info!("{}", t!("run.late_excluded", late, until));
+    }
+    let recent_consent_days = config.recent_consent_days.unwrap_or(0);
+    if recent_consent_days > 0 {
+        let discounted = sources::discount_recent_consents(&mut all_program_records, recent_consent_days);
+        info!("{}", t!("run.recent_consents", discounted, recent_consent_days));
+    }
+    let flagged = privileged::flag(
+        &mut all_program_records,
+        config.privileged_detection.unwrap_or_default(),
+        config.privileged_markers.as_deref().unwrap_or_default(),
+    );
+    if flagged > 0 {
+        info!("{}", t!("run.privileged_flagged", flagged));
+    }
+    // Before lists are merged, each numbers its own applications
+    let mut rank_discrepancies = ranks::recompute(&mut all_program_records);
+    if !rank_discrepancies.is_empty() {
+        warn!("{}", t!("ranks.found", rank_discrepancies.len()));
+    }
+
+    // One identifier per applicant, so applications listed under other IDs are theirs too
+    let linked = identities.resolve_records(&mut all_program_records);
+    if linked > 0 {
+        info!("{}", t!("run.identities_linked", linked));
+    }
+    let program_merges = match &config.program_aliases {
+        Some(aliases) => program_aliases::merge(&mut all_program_records, aliases),
+        None => Vec::new(),
+    };
+    if !program_merges.is_empty() {
+        let duplicates_removed: usize = program_merges.iter().map(|merge| merge.duplicates_removed).sum();
+        info!("{}", t!("aliases.merged", program_merges.len(), duplicates_removed));
+    }
+    // A typo in programs_of_interest would otherwise just leave the program out of the reports
+    for missing in interest::missing(config.programs_of_interest.as_deref().unwrap_or_default(), &all_program_records) {
+        if missing.closest.is_empty() {
+            warn!("{}", t!("run.interest_missing", missing.name));
+        } else {
+            warn!("{}", t!("run.interest_missing_closest", missing.name, missing.closest.join("; ")));
+        }
+    }
+
+    // Ground truth the lists have not caught up with yet
+    if let Some(excluded) = &config.excluded_applicants {
+        let excluded: HashSet<String> =
+            excluded.iter().map(|snils| models::normalize_snils(&identities.resolve(snils))).collect();
+        if excluded.contains(&models::normalize_snils(&identities.resolve(&config.target_snils))) {
+            warn!("{}", t!("run.target_excluded", config.target_snils));
+        }
+        let removed = sources::remove_excluded(&mut all_program_records, &excluded);
+        info!("{}", t!("run.applicants_excluded", removed, excluded.len()));
+    }
+    let target_snils = identities.resolve_target(&config.target_snils, &all_program_records)?;
+
+    // Replace SNILS with pseudonyms in everything generated from here on
+    let masked_snils = confidence::masked_shares(&all_program_records);
+    let target_snils = if config.anonymize.unwrap_or(false) {
+        let anonymizer = anonymize::Anonymizer::new(config.anonymize_salt.as_deref());
+        anonymizer.apply(&mut all_program_records);
+        for discrepancy in &mut rank_discrepancies {
+            discrepancy.snils = anonymizer.pseudonym(&discrepancy.snils);
+        }
+        let pseudonym = anonymizer.pseudonym(&target_snils);
+        info!("{}", t!("run.anonymized", pseudonym));
+        pseudonym
+    } else {
+        target_snils
+    };
+
+    let inconsistencies = consistency::reconcile(&mut all_program_records, config.score_conflicts.unwrap_or_default());
+    if !inconsistencies.is_empty() {
+        warn!("{}", t!("consistency.found", inconsistencies.len()));
+    }
+
+    // After reconciling, so lists are compared by the scores they print
+    let rescored = scoring.apply(&mut all_program_records);
+    if rescored > 0 {
+        info!("{}", t!("run.rescored", rescored));
+    }
+    if config.count_achievements.unwrap_or(false) {
+        for record in all_program_records.iter_mut().flat_map(|(_, records)| records.iter_mut()) {
+            record.count_achievements();
+        }
+    }
+
+    Ok(PreparedRecords {
+        target_snils,
+        all_program_records,
+        inconsistencies,
+        rank_discrepancies,
+        program_merges,
+        masked_snils,
+    })
+}
+
+/// Simulate admission over prepared records with the settings of `config` and derive what the
+/// reports show
+///
+/// Besides the simulation itself, `simulate` unless it reuses an earlier one, this checks the
+/// simulation's invariants, adds the projected scenario of `originals_only` and, until
+/// `campaign.quota_transfer`, the cutoffs after the transfer, and grades each program's data
+/// with `layout_changes`, the sources whose table layout changed since the last run.
+pub fn analyze_prepared(
+    config: &Config,
+    prepared: PreparedRecords,
+    layout_changes: &BTreeSet<String>,
+    simulate: impl FnOnce(&AdmissionAnalyzer, &[(String, Vec<models::StudentRecord>)]) -> AdmissionAnalysis,
+) -> AnalyzedRun {
+    let PreparedRecords {
+        target_snils,
+        all_program_records,
+        inconsistencies,
+        rank_discrepancies,
+        program_merges,
+        masked_snils,
+    } = prepared;
+
+    // Perform unified priority-based analysis for all funding types
+    info!("{}", t!("run.analyzing"));
+    let campaign = campaign_status(config);
+    if let Some(campaign) = &campaign {
+        info!("{}", t!("campaign.status", campaign.describe()));
+    }
+    let locked = campaign.as_ref().is_some_and(|campaign| campaign.mode == campaign::CampaignMode::Locked);
+    let analyzer = configured_analyzer(config, &target_snils, None);
+    let originals_only = analyzer.originals_only;
+
+    let analysis = simulate(&analyzer, &all_program_records);
+    for program_key in config.seat_overrides.iter().flat_map(|overrides| overrides.keys()) {
+        if !analysis.program_popularities.iter().any(|popularity| popularity.program_key.to_string() == *program_key) {
+            warn!("{}", t!("run.seat_override_unknown", program_key));
+        }
+    }
+    // A list without places admits no one, which is rarely true; a seat override of 0 confirms it
+    for popularity in &analysis.program_popularities {
+        let program_key = popularity.program_key.to_string();
+        if popularity.available_places == 0 && !analyzer.seat_overrides.contains_key(&program_key) {
+            warn!("{}", t!("run.no_places", program_key, popularity.total_eager_applicants, program_key));
+        }
+    }
+    let simulation_warnings = invariants::verify(&analyzer, &analysis, &all_program_records);
+    if !simulation_warnings.is_empty() {
+        warn!("{}", t!("invariants.found", simulation_warnings.len()));
+    }
+    let mut report = analyzer.report_model(&analysis, &all_program_records);
+    if originals_only && !locked {
+        // Projected scenario: consent-only applicants hand in their originals in time
+        let projected_analyzer = configured_analyzer(config, &target_snils, Some(false));
+        let projected_analysis = projected_analyzer.analyze_all_programs(&all_program_records);
+        report.set_projected(&projected_analyzer.report_model(&projected_analysis, &all_program_records));
+    }
+    // Until the quota transfer date, show what the unfilled quota places will do to the cutoffs
+    let quota_transfer_ahead = campaign
+        .as_ref()
+        .is_some_and(|campaign| campaign.upcoming.iter().any(|date| date.label == "campaign.quota_transfer"));
+    if quota_transfer_ahead {
+        let transfers = quota::unfilled(&analysis);
+        for transfer in &transfers {
+            info!("{}", t!("quota.transfer", transfer.program_key, transfer.places));
+        }
+        if !transfers.is_empty() {
+            let mut transfer_analyzer = analyzer.clone();
+            transfer_analyzer.seat_overrides = quota::seat_overrides(&analysis, &transfers, &analyzer.seat_overrides);
+            let transferred = transfer_analyzer.analyze_all_programs(&all_program_records);
+            report.set_quota_transfer(&transfer_analyzer.report_model(&transferred, &all_program_records), &transfers);
+        }
+    }
+    // Data quality of each program, so readers know which predictions to trust
+    let confidences = confidence::assess(&all_program_records, &masked_snils, layout_changes, chrono::Local::now());
+    let low: Vec<String> = confidences
+        .iter()
+        .filter(|(_, confidence)| confidence.grade == confidence::Grade::Low)
+        .map(|(program_key, _)| program_key.to_string())
+        .collect();
+    if !low.is_empty() {
+        warn!("{}", t!("confidence.lowered", low.len(), confidences.len(), low.join(", ")));
+    }
+    report.set_confidence(&confidences);
+    let mut target_outcomes = report.target_outcomes();
+    let mut admission_distances = analyzer.admission_distances(&analysis, &all_program_records);
+    if let Some(programs) = &config.programs_of_interest {
+        target_outcomes.retain(|outcome| programs.contains(&outcome.program_name));
+        admission_distances.retain(|distance| programs.contains(&distance.program_name));
+    }
+    let competitor_options = config.competitors.clone().unwrap_or_default();
+    let competitors = competitors::find(&report, &competitor_options);
+    let boundary = competitors::boundary(&report, config.programs_of_interest.as_deref(), &competitor_options);
+    let strategies = strategies::cluster(&report);
+    if let Some(strategy) = strategies.iter().find(|strategy| strategy.target) {
+        // The target counts among the applicants of its strategy once it is eager
+        let target_eager = report.programs.iter().filter_map(|program| program.target_row()).any(|row| row.eager);
+        let others = strategy.applicants - usize::from(target_eager);
+        info!("{}", t!("strategies.target", others, strategy.describe()));
+    }
+
+    AnalyzedRun {
+        target_snils,
+        all_program_records,
+        analysis,
+        report,
+        target_outcomes,
+        admission_distances,
+        competitors,
+        boundary,
+        flows: strategies::flows(&strategies),
+        strategies,
+        inconsistencies,
+        rank_discrepancies,
+        program_merges,
+        simulation_warnings,
+        campaign,
+        institutions: config.institutions.clone().unwrap_or_default(),
+        funding_order: config
+            .target_funding_types
+            .iter()
+            .flatten()
+            .map(|funding| models::FundingSource::parse(funding))
+            .collect(),
+        originals_only,
+    }
+}
+
+/// Analyzer for `target_snils` with the simulation settings of `config`
+///
+/// Only applicants with originals are enrolled with `originals_only` or once the campaign is
+/// locked, unless `originals_only` overrides both, as the projected scenario does.
+pub fn configured_analyzer<'a>(config: &Config, target_snils: &'a str, originals_only: Option<bool>) -> AdmissionAnalyzer<'a> {
+    let originals_only = originals_only.unwrap_or_else(|| {
+        let locked = campaign_status(config).is_some_and(|campaign| campaign.mode == campaign::CampaignMode::Locked);
+        config.originals_only.unwrap_or(false) || locked
+    });
+    AdmissionAnalyzer::new(target_snils)
+        .with_originals_only(originals_only)
+        .with_cutoff_ties(config.cutoff_ties.unwrap_or_default(), config.tie_breakers.as_deref())
+        .with_seat_overrides(config.seat_overrides.as_ref())
+}
+
+/// Campaign stage of today, if `config` sets the campaign dates
+///
+/// Once the originals deadline has passed, consent alone no longer gets anyone enrolled.
+pub fn campaign_status(config: &Config) -> Option<campaign::CampaignStatus> {
+    config
+        .campaign
+        .as_ref()
+        .and_then(|options| campaign::CampaignStatus::new(options, chrono::Local::now().date_naive()))
+}
@@ -0,0 +1,48 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::sync::Mutex;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, registry};
+
+/// Console verbosity selected by `--quiet`/`--verbose`
+pub fn console_level(quiet: bool, verbose: u8) -> LevelFilter {
+    match (quiet, verbose) {
+        (true, _) => LevelFilter::WARN,
+        (false, 0) => LevelFilter::INFO,
+        (false, 1) => LevelFilter::DEBUG,
+        (false, _) => LevelFilter::TRACE,
+    }
+}
+
+/// Only this crate's events; parser dependencies are very chatty at debug level
+fn own_events(level: LevelFilter) -> Targets {
+    Targets::new().with_target(env!("CARGO_CRATE_NAME"), level)
+}
+
+/// Install the global subscriber: plain messages on the console and, if `log_file` is set,
+/// structured JSON lines with timestamps in the file (at least debug level)
+pub fn init(console_level: LevelFilter, log_file: Option<&str>) -> Result<()> {
+    let console_layer = fmt::layer()
+        .without_time()
+        .with_target(false)
+        .with_level(false)
+        .with_writer(|| crate::progress::SuspendingStdout)
+        .with_filter(own_events(console_level));
+
+    let file_layer = match log_file {
+        Some(path) => {
+            let file = File::create(path).with_context(|| format!("Failed to create log file: {}", path))?;
+            Some(
+                fmt::layer()
+                    .json()
+                    .with_writer(Mutex::new(file))
+                    .with_filter(own_events(console_level.max(LevelFilter::DEBUG))),
+            )
+        }
+        None => None,
+    };
+
+    registry().with(console_layer).with(file_layer).init();
+    Ok(())
+}
@@ -1,900 +1,789 @@
-mod models;
-mod scraper;
-mod analyzer;
-
+#[macro_use]
+extern crate abitur_analyzer;
+
+mod backtest;
+mod cli;
+mod csv_output;
+mod csv_report;
+mod exit_codes;
+mod logging;
+mod output_dirs;
+mod program_dirs;
+mod charts;
+mod console_table;
+mod html_report;
+mod xlsx_report;
+mod markdown_report;
+mod pdf_report;
+mod heatmap;
+mod report_sink;
+mod notify;
+mod templates;
+mod schedule;
+mod serve;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod init;
+
+use abitur_analyzer::{analyzer, anonymize, atomic_file, campaign, capacity, checksums, confidence, compare, competitors, consistency, discovery, enrollment_order, fixture, http_cache, i18n, layout, invariants, models, overrides, priorities, program_aliases, progress, ranks, report_model, resume, run_summary, simulation_cache, single_program, snapshot, sources, storage, strategies, verdict};
 use analyzer::{AdmissionAnalyzer};
+use abitur_analyzer::{analyze_prepared, configured_analyzer, prepare_records, AnalyzedRun};
 use models::Config;
-use anyhow::Result;
-use clap::{Arg, Command};
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::Instant;
+use tracing::{debug, error, info, warn};
 
-/// Deduplicate records by SNILS within each program, keeping the best record for each SNILS
-/// Priority: Original document (Да) > Consent (Да) > Priority number (lower is better)
-fn deduplicate_records_by_snils(records: Vec<models::StudentRecord>) -> Vec<models::StudentRecord> {
-    use std::collections::HashMap;
-    use crate::models::normalize_snils;
-    
-    let mut best_records: HashMap<String, models::StudentRecord> = HashMap::new();
-    
-    for record in records {
-        let normalized_snils = normalize_snils(&record.snils);
-        
-        match best_records.get(&normalized_snils) {
-            None => {
-                // First occurrence of this SNILS
-                best_records.insert(normalized_snils, record);
-            }
-            Some(existing) => {
-                // Compare and keep the better record
-                let record_is_better = is_record_better(&record, existing);
-                if record_is_better {
-                    best_records.insert(normalized_snils, record);
-                }
-            }
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run_command().await {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("Error: {:?}", error);
+            exit_codes::of_error(&error)
         }
     }
-    
-    let mut result: Vec<models::StudentRecord> = best_records.into_values().collect();
-    // Sort by rank to maintain original order
-    result.sort_by_key(|r| r.rank);
-    result
 }
 
-/// finds max score in list of records
-/// starting from first record set score to max if it less than max. do until meet last record with actualy max score
-fn set_max_score_on_privileged_records(records: &mut Vec<models::StudentRecord>) {
-    let max_score = records.iter()
-        .filter_map(|r| r.get_numeric_score())
-        .fold(0.0, |max, score| if max < score { score } else { max });
-
-    let mut last_max_score_position = records.iter().rposition(|r| r.get_numeric_score() == Some(max_score)).unwrap_or(0);
+/// Run the subcommand given on the command line, returns the process exit code
+async fn run_command() -> Result<ExitCode> {
+    let matches = cli::command().get_matches();
 
-    for record in records {
-        if last_max_score_position == 0 { break; }
+    // Apply the language from arguments before any message is printed
+    if let Some(lang) = matches.get_one::<String>("lang") {
+        i18n::set_language(lang.parse()?);
+    }
 
-        if let Some(score) = record.get_numeric_score() {
-            if score < max_score {
-                record.set_numeric_score(max_score);
-            } 
+    let config_file = matches.get_one::<String>("config").unwrap();
+    if matches.subcommand_name() == Some("init") {
+        logging::init(logging::console_level(matches.get_flag("quiet"), matches.get_count("verbose")), None)?;
+        return init::run(config_file).await.map(|()| ExitCode::SUCCESS);
+    }
+    if let Some(("generate-fixture", sub_matches)) = matches.subcommand() {
+        logging::init(logging::console_level(matches.get_flag("quiet"), matches.get_count("verbose")), None)?;
+        return generate_fixture(sub_matches).map(|()| ExitCode::SUCCESS);
+    }
+    let config_exists = Path::new(config_file).exists();
+    let profile = matches.get_one::<String>("profile");
+    // Layers over the file: ABIT_* environment variables, then the command line
+    let mut config_overrides = overrides::from_env(std::env::vars())?;
+    let configured_by_env = !config_overrides.is_empty();
+    config_overrides.extend(cli::config_overrides(&matches)?);
+    let config = Config::load(
+        config_exists.then_some(config_file.as_str()),
+        profile.map(String::as_str),
+        &config_overrides,
+    )?;
+
+    i18n::set_language(config.language.unwrap_or(i18n::Language::En));
+
+    progress::init(!matches.get_flag("quiet"));
+    logging::init(
+        logging::console_level(matches.get_flag("quiet"), matches.get_count("verbose")),
+        config.log_file.as_deref(),
+    )?;
+
+    // Create a default configuration on first run, unless configured by the environment
+    if config_exists {
+        info!("{}", t!("config.loading", config_file));
+        if let Some(profile) = profile {
+            info!("{}", t!("config.profile", profile));
         }
+    } else if configured_by_env {
+        info!("{}", t!("config.from_environment"));
+    } else {
+        info!("{}", t!("config.creating_default", config_file));
+        config.save_to_file(config_file)?;
+        warn!("{}", t!("config.edit_and_rerun", config_file));
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let settings = cli::RunSettings::new(config)?.with_snapshot_options(&matches);
 
-        last_max_score_position -= 1;
+    // Validate configuration; only scraping works without a target applicant
+    if settings.target_snils.is_empty() && !matches!(matches.subcommand_name(), Some("scrape") | Some("history") | Some("config")) {
+        error!("{}", t!("config.empty_snils"));
+        error!("{}", t!("config.set_snils_hint", config_file));
+        return Ok(ExitCode::from(exit_codes::CONFIG));
     }
-}
 
-/// Determine if record1 is better than record2 for the same SNILS
-/// Priority: Original document (Да) > Consent (Да) > Priority number (lower is better)
-fn is_record_better(record1: &models::StudentRecord, record2: &models::StudentRecord) -> bool {
-    // First priority: Original document
-    let r1_has_doc = record1.has_original_document();
-    let r2_has_doc = record2.has_original_document();
-    
-    if r1_has_doc != r2_has_doc {
-        return r1_has_doc; // Prefer the one with original document
+    let snapshot_options = settings.from_snapshot.is_some() || settings.save_snapshot.is_some() || settings.resume;
+    if snapshot_options && !matches!(matches.subcommand_name(), None | Some("run") | Some("scrape")) {
+        bail!("{}", t!("run.snapshot_options_unsupported"));
     }
-    
-    // Second priority: Consent
-    let r1_has_consent = record1.has_consent();
-    let r2_has_consent = record2.has_consent();
-    
-    if r1_has_consent != r2_has_consent {
-        return r1_has_consent; // Prefer the one with consent
+
+    if matches.get_flag("dry_run") {
+        if !matches!(matches.subcommand_name(), None | Some("run") | Some("scrape")) {
+            bail!("{}", t!("dry_run.unsupported"));
+        }
+        return dry_run(&settings).await;
     }
-    
-    // Third priority: Lower priority number (1 is better than 2)
-    record1.priority < record2.priority
-}
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let matches = Command::new("abitur-analyzer")
-        .version("1.0")
-        .about("Simultes admission process")
-        .arg(
-            Arg::new("config")
-                .short('c')
-                .long("config")
-                .value_name("FILE")
-                .help("Configuration file path")
-                .default_value("config.toml"),
-        )
-        .arg(
-            Arg::new("snils")
-                .short('s')
-                .long("snils")
-                .value_name("SNILS")
-                .help("target applicant id")
-        )
-        .arg(
-            Arg::new("data_source_mode")
-                .short('d')
-                .long("data_source_mode")
-                .value_name("DATA_SOURCE_MODE")
-                .help("data source mode 'local'/'internet")
-                .default_value("")
-        )
-        .get_matches();
+    let mut exit_code = ExitCode::SUCCESS;
+    match matches.subcommand() {
+        Some(("scrape", _)) => {
+            let _lock = output_dirs::lock_output_root(&settings.output_root)?;
+            exit_code = match scrape(&settings).await? {
+                Fetched::New((run_dir, _), run_summary) => {
+                    finish_run(&settings, &run_dir, &run_summary)?;
+                    exit_codes::of_run(&run_summary)
+                }
+                Fetched::Unchanged(run_summary) => exit_codes::of_run(&run_summary),
+                Fetched::Empty => ExitCode::from(exit_codes::SOURCES),
+            };
+        }
+        Some(("analyze", sub_matches)) => {
+            let records = load_run_records(&settings, sub_matches.get_one::<String>("snapshot"))?;
+            if let Some(program_name) = estimated_program(&settings) {
+                estimate_single_program(&settings, records, program_name)?;
+            } else {
+                let analyzed = analyze_records(&settings, records, &mut run_summary::RunSummary::default())?;
+                print_results(&settings, &analyzed);
+            }
+        }
+        Some(("optimize", sub_matches)) => {
+            let records = load_run_records(&settings, sub_matches.get_one::<String>("snapshot"))?;
+            let analyzed = analyze_records(&settings, records, &mut run_summary::RunSummary::default())?;
+            let analyzer = configured_analyzer(&settings.config, &analyzed.target_snils, Some(analyzed.originals_only));
+            let options = priorities::optimize(&analyzer, &analyzed.all_program_records);
+            console_table::print_priority_options_table(&analyzed.target_snils, &options, settings.color);
+        }
+        Some(("stress", sub_matches)) => {
+            let records = load_run_records(&settings, sub_matches.get_one::<String>("snapshot"))?;
+            let analyzed = analyze_records(&settings, records, &mut run_summary::RunSummary::default())?;
+            let analyzer = configured_analyzer(&settings.config, &analyzed.target_snils, Some(analyzed.originals_only));
+            let range = *sub_matches.get_one::<u32>("range").unwrap();
+            info!("{}", t!("capacity.sweeping", analyzed.analysis.program_popularities.len(), range));
+            let current = capacity::target_seats(&analyzer, &analyzed.analysis);
+            let sensitivities = capacity::sweep(&analyzer, &analyzed.analysis, &analyzed.all_program_records, range);
+            console_table::print_capacity_table(&analyzed.target_snils, &current, &sensitivities, settings.color);
+        }
+        Some(("report", sub_matches)) => {
+            let snapshot_arg = sub_matches.get_one::<String>("snapshot");
+            if let Some(run) = snapshot_arg.filter(|arg| arg.starts_with('@')) {
+                bail!("{}", t!("report.stored_run_unsupported", run));
+            }
+            let snapshot_path = settings.snapshot_path(snapshot_arg);
+            let analyzed = analyze_records(&settings, load_snapshot(&snapshot_path)?, &mut run_summary::RunSummary::default())?;
+            let output_dir = report_directory(&settings, &snapshot_path)?;
+            write_reports(&settings, &analyzed, &output_dir)?;
+            info!("{}", t!("run.results", output_dir));
+        }
+        Some(("compare", sub_matches)) => {
+            let before = load_run_records(&settings, sub_matches.get_one::<String>("before"))?;
+            let after = load_run_records(&settings, sub_matches.get_one::<String>("after"))?;
+            let before_run = analyze_records(&settings, before, &mut run_summary::RunSummary::default())?;
+            let after_run = analyze_records(&settings, after, &mut run_summary::RunSummary::default())?;
+            let changes = compare::compare_outcomes(&before_run.target_outcomes, &after_run.target_outcomes);
+            console_table::print_changes_table(&after_run.target_snils, &changes, settings.color);
+        }
+        Some(("validate", sub_matches)) => {
+            let snapshot_arg = sub_matches.get_one::<String>("snapshot");
+            let analyzed = analyze_records(
+                &settings,
+                load_run_records(&settings, snapshot_arg)?,
+                &mut run_summary::RunSummary::default(),
+            )?;
+            let enrollments = load_enrollments(&settings, sub_matches.get_many::<String>("orders").into_iter().flatten())?;
+            let validation = enrollment_order::validate(&analyzed.report, &enrollments);
+            for (program_name, enrolled) in &validation.unknown_programs {
+                warn!("{}", t!("validation.unknown_program", program_name, enrolled));
+            }
+            console_table::print_validation_table(&analyzed.target_snils, &validation, settings.color);
+
+            // Stored runs have no directory of their own
+            let output_dir = match snapshot_arg.filter(|arg| arg.starts_with('@')) {
+                Some(_) => settings.output_root.clone(),
+                None => {
+                    let snapshot_path = settings.snapshot_path(snapshot_arg);
+                    snapshot_path.parent().unwrap_or(Path::new(".")).to_string_lossy().to_string()
+                }
+            };
+            csv_report::generate_validation_csvs(&settings.csv_dialect, &validation, &output_dir)?;
+            info!("{}", t!("validation.written", output_dir));
+        }
+        Some(("backtest", sub_matches)) => backtest::run(&settings, sub_matches)?,
+        Some(("watch", sub_matches)) => {
+            watch(&settings, &schedule::Schedule::from_matches(&settings.config, sub_matches)?).await?;
+        }
+        Some(("config", _)) => print!("{}", toml::to_string_pretty(&masked_secrets(&settings.config))?),
+        Some(("history", sub_matches)) => {
+            let database_path = settings.database_path();
+            if !database_path.exists() {
+                info!("{}", t!("history.empty"));
+                return Ok(ExitCode::SUCCESS);
+            }
+            let storage = storage::Storage::open(&database_path)?;
+            match sub_matches.get_one::<String>("program") {
+                Some(text) => {
+                    let needle = text.to_lowercase();
+                    let histories: Vec<storage::ProgramHistory> = storage
+                        .program_histories()?
+                        .into_iter()
+                        .filter(|history| history.program_key.to_string().to_lowercase().contains(&needle))
+                        .collect();
+                    if histories.is_empty() {
+                        info!("{}", t!("history.no_program", text));
+                    }
+                    for history in &histories {
+                        console_table::print_cutoff_history_table(history, settings.color);
+                    }
+                }
+                None => console_table::print_stored_runs_table(&storage.runs()?, settings.color),
+            }
+        }
+        Some(("serve", sub_matches)) => {
+            let bind = sub_matches.get_one::<String>("bind").unwrap().clone();
+            serve::serve(settings, &bind).await?;
+        }
+        #[cfg(feature = "grpc")]
+        Some(("grpc", sub_matches)) => {
+            let bind = sub_matches.get_one::<String>("bind").unwrap().clone();
+            grpc::serve(settings, &bind).await?;
+        }
+        _ => {
+            let (analyzed, output_dir, run_summary) = match run(&settings).await? {
+                Fetched::New((analyzed, output_dir), run_summary) => (analyzed, output_dir, run_summary),
+                Fetched::Unchanged(run_summary) => return Ok(exit_codes::of_run(&run_summary)),
+                Fetched::Empty => return Ok(ExitCode::from(exit_codes::SOURCES)),
+            };
+            exit_code = exit_codes::of_run(&run_summary);
+            print_results(&settings, &analyzed);
+            let notifier = notify::Notifier::new(&settings.config);
+            notifier.send_summary(&analyzed.target_snils, &analyzed.target_outcomes).await;
+            notifier
+                .publish_analysis(&analyzed.target_snils, &analyzed.target_outcomes, &analyzed.admission_distances)
+                .await;
+            info!("{}", t!("run.complete"));
+            info!("{}", t!("run.results", output_dir));
+            info!("{}", t!("run.check_output"));
+        }
+    }
+    Ok(exit_code)
+}
 
-    let config_file = matches.get_one::<String>("config").unwrap();
-    
-    // Load or create configuration
-    let config = if Path::new(config_file).exists() {
-        println!("📋 Loading configuration from: {}", config_file);
-        Config::load_from_file(config_file)?
-    } else {
-        println!("📝 Creating default configuration file: {}", config_file);
-        let default_config = Config::default();
-        default_config.save_to_file(config_file)?;
-        println!("⚠️  Please edit {} and set your target SNILS, then run the program again.", config_file);
-        return Ok(());
+/// Write the pages of a generated campaign and tell how to analyze them
+fn generate_fixture(matches: &clap::ArgMatches) -> Result<()> {
+    let count = |name: &str| *matches.get_one::<u64>(name).unwrap() as usize;
+    let options = fixture::FixtureOptions {
+        programs: count("programs"),
+        applicants: count("applicants"),
+        pages: count("pages"),
+        seed: *matches.get_one::<u64>("seed").unwrap(),
     };
+    let output_dir = Path::new(matches.get_one::<String>("output").unwrap());
+    fs::create_dir_all(output_dir)?;
 
-    let target_snils = matches.get_one::<String>("snils").cloned().unwrap_or_else(|| config.target_snils.clone());
+    let programs = fixture::generate(&options);
+    let pages = fixture::render_pages(&programs, options.pages);
+    for (index, page) in pages.iter().enumerate() {
+        atomic_file::write(output_dir.join(format!("list{}.html", index)), page)?;
+    }
+    let applications: usize = programs.iter().map(|(_, records)| records.len()).sum();
+    info!("{}", t!("fixture.written", pages.len(), programs.len(), applications, output_dir.display()));
 
-    // Validate configuration
-    if target_snils.is_empty() {
-        println!("❌ Error: target_snils is empty in configuration file and no argument provided");
-        println!("   Please edit {} and set the target SNILS or pass it as a command-line argument", config_file);
-        return Ok(());
+    // An applicant near the middle of the first list makes an interesting target
+    if let Some(target) = programs.first().and_then(|(_, records)| records.get(records.len() / 2)) {
+        info!("{}", t!("fixture.usage", output_dir.display(), target.snils));
     }
+    Ok(())
+}
 
-    println!("Data source mode from config: {:?}", config.data_source_mode);
+/// Copy of the configuration with passwords, tokens and salts hidden for printing
+fn masked_secrets(config: &Config) -> Config {
+    const MASK: &str = "********";
+    let mut config = config.clone();
+    if config.anonymize_salt.is_some() {
+        config.anonymize_salt = Some(MASK.to_string());
+    }
+    if let Some(telegram) = config.telegram.as_mut() {
+        telegram.bot_token = MASK.to_string();
+    }
+    if let Some(password) = config.email.as_mut().and_then(|email| email.password.as_mut()) {
+        *password = MASK.to_string();
+    }
+    if let Some(headers) = config.webhook.as_mut().and_then(|webhook| webhook.headers.as_mut()) {
+        headers.values_mut().for_each(|value| *value = MASK.to_string());
+    }
+    config
+}
 
-    let data_source_mode_arg = matches.get_one::<String>("data_source_mode");
-    println!("📂 Using data source mode from arguments: {:?}", data_source_mode_arg);
-    let data_source_mode = match data_source_mode_arg {
-        Some(str) => {
-            if str == "local" {
-                models::DataSourceMode::Local
-            } else if str == "internet" {
-                models::DataSourceMode::Internet
-            } else {
-                config.data_source_mode.clone()
-            }
-        },
-        _ => config.data_source_mode.clone(),
-    };
+/// What fetching the sources led to
+enum Fetched<T> {
+    /// New lists, and what was made of them
+    New(T, run_summary::RunSummary),
+    /// Every source is unchanged since the last completed run and skip_unchanged is set
+    Unchanged(run_summary::RunSummary),
+    /// No source produced any program list; the run is already finished
+    Empty,
+}
 
-    let output_dir = config.output_directory.as_deref().unwrap_or("output");
+/// Fetch and parse all sources into a new run directory and save the snapshot there
+///
+/// With skip_unchanged nothing is written when every source parses to the same lists
+/// as in the last completed run.
+async fn scrape(
+    settings: &cli::RunSettings,
+) -> Result<Fetched<(PathBuf, Vec<(String, Vec<models::StudentRecord>)>)>> {
+    let output_root = settings.output_root.as_str();
 
     // Create output directory if it doesn't exist
-    fs::create_dir_all(output_dir)?;
-    
-    // Clean up previous results
-    clean_output_directory(output_dir)?;
+    fs::create_dir_all(output_root)?;
 
-    println!("🔍 Analyzing admission data for SNILS: {}", target_snils);
-    println!(" Output directory: {} (cleaned)", output_dir);
-    println!("🌐 Data source mode: {:?}", data_source_mode);
-
-    // Initialize components
-    let scraper = scraper::AdmissionScraper::new();
+    info!("{}", t!("run.analyzing_snils", settings.target_snils));
+    info!("{}", t!("run.data_source_mode", format!("{:?}", settings.data_source_mode)));
+    info!("{}", t!("run.output_formats", format!("{:?}", settings.output_formats)));
 
     // Process data sources based on configuration
-    let mut all_program_records = Vec::new();
-    
-    // Process local files if configured
-    if matches!(data_source_mode, models::DataSourceMode::Local | models::DataSourceMode::Both) {
-        if let Some(data_dir) = &config.data_directory {
-            println!("📂 Processing local files from: {}", data_dir);
-            
-            if std::path::Path::new(data_dir).exists() {
-                for entry in fs::read_dir(data_dir)? {
-                    let entry = entry?;
-                    let path = entry.path();
-                    
-                    if path.extension().and_then(|s| s.to_str()) == Some("html") {
-                        println!("📄 Processing local file: {:?}", path.file_name().unwrap());
-                        
-                        match scraper.scrape_file(path.to_str().unwrap()) {
-                            Ok(programs) => {
-                                for (program_info, records) in programs {
-                                    let original_count = records.len();
-                                    println!("   ✅ Found {} applicants for program: {}", 
-                                           original_count, program_info.name);
-                                    
-                                    // Deduplicate records by SNILS within this program
-                                    let mut deduplicated_records = deduplicate_records_by_snils(records);
-                                    let duplicates_removed = original_count - deduplicated_records.len();
-                                    if duplicates_removed > 0 {
-                                        println!("   🔄 Removed {} duplicate SNILS records", duplicates_removed);
-                                    }
-                                    set_max_score_on_privileged_records(&mut deduplicated_records);
-                                    all_program_records.push((program_info.name, deduplicated_records));
-                                }
-                            }
-                            Err(e) => {
-                                println!("   ❌ Error processing local file: {}", e);
-                            }
-                        }
-                    }
+    let mut run_summary = run_summary::RunSummary::default();
+    let phase_started = Instant::now();
+    let all_program_records = match &settings.from_snapshot {
+        // The lists exactly as an earlier run parsed them, dated when they were parsed
+        Some(arg) => {
+            let snapshot = load_run_snapshot(settings, Some(arg))?;
+            run_summary.add_source(snapshot.created_at);
+            let all_program_records = snapshot.into_program_records();
+            for (_, records) in &all_program_records {
+                run_summary.add_program(records, 0);
+            }
+            all_program_records
+        }
+        None => {
+            let conditional_requests = settings.config.conditional_requests.unwrap_or(true);
+            if conditional_requests {
+                run_summary.http_cache = http_cache::load(Path::new(output_root));
+            }
+            run_summary.discovered = discovery::load(Path::new(output_root));
+            run_summary.layouts = layout::load(Path::new(output_root));
+            // Saved after every page, so an interrupted scrape can go on from there with --resume
+            let progress = if settings.resume {
+                let progress = resume::ScrapeProgress::resume(Path::new(output_root));
+                if progress.is_empty() {
+                    info!("{}", t!("resume.nothing"));
+                } else {
+                    info!("{}", t!("resume.resuming", progress.len()));
                 }
+                progress
             } else {
-                println!("   ⚠️  Local data directory '{}' does not exist", data_dir);
+                resume::ScrapeProgress::start(Path::new(output_root))
+            };
+            run_summary.scrape_progress = Some(progress);
+            let all_program_records =
+                sources::load_program_records(&settings.config, &settings.data_source_mode, &mut run_summary).await?;
+            if let Some(progress) = run_summary.scrape_progress.take() {
+                if run_summary.sources_failed == 0 {
+                    progress.finish();
+                } else if !progress.is_empty() {
+                    warn!("{}", t!("resume.hint", run_summary.sources_failed));
+                }
+            }
+            if conditional_requests && !run_summary.http_cache.is_empty() {
+                http_cache::save(Path::new(output_root), &run_summary.http_cache)?;
             }
+            if !run_summary.discovered.is_empty() {
+                discovery::save(Path::new(output_root), &run_summary.discovered)?;
+            }
+            if !run_summary.layouts.is_empty() {
+                layout::save(Path::new(output_root), &run_summary.layouts)?;
+            }
+            all_program_records
         }
+    };
+
+    run_summary.finish_phase(t!("summary.phase_sources"), phase_started);
+
+    let unchanged = settings.from_snapshot.is_none()
+        && settings.config.skip_unchanged.unwrap_or(false)
+        && run_summary.sources_failed == 0
+        && checksums::load(Path::new(output_root)).is_some_and(|previous| previous == run_summary.checksums);
+    if unchanged {
+        info!("{}", t!("run.unchanged"));
+        return Ok(Fetched::Unchanged(run_summary));
     }
-    
-    // Process internet URLs if configured
-    if matches!(data_source_mode, models::DataSourceMode::Internet | models::DataSourceMode::Both) {
-        if let Some(urls) = &config.internet_urls {
-            println!("🌐 Processing internet sources ({} URLs)", urls.len());
-            
-            for url in urls {
-                match scraper.scrape_url(url).await {
-                    Ok(programs) => {
-                        for (program_info, records) in programs {
-                            let original_count = records.len();
-                            println!("   ✅ Found {} applicants for program: {}", 
-                                   original_count, program_info.name);
-                            
-                            // Deduplicate records by SNILS within this program
-                            let mut deduplicated_records = deduplicate_records_by_snils(records);
-                            let duplicates_removed = original_count - deduplicated_records.len();
-                            if duplicates_removed > 0 {
-                                println!("   🔄 Removed {} duplicate SNILS records", duplicates_removed);
-                            }
-                            set_max_score_on_privileged_records(&mut deduplicated_records);
-                            
-                            all_program_records.push((program_info.name, deduplicated_records));
-                        }
-                    }
-                    Err(e) => {
-                        println!("   ❌ Error processing URL {}: {}", url, e);
-                    }
-                }
-            }
-        } else {
-            println!("   ⚠️  No internet URLs configured");
+
+    // Each run gets its own timestamped directory unless retention is disabled
+    let run_dir = if settings.output_retention > 0 {
+        output_dirs::create_run_directory(output_root)?
+    } else {
+        Path::new(output_root).to_path_buf()
+    };
+    let output_dir: &str = &run_dir.to_string_lossy();
+
+    // Clean up previous results written to the same directory
+    clean_output_directory(output_dir)?;
+    info!("{}", t!("run.output_directory", output_dir));
+
+    if let Err(failure) = sources::check_failure_policy(&settings.config, &run_summary) {
+        finish_run(settings, &run_dir, &run_summary)?;
+        return Err(failure.into());
+    }
+    if all_program_records.is_empty() {
+        error!("{}", t!("run.no_sources"));
+        finish_run(settings, &run_dir, &run_summary)?;
+        return Ok(Fetched::Empty);
+    }
+
+    let snapshot_path = run_dir.join(snapshot::SNAPSHOT_FILE);
+    let run_snapshot = snapshot::Snapshot::new(&all_program_records);
+    run_snapshot.save(&snapshot_path)?;
+    info!("{}", t!("run.snapshot_saved", snapshot_path.display()));
+    if let Some(path) = &settings.save_snapshot {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
         }
+        run_snapshot.save(path)?;
+        info!("{}", t!("run.snapshot_saved", path.display()));
     }
 
+    Ok(Fetched::New((run_dir, all_program_records), run_summary))
+}
+
+/// Fetch and parse all sources and print what a run would analyze, without touching the output directory
+async fn dry_run(settings: &cli::RunSettings) -> Result<ExitCode> {
+    info!("{}", t!("dry_run.start"));
+    let mut run_summary = run_summary::RunSummary::default();
+    let phase_started = Instant::now();
+    let mut all_program_records =
+        sources::load_program_records(&settings.config, &settings.data_source_mode, &mut run_summary).await?;
+    run_summary.finish_phase(t!("summary.phase_sources"), phase_started);
+    sources::check_failure_policy(&settings.config, &run_summary)?;
     if all_program_records.is_empty() {
-        println!("❌ No valid data sources found or all sources failed");
-        return Ok(());
+        error!("{}", t!("run.no_sources"));
+        return Ok(ExitCode::from(exit_codes::SOURCES));
     }
 
-    // Perform unified priority-based analysis for all funding types
-    println!("\n🎯 Analyzing admission chances using priority-based algorithm...");
-    let analyzer = AdmissionAnalyzer::new(&target_snils);
+    settings.identities.resolve_records(&mut all_program_records);
+    if let Some(aliases) = &settings.config.program_aliases {
+        program_aliases::merge(&mut all_program_records, aliases);
+    }
+    let target_snils = settings.identities.resolve_target(&settings.target_snils, &all_program_records)?;
+    console_table::print_sources_table(&target_snils, &all_program_records, settings.color);
+    if !target_snils.is_empty() {
+        let target = models::normalize_snils(&target_snils);
+        let target_lists = all_program_records
+            .iter()
+            .filter(|(_, records)| records.iter().any(|record| models::normalize_snils(&record.snils) == target))
+            .count();
+        if target_lists > 0 {
+            info!("{}", t!("dry_run.target_lists", target_snils, target_lists, all_program_records.len()));
+        } else {
+            warn!("{}", t!("dry_run.target_missing", target_snils));
+        }
+    }
 
-    let analysis = analyzer.analyze_all_programs(&all_program_records);
+    let inconsistencies = consistency::reconcile(&mut all_program_records, models::ScoreResolution::Keep);
+    if !inconsistencies.is_empty() {
+        warn!("{}", t!("dry_run.inconsistencies", inconsistencies.len()));
+    }
 
-    // Generate reports with new unified data
-    generate_program_popularity_report(&analysis, output_dir)?;
-    generate_detailed_csv(&all_program_records, output_dir)?;
-    generate_individual_program_csvs(&all_program_records, output_dir)?;
-    generate_filtered_eager_csvs(&target_snils, &analysis, &all_program_records, output_dir)?;
-    generate_available_places_csvs(&target_snils, &analysis, &all_program_records, output_dir)?;
-    generate_final_cutoff_analysis(&target_snils, &analysis,  &all_program_records, output_dir)?;
+    run_summary.print();
+    info!("{}", t!("dry_run.done"));
+    Ok(exit_codes::of_run(&run_summary))
+}
 
-    println!("✅ Priority-based analysis complete!");
-    println!("📂 Results: {}", output_dir);
-    println!("Check the output directory for detailed reports.");
+/// Write the run summary and point `latest` at the finished run directory
+fn finish_run(settings: &cli::RunSettings, run_dir: &Path, run_summary: &run_summary::RunSummary) -> Result<()> {
+    run_summary.report(&run_dir.to_string_lossy())?;
+    if !run_summary.events.is_empty() {
+        run_summary.events.write(run_dir)?;
+    }
+    if settings.output_retention > 0 {
+        output_dirs::finish_run(&settings.output_root, run_dir, settings.output_retention)?;
+    }
+    // Only a run that loaded every source tells what "unchanged" means for the next one
+    if run_summary.sources_failed == 0 && !run_summary.checksums.is_empty() {
+        checksums::save(Path::new(&settings.output_root), &run_summary.checksums)?;
+    }
     Ok(())
 }
 
-fn generate_program_popularity_report(
-    analysis: &analyzer::AdmissionAnalysis,
-    output_dir: &str,
-) -> Result<()> {
-    let mut content = String::new();
-    content.push_str("Program Popularity Analysis\n");
-    content.push_str("==========================\n\n");
+fn load_snapshot(path: &Path) -> Result<Vec<(String, Vec<models::StudentRecord>)>> {
+    info!("{}", t!("run.loading_snapshot", path.display()));
+    Ok(snapshot::Snapshot::load(path)?.into_program_records())
+}
 
-    for popularity in &analysis.program_popularities {
-        let eager_per_place = popularity.total_eager_applicants as f64 / popularity.available_places as f64;
-        
-        content.push_str(&format!(
-            "Program: {} ({})\n\
-            Eager applicants per place: {:.2}\n\
-            Top candidates average priority: {:.2}\n\
-            Average score: {:.2}\n\
-            Available places: {}\n\
-            Total eager applicants: {}\n\n",
-            popularity.program_name,
-            popularity.funding_source,
-            eager_per_place,
-            popularity.top_candidates_average_priority,
-            popularity.average_score,
-            popularity.available_places,
-            popularity.total_eager_applicants
-        ));
-    }
-
-    fs::write(Path::new(output_dir).join("program_popularity.txt"), content)?;
-    Ok(())
+/// Records of a snapshot file, run directory or `@ID` stored run; the latest run by default
+fn load_run_records(
+    settings: &cli::RunSettings,
+    arg: Option<&String>,
+) -> Result<Vec<(String, Vec<models::StudentRecord>)>> {
+    Ok(load_run_snapshot(settings, arg)?.into_program_records())
 }
 
+/// Snapshot file, run directory or `@ID` stored run; the latest run by default
+fn load_run_snapshot(settings: &cli::RunSettings, arg: Option<&String>) -> Result<snapshot::Snapshot> {
+    let Some(run_id) = arg.and_then(|arg| arg.strip_prefix('@')) else {
+        let path = settings.snapshot_path(arg);
+        info!("{}", t!("run.loading_snapshot", path.display()));
+        return Ok(snapshot::Snapshot::load(&path)?);
+    };
+    let run_id: i64 = run_id.parse().with_context(|| format!("Invalid run id: {}", run_id))?;
+    let database_path = settings.database_path();
+    info!("{}", t!("run.loading_stored_run", run_id, database_path.display()));
+    Ok(storage::Storage::open(&database_path)?.load_snapshot(run_id)?)
+}
 
-fn generate_detailed_csv(
-    all_program_records: &[(String, Vec<models::StudentRecord>)],
-    output_dir: &str,
-) -> Result<()> {
-    use csv::Writer;
-
-    let csv_path = Path::new(output_dir).join("all_applicants.csv");
-    let mut writer = Writer::from_path(csv_path)?;
-
-    // Write headers
-    writer.write_record(&[
-        "Program",
-        "Rank",
-        "SNILS",
-        "Priority",
-        "Consent",
-        "Document Type",
-        "Average Score",
-        "Subject Scores",
-        "Psychological Test",
-        "Funding Source",
-        "Study Form",
-        "Available Places",
-    ])?;
-
-    // Write data
-    for (program_name, records) in all_program_records {
-        for record in records {
-            writer.write_record(&[
-                program_name,
-                &record.rank.to_string(),
-                &record.snils,
-                &record.priority.to_string(),
-                &record.consent,
-                &record.document_type,
-                &record.average_score,
-                &record.subject_scores,
-                &record.psychological_test,
-                &record.funding_source,
-                &record.study_form,
-                &record.available_places.to_string(),
-            ])?;
+/// Enrollments of all `orders`, with identifiers spelled as in the analyzed lists
+fn load_enrollments<'a>(
+    settings: &cli::RunSettings,
+    orders: impl Iterator<Item = &'a String>,
+) -> Result<Vec<enrollment_order::Enrollment>> {
+    let anonymizer = settings
+        .anonymize
+        .then(|| anonymize::Anonymizer::new(settings.config.anonymize_salt.as_deref()));
+    let mut enrollments = Vec::new();
+    for order in orders {
+        let mut enrolled = enrollment_order::load(order)?;
+        info!("{}", t!("validation.order_loaded", enrolled.len(), order));
+        for enrollment in &mut enrolled {
+            enrollment.snils = settings.identities.resolve(&enrollment.snils);
+            if let Some(anonymizer) = &anonymizer {
+                enrollment.snils = anonymizer.pseudonym(&enrollment.snils);
+            }
         }
+        enrollments.extend(enrolled);
     }
-
-    writer.flush()?;
-    Ok(())
+    Ok(enrollments)
 }
 
-// 2. Generate individual CSV files for each program
-fn generate_individual_program_csvs(
+/// The simulation of `all_program_records`, reused from an earlier run over the same
+/// lists and settings when the simulation cache has it
+fn simulate(
+    settings: &cli::RunSettings,
+    analyzer: &AdmissionAnalyzer,
     all_program_records: &[(String, Vec<models::StudentRecord>)],
-    output_dir: &str,
-) -> Result<()> {
-    use csv::Writer;
-    
-    let programs_dir = Path::new(output_dir).join("programs");
-    fs::create_dir_all(&programs_dir)?;
-
-    for (program_name, records) in all_program_records {
-        let safe_name = program_name.replace("/", "_").replace(" ", "_");
-        let csv_path = programs_dir.join(format!("{}.csv", safe_name));
-        let mut writer = Writer::from_path(csv_path)?;
-
-        // Write headers
-        writer.write_record(&[
-            "Rank", "SNILS", "Priority", "Consent", "Document_Type", "Average_Score",
-            "Subject_Scores", "Psychological_Test", "Funding_Source", "Study_Form", "Available_Places"
-        ])?;
-
-        // Write data
-        for record in records {
-            writer.write_record(&[
-                &record.rank.to_string(),
-                &record.snils,
-                &record.priority.to_string(),
-                &record.consent,
-                &record.document_type,
-                &record.average_score,
-                &record.subject_scores,
-                &record.psychological_test,
-                &record.funding_source,
-                &record.study_form,
-                &record.available_places.to_string(),
-            ])?;
-        }
-
-        writer.flush()?;
+) -> analyzer::AdmissionAnalysis {
+    if !settings.config.simulation_cache.unwrap_or(true) {
+        return analyzer.analyze_all_programs(all_program_records);
     }
+    let output_root = Path::new(&settings.output_root);
+    let key = simulation_cache::key(analyzer, all_program_records);
+    let mut cache = simulation_cache::SimulationCache::load(output_root);
+    if let Some(analysis) = cache.analysis(&key, analyzer, all_program_records) {
+        info!("{}", t!("run.simulation_cached"));
+        return analysis;
+    }
+    let analysis = analyzer.analyze_all_programs(all_program_records);
+    cache.insert(key, &analysis);
+    // A cache that cannot be saved only costs the next run time
+    let saved = fs::create_dir_all(output_root)
+        .map_err(|cause| abitur_analyzer::error::StorageError::Write { path: settings.output_root.clone(), cause })
+        .and_then(|()| cache.save(output_root));
+    if let Err(e) = saved {
+        warn!("{}", t!("run.simulation_cache_failed", e));
+    }
+    analysis
+}
 
-    Ok(())
+/// Anonymize if requested and simulate admission for the target applicant
+fn analyze_records(
+    settings: &cli::RunSettings,
+    all_program_records: Vec<(String, Vec<models::StudentRecord>)>,
+    run_summary: &mut run_summary::RunSummary,
+) -> Result<AnalyzedRun> {
+    let prepared = prepare_records(&settings.config, &settings.identities, &settings.scoring, all_program_records)?;
+    let phase_started = Instant::now();
+    let analyzed = analyze_prepared(&settings.config, prepared, &run_summary.layout_changes, |analyzer, all_program_records| {
+        simulate(settings, analyzer, all_program_records)
+    });
+    run_summary.events.push_analysis(&analyzed.report, &analyzed.target_outcomes);
+    run_summary.finish_phase(t!("summary.phase_analysis"), phase_started);
+    Ok(analyzed)
 }
 
-// 3. Generate filtered eager applicants with exclusion marks
-fn generate_filtered_eager_csvs(
-    target_snils: &str,
-    analysis: &analyzer::AdmissionAnalysis,
-    all_program_records: &[(String, Vec<models::StudentRecord>)],
-    output_dir: &str,
-) -> Result<()> {
-    use csv::Writer;
-    use crate::models::normalize_snils;
-    
-    let filtered_dir = Path::new(output_dir).join("filtered_eager");
-    fs::create_dir_all(&filtered_dir)?;
-
-    // Create exclusion tracker based on admission simulation
-    let analyzer_instance = analyzer::AdmissionAnalyzer::new(target_snils);
-    let program_funding_groups = analyzer_instance.group_by_program_and_funding_public(all_program_records.to_vec());
-    let mut excluded_normalized_snils = std::collections::HashSet::new();
-
-    // Process programs in popularity order
-    for popularity in &analysis.program_popularities {
-        let program_name = &popularity.program_name;
-        let safe_name = program_name.replace("/", "_").replace(" ", "_");
-        let csv_path = filtered_dir.join(format!("{}_filtered_eager.csv", safe_name));
-        let mut writer = Writer::from_path(csv_path)?;
-
-        // Write headers
-        writer.write_record(&[
-            "Rank", "SNILS", "Priority", "Consent", "Document_Type", "Average_Score",
-            "Subject_Scores", "Psychological_Test", "Funding_Source", "Study_Form", 
-            "Available_Places", "Is_Eager", "Excluded_By_Higher_Priority"
-        ])?;
-
-        if let Some(funding_groups) = program_funding_groups.get(program_name) {
-            // Process budget funding first
-            if let Some(budget_records) = funding_groups.get("Бюджетное финансирование") {
-                for record in budget_records {
-                    let is_eager = record.has_original_document() || record.has_consent();
-                    let normalized_snils = normalize_snils(&record.snils);
-                    let is_excluded = excluded_normalized_snils.contains(&normalized_snils);
-                    
-                    writer.write_record(&[
-                        &record.rank.to_string(),
-                        &record.snils,
-                        &record.priority.to_string(),
-                        &record.consent,
-                        &record.document_type,
-                        &record.average_score,
-                        &record.subject_scores,
-                        &record.psychological_test,
-                        &record.funding_source,
-                        &record.study_form,
-                        &record.available_places.to_string(),
-                        &if is_eager { "Да".to_string() } else { "Нет".to_string() },
-                        &if is_excluded { "Да".to_string() } else { "Нет".to_string() },
-                    ])?;
-                }
-                
-                // Mark as excluded those who get admitted
-                let available_places = budget_records[0].available_places as usize;
-                let to_exclude: Vec<String> = budget_records
-                    .iter()
-                    .filter(|r| (r.has_original_document() || r.has_consent()) && !excluded_normalized_snils.contains(&normalize_snils(&r.snils)))
-                    .take(available_places)
-                    .map(|r| normalize_snils(&r.snils))
-                    .collect();
-                
-                for snils in to_exclude {
-                    excluded_normalized_snils.insert(snils);
-                }
-            }
-            
-            // Process commercial funding
-            if let Some(commercial_records) = funding_groups.get("Коммерческое финансирование") {
-                for record in commercial_records {
-                    let is_eager = record.has_original_document() || record.has_consent();
-                    let normalized_snils = normalize_snils(&record.snils);
-                    let is_excluded = excluded_normalized_snils.contains(&normalized_snils);
-                    
-                    writer.write_record(&[
-                        &record.rank.to_string(),
-                        &record.snils,
-                        &record.priority.to_string(),
-                        &record.consent,
-                        &record.document_type,
-                        &record.average_score,
-                        &record.subject_scores,
-                        &record.psychological_test,
-                        &record.funding_source,
-                        &record.study_form,
-                        &record.available_places.to_string(),
-                        &if is_eager { "Да".to_string() } else { "Нет".to_string() },
-                        &if is_excluded { "Да".to_string() } else { "Нет".to_string() },
-                    ])?;
-                }
-                
-                // Mark as excluded those who get admitted
-                let available_places = commercial_records[0].available_places as usize;
-                let to_exclude: Vec<String> = commercial_records
-                    .iter()
-                    .filter(|r| (r.has_original_document() || r.has_consent()) && !excluded_normalized_snils.contains(&normalize_snils(&r.snils)))
-                    .take(available_places)
-                    .map(|r| normalize_snils(&r.snils))
-                    .collect();
-                
-                for snils in to_exclude {
-                    excluded_normalized_snils.insert(snils);
-                }
-            }
+/// The program of interest `analyze` estimates alone with single_program_estimate, if there is one
+fn estimated_program(settings: &cli::RunSettings) -> Option<&str> {
+    if !settings.config.single_program_estimate.unwrap_or(false) {
+        return None;
+    }
+    match settings.config.programs_of_interest.as_deref() {
+        Some([program_name]) => Some(program_name),
+        _ => {
+            warn!("{}", t!("estimate.needs_one_program"));
+            None
         }
-
-        writer.flush()?;
     }
-
-    Ok(())
 }
 
-// 4. Generate available places CSV files (only admitted students)
-fn generate_available_places_csvs(
-    target_snils: &str,
-    analysis: &analyzer::AdmissionAnalysis,
-    all_program_records: &[(String, Vec<models::StudentRecord>)],
-    output_dir: &str,
+/// Print the target's chances in `program_name` without simulating the other programs
+fn estimate_single_program(
+    settings: &cli::RunSettings,
+    all_program_records: Vec<(String, Vec<models::StudentRecord>)>,
+    program_name: &str,
 ) -> Result<()> {
-    use csv::Writer;
-    use crate::models::normalize_snils;
-    
-    let admitted_dir = Path::new(output_dir).join("admitted_lists");
-    fs::create_dir_all(&admitted_dir)?;
-
-    // Get target SNILS from the analysis
-    let normalized_target = normalize_snils(target_snils);
-
-    // Process each program-funding combination
-    for (program_key, admitted_snils_list) in &analysis.final_admission_results {
-        let safe_name = program_key.replace("/", "_").replace(" ", "_");
-        let csv_path = admitted_dir.join(format!("{}_admitted.csv", safe_name));
-        let mut writer = Writer::from_path(csv_path)?;
-
-        // Write headers
-        writer.write_record(&[
-            "Rank", "SNILS", "Priority", "Consent", "Document_Type", "Average_Score",
-            "Subject_Scores", "Psychological_Test", "Funding_Source", "Study_Form", 
-            "Available_Places", "Admission_Status"
-        ])?;
-
-        // Parse program_key to get program_name and funding_source
-        let (program_name, funding_source) = if program_key.ends_with("_Бюджетное финансирование") {
-            let name_part = program_key.strip_suffix("_Бюджетное финансирование").unwrap();
-            (name_part.to_string(), "Бюджетное финансирование".to_string())
-        } else if program_key.ends_with("_Коммерческое финансирование") {
-            let name_part = program_key.strip_suffix("_Коммерческое финансирование").unwrap();
-            (name_part.to_string(), "Коммерческое финансирование".to_string())
-        } else {
-            // Fallback for other funding types
-            let last_underscore_pos = program_key.rfind('_').unwrap_or(0);
-            if last_underscore_pos == 0 {
-                continue; // Skip malformed keys
-            }
-            let program_name = program_key[..last_underscore_pos].to_string();
-            let funding_source = program_key[last_underscore_pos + 1..].to_string();
-            (program_name, funding_source)
-        };
+    let prepared = prepare_records(&settings.config, &settings.identities, &settings.scoring, all_program_records)?;
+    let analyzer = configured_analyzer(&settings.config, &prepared.target_snils, None);
+    info!("{}", t!("estimate.running", program_name));
+    let estimates = single_program::estimate(&analyzer, &prepared.all_program_records, program_name);
+    console_table::print_estimate_table(&prepared.target_snils, &estimates, settings.color);
+    Ok(())
+}
 
-        // Find matching records in all_program_records
-        let mut matching_records = Vec::new();
-        
-        for (record_program_name, program_records) in all_program_records {
-            if record_program_name == &program_name {
-                for record in program_records {
-                    if record.funding_source == funding_source {
-                        matching_records.push(record.clone());
-                    }
-                }
-            }
-        }
+/// Write every configured report format into `output_dir`
+fn write_reports(settings: &cli::RunSettings, analyzed: &AnalyzedRun, output_dir: &str) -> Result<()> {
+    let report_templates = templates::ReportTemplates::load(settings.config.templates_directory.as_deref().unwrap_or("templates"))?;
 
-        if !matching_records.is_empty() {
-            // Sort matching records by rank to maintain order
-            matching_records.sort_by_key(|r| r.rank);
-            
-            // Create a set of admitted SNILS for quick lookup
-            let admitted_snils_set: std::collections::HashSet<String> = admitted_snils_list
-                .iter()
-                .map(|snils| normalize_snils(snils))
-                .collect();
-
-            let available_places = matching_records[0].available_places as usize;
-
-            // Calculate cutoff score from admitted students
-            let cutoff_score = if !admitted_snils_list.is_empty() {
-                let mut lowest_score = f64::MAX;
-                for admitted_snils in admitted_snils_list {
-                    for record in &matching_records {
-                        if normalize_snils(&record.snils) == normalize_snils(admitted_snils) {
-                            if let Some(score) = record.get_numeric_score() {
-                                lowest_score = lowest_score.min(score);
-                            }
-                        }
-                    }
-                }
-                if lowest_score == f64::MAX { 0.0 } else { lowest_score }
-            } else {
-                0.0
-            };
+    // Written with any format, the differences may change the result
+    if !analyzed.inconsistencies.is_empty() {
+        csv_report::generate_reconciliation_csv(&settings.csv_dialect, &analyzed.inconsistencies, output_dir)?;
+    }
+    if !analyzed.rank_discrepancies.is_empty() {
+        csv_report::generate_rank_discrepancies_csv(&settings.csv_dialect, &analyzed.rank_discrepancies, output_dir)?;
+    }
+    if !analyzed.program_merges.is_empty() {
+        csv_report::generate_program_merges_csv(&settings.csv_dialect, &analyzed.program_merges, output_dir)?;
+    }
+    if !analyzed.simulation_warnings.is_empty() {
+        write_simulation_warnings(&analyzed.simulation_warnings, output_dir)?;
+    }
 
-            // Create a combined list with both admitted students and target applicant in proper rank order
-            let mut all_relevant_records = Vec::new();
-            
-            for record in &matching_records {
-                let normalized_record_snils = normalize_snils(&record.snils);
-                let is_admitted = admitted_snils_set.contains(&normalized_record_snils);
-                let is_target = normalized_record_snils == normalized_target;
-                
-                // Include if: admitted OR target applicant
-                if is_admitted || is_target {
-                    all_relevant_records.push((record.clone(), is_admitted, is_target));
-                }
-            }
+    // Directories of the per-program files of CSV and charts
+    if [models::OutputFormat::Csv, models::OutputFormat::Charts].iter().any(|format| settings.output_formats.contains(format)) {
+        program_dirs::ProgramDirectories::of(analyzed).write_manifest(analyzed, output_dir)?;
+    }
 
-            // Sort by rank to ensure proper order
-            all_relevant_records.sort_by_key(|(record, _, _)| record.rank);
+    let sinks = report_sink::sinks(settings, &report_templates);
+    report_sink::write_all(&sinks, analyzed, output_dir)
+}
 
-            // Write all records in proper rank order
-            let mut admission_position = 0;
-            for (record, is_admitted, is_target) in all_relevant_records {
-                if is_admitted {
-                    admission_position += 1;
-                }
+/// One line per broken invariant, so a wrong simulation does not pass for a result
+fn write_simulation_warnings(violations: &[invariants::Violation], output_dir: &str) -> Result<()> {
+    let title = t!("invariants.title");
+    let mut content = format!("{}\n{}\n\n", title, "=".repeat(title.chars().count()));
+    for violation in violations {
+        content.push_str(&format!("{}\n", violation));
+    }
+    let path = Path::new(output_dir).join("simulation_warnings.txt");
+    atomic_file::write(&path, content)?;
+    info!("{}", t!("invariants.report", path.display()));
+    Ok(())
+}
 
-                let admission_status = if is_target {
-                    // For target applicant, determine status based on score vs cutoff
-                    let target_score = record.get_numeric_score().unwrap_or(0.0);
-                    
-                    if is_admitted {
-                        // Target was actually admitted
-                        if admission_position <= available_places {
-                            match record.funding_source.as_str() {
-                                "Бюджетное финансирование" => "Admitted_Budget+",
-                                "Коммерческое финансирование" => "Admitted_Commercial+",
-                                _ => "Admitted_Other+",
-                            }
-                        } else {
-                            match record.funding_source.as_str() {
-                                "Бюджетное финансирование" => "Admitted_Budget-",
-                                "Коммерческое финансирование" => "Admitted_Commercial-",
-                                _ => "Admitted_Other-",
-                            }
-                        }
-                    } else {
-                        // Target was not admitted - check if their score is above cutoff
-                        if target_score > cutoff_score && cutoff_score > 0.0 {
-                            "Target_NotAdmitted+"  // Score above cutoff but not admitted due to priority
-                        } else {
-                            "Target_NotAdmitted-"  // Score below cutoff or no cutoff available
-                        }
-                    }
-                } else {
-                    // Regular admitted student
-                    match record.funding_source.as_str() {
-                        "Бюджетное финансирование" => "Admitted_Budget",
-                        "Коммерческое финансирование" => "Admitted_Commercial",
-                        _ => "Admitted_Other",
-                    }
-                };
-
-                writer.write_record(&[
-                    &record.rank.to_string(),
-                    &record.snils,
-                    &record.priority.to_string(),
-                    &record.consent,
-                    &record.document_type,
-                    &record.average_score,
-                    &record.subject_scores,
-                    &record.psychological_test,
-                    &record.funding_source,
-                    &record.study_form,
-                    &record.available_places.to_string(),
-                    admission_status,
-                ])?;
-            }
-        }
+fn print_results(settings: &cli::RunSettings, analyzed: &AnalyzedRun) {
+    console_table::print_final_cutoff_table(&analyzed.target_snils, &analyzed.analysis, &analyzed.target_outcomes, settings.color);
+    console_table::print_admission_distance_table(&analyzed.admission_distances, settings.color);
+}
 
-        writer.flush()?;
+/// Full pipeline: scrape, analyze and write reports into a new run directory
+async fn run(settings: &cli::RunSettings) -> Result<Fetched<(AnalyzedRun, String)>> {
+    let _lock = output_dirs::lock_output_root(&settings.output_root)?;
+    let (run_dir, all_program_records, mut run_summary) = match scrape(settings).await? {
+        Fetched::New((run_dir, all_program_records), run_summary) => (run_dir, all_program_records, run_summary),
+        Fetched::Unchanged(run_summary) => return Ok(Fetched::Unchanged(run_summary)),
+        Fetched::Empty => return Ok(Fetched::Empty),
+    };
+    let output_dir = run_dir.to_string_lossy().to_string();
+
+    let analyzed = analyze_records(settings, all_program_records, &mut run_summary)?;
+    let phase_started = Instant::now();
+    write_reports(settings, &analyzed, &output_dir)?;
+    run_summary.finish_phase(t!("summary.phase_reports"), phase_started);
+
+    finish_run(settings, &run_dir, &run_summary)?;
+    if run_summary.sources_failed == 0 {
+        output_dirs::write_latest_pointer(
+            &settings.output_root,
+            &run_dir,
+            &analyzed.target_snils,
+            run_summary.sources_processed,
+        )?;
     }
-
-    Ok(())
+    Ok(Fetched::New((analyzed, output_dir), run_summary))
 }
 
-// 5. Generate final cutoff analysis for programs by popularity of interest with target applicant position
-fn generate_final_cutoff_analysis(
-    target_snils: &str,
-    analysis: &analyzer::AdmissionAnalysis,
-    all_program_records: &[(String, Vec<models::StudentRecord>)],
-    output_dir: &str,
-) -> Result<()> {
-    use csv::Writer;
-    use crate::models::normalize_snils;
-    
-    let final_path = Path::new(output_dir).join("final_cutoff_analysis.txt");
-    let final_csv_path = Path::new(output_dir).join("final_cutoff_analysis.csv");
-    
-    let mut content = String::new();
-    content.push_str(&format!("Final Cutoff Analysis for SNILS: {}\n", target_snils));
-    content.push_str("==========================================\n\n");
-
-    let mut csv_writer = Writer::from_path(final_csv_path)?;
-    csv_writer.write_record(&[
-        "Program", "Funding_Type", "Position_In_Admitted", "Available_Places", 
-        "Target_Score", "Cutoff_Score", "Admission_Position", "Admission_Status"
-    ])?;
-
-    let normalized_target = normalize_snils(target_snils);
-
-    println!("📊 UNIFIED PRIORITY-BASED ADMISSION ANALYSIS for target SNILS: {}", target_snils);
-    println!("==========================================");
-
-    // Process each program-funding combination from admission results in order of popularity
-    for program_popularity in &analysis.program_popularities {
-        let program_key = &program_popularity.program_key;
-        let admitted_snils_list = &analysis.final_admission_results[program_key];
-
-        // Parse program_key to get program_name and funding_source
-        let (program_name, funding_source) = if program_key.ends_with("_Бюджетное финансирование") {
-            let name_part = program_key.strip_suffix("_Бюджетное финансирование").unwrap();
-            (name_part.to_string(), "Бюджетное финансирование".to_string())
-        } else if program_key.ends_with("_Коммерческое финансирование") {
-            let name_part = program_key.strip_suffix("_Коммерческое финансирование").unwrap();
-            (name_part.to_string(), "Коммерческое финансирование".to_string())
-        } else {
-            let last_underscore_pos = program_key.rfind('_').unwrap_or(0);
-            if last_underscore_pos == 0 {
-                continue; // Skip malformed keys
-            }
-            let program_name = program_key[..last_underscore_pos].to_string();
-            let funding_source = program_key[last_underscore_pos + 1..].to_string();
-            (program_name, funding_source)
+/// Run the full pipeline on `schedule` and print what changed for the target
+///
+/// A stop request during a run lets it finish, so no half-written run directory is left.
+async fn watch(settings: &cli::RunSettings, schedule: &schedule::Schedule) -> Result<()> {
+    info!("{}", schedule.describe());
+    let mut shutdown = schedule::Shutdown::new()?;
+    let notifier = notify::Notifier::new(&settings.config);
+    let mut previous_outcomes: Option<Vec<analyzer::TargetOutcome>> = None;
+    let mut next_run = schedule.first_run(Utc::now());
+
+    loop {
+        let Some(start) = next_run else {
+            bail!("{}", t!("watch.schedule_exhausted"));
         };
-
-        // Find matching records in all_program_records
-        let mut all_matching_records = Vec::new();
-        
-        for (record_program_name, program_records) in all_program_records {
-            if record_program_name == &program_name {
-                for record in program_records {
-                    if record.funding_source == funding_source {
-                        all_matching_records.push(record.clone());
-                    }
-                }
-            }
+        let wait = (start - Utc::now()).to_std().unwrap_or_default();
+        if !wait.is_zero() {
+            info!("{}", t!("watch.next_run", schedule.format_time(start)));
         }
-
-        if all_matching_records.is_empty() {
-            continue;
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = shutdown.recv() => {
+                info!("{}", t!("watch.stopped"));
+                return Ok(());
+            }
         }
 
-        // Sort records by rank to maintain order
-        all_matching_records.sort_by_key(|r| r.rank);
-        let available_places = all_matching_records[0].available_places as usize;
-
-        // Check if target was admitted to this specific program-funding combination
-        let is_admitted = admitted_snils_list
-            .iter()
-            .any(|snils| normalize_snils(snils) == normalized_target);
-
-        // Calculate actual cutoff score (lowest score among admitted applicants)
-        let cutoff_score = if !admitted_snils_list.is_empty() {
-            let mut lowest_score = f64::MAX;
-            for admitted_snils in admitted_snils_list {
-                for record in &all_matching_records {
-                    if normalize_snils(&record.snils) == normalize_snils(admitted_snils) {
-                        if let Some(score) = record.get_numeric_score() {
-                            lowest_score = lowest_score.min(score);
-                        }
-                    }
-                }
+        let mut stopping = false;
+        let pipeline = run(settings);
+        tokio::pin!(pipeline);
+        let result = tokio::select! {
+            result = &mut pipeline => result,
+            _ = shutdown.recv() => {
+                info!("{}", t!("watch.finishing_run"));
+                stopping = true;
+                pipeline.await
             }
-            if lowest_score == f64::MAX { 0.0 } else { lowest_score }
-        } else {
-            0.0
         };
 
-        // Find the target record in the matching records
-        let target_record = all_matching_records
-            .iter()
-            .find(|record| normalize_snils(&record.snils) == normalized_target);
-
-        if let Some(target_rec) = target_record {
-            let target_score = target_rec.get_numeric_score().unwrap_or(0.0);
-            
-            // Calculate position and status - FIXED LOGIC
-            let (admission_status, status_detail, position_info) = if is_admitted {
-                let position = admitted_snils_list
-                    .iter()
-                    .position(|snils| normalize_snils(snils) == normalized_target)
-                    .map(|pos| pos + 1)
-                    .unwrap_or(0);
-                
-                let position_str = format!("Position in admitted list: {} (of {} admitted)\n", position, admitted_snils_list.len());
-                ("Admitted".to_string(), String::new(), position_str)
-            } else {
-                // FIXED: Check if target score is higher than cutoff - should be "Admitted" status
-                if target_score > cutoff_score && cutoff_score > 0.0 {
-                    let detail = format!(" (would qualify by score but priority {} not selected)", target_rec.priority);
-                    ("Admitted_ByScore_NotByPriority".to_string(), detail, String::new())
-                } else {
-                    let detail = String::new(); 
-                    ("Not_Admitted".to_string(), detail, String::new())
+        match result {
+            Ok(Fetched::New((analyzed, _), _)) => {
+                notifier
+                    .publish_analysis(&analyzed.target_snils, &analyzed.target_outcomes, &analyzed.admission_distances)
+                    .await;
+                match &previous_outcomes {
+                    Some(previous) => {
+                        let changes = compare::compare_outcomes(previous, &analyzed.target_outcomes);
+                        console_table::print_changes_table(&analyzed.target_snils, &changes, settings.color);
+                        let events = compare::significant_events(&changes);
+                        notifier.publish_changes(&analyzed.target_snils, &changes, &events).await;
+                        notifier.notify(&analyzed.target_snils, &events).await;
+                    }
+                    None => print_results(settings, &analyzed),
                 }
-            };
-
-            content.push_str(&format!(
-                "Program: {}\n\
-                Funding: {}\n\
-                {}Available places: {}\n\
-                Target score: {:.4}\n\
-                Cutoff score: {:.4}\n\
-                Status: {}{}\n\n",
-                program_name,
-                funding_source,
-                position_info,
-                available_places,
-                target_score,
-                cutoff_score,
-                admission_status,
-                status_detail
-            ));
-
-            let position_csv = if is_admitted {
-                let position = admitted_snils_list
-                    .iter()
-                    .position(|snils| normalize_snils(snils) == normalized_target)
-                    .map(|pos| pos + 1)
-                    .unwrap_or(0);
-                format!("Position {} of {}", position, admitted_snils_list.len())
-            } else {
-                "Not in list".to_string()
-            };
-            let eager_per_place = program_popularity.eager_applicants.len() as f64 / program_popularity.available_places as f64;
+                previous_outcomes = Some(analyzed.target_outcomes);
+            }
+            Ok(Fetched::Unchanged(_) | Fetched::Empty) => {}
+            Err(e) => error!("{}", t!("watch.run_failed", e)),
+        }
 
-            let status_ico = if is_admitted {
-                "✅"
-            } else {
-                "❌"
-            };
-            let target_priority = all_matching_records
-                .iter()
-                .find(|r| normalize_snils(&r.snils) == normalized_target)
-                .map(|r| r.priority)
-                .unwrap_or(0);
-            println!("{} Program: {}, funding: {}", status_ico, program_name, funding_source);
-            println!(
-                "Available Places: {}, Cutoff Score: {:.4}, Applicants per place: {:.1}, Avg priority: {:.2}",
-                available_places, cutoff_score, eager_per_place, program_popularity.top_candidates_average_priority
-            );
-            println!(
-                "Priority:{}, Target Score: {:.4}, Status: {}, Position in admitted: {}",
-                target_priority, target_score, admission_status, position_csv
-            );
-            println!("");
-
-
-            csv_writer.write_record(&[
-                &program_name,
-                &funding_source,
-                &position_csv,
-                &available_places.to_string(),
-                &format!("{:.4}", target_score),
-                &format!("{:.4}", cutoff_score),
-                &position_csv,
-                &admission_status,
-            ])?;
-        } else {
-            // Target applicant not found in this program-funding combination
-            content.push_str(&format!(
-                "Program: {} - Target applicant not found\n\
-                Funding: {}\n\
-                Available places: {}\n\
-                Target score: N/A\n\
-                Cutoff score: {:.4}\n\
-                Status: Hypothetical: Cannot determine (target did not apply)\n\n",
-                program_name,
-                funding_source,
-                available_places,
-                cutoff_score
-            ));
+        if stopping {
+            info!("{}", t!("watch.stopped"));
+            return Ok(());
         }
+        next_run = schedule.next_run(Utc::now());
     }
-
-    fs::write(final_path, content)?;
-    csv_writer.flush()?;
-    Ok(())
 }
 
 // Clean up previous results from output directory
+/// Directory `report` writes to: the run directory holding the snapshot, cleaned first, or a
+/// new run directory when the snapshot lies outside the output root, so files next to it
+/// are left alone
+fn report_directory(settings: &cli::RunSettings, snapshot_path: &Path) -> Result<String> {
+    let snapshot_dir = snapshot_path.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let is_run_dir = snapshot_dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(output_dirs::is_run_directory_name);
+    let in_output_root = match (fs::canonicalize(snapshot_dir), fs::canonicalize(&settings.output_root)) {
+        (Ok(dir), Ok(output_root)) => dir.starts_with(output_root),
+        _ => false,
+    };
+    if is_run_dir || in_output_root {
+        let output_dir = snapshot_dir.to_string_lossy().to_string();
+        clean_output_directory(&output_dir)?;
+        return Ok(output_dir);
+    }
+    let run_dir = output_dirs::create_run_directory(&settings.output_root)?;
+    Ok(run_dir.to_string_lossy().to_string())
+}
+
 fn clean_output_directory(output_dir: &str) -> Result<()> {
     let output_path = Path::new(output_dir);
     
@@ -902,7 +791,7 @@ fn clean_output_directory(output_dir: &str) -> Result<()> {
         return Ok(());
     }
     
-    println!("🧹 Cleaning previous results...");
+    debug!("{}", t!("run.cleaning"));
     
     // List of files/directories to clean
     let items_to_clean = [
@@ -912,9 +801,28 @@ fn clean_output_directory(output_dir: &str) -> Result<()> {
         "program_popularity.txt",
         "final_cutoff_analysis.txt",
         "final_cutoff_analysis.csv",
-        "programs",
+        "admission_distance.csv",
+        "competitors.csv",
+        "boundary.csv",
+        "application_strategies.csv",
+        "applicant_flows.csv",
+        "applicant_flows.json",
+        "reconciliation.csv",
+        "rank_discrepancies.csv",
+        "program_merges.csv",
+        "simulation_warnings.txt",
+        "analysis.json",
+        "report.html",
+        "analysis.xlsx",
+        "summary.md",
+        "verdict.pdf",
+        "seat_heatmap.csv",
+        "seat_heatmap.html",
+        program_dirs::PROGRAMS_DIR,
+        // Per-program files of earlier versions
         "filtered_eager",
         "admitted_lists",
+        "charts",
     ];
     
     for item in &items_to_clean {
@@ -923,14 +831,14 @@ fn clean_output_directory(output_dir: &str) -> Result<()> {
         if item_path.exists() {
             if item_path.is_file() {
                 fs::remove_file(&item_path)?;
-                println!("   🗑️  Removed file: {}", item);
+                debug!("{}", t!("run.removed_file", item));
             } else if item_path.is_dir() {
                 fs::remove_dir_all(&item_path)?;
-                println!("   🗑️  Removed directory: {}", item);
+                debug!("{}", t!("run.removed_directory", item));
             }
         }
     }
     
-    println!("   ✅ Output directory cleaned");
+    debug!("{}", t!("run.cleaned"));
     Ok(())
 }
@@ -0,0 +1,58 @@
+use crate::analyzer::TargetOutcome;
+use crate::atomic_file;
+use crate::campaign::CampaignStatus;
+use crate::models::FundingSource;
+use crate::templates::ReportTemplates;
+use anyhow::Result;
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Serialize)]
+struct FundingGroup<'a> {
+    funding_source: &'a FundingSource,
+    outcomes: Vec<&'a TargetOutcome>,
+}
+
+#[derive(Serialize)]
+struct MarkdownSummary<'a> {
+    target_snils: &'a str,
+    campaign: Option<String>,
+    funding_groups: Vec<FundingGroup<'a>>,
+}
+
+/// Generate a compact summary.md for pasting into chats and forums
+pub fn generate_markdown_summary(
+    templates: &ReportTemplates,
+    target_snils: &str,
+    campaign: Option<&CampaignStatus>,
+    target_outcomes: &[TargetOutcome],
+    output_dir: &str,
+) -> Result<()> {
+    // Group rows by funding source so budget and commercial tables stay short
+    let mut funding_groups: Vec<FundingGroup> = Vec::new();
+    for outcome in target_outcomes.iter().filter(|outcome| outcome.status != "Not_Applied") {
+        match funding_groups.iter_mut().find(|group| *group.funding_source == outcome.funding_source) {
+            Some(group) => group.outcomes.push(outcome),
+            None => funding_groups.push(FundingGroup {
+                funding_source: &outcome.funding_source,
+                outcomes: vec![outcome],
+            }),
+        }
+    }
+    // Budget first, then targeted, commercial and other funding
+    funding_groups.sort_by(|a, b| a.funding_source.cmp(b.funding_source));
+    for group in &mut funding_groups {
+        group.outcomes.sort_by_key(|outcome| outcome.priority.unwrap_or(u32::MAX));
+    }
+
+    let content = templates.render(
+        "summary.md.tera",
+        &MarkdownSummary {
+            target_snils,
+            campaign: campaign.map(CampaignStatus::describe),
+            funding_groups,
+        },
+    )?;
+    atomic_file::write(Path::new(output_dir).join("summary.md"), content)?;
+    Ok(())
+}
@@ -1,13 +1,314 @@
+use crate::error::ConfigError;
+use crate::i18n::Language;
+use crate::scraper::{DEFAULT_MAX_RESPONSE_MB, DEFAULT_STREAMING_THRESHOLD_MB};
 use serde::{Deserialize, Serialize};
 
+/// Settings read from config.toml
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub target_snils: String,
+    // Campaign rules filling in the simulation settings this file leaves out: "spo_default",
+    // "vo_2024" or presets/NAME.toml next to the file
+    pub preset: Option<String>,
     // Data source configuration
     pub data_source_mode: DataSourceMode,
     pub data_directory: Option<String>,
     pub internet_urls: Option<Vec<String>>,
+    // Pages linking to the lists; the links they have now are fetched along with internet_urls
+    pub index_pages: Option<Vec<IndexPageOptions>>,
     pub output_directory: Option<String>,
+    // Programs whose outcomes for the target are reported, all if unset; the simulation always covers every program
+    pub programs_of_interest: Option<Vec<String>>,
+    // Lists simulated and reported: "all" (default), "budget" (with targeted and quota places) or "commercial"
+    pub funding: Option<FundingScope>,
+    // Other names programs are published under -> canonical name; such lists are merged into the canonical program
+    pub program_aliases: Option<std::collections::BTreeMap<String, String>>,
+    // Applicants known to have withdrawn or enrolled elsewhere, left out of the simulation
+    pub excluded_applicants: Option<Vec<String>>,
+    // Program key -> places to simulate instead of those the list shows, e.g. seats announced but not yet published
+    pub seat_overrides: Option<std::collections::BTreeMap<String, u32>>,
+    // Score used when an applicant's lists disagree: "keep" (default), "highest", "lowest" or "most_common"
+    pub score_conflicts: Option<ScoreResolution>,
+    // How applicants admitted without entrance tests are recognized: "markers", "position", "both" (default) or "off"
+    pub privileged_detection: Option<PrivilegedDetection>,
+    // Extra lowercase markers of privileged applicants in the score, marks, test or achievements columns
+    pub privileged_markers: Option<Vec<String>>,
+    // Add individual achievement points to the average score in the simulation
+    pub count_achievements: Option<bool>,
+    // Funding types in the order their places are filled in filtered_eager.csv, as the lists word them;
+    // those not listed follow budget, targeted, special quota, commercial and other funding
+    pub target_funding_types: Option<Vec<String>>,
+    // Score each program ranks by, the first rule matching its name wins; the average score otherwise
+    pub scoring: Option<Vec<ScoringRule>>,
+    // Enroll only applicants with original documents; consent-only ones count in a projected scenario
+    pub originals_only: Option<bool>,
+    // Applicants sharing the score of a program's last seat: "strict" (default), "admit_all" or "secondary"
+    pub cutoff_ties: Option<CutoffTies>,
+    // Criteria ordering equal scores with cutoff_ties = "secondary", the first deciding
+    pub tie_breakers: Option<Vec<TieBreaker>>,
+    // Leave out applications submitted later, for lists that show when; e.g. the lists as of a deadline
+    pub submitted_until: Option<chrono::NaiveDateTime>,
+    // Consents given fewer days than this before the list was read do not count yet, for lists
+    // that show when; 0 counts every consent
+    pub recent_consent_days: Option<u32>,
+    // What a failed source does to the run: "continue" (default), "fail_fast" or "require_sources"
+    pub failure_policy: Option<FailurePolicy>,
+    // Sources that have to load with failure_policy = "require_sources"
+    pub required_sources: Option<usize>,
+    // Pages larger than this many megabytes are parsed as a stream to keep memory bounded
+    pub streaming_threshold_mb: Option<u64>,
+    // Responses larger than this many megabytes once decompressed are refused, 0 for no limit
+    pub max_response_mb: Option<u64>,
+    // How list tables spread an applicant over rows: "off" (default), "pairs" or "continuation"
+    pub row_merge: Option<RowMerge>,
+    // Skip analysis and writing when every source is unchanged since the last completed run
+    pub skip_unchanged: Option<bool>,
+    // Ask servers whether a page changed since the last fetch and reuse its lists when it did not
+    pub conditional_requests: Option<bool>,
+    // Reuse the simulation of the last runs over the same lists and simulation settings
+    pub simulation_cache: Option<bool>,
+    // With a single program of interest, `analyze` estimates it alone and models the other
+    // programs as chances of its applicants leaving, instead of simulating everything
+    pub single_program_estimate: Option<bool>,
+    // Number of timestamped run directories to keep, 0 writes into output_directory directly
+    pub output_retention: Option<usize>,
+    // Report formats to generate, defaults to csv only
+    pub output_formats: Option<Vec<OutputFormat>>,
+    // Directory with custom report templates overriding the built-in ones
+    pub templates_directory: Option<String>,
+    // TrueType font with Cyrillic letters for verdict.pdf; common system fonts are tried if unset
+    pub pdf_font: Option<String>,
+    // Language of console output and reports
+    pub language: Option<Language>,
+    // Replace SNILS with pseudonymous IDs in all outputs for sharing
+    pub anonymize: Option<bool>,
+    // Salt for pseudonymous IDs, keeps them stable between runs; random per run if unset
+    pub anonymize_salt: Option<String>,
+    // Write structured JSON log to this file
+    pub log_file: Option<String>,
+    // Colorize console tables, disable for piping into files
+    pub console_color: Option<bool>,
+    // CSV dialect for spreadsheet compatibility
+    pub csv: Option<CsvOptions>,
+    // Telegram bot notified about significant changes in watch mode
+    pub telegram: Option<TelegramOptions>,
+    // SMTP server for the run summary and watch mode alerts by email
+    pub email: Option<EmailOptions>,
+    // URL receiving analysis results and change events as JSON POSTs
+    pub webhook: Option<WebhookOptions>,
+    // Run times of watch mode instead of a fixed interval
+    pub schedule: Option<ScheduleOptions>,
+    // Linking applicants listed under different identifiers in different lists
+    pub identity: Option<IdentityOptions>,
+    // Key dates of the admission campaign, shown in reports and switching to locked mode
+    pub campaign: Option<CampaignOptions>,
+    // Which applicants competing with the target competitors.csv lists
+    pub competitors: Option<CompetitorOptions>,
+    // Columns of the generated CSVs
+    pub reports: Option<ReportOptions>,
+    // Institutions publishing the lists, linked to their sources by directory or URL
+    pub institutions: Option<Vec<InstitutionOptions>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvOptions {
+    // Field delimiter, a single character like "," or ";"
+    pub delimiter: Option<String>,
+    // Write decimal numbers with a comma instead of a dot
+    pub decimal_comma: Option<bool>,
+    // Output encoding label, e.g. "utf-8" or "windows-1251"
+    pub encoding: Option<String>,
+    // Prepend a UTF-8 byte order mark
+    pub bom: Option<bool>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: Some(",".to_string()),
+            decimal_comma: Some(false),
+            encoding: Some("utf-8".to_string()),
+            bom: Some(false),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelegramOptions {
+    // Token from @BotFather
+    pub bot_token: String,
+    // Chat to send messages to: user id, group id or @channel
+    pub chat_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailOptions {
+    pub smtp_server: String,
+    // Defaults to 587 for "starttls", 465 for "tls" and 25 for "none"
+    pub smtp_port: Option<u16>,
+    // Connection security: "starttls" (default), "tls" or "none"
+    pub security: Option<String>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookOptions {
+    pub url: String,
+    // Extra request headers, e.g. Authorization
+    pub headers: Option<std::collections::BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleOptions {
+    // Cron expression "minute hour day month weekday", e.g. "0 8-20 * * *"
+    pub cron: String,
+    // IANA time zone of the cron expression, e.g. "Europe/Moscow"; system time zone if unset
+    pub timezone: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdentityOptions {
+    // File with the identifiers of one applicant per line, comma separated; the first is reported
+    pub cross_reference: Option<String>,
+    // Link a masked SNILS like "***-***-600 41" to the only full SNILS in the lists that fits it
+    pub masked_snils: Option<bool>,
+    // Match a target not listed as given against masked identifiers or as a fragment of one
+    pub partial_target: Option<bool>,
+}
+
+/// A page whose links lead to program lists, e.g. the institution's "admission lists" page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexPageOptions {
+    pub url: String,
+    // Fragments of the URLs of the list links, case-insensitive; other links are ignored
+    pub link_patterns: Vec<String>,
+}
+
+impl IndexPageOptions {
+    /// The link at `url` leads to a program list
+    pub fn matches_link(&self, url: &str) -> bool {
+        let url = url.to_lowercase();
+        self.link_patterns.iter().any(|pattern| url.contains(&pattern.to_lowercase()))
+    }
+}
+
+/// An institution publishing admission lists and where its lists come from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstitutionOptions {
+    pub name: String,
+    pub city: Option<String>,
+    // Home page, shown in reports
+    pub site: Option<String>,
+    // Fragments of the URLs of its lists, case-insensitive; `site` is used if unset
+    pub url_patterns: Option<Vec<String>>,
+    // Subdirectory of data_directory with its pages, `name` if unset
+    pub directory: Option<String>,
+    // How its lists spread an applicant over rows, row_merge of the configuration if unset
+    pub row_merge: Option<RowMerge>,
+}
+
+impl InstitutionOptions {
+    /// The list at `url` is published by this institution
+    pub fn matches_url(&self, url: &str) -> bool {
+        let url = url.to_lowercase();
+        let site = self.site.as_deref().map(|site| {
+            site.trim_start_matches("https://").trim_start_matches("http://").trim_end_matches('/').to_string()
+        });
+        match &self.url_patterns {
+            Some(patterns) => patterns.iter().any(|pattern| url.contains(&pattern.to_lowercase())),
+            None => site.is_some_and(|site| !site.is_empty() && url.contains(&site.to_lowercase())),
+        }
+    }
+
+    /// Name of its subdirectory of data_directory
+    pub fn directory(&self) -> &str {
+        self.directory.as_deref().unwrap_or(&self.name)
+    }
+
+    /// City and site for report headings, empty if neither is configured
+    pub fn describe(&self) -> String {
+        [self.city.as_deref(), self.site.as_deref()].into_iter().flatten().collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// Score strategy of the programs whose name contains `programs`, and how its score is
+/// converted to a common scale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoringRule {
+    // Fragment of the program name, case-insensitive; "" matches every program
+    pub programs: String,
+    #[serde(default)]
+    pub strategy: ScoreStrategyKind,
+    // Weights of the average score and of the subject sum with "weighted", 1 if unset
+    pub average_weight: Option<f64>,
+    pub subjects_weight: Option<f64>,
+    // Linear conversion from the range scale_from to scale_to, e.g. [0, 5] to [0, 100]
+    pub scale_from: Option<[f64; 2]>,
+    pub scale_to: Option<[f64; 2]>,
+    // Decimal places of the converted score, rounded as `rounding` says (nearest if unset)
+    pub round_to: Option<u32>,
+    pub rounding: Option<Rounding>,
+    // Lowest and highest converted score
+    pub floor: Option<f64>,
+    pub cap: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoreStrategyKind {
+    // Average certificate score as printed
+    #[default]
+    #[serde(rename = "average")]
+    Average,
+    // Sum of the subject or entrance test scores
+    #[serde(rename = "subject_sum")]
+    SubjectSum,
+    // average_weight * average score + subjects_weight * subject sum
+    #[serde(rename = "weighted")]
+    Weighted,
+}
+
+/// How `round_to` rounds a converted score
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    // Half away from zero
+    #[default]
+    Nearest,
+    Down,
+    Up,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CampaignOptions {
+    // Last day to hand in original documents; after it the lists are locked
+    pub originals_deadline: Option<chrono::NaiveDate>,
+    // Day the enrollment order is published
+    pub enrollment_order: Option<chrono::NaiveDate>,
+    // Last days of the enrollment waves
+    pub waves: Option<Vec<chrono::NaiveDate>>,
+    // Day unfilled targeted and special quota places go to the budget competition
+    pub quota_transfer: Option<chrono::NaiveDate>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CompetitorOptions {
+    // Only applicants taking part in the simulation: original handed in, or consent without originals_only
+    pub eager_only: Option<bool>,
+    // Only the target's programs whose name contains one of these, case-insensitive
+    pub programs: Option<Vec<String>>,
+    // Leave out applicants the simulation seats in another program
+    pub staying_only: Option<bool>,
+    // Applicants boundary.csv lists on each side of a program's projected cutoff
+    pub boundary: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportOptions {
+    // CSV file name, e.g. "all_applicants.csv" or "admitted.csv" -> its columns in order, by header
+    pub columns: Option<std::collections::BTreeMap<String, Vec<String>>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,94 +321,791 @@ pub enum DataSourceMode {
     Both,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputFormat {
+    #[serde(rename = "csv")]
+    Csv,
+    #[serde(rename = "json")]
+    Json,
+    #[serde(rename = "html")]
+    Html,
+    #[serde(rename = "xlsx")]
+    Xlsx,
+    #[serde(rename = "markdown")]
+    Markdown,
+    #[serde(rename = "sqlite")]
+    Sqlite,
+    #[serde(rename = "charts")]
+    Charts,
+    #[serde(rename = "pdf")]
+    Pdf,
+    #[serde(rename = "heatmap")]
+    Heatmap,
+}
+
+/// How a run treats sources that fail to load
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FailurePolicy {
+    // Skip failed sources; the run fails only if none loaded
+    #[default]
+    #[serde(rename = "continue")]
+    Continue,
+    // Abort the run at the first failed source
+    #[serde(rename = "fail_fast")]
+    FailFast,
+    // Skip failed sources, but fail the run if fewer than `required_sources` loaded
+    #[serde(rename = "require_sources")]
+    RequireSources,
+}
+
+/// Which lists take part in the analysis, by how their places are paid for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FundingScope {
+    #[default]
+    #[serde(rename = "all")]
+    All,
+    // Budget, targeted and special quota places
+    #[serde(rename = "budget")]
+    Budget,
+    #[serde(rename = "commercial")]
+    Commercial,
+}
+
+impl FundingScope {
+    /// Name as written in config.toml and on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Budget => "budget",
+            Self::Commercial => "commercial",
+        }
+    }
+
+    /// Whether lists with `funding` take part; those of unrecognized funding only with all lists
+    pub fn includes(self, funding: &FundingSource) -> bool {
+        match self {
+            Self::All => true,
+            Self::Budget => matches!(funding, FundingSource::Budget | FundingSource::Targeted | FundingSource::SpecialQuota),
+            Self::Commercial => *funding == FundingSource::Commercial,
+        }
+    }
+}
+
+/// How the rows of a list table make up applicants, for portals spreading one over several rows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RowMerge {
+    // Every row is an applicant
+    #[default]
+    #[serde(rename = "off")]
+    Off,
+    // Every applicant takes two rows, the second e.g. with the subject scores
+    #[serde(rename = "pairs")]
+    Pairs,
+    // Rows without a number of their own, that are no section or subtotal, belong to the applicant above
+    #[serde(rename = "continuation")]
+    Continuation,
+}
+
+/// Who gets the last seats when applicants share the score of the last seat
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CutoffTies {
+    // Seats are filled in list rank order and never exceeded
+    #[default]
+    #[serde(rename = "strict")]
+    Strict,
+    // Everyone with the score of the last seat is admitted, even beyond the places
+    #[serde(rename = "admit_all")]
+    AdmitAll,
+    // Equal scores are ordered by `tie_breakers`, then by list rank
+    #[serde(rename = "secondary")]
+    Secondary,
+}
+
+/// Secondary criterion for applicants with equal scores, higher wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TieBreaker {
+    // Sum of the subject or entrance test scores
+    #[serde(rename = "subject_sum")]
+    SubjectSum,
+    // Individual achievement points
+    #[serde(rename = "achievements")]
+    Achievements,
+    // Original documents handed in
+    #[serde(rename = "original")]
+    Original,
+    // Earlier submission of the application, for lists that show when
+    #[serde(rename = "submitted")]
+    Submitted,
+}
+
+/// How to settle a score that differs between an applicant's lists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoreResolution {
+    // Each list keeps its own score
+    #[default]
+    #[serde(rename = "keep")]
+    Keep,
+    #[serde(rename = "highest")]
+    Highest,
+    #[serde(rename = "lowest")]
+    Lowest,
+    // The score most lists show, the highest of those on a tie
+    #[serde(rename = "most_common")]
+    MostCommon,
+}
+
+/// How privileged applicants, seated ahead of the competition, are recognized in the lists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrivilegedDetection {
+    // A marker such as "БВИ" in the row
+    #[serde(rename = "markers")]
+    Markers,
+    // Listed above the best score with a lower one
+    #[serde(rename = "position")]
+    Position,
+    #[default]
+    #[serde(rename = "both")]
+    Both,
+    #[serde(rename = "off")]
+    Off,
+}
+
+impl OutputFormat {
+    /// Name as written in config.toml and on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Csv => "csv",
+            OutputFormat::Json => "json",
+            OutputFormat::Html => "html",
+            OutputFormat::Xlsx => "xlsx",
+            OutputFormat::Markdown => "markdown",
+            OutputFormat::Sqlite => "sqlite",
+            OutputFormat::Charts => "charts",
+            OutputFormat::Pdf => "pdf",
+            OutputFormat::Heatmap => "heatmap",
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            "html" => Ok(OutputFormat::Html),
+            "xlsx" => Ok(OutputFormat::Xlsx),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            "sqlite" => Ok(OutputFormat::Sqlite),
+            "charts" => Ok(OutputFormat::Charts),
+            "pdf" => Ok(OutputFormat::Pdf),
+            "heatmap" => Ok(OutputFormat::Heatmap),
+            other => Err(ConfigError::Invalid(format!("Unknown output format: {}", other))),
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             target_snils: "".to_string(),
+            preset: None,
             data_source_mode: DataSourceMode::Internet,
             data_directory: Some("data-source".to_string()),
             internet_urls: Some(vec![
                 "https://example.com/admission-list1".to_string(),
                 "https://example.com/admission-list2".to_string(),
             ]),
+            index_pages: None,
             output_directory: Some("output".to_string()),
+            programs_of_interest: None,
+            funding: Some(FundingScope::All),
+            program_aliases: None,
+            excluded_applicants: None,
+            seat_overrides: None,
+            score_conflicts: Some(ScoreResolution::Keep),
+            privileged_detection: Some(PrivilegedDetection::Both),
+            privileged_markers: None,
+            count_achievements: Some(false),
+            scoring: None,
+            originals_only: Some(false),
+            cutoff_ties: Some(CutoffTies::Strict),
+            tie_breakers: None,
+            target_funding_types: None,
+            submitted_until: None,
+            recent_consent_days: Some(0),
+            failure_policy: Some(FailurePolicy::Continue),
+            required_sources: None,
+            streaming_threshold_mb: Some(DEFAULT_STREAMING_THRESHOLD_MB),
+            max_response_mb: Some(DEFAULT_MAX_RESPONSE_MB),
+            row_merge: Some(RowMerge::Off),
+            skip_unchanged: Some(false),
+            conditional_requests: Some(true),
+            simulation_cache: Some(true),
+            single_program_estimate: Some(false),
+            output_retention: Some(10),
+            output_formats: Some(vec![OutputFormat::Csv]),
+            templates_directory: Some("templates".to_string()),
+            pdf_font: None,
+            language: Some(Language::En),
+            anonymize: Some(false),
+            anonymize_salt: None,
+            log_file: None,
+            console_color: Some(true),
+            csv: Some(CsvOptions::default()),
+            telegram: None,
+            email: None,
+            webhook: None,
+            schedule: None,
+            identity: None,
+            campaign: None,
+            competitors: None,
+            reports: None,
+            institutions: None,
         }
     }
 }
 
 impl Config {
-    pub fn load_from_file(file_path: &str) -> anyhow::Result<Self> {
-        let content = std::fs::read_to_string(file_path)?;
-        let config: Config = toml::from_str(&content)?;
-        Ok(config)
+    pub fn load_from_file(file_path: &str) -> Result<Self, ConfigError> {
+        Self::load(Some(file_path), None, &[])
+    }
+
+    /// Layered configuration: the file (built-in defaults without one), the profile,
+    /// then `(key, value)` overrides from the environment and command line in order;
+    /// the preset they name fills in what all of them leave out
+    pub fn load(
+        file_path: Option<&str>,
+        profile: Option<&str>,
+        overrides: &[(String, String)],
+    ) -> Result<Self, ConfigError> {
+        let mut table = match file_path {
+            Some(file_path) => Self::read_table(file_path, profile)?,
+            None if profile.is_some() => {
+                return Err(ConfigError::Invalid("Profiles need a configuration file".to_string()))
+            }
+            None => toml::Table::new(),
+        };
+        for (key, value) in overrides {
+            crate::overrides::apply(&mut table, key, value)?;
+        }
+        crate::presets::apply(&mut table, file_path.map(std::path::Path::new))?;
+        if file_path.is_none() {
+            let mut defaults = toml::Table::try_from(Config::default())?;
+            merge_tables(&mut defaults, table);
+            table = defaults;
+        }
+        toml::Value::Table(table).try_into().map_err(ConfigError::Settings)
+    }
+
+    /// Configuration given as TOML text, with the built-in defaults for keys it leaves out
+    pub fn parse(content: &str) -> Result<Self, ConfigError> {
+        let mut table = toml::Table::try_from(Config::default())?;
+        let mut overlay = toml::from_str(content).map_err(|cause| ConfigError::Syntax { path: "configuration".to_string(), cause })?;
+        crate::presets::apply(&mut overlay, None)?;
+        merge_tables(&mut table, overlay);
+        toml::Value::Table(table).try_into().map_err(ConfigError::Settings)
+    }
+
+    /// This configuration with `(key, value)` overrides applied as [`Config::load`] applies them;
+    /// a `preset` override replaces the settings of the current preset with its own
+    pub fn with_overrides(&self, overrides: &[(String, String)]) -> Result<Self, ConfigError> {
+        let mut table = toml::Table::try_from(self)?;
+        for (key, value) in overrides {
+            if key == "preset" {
+                crate::presets::overlay(&mut table, value, None)?;
+            }
+            crate::overrides::apply(&mut table, key, value)?;
+        }
+        toml::Value::Table(table).try_into().map_err(ConfigError::Settings)
     }
 
-    pub fn save_to_file(&self, file_path: &str) -> anyhow::Result<()> {
+    /// The file with a named profile applied on top of its top-level settings
+    ///
+    /// A profile is a `[profiles.NAME]` table in the file or `profiles/NAME.toml` next to it
+    /// and overrides only the keys it sets. Unless it sets `output_directory`, its output
+    /// goes to a `NAME` subdirectory so profiles never share runs or history.
+    fn read_table(file_path: &str, profile: Option<&str>) -> Result<toml::Table, ConfigError> {
+        let mut table = read_toml(std::path::Path::new(file_path))?;
+        let profiles = table.remove("profiles");
+
+        if let Some(name) = profile {
+            let overlay = match profiles.as_ref().and_then(|profiles| profiles.get(name)) {
+                Some(toml::Value::Table(overlay)) => overlay.clone(),
+                Some(_) => return Err(ConfigError::Invalid(format!("Profile {} must be a table", name))),
+                None => {
+                    let profile_file = Self::profiles_directory(file_path).join(format!("{}.toml", name));
+                    if !profile_file.exists() {
+                        return Err(ConfigError::Invalid(format!(
+                            "Unknown profile {}, available: {}",
+                            name,
+                            Self::profile_names(file_path)?.join(", ")
+                        )));
+                    }
+                    read_toml(&profile_file)?
+                }
+            };
+
+            if !overlay.contains_key("output_directory") {
+                let output_root = table.get("output_directory").and_then(|value| value.as_str()).unwrap_or("output");
+                let output_directory = std::path::Path::new(output_root).join(name);
+                table.insert(
+                    "output_directory".to_string(),
+                    toml::Value::String(output_directory.to_string_lossy().to_string()),
+                );
+            }
+            merge_tables(&mut table, overlay);
+        }
+        Ok(table)
+    }
+
+    /// Profiles defined in the file and in the `profiles` directory next to it
+    pub fn profile_names(file_path: &str) -> Result<Vec<String>, ConfigError> {
+        let table = read_toml(std::path::Path::new(file_path))?;
+        let mut names: Vec<String> = table
+            .get("profiles")
+            .and_then(|profiles| profiles.as_table())
+            .map(|profiles| profiles.keys().cloned().collect())
+            .unwrap_or_default();
+
+        if let Ok(entries) = std::fs::read_dir(Self::profiles_directory(file_path)) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_some_and(|extension| extension == "toml") {
+                    if let Some(stem) = path.file_stem() {
+                        names.push(stem.to_string_lossy().to_string());
+                    }
+                }
+            }
+        }
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    fn profiles_directory(file_path: &str) -> std::path::PathBuf {
+        std::path::Path::new(file_path)
+            .parent()
+            .unwrap_or(std::path::Path::new("."))
+            .join("profiles")
+    }
+
+    pub fn save_to_file(&self, file_path: &str) -> Result<(), ConfigError> {
         let content = toml::to_string_pretty(self)?;
-        std::fs::write(file_path, content)?;
-        Ok(())
+        std::fs::write(file_path, content).map_err(|cause| ConfigError::Write {
+            path: file_path.to_string(),
+            cause,
+        })
     }
 }
 
+fn read_toml(path: &std::path::Path) -> Result<toml::Table, ConfigError> {
+    let path_name = path.display().to_string();
+    let content = std::fs::read_to_string(path).map_err(|cause| ConfigError::Read {
+        path: path_name.clone(),
+        cause,
+    })?;
+    toml::from_str(&content).map_err(|cause| ConfigError::Syntax { path: path_name, cause })
+}
+
+/// Override keys of `base` with `overlay`; nested tables like `[csv]` are merged key by key
+pub(crate) fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table)
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// One row of a program's applicant list
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StudentRecord {
     pub rank: u32,
     pub snils: String,
     pub priority: u32,
+    // Optional fields may be left out of third-party snapshots
+    #[serde(default)]
     pub consent: String,
+    #[serde(default)]
     pub document_type: String,
+    // Score as printed in the list, kept for reports
     pub average_score: String,
+    // `average_score` parsed once by the scraper or snapshot loader, `None` if it is not a number;
+    // includes `bonus` when achievements are counted
+    #[serde(skip)]
+    pub score: Option<f64>,
+    // Individual achievement points as printed, empty if the list has no such column
+    #[serde(default)]
+    pub achievements: String,
+    // Part of `score` that comes from `achievements`, 0 unless they are counted
+    #[serde(skip)]
+    pub bonus: f64,
+    #[serde(default)]
     pub subject_scores: String,
+    #[serde(default)]
     pub psychological_test: String,
+    #[serde(default)]
     pub program_name: String,
-    pub funding_source: String,
-    pub study_form: String,
+    // Institution of the list, empty with a single one; each institution admits separately
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub institution: String,
+    #[serde(default)]
+    pub funding_source: FundingSource,
+    #[serde(default)]
+    pub study_form: StudyForm,
+    #[serde(default)]
     pub available_places: u32,
+    // Application marked as withdrawn in the list, kept for the snapshot but not simulated
+    #[serde(default)]
+    pub withdrawn: bool,
+    // Admitted without entrance tests or by another special right, seated ahead of the competition
+    #[serde(default)]
+    pub privileged: bool,
+    // Rank by score within the competition, set by `ranks::recompute`
+    #[serde(skip)]
+    pub computed_rank: Option<u32>,
+    // Page, table and row the record was read from, None for records made up elsewhere
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    // When the application was submitted, for lists that show it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submitted_at: Option<chrono::NaiveDateTime>,
+    // When the consent and the original documents were handed in, for lists that show it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consent_at: Option<chrono::NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub original_at: Option<chrono::NaiveDateTime>,
+    // Consent given too recently to count, set by `sources::discount_recent_consents`
+    #[serde(skip)]
+    pub recent_consent: bool,
+    // Number of the application at the institution, for lists that show it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where a record was read from, to trace a bad row back to the page that produced it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Provenance {
+    // File path or URL of the page
+    pub source: String,
+    // 1-based position of the list's table on the page
+    pub table: usize,
+    // 1-based row in the table's body, counting skipped rows
+    pub row: usize,
+    pub scraped_at: chrono::DateTime<chrono::Local>,
+}
+
+impl std::fmt::Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}, table {}, row {}", self.source, self.table, self.row)
+    }
+}
+
+/// Who pays for the places of a competition
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum FundingSource {
+    Budget,
+    Targeted,
+    // Separate quota of budget places for applicants with special rights
+    SpecialQuota,
+    Commercial,
+    // Wording that matches no alias, kept as printed
+    Other(String),
+}
+
+// Lowercase fragments of the wordings used by institutions, checked in order:
+// "Целевое обучение за счёт бюджета" is targeted, "Внебюджетное" is commercial
+const FUNDING_ALIASES: &[(&str, FundingSource)] = &[
+    ("целев", FundingSource::Targeted),
+    ("target", FundingSource::Targeted),
+    ("квот", FundingSource::SpecialQuota),
+    ("quota", FundingSource::SpecialQuota),
+    ("внебюджет", FundingSource::Commercial),
+    ("коммерч", FundingSource::Commercial),
+    ("платн", FundingSource::Commercial),
+    ("договор", FundingSource::Commercial),
+    ("commercial", FundingSource::Commercial),
+    ("paid", FundingSource::Commercial),
+    ("бюджет", FundingSource::Budget),
+    ("budget", FundingSource::Budget),
+];
+
+impl FundingSource {
+    /// Recognize the funding from its wording in a list
+    pub fn parse(text: &str) -> Self {
+        match_alias(FUNDING_ALIASES, text).unwrap_or_else(|| Self::Other(text.trim().to_string()))
+    }
+
+    /// Name written in reports, snapshots and the history database
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Budget => "Бюджетное финансирование",
+            Self::Targeted => "Целевое обучение",
+            Self::SpecialQuota => "Особая квота",
+            Self::Commercial => "Коммерческое финансирование",
+            Self::Other(text) => text,
+        }
+    }
+
+    /// Places of a quota that go to the budget competition of the program when left unfilled
+    pub fn is_quota(&self) -> bool {
+        matches!(self, Self::Targeted | Self::SpecialQuota)
+    }
+
+    /// Position in `order`, then the default order: budget, targeted, special quota, commercial, other
+    pub fn order_key(&self, order: &[FundingSource]) -> (usize, &Self) {
+        (order.iter().position(|funding| funding == self).unwrap_or(order.len()), self)
+    }
+
+    /// Not given, as in records of third-party snapshots that rely on the program header
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Other(text) if text.is_empty())
+    }
+}
+
+/// Study form of a competition
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum StudyForm {
+    FullTime,
+    PartTime,
+    Extramural,
+    // Wording that matches no alias, kept as printed
+    Other(String),
+}
+
+// "Очно-заочная" contains both "очн" and "заочн", so it is checked first
+const STUDY_FORM_ALIASES: &[(&str, StudyForm)] = &[
+    ("очно-заочн", StudyForm::PartTime),
+    ("вечерн", StudyForm::PartTime),
+    ("part-time", StudyForm::PartTime),
+    ("заочн", StudyForm::Extramural),
+    ("extramural", StudyForm::Extramural),
+    ("очн", StudyForm::FullTime),
+    ("full-time", StudyForm::FullTime),
+];
+
+impl StudyForm {
+    /// Recognize the study form from its wording in a list
+    pub fn parse(text: &str) -> Self {
+        match_alias(STUDY_FORM_ALIASES, text).unwrap_or_else(|| Self::Other(text.trim().to_string()))
+    }
+
+    /// Name written in reports, snapshots and the history database
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::FullTime => "Очная",
+            Self::PartTime => "Очно-заочная",
+            Self::Extramural => "Заочная",
+            Self::Other(text) => text,
+        }
+    }
+
+    /// Not given, as in records of third-party snapshots that rely on the program header
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Other(text) if text.is_empty())
+    }
+}
+
+fn match_alias<T: Clone>(aliases: &[(&str, T)], text: &str) -> Option<T> {
+    let text = text.to_lowercase();
+    aliases
+        .iter()
+        .find(|(fragment, _)| text.contains(fragment))
+        .map(|(_, value)| value.clone())
+}
+
+/// Funding and study form are written by name and parsed back through the alias tables
+macro_rules! string_serde {
+    ($($type:ty),+) => {$(
+        impl Default for $type {
+            fn default() -> Self {
+                Self::Other(String::new())
+            }
+        }
+
+        impl std::fmt::Display for $type {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl Serialize for $type {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Ok(Self::parse(&String::deserialize(deserializer)?))
+            }
+        }
+    )+};
+}
+
+string_serde!(FundingSource, StudyForm);
+
+/// Identity of one competition: a program with one funding source and study form
+///
+/// Written as `name_funding_form` (`name_funding` without a study form) in reports,
+/// URLs and the history database.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ProgramKey {
+    pub name: String,
+    pub funding: FundingSource,
+    pub study_form: StudyForm,
+}
+
+impl ProgramKey {
+    /// Key of the competition `record` takes part in, `program_name` being its list's program
+    pub fn of(program_name: &str, record: &StudentRecord) -> Self {
+        Self {
+            name: program_name.to_string(),
+            funding: record.funding_source.clone(),
+            study_form: record.study_form.clone(),
+        }
+    }
+
+    /// Whether `record` from the list of `program_name` belongs to this competition
+    pub fn matches(&self, program_name: &str, record: &StudentRecord) -> bool {
+        self.name == program_name && self.funding == record.funding_source && self.study_form == record.study_form
+    }
+
+    /// Relative directory `program/funding/form` (`program/funding` without a study form)
+    /// with characters unsafe in file names replaced
+    pub fn directory(&self) -> std::path::PathBuf {
+        let mut directory = std::path::PathBuf::from(path_component(&self.name));
+        directory.push(path_component(self.funding.as_str()));
+        if !self.study_form.is_empty() {
+            directory.push(path_component(self.study_form.as_str()));
+        }
+        directory
+    }
+}
+
+/// Longest directory name [`ProgramKey::directory`] makes of a program name, in characters
+const PATH_COMPONENT_LENGTH: usize = 80;
+
+/// `text` as one directory name: separators, reserved characters and spaces become `_`
+fn path_component(text: &str) -> String {
+    let component: String = text
+        .trim()
+        .chars()
+        .map(|c| if c.is_control() || c.is_whitespace() || r#"/\:*?"<>|"#.contains(c) { '_' } else { c })
+        .take(PATH_COMPONENT_LENGTH)
+        .collect();
+    let component = component.trim_matches('.');
+    if component.is_empty() { "_".to_string() } else { component.to_string() }
+}
+
+impl std::fmt::Display for ProgramKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}_{}", self.name, self.funding)?;
+        if !self.study_form.is_empty() {
+            write!(f, "_{}", self.study_form)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for ProgramKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ApplicantApplication {
     pub snils: String,
-    pub program_key: String, // program_name + funding_source for uniqueness
+    pub program_key: ProgramKey,
     pub program_name: String,
-    pub funding_source: String,
+    pub funding_source: FundingSource,
     pub priority: u32,
     pub rank: u32,
-    pub score: f64,
+    pub score: f64, // score by the program's own strategy, the program seats by it
+    pub tie_breaks: Vec<f64>, // values of the tie breakers in use, higher first
+    pub privileged: bool, // seated ahead of this program's competition, whatever the score
     pub has_consent: bool,
     pub has_original_document: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EagerApplicant {
     pub snils: String,
+    pub institution: String, // institution of all the applications, simulated apart from the others
     pub applications: Vec<ApplicantApplication>, // sorted by priority
-    pub average_rank: f64, // average rank across all applications
-    pub score: f64, // average score across all applications
 }
 
+/// Program header of an applicant list
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProgramInfo {
     pub name: String,
-    pub funding_source: String,
-    pub study_form: String,
+    // Institution publishing the list, empty with a single one
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub institution: String,
+    pub funding_source: FundingSource,
+    #[serde(default)]
+    pub study_form: StudyForm,
     pub available_places: u32,
+    // Header cells of its table as published, to notice layout changes; empty if not parsed from a page
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
 }
 
-impl StudentRecord {
-    pub fn get_numeric_score(&self) -> Option<f64> {
-        self.average_score
-            .replace(',', ".")
-            .parse::<f64>()
-            .ok()
+impl ProgramInfo {
+    /// Attach the list and its records to `institution`
+    ///
+    /// The program name gets the institution appended, so the same program of two
+    /// institutions stays two competitions.
+    pub fn set_institution(&mut self, records: &mut [StudentRecord], institution: &str) {
+        self.institution = institution.to_string();
+        self.name = format!("{} ({})", self.name, institution);
+        for record in records {
+            record.institution = institution.to_string();
+            record.program_name = self.name.clone();
+        }
     }
+}
 
+impl StudentRecord {
+    /// Replace the score, writing `average_score` with the list's decimal separator and precision
     pub fn set_numeric_score(&mut self, score: f64) {
-        self.average_score = format!("{:.4}", score);
+        let decimals = match self.average_score.rsplit_once([',', '.']) {
+            Some((_, fraction)) => fraction.len(),
+            None if self.score.is_some() => 0,
+            None => 4,
+        };
+        let formatted = format!("{:.*}", decimals, score);
+        self.average_score = if self.average_score.contains(',') {
+            formatted.replace('.', ",")
+        } else {
+            formatted
+        };
+        self.score = Some(score);
+    }
+
+    /// Score without individual achievements
+    pub fn base_score(&self) -> Option<f64> {
+        self.score.map(|score| score - self.bonus)
+    }
+
+    /// Add the achievement points to the score used in the simulation
+    pub fn count_achievements(&mut self) {
+        let bonus = parse_score(&self.achievements).unwrap_or(0.0);
+        if let Some(score) = self.score.as_mut() {
+            *score += bonus - self.bonus;
+            self.bonus = bonus;
+        }
     }
 
     pub fn has_consent(&self) -> bool {
-        self.consent.to_lowercase().contains("да")
+        !self.recent_consent && self.consent.to_lowercase().contains("да")
     }
 
     pub fn has_original_document(&self) -> bool {
@@ -115,6 +1113,11 @@ impl StudentRecord {
     }
 }
 
+/// Parse a score as printed in the lists, with a decimal comma or dot
+pub fn parse_score(raw: &str) -> Option<f64> {
+    raw.trim().replace(',', ".").parse::<f64>().ok()
+}
+
 /// Normalize SNILS by keeping only alphanumeric characters
 pub fn normalize_snils(snils: &str) -> String {
     snils.chars()
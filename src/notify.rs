@@ -0,0 +1,207 @@
+use crate::analyzer::{AdmissionDistance, TargetOutcome};
+use crate::compare::{ChangeEvent, OutcomeChange};
+use crate::i18n;
+use crate::models::{Config, EmailOptions, TelegramOptions, WebhookOptions};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Local;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Sends alerts about significant changes to the channels configured in config.toml
+pub struct Notifier {
+    client: reqwest::Client,
+    telegram: Option<TelegramOptions>,
+    email: Option<EmailOptions>,
+    webhook: Option<WebhookOptions>,
+}
+
+impl Notifier {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            telegram: config.telegram.clone(),
+            email: config.email.clone(),
+            webhook: config.webhook.clone(),
+        }
+    }
+
+    /// POST the target's outcomes and the distance-to-admission leaderboard to the webhook
+    pub async fn publish_analysis(
+        &self,
+        target_snils: &str,
+        target_outcomes: &[TargetOutcome],
+        admission_distances: &[AdmissionDistance],
+    ) {
+        self.post_webhook(serde_json::json!({
+            "event": "analysis",
+            "created_at": Local::now(),
+            "target_snils": target_snils,
+            "target_outcomes": target_outcomes,
+            "admission_distances": admission_distances,
+        }))
+        .await;
+    }
+
+    /// POST programs whose outcome changed since the previous run, with the significant events
+    pub async fn publish_changes(&self, target_snils: &str, changes: &[OutcomeChange], events: &[ChangeEvent]) {
+        let changed: Vec<&OutcomeChange> = changes.iter().filter(|change| change.is_changed()).collect();
+        if changed.is_empty() {
+            return;
+        }
+        self.post_webhook(serde_json::json!({
+            "event": "changes",
+            "created_at": Local::now(),
+            "target_snils": target_snils,
+            "changes": changed,
+            "significant_events": events,
+        }))
+        .await;
+    }
+
+    async fn post_webhook(&self, payload: serde_json::Value) {
+        if let Some(webhook) = &self.webhook {
+            match send_webhook(&self.client, webhook, &payload).await {
+                Ok(()) => debug!("{}", t!("notify.sent", "webhook")),
+                Err(e) => warn!("{}", t!("notify.failed", "webhook", e)),
+            }
+        }
+    }
+
+    /// Send one message listing all `events`; failures are logged so watch mode keeps running
+    pub async fn notify(&self, target_snils: &str, events: &[ChangeEvent]) {
+        if events.is_empty() {
+            return;
+        }
+        let title = t!("notify.title", target_snils);
+        let text = message(&title, events.iter().map(describe));
+
+        if let Some(telegram) = &self.telegram {
+            match send_telegram(&self.client, telegram, &text).await {
+                Ok(()) => info!("{}", t!("notify.sent", "Telegram")),
+                Err(e) => warn!("{}", t!("notify.failed", "Telegram", e)),
+            }
+        }
+        self.email(&title, &text).await;
+    }
+
+    /// Email the target's outcome per program after a run; other channels only get alerts
+    pub async fn send_summary(&self, target_snils: &str, target_outcomes: &[TargetOutcome]) {
+        let title = t!("notify.summary_title", target_snils);
+        let lines = target_outcomes
+            .iter()
+            .filter(|outcome| outcome.status != "Not_Applied")
+            .map(|outcome| {
+                t!(
+                    "notify.summary_line",
+                    outcome.program_name,
+                    outcome.funding_source,
+                    i18n::status_label(&outcome.status),
+                    format!("{:.4}", outcome.cutoff_score),
+                    format!("{:.4}", outcome.target_score.unwrap_or(0.0))
+                )
+            });
+        let text = message(&title, lines);
+        self.email(&title, &text).await;
+    }
+
+    async fn email(&self, subject: &str, text: &str) {
+        if let Some(email) = &self.email {
+            match send_email(email, subject, text).await {
+                Ok(()) => info!("{}", t!("notify.sent", "email")),
+                Err(e) => warn!("{}", t!("notify.failed", "email", e)),
+            }
+        }
+    }
+}
+
+fn message(title: &str, lines: impl Iterator<Item = String>) -> String {
+    let mut text = vec![title.to_string()];
+    text.extend(lines);
+    text.join("\n")
+}
+
+/// One line per event in the current language
+fn describe(event: &ChangeEvent) -> String {
+    match event {
+        ChangeEvent::DroppedOut { program_name, funding_source } => {
+            t!("notify.dropped_out", program_name, funding_source)
+        }
+        ChangeEvent::CutoffAboveTarget { program_name, funding_source, cutoff_score, target_score } => t!(
+            "notify.cutoff_above_target",
+            program_name,
+            funding_source,
+            format!("{:.4}", cutoff_score),
+            format!("{:.4}", target_score)
+        ),
+        ChangeEvent::SeatsOpened { program_name, funding_source, places_before, places_after } => {
+            t!("notify.seats_opened", program_name, funding_source, places_before, places_after)
+        }
+    }
+}
+
+async fn send_telegram(client: &reqwest::Client, telegram: &TelegramOptions, text: &str) -> Result<()> {
+    let response = client
+        .post(format!("https://api.telegram.org/bot{}/sendMessage", telegram.bot_token))
+        .json(&serde_json::json!({ "chat_id": telegram.chat_id, "text": text }))
+        .timeout(Duration::from_secs(30))
+        .send()
+        .await
+        .context("Failed to reach the Telegram API")?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        bail!("Telegram API returned {}: {}", status, body);
+    }
+    Ok(())
+}
+
+async fn send_webhook(client: &reqwest::Client, webhook: &WebhookOptions, payload: &serde_json::Value) -> Result<()> {
+    let mut request = client.post(&webhook.url).json(payload).timeout(Duration::from_secs(30));
+    for (name, value) in webhook.headers.iter().flatten() {
+        request = request.header(name, value);
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach webhook {}", webhook.url))?;
+    if !response.status().is_success() {
+        bail!("Webhook {} returned {}", webhook.url, response.status());
+    }
+    Ok(())
+}
+
+async fn send_email(email: &EmailOptions, subject: &str, text: &str) -> Result<()> {
+    let mut builder = Message::builder()
+        .from(email.from.parse().with_context(|| format!("Invalid sender address: {}", email.from))?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for to in &email.to {
+        builder = builder.to(to.parse().with_context(|| format!("Invalid recipient address: {}", to))?);
+    }
+    let message = builder.body(text.to_string())?;
+
+    let mut transport = match email.security.as_deref().unwrap_or("starttls") {
+        "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&email.smtp_server)?,
+        "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&email.smtp_server)?,
+        "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&email.smtp_server),
+        other => return Err(anyhow!("Unknown SMTP security: {} (use starttls, tls or none)", other)),
+    }
+    .timeout(Some(Duration::from_secs(30)));
+    if let Some(port) = email.smtp_port {
+        transport = transport.port(port);
+    }
+    if let (Some(username), Some(password)) = (&email.username, &email.password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+
+    transport
+        .build()
+        .send(message)
+        .await
+        .with_context(|| format!("Failed to send email via {}", email.smtp_server))?;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::models::{normalize_snils, ApplicantApplication, EagerApplicant};
+use tracing::{debug, trace};
+
+/// Follows the admission simulation step by step without taking part in it
+///
+/// Subscribe with [`AdmissionAnalyzer::analyze_all_programs_observed`]. Applicants apply
+/// by priority until a program holds a seat for them; a program turns away again whoever
+/// falls beyond its places as better applicants come, and they go on to their next priority.
+/// Every method has an empty default, so an observer implements only what it needs:
+///
+/// ```
+/// use abitur_analyzer::models::ApplicantApplication;
+/// use abitur_analyzer::observer::SimulationObserver;
+///
+/// // Applications turned away by a full program
+/// #[derive(Default)]
+/// struct Rejections(usize);
+///
+/// impl SimulationObserver for Rejections {
+///     fn on_reject(&mut self, _application: &ApplicantApplication, _admitted: &[String], _places: u32) {
+///         self.0 += 1;
+///     }
+/// }
+/// ```
+///
+/// [`AdmissionAnalyzer::analyze_all_programs_observed`]: crate::AdmissionAnalyzer::analyze_all_programs_observed
+pub trait SimulationObserver {
+    /// An application is looked at; `admitted` is the program's admission list so far
+    fn on_consider(&mut self, _application: &ApplicantApplication, _admitted: &[String], _places: u32) {}
+
+    /// The application got a seat, `position` being its 1-based place in the admission list
+    fn on_admit(&mut self, _application: &ApplicantApplication, _position: usize) {}
+
+    /// The program was full, `admitted` being those who hold its seats; also sent when a
+    /// seat held so far goes to an applicant ahead
+    fn on_reject(&mut self, _application: &ApplicantApplication, _admitted: &[String], _places: u32) {}
+
+    /// Applicants ahead took the seats of the applicant's first priority; `admitted_to` is
+    /// the lower priority they got instead, `None` if they got no seat at all. Sent once
+    /// every seat is settled
+    fn on_displace(&mut self, _applicant: &EagerApplicant, _admitted_to: Option<&ApplicantApplication>) {}
+}
+
+impl<T: SimulationObserver + ?Sized> SimulationObserver for &mut T {
+    fn on_consider(&mut self, application: &ApplicantApplication, admitted: &[String], places: u32) {
+        (**self).on_consider(application, admitted, places);
+    }
+
+    fn on_admit(&mut self, application: &ApplicantApplication, position: usize) {
+        (**self).on_admit(application, position);
+    }
+
+    fn on_reject(&mut self, application: &ApplicantApplication, admitted: &[String], places: u32) {
+        (**self).on_reject(application, admitted, places);
+    }
+
+    fn on_displace(&mut self, applicant: &EagerApplicant, admitted_to: Option<&ApplicantApplication>) {
+        (**self).on_displace(applicant, admitted_to);
+    }
+}
+
+/// Both observers, the first told first
+impl<A: SimulationObserver, B: SimulationObserver> SimulationObserver for (A, B) {
+    fn on_consider(&mut self, application: &ApplicantApplication, admitted: &[String], places: u32) {
+        self.0.on_consider(application, admitted, places);
+        self.1.on_consider(application, admitted, places);
+    }
+
+    fn on_admit(&mut self, application: &ApplicantApplication, position: usize) {
+        self.0.on_admit(application, position);
+        self.1.on_admit(application, position);
+    }
+
+    fn on_reject(&mut self, application: &ApplicantApplication, admitted: &[String], places: u32) {
+        self.0.on_reject(application, admitted, places);
+        self.1.on_reject(application, admitted, places);
+    }
+
+    fn on_displace(&mut self, applicant: &EagerApplicant, admitted_to: Option<&ApplicantApplication>) {
+        self.0.on_displace(applicant, admitted_to);
+        self.1.on_displace(applicant, admitted_to);
+    }
+}
+
+/// Debug log of the target's way through the simulation, shown with `RUST_LOG=debug`
+pub struct TargetTrace {
+    target: String,
+}
+
+impl TargetTrace {
+    pub fn new(target_snils: &str) -> Self {
+        Self {
+            target: normalize_snils(target_snils),
+        }
+    }
+
+    fn is_target(&self, snils: &str) -> bool {
+        normalize_snils(snils) == self.target
+    }
+}
+
+impl SimulationObserver for TargetTrace {
+    fn on_consider(&mut self, application: &ApplicantApplication, admitted: &[String], places: u32) {
+        if !self.is_target(&application.snils) {
+            return;
+        }
+        debug!(snils = %application.snils, program = %application.program_key, admitted = admitted.len(), places, "Processing target applicant");
+        // Admission list so far, the target marked with asterisks
+        let list: Vec<String> = admitted
+            .iter()
+            .map(|snils| if self.is_target(snils) { format!("*{}*", snils) } else { snils.clone() })
+            .collect();
+        trace!("{}", list.join(", "));
+    }
+
+    fn on_admit(&mut self, application: &ApplicantApplication, position: usize) {
+        if self.is_target(&application.snils) {
+            debug!(snils = %application.snils, program = %application.program_key, position, "Admitted target applicant");
+        }
+    }
+
+    fn on_reject(&mut self, application: &ApplicantApplication, _admitted: &[String], places: u32) {
+        if self.is_target(&application.snils) {
+            debug!(snils = %application.snils, program = %application.program_key, places, "Target applicant rejected, program full");
+        }
+    }
+
+    fn on_displace(&mut self, applicant: &EagerApplicant, admitted_to: Option<&ApplicantApplication>) {
+        if self.is_target(&applicant.snils) {
+            let admitted_to = admitted_to.map_or_else(|| "-".to_string(), |application| application.program_key.to_string());
+            debug!(snils = %applicant.snils, admitted_to, "Target applicant displaced from the first priority");
+        }
+    }
+}
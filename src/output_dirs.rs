@@ -0,0 +1,167 @@
+use crate::atomic_file;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local, NaiveDateTime};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Name format of timestamped run directories, e.g. `2024-08-01_15-30-05`
+const RUN_DIR_FORMAT: &str = "%Y-%m-%d_%H-%M-%S";
+// Format of run directories written by earlier versions, still recognized for retention
+const LEGACY_RUN_DIR_FORMAT: &str = "%Y-%m-%d_%H-%M";
+const LATEST: &str = "latest";
+const LATEST_POINTER: &str = "latest.json";
+const LOCK_FILE: &str = ".abitur-analyzer.lock";
+
+/// Lock `output_root` so scheduled and manual runs do not write into it at the same time
+///
+/// The lock is held until the returned file is dropped and released by the OS if the process dies.
+pub fn lock_output_root(output_root: &str) -> Result<fs::File> {
+    fs::create_dir_all(output_root)?;
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(Path::new(output_root).join(LOCK_FILE))?;
+    match file.try_lock() {
+        Ok(()) => Ok(file),
+        Err(fs::TryLockError::WouldBlock) => bail!("{}", t!("run.locked", output_root)),
+        Err(fs::TryLockError::Error(e)) => Err(e.into()),
+    }
+}
+
+/// Create the directory for this run inside `output_root`, named after the current time
+///
+/// A run started within the same second as an earlier one gets a `-2`, `-3`, ... suffix, so
+/// it never writes into the earlier run's directory.
+pub fn create_run_directory(output_root: &str) -> Result<PathBuf> {
+    fs::create_dir_all(output_root)?;
+    let name = Local::now().format(RUN_DIR_FORMAT).to_string();
+    let mut run_dir = Path::new(output_root).join(&name);
+    let mut suffix = 1;
+    loop {
+        match fs::create_dir(&run_dir) {
+            Ok(()) => return Ok(run_dir),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                suffix += 1;
+                run_dir = Path::new(output_root).join(format!("{}-{}", name, suffix));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Point `latest` at the finished run and delete run directories beyond `retention`
+pub fn finish_run(output_root: &str, run_dir: &Path, retention: usize) -> Result<()> {
+    update_latest(Path::new(output_root), run_dir)?;
+
+    let mut run_dirs = list_run_directories(Path::new(output_root))?;
+    run_dirs.sort();
+    let excess = run_dirs.len().saturating_sub(retention.max(1));
+    for old_run in run_dirs.into_iter().take(excess) {
+        debug!("{}", t!("run.removed_directory", old_run.display()));
+        fs::remove_dir_all(old_run)?;
+    }
+    Ok(())
+}
+
+/// Contents of latest.json
+#[derive(Serialize)]
+struct LatestRun<'a> {
+    // Relative to the output root, "." when output_retention is 0
+    run_directory: String,
+    finished_at: DateTime<Local>,
+    target_snils: &'a str,
+    sources_processed: usize,
+}
+
+/// Point latest.json at `run_dir`, which has to hold a complete run
+///
+/// Unlike `latest`, which follows every run, it only moves after a run that loaded
+/// every source and wrote all its reports, so other tools can rely on what it names.
+pub fn write_latest_pointer(output_root: &str, run_dir: &Path, target_snils: &str, sources_processed: usize) -> Result<()> {
+    let relative = run_dir.strip_prefix(output_root).unwrap_or(run_dir);
+    let run_directory = match relative.to_string_lossy() {
+        name if name.is_empty() => ".".to_string(),
+        name => name.to_string(),
+    };
+    let latest_run = LatestRun {
+        run_directory,
+        finished_at: Local::now(),
+        target_snils,
+        sources_processed,
+    };
+    atomic_file::write(Path::new(output_root).join(LATEST_POINTER), serde_json::to_string_pretty(&latest_run)?)?;
+    Ok(())
+}
+
+/// Run directories are recognized by their timestamp name, everything else is left alone
+pub fn list_run_directories(output_root: &Path) -> Result<Vec<PathBuf>> {
+    let mut run_dirs = Vec::new();
+    for entry in fs::read_dir(output_root)? {
+        let entry = entry?;
+        let is_run_dir = entry.file_type()?.is_dir()
+            && entry
+                .file_name()
+                .to_str()
+                .is_some_and(is_run_directory_name);
+        if is_run_dir {
+            run_dirs.push(entry.path());
+        }
+    }
+    Ok(run_dirs)
+}
+
+/// Whether `name` is a run directory timestamp, with or without a same-second suffix
+pub fn is_run_directory_name(name: &str) -> bool {
+    let timestamp = match name.rsplit_once('-') {
+        Some((timestamp, suffix)) if name.len() > "2024-08-01_15-30-05".len() && suffix.parse::<u32>().is_ok() => timestamp,
+        _ => name,
+    };
+    [RUN_DIR_FORMAT, LEGACY_RUN_DIR_FORMAT]
+        .iter()
+        .any(|format| NaiveDateTime::parse_from_str(timestamp, format).is_ok())
+}
+
+/// Replace `latest` with a symlink to the run directory, or a copy where symlinks are unavailable
+///
+/// The symlink is created under a temporary name and renamed over the old one, so `latest`
+/// never goes missing in between.
+fn update_latest(output_root: &Path, run_dir: &Path) -> Result<()> {
+    let latest = output_root.join(LATEST);
+    match fs::symlink_metadata(&latest) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(&latest)?,
+        #[cfg(not(unix))]
+        Ok(_) => fs::remove_file(&latest)?,
+        _ => {}
+    }
+
+    #[cfg(unix)]
+    {
+        let target = run_dir.file_name().map(PathBuf::from).unwrap_or_else(|| run_dir.to_path_buf());
+        let staged = output_root.join(format!(".{}.tmp", LATEST));
+        let _ = fs::remove_file(&staged);
+        std::os::unix::fs::symlink(target, &staged)?;
+        fs::rename(&staged, &latest)?;
+    }
+    #[cfg(not(unix))]
+    copy_dir(run_dir, &latest)?;
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
+}
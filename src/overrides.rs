@@ -0,0 +1,173 @@
+use crate::error::ConfigError;
+use toml::{Table, Value};
+
+/// Prefix of environment variables holding config keys
+pub const ENV_PREFIX: &str = "ABIT_";
+
+/// How a value is written on the command line or in the environment
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Text,
+    Integer,
+    Bool,
+    // Comma separated: "csv,json"
+    List,
+    // Comma separated pairs: "Authorization=Bearer secret,X-Source=abit"
+    Map,
+}
+
+/// Every `Config` key as a dotted TOML path, e.g. `csv.delimiter`, with its kind
+const KEYS: &[(&str, Kind)] = &[
+    ("target_snils", Kind::Text),
+    ("preset", Kind::Text),
+    ("data_source_mode", Kind::Text),
+    ("data_directory", Kind::Text),
+    ("internet_urls", Kind::List),
+    ("output_directory", Kind::Text),
+    ("programs_of_interest", Kind::List),
+    ("funding", Kind::Text),
+    ("program_aliases", Kind::Map),
+    ("excluded_applicants", Kind::List),
+    ("score_conflicts", Kind::Text),
+    ("privileged_detection", Kind::Text),
+    ("privileged_markers", Kind::List),
+    ("count_achievements", Kind::Bool),
+    ("target_funding_types", Kind::List),
+    ("originals_only", Kind::Bool),
+    ("cutoff_ties", Kind::Text),
+    ("tie_breakers", Kind::List),
+    ("submitted_until", Kind::Text),
+    ("recent_consent_days", Kind::Integer),
+    ("failure_policy", Kind::Text),
+    ("required_sources", Kind::Integer),
+    ("streaming_threshold_mb", Kind::Integer),
+    ("max_response_mb", Kind::Integer),
+    ("row_merge", Kind::Text),
+    ("skip_unchanged", Kind::Bool),
+    ("conditional_requests", Kind::Bool),
+    ("simulation_cache", Kind::Bool),
+    ("single_program_estimate", Kind::Bool),
+    ("output_retention", Kind::Integer),
+    ("output_formats", Kind::List),
+    ("templates_directory", Kind::Text),
+    ("pdf_font", Kind::Text),
+    ("language", Kind::Text),
+    ("anonymize", Kind::Bool),
+    ("anonymize_salt", Kind::Text),
+    ("log_file", Kind::Text),
+    ("console_color", Kind::Bool),
+    ("csv.delimiter", Kind::Text),
+    ("csv.decimal_comma", Kind::Bool),
+    ("csv.encoding", Kind::Text),
+    ("csv.bom", Kind::Bool),
+    ("telegram.bot_token", Kind::Text),
+    ("telegram.chat_id", Kind::Text),
+    ("email.smtp_server", Kind::Text),
+    ("email.smtp_port", Kind::Integer),
+    ("email.security", Kind::Text),
+    ("email.username", Kind::Text),
+    ("email.password", Kind::Text),
+    ("email.from", Kind::Text),
+    ("email.to", Kind::List),
+    ("webhook.url", Kind::Text),
+    ("webhook.headers", Kind::Map),
+    ("schedule.cron", Kind::Text),
+    ("schedule.timezone", Kind::Text),
+    ("identity.cross_reference", Kind::Text),
+    ("identity.masked_snils", Kind::Bool),
+    ("identity.partial_target", Kind::Bool),
+    ("campaign.originals_deadline", Kind::Text),
+    ("campaign.enrollment_order", Kind::Text),
+    ("campaign.waves", Kind::List),
+    ("campaign.quota_transfer", Kind::Text),
+    ("competitors.eager_only", Kind::Bool),
+    ("competitors.programs", Kind::List),
+    ("competitors.staying_only", Kind::Bool),
+    ("competitors.boundary", Kind::Integer),
+];
+
+/// `(key, value)` pairs of all `ABIT_*` variables in `vars`, sorted by key
+///
+/// Variable names are the keys in upper case with `__` for the dot, e.g. `ABIT_CSV__DELIMITER`.
+pub fn from_env(vars: impl IntoIterator<Item = (String, String)>) -> Result<Vec<(String, String)>, ConfigError> {
+    let mut overrides = Vec::new();
+    for (name, value) in vars {
+        let Some(key) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        let key = key.to_lowercase().replace("__", ".");
+        if kind_of(&key).is_none() {
+            return Err(ConfigError::Invalid(format!("Unknown configuration key in environment variable {}", name)));
+        }
+        overrides.push((key, value));
+    }
+    overrides.sort();
+    Ok(overrides)
+}
+
+/// Parse a `key=value` command line override
+pub fn parse_assignment(assignment: &str) -> Result<(String, String), ConfigError> {
+    let (key, value) = assignment
+        .split_once('=')
+        .ok_or_else(|| ConfigError::Invalid(format!("Expected KEY=VALUE, got {}", assignment)))?;
+    let key = key.trim().to_string();
+    if kind_of(&key).is_none() {
+        return Err(ConfigError::Invalid(format!("Unknown configuration key: {}", key)));
+    }
+    Ok((key, value.to_string()))
+}
+
+/// Set `key` in a parsed config file, creating tables like `[csv]` as needed
+pub fn apply(table: &mut Table, key: &str, value: &str) -> Result<(), ConfigError> {
+    let kind = kind_of(key).ok_or_else(|| ConfigError::Invalid(format!("Unknown configuration key: {}", key)))?;
+    let value = match kind {
+        Kind::Text => Value::String(value.to_string()),
+        Kind::Integer => Value::Integer(
+            value
+                .trim()
+                .parse()
+                .map_err(|_| ConfigError::Invalid(format!("{} must be a number, got {}", key, value)))?,
+        ),
+        Kind::Bool => Value::Boolean(match value.trim().to_lowercase().as_str() {
+            "true" | "1" | "yes" | "on" => true,
+            "false" | "0" | "no" | "off" => false,
+            _ => return Err(ConfigError::Invalid(format!("{} must be true or false, got {}", key, value))),
+        }),
+        Kind::List => Value::Array(split_list(value).map(|item| Value::String(item.to_string())).collect()),
+        Kind::Map => {
+            let mut map = Table::new();
+            for pair in split_list(value) {
+                let (name, item) = pair
+                    .split_once('=')
+                    .ok_or_else(|| ConfigError::Invalid(format!("{} expects NAME=VALUE pairs, got {}", key, pair)))?;
+                map.insert(name.trim().to_string(), Value::String(item.trim().to_string()));
+            }
+            Value::Table(map)
+        }
+    };
+
+    let mut target = table;
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            target.insert(part.to_string(), value);
+            break;
+        }
+        target = match target
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Table(Table::new()))
+        {
+            Value::Table(nested) => nested,
+            _ => return Err(ConfigError::Invalid(format!("{} is not a table in the configuration", part))),
+        };
+    }
+    Ok(())
+}
+
+fn kind_of(key: &str) -> Option<Kind> {
+    KEYS.iter().find(|(name, _)| *name == key).map(|(_, kind)| *kind)
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim).filter(|item| !item.is_empty())
+}
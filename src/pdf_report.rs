@@ -0,0 +1,212 @@
+use crate::analyzer::{self, TargetOutcome};
+use crate::atomic_file;
+use crate::campaign::CampaignStatus;
+use crate::confidence;
+use crate::models::{normalize_snils, StudentRecord};
+use crate::verdict::{self, Chance};
+use anyhow::{anyhow, Context, Result};
+use chrono::Local;
+use printpdf::{Color, IndirectFontRef, Mm, PdfDocument, PdfLayerReference, Rgb};
+use std::fs::File;
+use std::path::Path;
+
+/// Fonts with Cyrillic letters tried when pdf_font is not set
+const SYSTEM_FONTS: [&str; 6] = [
+    "/usr/share/fonts/truetype/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/dejavu/DejaVuSans.ttf",
+    "/usr/share/fonts/TTF/DejaVuSans.ttf",
+    "/System/Library/Fonts/Supplemental/Arial.ttf",
+    "/Library/Fonts/Arial Unicode.ttf",
+    "C:\\Windows\\Fonts\\arial.ttf",
+];
+
+// A4 portrait, in millimetres
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+// Room kept free at the bottom of the page for the legend
+const FOOTER: f32 = 25.0;
+
+/// Left edges of the outcome table's columns and how many characters fit into them
+const COLUMNS: [(f32, usize); 7] = [(15.0, 9), (32.0, 33), (94.0, 22), (136.0, 8), (152.0, 8), (168.0, 9), (186.0, 7)];
+
+/// Write verdict.pdf, one printable page with the target's dossier and every program the
+/// target applied to classified as safe, match or reach
+pub fn generate_pdf_verdict(
+    target_snils: &str,
+    campaign: Option<&CampaignStatus>,
+    target_outcomes: &[TargetOutcome],
+    all_program_records: &[(String, Vec<StudentRecord>)],
+    font_path: Option<&str>,
+    output_dir: &str,
+) -> Result<()> {
+    let font_path = match font_path {
+        Some(path) => path,
+        None => SYSTEM_FONTS
+            .into_iter()
+            .find(|path| Path::new(path).exists())
+            .ok_or_else(|| anyhow!("No font for verdict.pdf found, set pdf_font to a TrueType font with Cyrillic letters"))?,
+    };
+    let font_file = File::open(font_path).with_context(|| format!("Failed to open PDF font {}", font_path))?;
+
+    let title = t!("pdf.title", target_snils);
+    let (document, page, layer) = PdfDocument::new(&title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "verdict");
+    let font = document
+        .add_external_font(font_file)
+        .with_context(|| format!("Failed to load PDF font {}", font_path))?;
+    let mut page = Page {
+        layer: document.get_page(page).get_layer(layer),
+        font,
+        y: PAGE_HEIGHT - MARGIN,
+    };
+
+    page.line(&title, 16.0, MARGIN, 9.0);
+    page.line(&t!("pdf.generated", Local::now().format("%d.%m.%Y %H:%M")), 9.0, MARGIN, 5.0);
+    if let Some(campaign) = campaign {
+        page.line(&t!("pdf.campaign", campaign.describe()), 9.0, MARGIN, 5.0);
+    }
+    page.y -= 4.0;
+
+    // Dossier: the target's applications as the lists show them
+    let normalized_target = normalize_snils(target_snils);
+    let applications: Vec<&StudentRecord> = all_program_records
+        .iter()
+        .flat_map(|(_, records)| records)
+        .filter(|record| normalize_snils(&record.snils) == normalized_target)
+        .collect();
+    let yes_no = |value: bool| if value { t!("report.yes") } else { t!("report.no") };
+    let scores: Vec<f64> = target_outcomes.iter().filter_map(|outcome| outcome.target_score).collect();
+    let lowest = scores.iter().copied().fold(f64::INFINITY, f64::min);
+    let highest = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let score = match scores.len() {
+        0 => "-".to_string(),
+        _ if highest - lowest < 1e-6 => format!("{:.4}", highest),
+        _ => format!("{:.4} – {:.4}", lowest, highest),
+    };
+
+    page.line(&t!("pdf.dossier"), 12.0, MARGIN, 6.5);
+    page.line(&t!("pdf.applications", applications.len(), score), 10.0, MARGIN, 5.5);
+    page.line(
+        &t!(
+            "pdf.documents",
+            yes_no(applications.iter().any(|record| record.has_original_document())),
+            yes_no(applications.iter().any(|record| record.has_consent()))
+        ),
+        10.0,
+        MARGIN,
+        5.5,
+    );
+    let best = match analyzer::best_option(target_outcomes) {
+        Some(best) => t!("pdf.best_option", best.program_name, best.funding_source, best.priority.unwrap_or(0)),
+        None => t!("pdf.no_option"),
+    };
+    page.line(&best, 10.0, MARGIN, 5.5);
+    page.y -= 4.0;
+
+    // One section per class, most preferred programs first
+    let mut classified: Vec<(Chance, &TargetOutcome)> = target_outcomes
+        .iter()
+        .filter_map(|outcome| Some((verdict::classify(outcome)?, outcome)))
+        .collect();
+    classified.sort_by_key(|(chance, outcome)| (*chance, outcome.priority));
+    let headers = [
+        t!("report.priority"),
+        t!("report.program"),
+        t!("report.funding"),
+        t!("report.score"),
+        t!("report.cutoff_short"),
+        t!("report.position"),
+        t!("confidence.short"),
+    ];
+    let mut rows = classified.iter().peekable();
+    while let Some((chance, _)) = rows.peek().copied() {
+        if page.y < FOOTER + 20.0 {
+            break;
+        }
+        page.layer.set_fill_color(chance_color(*chance));
+        page.line(&t!(chance.label_key()), 12.0, MARGIN, 6.0);
+        page.layer.set_fill_color(black());
+        page.row(&headers, 8.0);
+
+        while let Some((_, outcome)) = rows.next_if(|(other, _)| other == chance) {
+            page.row(
+                &[
+                    outcome.priority.map_or_else(|| "-".to_string(), |priority| priority.to_string()),
+                    outcome.program_name.clone(),
+                    outcome.funding_source.to_string(),
+                    format!("{:.4}", outcome.target_score.unwrap_or(0.0)),
+                    format!("{:.4}", outcome.cutoff_score),
+                    outcome
+                        .position_in_admitted
+                        .map_or_else(|| "-".to_string(), |position| t!("report.of", position, outcome.admitted_count)),
+                    confidence::label(outcome.confidence.as_ref()),
+                ],
+                9.0,
+            );
+            if page.y < FOOTER {
+                break;
+            }
+        }
+        page.y -= 3.0;
+    }
+    let left_out = rows.count();
+    if left_out > 0 {
+        page.line(&t!("pdf.more", left_out), 9.0, MARGIN, 5.0);
+    }
+
+    // A sentence per line, the legend is too long for one
+    page.y = FOOTER - 5.0;
+    for sentence in t!("pdf.legend", (verdict::SAFE_SHARE * 100.0).round()).split_inclusive(". ") {
+        page.line(sentence.trim(), 8.0, MARGIN, 4.0);
+    }
+
+    let content = document.save_to_bytes()?;
+    atomic_file::write(Path::new(output_dir).join("verdict.pdf"), content)?;
+    Ok(())
+}
+
+/// The page being written, top to bottom
+struct Page {
+    layer: PdfLayerReference,
+    font: IndirectFontRef,
+    // Baseline of the next line, from the bottom of the page
+    y: f32,
+}
+
+impl Page {
+    /// Write `text` at `x` and move down by `height`
+    fn line(&mut self, text: &str, size: f32, x: f32, height: f32) {
+        self.layer.use_text(text, size, Mm(x), Mm(self.y), &self.font);
+        self.y -= height;
+    }
+
+    /// Write a row of the outcome table, cutting what does not fit its column
+    fn row(&mut self, cells: &[String], size: f32) {
+        for ((x, width), cell) in COLUMNS.iter().zip(cells) {
+            self.layer.use_text(fit(cell, *width), size, Mm(*x), Mm(self.y), &self.font);
+        }
+        self.y -= size * 0.5;
+    }
+}
+
+/// `text` cut to `width` characters, ending with an ellipsis when cut
+fn fit(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+fn chance_color(chance: Chance) -> Color {
+    match chance {
+        Chance::Safe => Color::Rgb(Rgb::new(0.13, 0.55, 0.13, None)),
+        Chance::Match => Color::Rgb(Rgb::new(0.80, 0.55, 0.0, None)),
+        Chance::Reach => Color::Rgb(Rgb::new(0.75, 0.15, 0.15, None)),
+    }
+}
+
+fn black() -> Color {
+    Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None))
+}
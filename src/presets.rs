@@ -0,0 +1,167 @@
+use crate::error::ConfigError;
+use crate::models::merge_tables;
+use chrono::Datelike;
+use std::path::Path;
+use toml::Table;
+
+/// Built-in campaign rule presets, name -> settings
+pub const PRESETS: [(&str, &str); 3] = [
+    ("spo_default", include_str!("../presets/spo_default.toml")),
+    ("spo_moscow", include_str!("../presets/spo_moscow.toml")),
+    ("vo_2024", include_str!("../presets/vo_2024.toml")),
+];
+
+/// Fill in the settings of the preset `table` names where `table` leaves them out
+///
+/// A preset is built in or a `presets/NAME.toml` file next to `config_file`, and may name
+/// another preset it builds on. Settings of `table` always win over those of its preset.
+pub fn apply(table: &mut Table, config_file: Option<&Path>) -> Result<(), ConfigError> {
+    let mut merged = settings(table, config_file)?;
+    merge_tables(&mut merged, std::mem::take(table));
+    *table = merged;
+    Ok(())
+}
+
+/// Put the settings of preset `name` over those of `table`, for switching presets of a
+/// configuration whose settings are already filled in
+pub fn overlay(table: &mut Table, name: &str, config_file: Option<&Path>) -> Result<(), ConfigError> {
+    let mut preset = Table::new();
+    preset.insert("preset".to_string(), toml::Value::String(name.to_string()));
+    merge_tables(table, settings(&preset, config_file)?);
+    Ok(())
+}
+
+/// Remove the settings the preset of `table` would fill in with the same values, so a saved
+/// configuration leaves them to the preset
+pub fn strip(table: &mut Table, config_file: Option<&Path>) -> Result<(), ConfigError> {
+    let preset = settings(table, config_file)?;
+    remove_keys(table, &preset);
+    Ok(())
+}
+
+/// Settings of the preset `table` names, with those of the presets it builds on
+fn settings(table: &Table, config_file: Option<&Path>) -> Result<Table, ConfigError> {
+    let mut merged = Table::new();
+    for layer in chain(table, config_file)?.into_iter().rev() {
+        merge_tables(&mut merged, layer);
+    }
+    Ok(merged)
+}
+
+/// The presets `table` builds on, its own preset first
+fn chain(table: &Table, config_file: Option<&Path>) -> Result<Vec<Table>, ConfigError> {
+    let mut names: Vec<String> = Vec::new();
+    let mut layers = Vec::new();
+    let mut next = preset_name(table)?;
+    while let Some(name) = next {
+        if names.contains(&name) {
+            return Err(ConfigError::Invalid(format!("Preset {} builds on itself", name)));
+        }
+        let layer = load(&name, config_file)?;
+        next = preset_name(&layer)?;
+        names.push(name);
+        layers.push(layer);
+    }
+    Ok(layers)
+}
+
+fn preset_name(table: &Table) -> Result<Option<String>, ConfigError> {
+    match table.get("preset") {
+        None => Ok(None),
+        Some(toml::Value::String(name)) => Ok(Some(name.clone())),
+        Some(_) => Err(ConfigError::Invalid("preset must be a name".to_string())),
+    }
+}
+
+/// Settings of preset `name`, built-in ones taking precedence over files
+fn load(name: &str, config_file: Option<&Path>) -> Result<Table, ConfigError> {
+    let mut layer: Table = match PRESETS.iter().find(|(preset, _)| *preset == name) {
+        Some((_, content)) => {
+            toml::from_str(content).map_err(|cause| ConfigError::Syntax { path: format!("preset {}", name), cause })?
+        }
+        None => {
+            let preset_file = config_file.map(|config_file| directory(config_file).join(format!("{}.toml", name)));
+            match preset_file {
+                Some(preset_file) if preset_file.exists() => {
+                    let path = preset_file.display().to_string();
+                    let content =
+                        std::fs::read_to_string(&preset_file).map_err(|cause| ConfigError::Read { path: path.clone(), cause })?;
+                    toml::from_str(&content).map_err(|cause| ConfigError::Syntax { path, cause })?
+                }
+                _ => {
+                    return Err(ConfigError::Invalid(format!(
+                        "Unknown preset {}, available: {}",
+                        name,
+                        names(config_file).join(", ")
+                    )))
+                }
+            }
+        }
+    };
+    if let Some(toml::Value::Table(campaign)) = layer.get_mut("campaign") {
+        date_campaign(campaign, chrono::Local::now().year())
+            .map_err(|date| ConfigError::Invalid(format!("Preset {}: campaign date {} is not MM-DD", name, date)))?;
+    }
+    Ok(layer)
+}
+
+/// Give the campaign dates of a preset, written as `"MM-DD"` to hold every year, the year `year`
+fn date_campaign(campaign: &mut Table, year: i32) -> Result<(), String> {
+    for (_, value) in campaign.iter_mut() {
+        match value {
+            toml::Value::Array(dates) => dates.iter_mut().try_for_each(|date| date_in(date, year))?,
+            value => date_in(value, year)?,
+        }
+    }
+    Ok(())
+}
+
+fn date_in(value: &mut toml::Value, year: i32) -> Result<(), String> {
+    if let toml::Value::String(day) = value {
+        let date = chrono::NaiveDate::parse_from_str(&format!("{}-{}", year, day), "%Y-%m-%d").map_err(|_| day.clone())?;
+        *day = date.to_string();
+    }
+    Ok(())
+}
+
+/// Built-in presets and those in the `presets` directory next to `config_file`
+pub fn names(config_file: Option<&Path>) -> Vec<String> {
+    let mut names: Vec<String> = PRESETS.iter().map(|(name, _)| name.to_string()).collect();
+    let entries = config_file.and_then(|config_file| std::fs::read_dir(directory(config_file)).ok());
+    for entry in entries.into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|extension| extension == "toml") {
+            if let Some(stem) = path.file_stem() {
+                names.push(stem.to_string_lossy().to_string());
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn directory(config_file: &Path) -> std::path::PathBuf {
+    config_file.parent().unwrap_or(Path::new(".")).join("presets")
+}
+
+/// Remove from `table` every setting `layer` has with the same value, nested tables key by key
+fn remove_keys(table: &mut Table, layer: &Table) {
+    for (key, value) in layer {
+        if key == "preset" {
+            continue;
+        }
+        match (table.get_mut(key), value) {
+            (Some(toml::Value::Table(nested)), toml::Value::Table(layer_nested)) => {
+                remove_keys(nested, layer_nested);
+                if nested.is_empty() {
+                    table.remove(key);
+                }
+            }
+            (Some(own), value) if own == value => {
+                table.remove(key);
+            }
+            _ => {}
+        }
+    }
+}
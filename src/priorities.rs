@@ -0,0 +1,160 @@
+use crate::analyzer::AdmissionAnalyzer;
+use crate::models::{normalize_snils, ProgramKey, StudentRecord};
+use crate::verdict::{self, Chance};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// An order of the target's priorities at one institution and the seat it gets the target
+#[derive(Debug, Clone, Serialize)]
+pub struct PriorityOption {
+    // Empty with a single institution
+    pub institution: String,
+    // The target's programs at the institution, most wanted first, to be given priorities 1, 2, 3…
+    pub ordering: Vec<ProgramKey>,
+    // Program the simulation admits the target to, None without a seat
+    pub admitted_to: Option<ProgramKey>,
+    pub chance: Option<Chance>,
+    // Target score minus the cutoff of `admitted_to`
+    pub margin: Option<f64>,
+    // Position of `admitted_to` in the current order, 1-based
+    pub preference: Option<usize>,
+    // Applications whose priority differs from the current one
+    pub moved: usize,
+    pub current: bool,
+}
+
+/// Every seat the target can get by reordering its priorities, best first per institution
+///
+/// Whether a program would seat the target depends on the others and the program's own
+/// order, not on the target's priorities: the simulation gives the target the first
+/// program of its order that would. Putting each application first therefore finds every reachable seat, with as
+/// many simulations as the target has applications. Each seat is reported with the
+/// smallest change to the current order that gets it.
+///
+/// Options are ranked by the chance of the seat, then by how much the target wants the
+/// program now, then by the margin above the cutoff, so a safe seat beats a more wanted
+/// match. The current order is always among them.
+pub fn optimize(analyzer: &AdmissionAnalyzer, all_program_records: &[(String, Vec<StudentRecord>)]) -> Vec<PriorityOption> {
+    let normalized_target = normalize_snils(analyzer.target_snils);
+    // Institution -> the target's (priority, program) there
+    let mut applications: BTreeMap<String, Vec<(u32, ProgramKey)>> = BTreeMap::new();
+    for (program_name, records) in all_program_records {
+        for record in records.iter().filter(|record| normalize_snils(&record.snils) == normalized_target) {
+            applications
+                .entry(record.institution.clone())
+                .or_default()
+                .push((record.priority, ProgramKey::of(program_name, record)));
+        }
+    }
+
+    let mut options = Vec::new();
+    for (institution, mut applied) in applications {
+        // Stable, so equal priorities keep the order the simulation gives them
+        applied.sort_by_key(|(priority, _)| *priority);
+        let current: Vec<ProgramKey> = applied.into_iter().map(|(_, program_key)| program_key).collect();
+
+        // Programs with a seat for the target when tried first, in the current order
+        let reachable: Vec<(usize, Outcome)> = current
+            .iter()
+            .enumerate()
+            .filter_map(|(index, program_key)| {
+                let mut ordering = current.clone();
+                let tried = ordering.remove(index);
+                ordering.insert(0, tried);
+                let outcome = simulate(analyzer, all_program_records, &institution, &ordering);
+                (outcome.admitted_to.as_ref() == Some(program_key)).then_some((index, outcome))
+            })
+            .collect();
+
+        let first_reachable = reachable.first().map(|(index, _)| *index);
+        let mut institution_options: Vec<PriorityOption> = reachable
+            .into_iter()
+            .map(|(index, outcome)| {
+                // Just ahead of the program the current order gets
+                let mut ordering = current.clone();
+                let tried = ordering.remove(index);
+                ordering.insert(first_reachable.unwrap_or(index), tried);
+                let moved = ordering.iter().zip(&current).filter(|(a, b)| a != b).count();
+                PriorityOption {
+                    institution: institution.clone(),
+                    ordering,
+                    admitted_to: outcome.admitted_to,
+                    chance: outcome.chance,
+                    margin: outcome.margin,
+                    preference: Some(index + 1),
+                    moved,
+                    current: moved == 0,
+                }
+            })
+            .collect();
+        if institution_options.is_empty() {
+            institution_options.push(PriorityOption {
+                institution: institution.clone(),
+                ordering: current,
+                admitted_to: None,
+                chance: None,
+                margin: None,
+                preference: None,
+                moved: 0,
+                current: true,
+            });
+        }
+        institution_options.sort_by(|a, b| {
+            a.chance
+                .cmp(&b.chance)
+                .then(a.preference.cmp(&b.preference))
+                .then(b.margin.unwrap_or(0.0).total_cmp(&a.margin.unwrap_or(0.0)))
+        });
+        options.extend(institution_options);
+    }
+    options
+}
+
+/// The target's seat in one simulation
+struct Outcome {
+    admitted_to: Option<ProgramKey>,
+    chance: Option<Chance>,
+    margin: Option<f64>,
+}
+
+/// Simulate with the target's applications at `institution` given priorities 1, 2, 3… in
+/// `ordering`
+fn simulate(
+    analyzer: &AdmissionAnalyzer,
+    all_program_records: &[(String, Vec<StudentRecord>)],
+    institution: &str,
+    ordering: &[ProgramKey],
+) -> Outcome {
+    let normalized_target = normalize_snils(analyzer.target_snils);
+    let mut reordered = all_program_records.to_vec();
+    for (program_name, records) in &mut reordered {
+        for record in records.iter_mut() {
+            if record.institution != institution || normalize_snils(&record.snils) != normalized_target {
+                continue;
+            }
+            let program_key = ProgramKey::of(program_name, record);
+            if let Some(index) = ordering.iter().position(|other| *other == program_key) {
+                record.priority = index as u32 + 1;
+            }
+        }
+    }
+
+    let analysis = analyzer.analyze_all_programs(&reordered);
+    let admitted = analyzer
+        .report_model(&analysis, &reordered)
+        .target_outcomes()
+        .into_iter()
+        .find(|outcome| outcome.status == "Admitted" && ordering.contains(&outcome.program_key));
+    match admitted {
+        Some(outcome) => Outcome {
+            chance: verdict::classify(&outcome),
+            margin: outcome.target_score.map(|score| score - outcome.cutoff_score),
+            admitted_to: Some(outcome.program_key),
+        },
+        None => Outcome {
+            admitted_to: None,
+            chance: None,
+            margin: None,
+        },
+    }
+}
@@ -0,0 +1,57 @@
+use crate::models::{PrivilegedDetection, StudentRecord};
+
+/// Lowercase fragments marking an applicant admitted without entrance tests (БВИ);
+/// the scraper looks for them in every cell of a row
+pub const PRIVILEGED_MARKERS: &[&str] = &["бви", "без вступительных"];
+
+/// Decide which applications are privileged, seated ahead of the competition in their program
+///
+/// `Markers` keeps the rows the scraper found a marker in and adds those whose score, marks,
+/// test or achievements show one of `extra_markers`. `Position` takes applicants listed above
+/// the last one with the list's best score with a lower or no score, as lists that rank
+/// privileged applicants first without saying so show them. Returns how many applications
+/// are privileged.
+pub fn flag(
+    all_program_records: &mut [(String, Vec<StudentRecord>)],
+    detection: PrivilegedDetection,
+    extra_markers: &[String],
+) -> usize {
+    let extra_markers: Vec<String> = extra_markers.iter().map(|marker| marker.to_lowercase()).collect();
+    let mut flagged = 0;
+    for (_, records) in all_program_records.iter_mut() {
+        let listed_above_best = listed_above_best(records);
+        for (record, above_best) in records.iter_mut().zip(listed_above_best) {
+            let marked = record.privileged || has_marker(record, &extra_markers);
+            record.privileged = match detection {
+                PrivilegedDetection::Markers => marked,
+                PrivilegedDetection::Position => above_best,
+                PrivilegedDetection::Both => marked || above_best,
+                PrivilegedDetection::Off => false,
+            };
+            flagged += usize::from(record.privileged);
+        }
+    }
+    flagged
+}
+
+fn has_marker(record: &StudentRecord, markers: &[String]) -> bool {
+    [&record.average_score, &record.subject_scores, &record.psychological_test, &record.achievements]
+        .iter()
+        .map(|text| text.to_lowercase())
+        .any(|text| markers.iter().any(|marker| text.contains(marker.as_str())))
+}
+
+/// For each record in list order, whether it is listed above the last record with the
+/// list's best score and scores lower
+fn listed_above_best(records: &[StudentRecord]) -> Vec<bool> {
+    let best = records.iter().filter_map(|record| record.score).fold(f64::MIN, f64::max);
+    let mut order: Vec<usize> = (0..records.len()).collect();
+    order.sort_by_key(|&index| records[index].rank);
+    let last_best = order.iter().rposition(|&index| records[index].score == Some(best));
+
+    let mut above = vec![false; records.len()];
+    for &index in order.iter().take(last_best.unwrap_or(0)) {
+        above[index] = records[index].score.is_none_or(|score| score < best);
+    }
+    above
+}
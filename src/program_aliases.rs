@@ -0,0 +1,82 @@
+use crate::models::{normalize_snils, ProgramKey, StudentRecord};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// A list published under another name of a program, renamed to the canonical one
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramMerge {
+    // Name the list was published under
+    pub alias: String,
+    // Competition of the canonical program the list belongs to
+    pub program_key: ProgramKey,
+    pub applications: usize,
+    // Applications dropped because the canonical program's list already had the applicant
+    pub duplicates_removed: usize,
+}
+
+/// Rename lists of the programs in `aliases` (alias -> canonical name) and merge each
+/// into the list of the same competition, if there is one
+///
+/// Applicants listed under both names keep the record of the list merged into. Lists that
+/// are not renamed stay as loaded, even when a program appears in several sources.
+pub fn merge(
+    all_program_records: &mut Vec<(String, Vec<StudentRecord>)>,
+    aliases: &BTreeMap<String, String>,
+) -> Vec<ProgramMerge> {
+    let mut renamed = Vec::new();
+    for (index, (program_name, records)) in all_program_records.iter_mut().enumerate() {
+        let Some(canonical) = aliases.get(program_name.trim()).map(|name| name.trim()) else {
+            continue;
+        };
+        if canonical == program_name.trim() {
+            continue;
+        }
+        let alias = std::mem::replace(program_name, canonical.to_string());
+        for record in records.iter_mut() {
+            record.program_name = canonical.to_string();
+        }
+        renamed.push((index, alias));
+    }
+
+    let competition = |(program_name, records): &(String, Vec<StudentRecord>)| {
+        records.first().map(|record| ProgramKey::of(program_name, record))
+    };
+    let mut absorbed = vec![false; all_program_records.len()];
+    let mut merges = Vec::new();
+    for (index, alias) in renamed {
+        let Some(program_key) = competition(&all_program_records[index]) else {
+            continue;
+        };
+        let applications = all_program_records[index].1.len();
+        let mut duplicates_removed = 0;
+        let target = (0..all_program_records.len()).find(|&other| {
+            other != index && !absorbed[other] && competition(&all_program_records[other]).as_ref() == Some(&program_key)
+        });
+        if let Some(target) = target {
+            let records = std::mem::take(&mut all_program_records[index].1);
+            let listed: HashSet<String> =
+                all_program_records[target].1.iter().map(|record| normalize_snils(&record.snils)).collect();
+            for record in records {
+                if listed.contains(&normalize_snils(&record.snils)) {
+                    duplicates_removed += 1;
+                } else {
+                    all_program_records[target].1.push(record);
+                }
+            }
+            absorbed[index] = true;
+        }
+        merges.push(ProgramMerge {
+            alias,
+            program_key,
+            applications,
+            duplicates_removed,
+        });
+    }
+
+    let mut index = 0;
+    all_program_records.retain(|_| {
+        index += 1;
+        !absorbed[index - 1]
+    });
+    merges
+}
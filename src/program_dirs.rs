@@ -0,0 +1,109 @@
+use crate::{atomic_file, AnalyzedRun};
+use abitur_analyzer::models::{FundingSource, ProgramKey, StudyForm};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Output directory holding one directory per competition and the manifest
+pub const PROGRAMS_DIR: &str = "programs";
+const MANIFEST: &str = "manifest.json";
+
+/// Directory of every competition of a run below `programs/`: `<program>/<funding>/<form>`
+///
+/// Competitions whose names come out as the same directory get `_2`, `_3`, … on its last
+/// part in the order of their keys, so the layout of the same data is always the same.
+pub struct ProgramDirectories {
+    directories: BTreeMap<ProgramKey, PathBuf>,
+}
+
+impl ProgramDirectories {
+    pub fn of(analyzed: &AnalyzedRun) -> Self {
+        let mut keys: Vec<ProgramKey> = analyzed
+            .all_program_records
+            .iter()
+            .flat_map(|(program_name, records)| records.iter().map(|record| ProgramKey::of(program_name, record)))
+            .chain(analyzed.report.programs.iter().map(|program| program.program_key.clone()))
+            .collect();
+        keys.sort();
+        keys.dedup();
+
+        let mut taken = HashSet::new();
+        let directories = keys
+            .into_iter()
+            .map(|key| {
+                let base = key.directory();
+                let mut directory = base.clone();
+                let mut suffix = 1;
+                while !taken.insert(directory.clone()) {
+                    suffix += 1;
+                    let name = base.file_name().unwrap_or_default().to_string_lossy();
+                    directory = base.with_file_name(format!("{}_{}", name, suffix));
+                }
+                (key, directory)
+            })
+            .collect();
+        Self { directories }
+    }
+
+    /// Directory of `program_key` inside `output_dir`, created if missing
+    pub fn create(&self, output_dir: &str, program_key: &ProgramKey) -> Result<PathBuf> {
+        let relative = self.directories.get(program_key).cloned().unwrap_or_else(|| program_key.directory());
+        let directory = Path::new(output_dir).join(PROGRAMS_DIR).join(relative);
+        fs::create_dir_all(&directory)?;
+        Ok(directory)
+    }
+
+    /// Write `programs/manifest.json`, telling which competition every directory holds
+    pub fn write_manifest(&self, analyzed: &AnalyzedRun, output_dir: &str) -> Result<()> {
+        #[derive(Serialize)]
+        struct ManifestEntry<'a> {
+            // Relative to programs/, always with `/` separators
+            directory: String,
+            program_key: &'a ProgramKey,
+            program_name: &'a str,
+            funding_source: &'a FundingSource,
+            study_form: &'a StudyForm,
+            // None for lists the analysis found no competition in
+            available_places: Option<u32>,
+            applications: usize,
+        }
+
+        #[derive(Serialize)]
+        struct Manifest<'a> {
+            programs: Vec<ManifestEntry<'a>>,
+        }
+
+        let entries: Vec<ManifestEntry> = self
+            .directories
+            .iter()
+            .map(|(program_key, directory)| ManifestEntry {
+                directory: directory.iter().map(|part| part.to_string_lossy()).collect::<Vec<_>>().join("/"),
+                program_key,
+                program_name: &program_key.name,
+                funding_source: &program_key.funding,
+                study_form: &program_key.study_form,
+                available_places: analyzed
+                    .report
+                    .programs
+                    .iter()
+                    .find(|program| program.program_key == *program_key)
+                    .map(|program| program.available_places),
+                applications: analyzed
+                    .all_program_records
+                    .iter()
+                    .map(|(program_name, records)| {
+                        records.iter().filter(|record| program_key.matches(program_name, record)).count()
+                    })
+                    .sum(),
+            })
+            .collect();
+
+        let programs_dir = Path::new(output_dir).join(PROGRAMS_DIR);
+        fs::create_dir_all(&programs_dir)?;
+        let content = serde_json::to_string_pretty(&Manifest { programs: entries })?;
+        atomic_file::write(programs_dir.join(MANIFEST), content)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::io::{self, Write};
+use std::sync::OnceLock;
+
+static PROGRESS: OnceLock<MultiProgress> = OnceLock::new();
+
+/// Enable or hide progress bars for the rest of the run; only the first call takes effect
+pub fn init(enabled: bool) {
+    let target = if enabled {
+        ProgressDrawTarget::stderr()
+    } else {
+        ProgressDrawTarget::hidden()
+    };
+    let _ = PROGRESS.set(MultiProgress::with_draw_target(target));
+}
+
+fn multi() -> &'static MultiProgress {
+    PROGRESS.get_or_init(|| MultiProgress::with_draw_target(ProgressDrawTarget::hidden()))
+}
+
+/// Progress bar with position, ETA and a message for the current item
+pub fn bar(len: usize, prefix: &str) -> ProgressBar {
+    let bar = multi().add(ProgressBar::new(len as u64));
+    bar.set_style(
+        ProgressStyle::with_template("{prefix} [{bar:30}] {pos}/{len} ETA {eta} {wide_msg}")
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    bar.set_prefix(prefix.to_string());
+    bar
+}
+
+/// Stdout writer that hides progress bars while a log line is printed
+pub struct SuspendingStdout;
+
+impl Write for SuspendingStdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        multi().suspend(|| io::stdout().write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
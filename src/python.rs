@@ -0,0 +1,215 @@
+//! Python module `abitur_analyzer` with the parsing and the simulation, for scripts and
+//! notebooks that want the lists and outcomes as Python objects
+
+use crate::analyzer::TargetOutcome;
+use crate::error::error_chain;
+use crate::models::{Config, StudentRecord};
+use crate::identity::IdentityResolver;
+use crate::scoring::ScoringRules;
+use crate::scraper::AdmissionScraper;
+use crate::{analyze_prepared, prepare_records, sources};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// One application in a list
+#[pyclass(frozen, from_py_object, module = "abitur_analyzer")]
+#[derive(Clone)]
+struct Record {
+    program_name: String,
+    record: StudentRecord,
+}
+
+#[pymethods]
+impl Record {
+    #[getter]
+    fn program_name(&self) -> &str {
+        &self.program_name
+    }
+
+    #[getter]
+    fn institution(&self) -> &str {
+        &self.record.institution
+    }
+
+    #[getter]
+    fn snils(&self) -> &str {
+        &self.record.snils
+    }
+
+    #[getter]
+    fn rank(&self) -> u32 {
+        self.record.rank
+    }
+
+    #[getter]
+    fn priority(&self) -> u32 {
+        self.record.priority
+    }
+
+    #[getter]
+    fn score(&self) -> Option<f64> {
+        self.record.score
+    }
+
+    #[getter]
+    fn consent(&self) -> bool {
+        self.record.has_consent()
+    }
+
+    #[getter]
+    fn original(&self) -> bool {
+        self.record.has_original_document()
+    }
+
+    #[getter]
+    fn funding_source(&self) -> String {
+        self.record.funding_source.to_string()
+    }
+
+    #[getter]
+    fn study_form(&self) -> String {
+        self.record.study_form.to_string()
+    }
+
+    #[getter]
+    fn available_places(&self) -> u32 {
+        self.record.available_places
+    }
+
+    /// Every field of the record as in snapshot.json, e.g. for a pandas DataFrame
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let json = serde_json::to_string(&self.record).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let dict = py.import("json")?.call_method1("loads", (json,))?;
+        dict.set_item("program_name", &self.program_name)?;
+        Ok(dict)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Record(program_name={:?}, snils={:?}, rank={}, priority={})", self.program_name, self.record.snils, self.record.rank, self.record.priority)
+    }
+}
+
+/// The simulated outcome for the target in one program-funding combination
+#[pyclass(frozen, get_all, skip_from_py_object, module = "abitur_analyzer")]
+#[derive(Clone)]
+struct Outcome {
+    program_key: String,
+    program_name: String,
+    institution: String,
+    funding_source: String,
+    priority: Option<u32>,
+    available_places: u32,
+    target_score: Option<f64>,
+    cutoff_score: f64,
+    position_in_admitted: Option<usize>,
+    admitted_count: usize,
+    status: String,
+}
+
+impl From<TargetOutcome> for Outcome {
+    fn from(outcome: TargetOutcome) -> Self {
+        Self {
+            program_key: outcome.program_key.to_string(),
+            program_name: outcome.program_name,
+            institution: outcome.institution,
+            funding_source: outcome.funding_source.to_string(),
+            priority: outcome.priority,
+            available_places: outcome.available_places,
+            target_score: outcome.target_score,
+            cutoff_score: outcome.cutoff_score,
+            position_in_admitted: outcome.position_in_admitted,
+            admitted_count: outcome.admitted_count,
+            status: outcome.status,
+        }
+    }
+}
+
+#[pymethods]
+impl Outcome {
+    fn __repr__(&self) -> String {
+        format!("Outcome(program_key={:?}, status={:?}, cutoff_score={})", self.program_key, self.status, self.cutoff_score)
+    }
+}
+
+/// Result of [`analyze`]
+#[pyclass(frozen, get_all, module = "abitur_analyzer")]
+struct Analysis {
+    // As the lists spell it
+    target_snils: String,
+    outcomes: Vec<Outcome>,
+    // Program key -> admitted SNILSes in admission order
+    admitted: BTreeMap<String, Vec<String>>,
+}
+
+#[pymethods]
+impl Analysis {
+    fn __repr__(&self) -> String {
+        format!("Analysis(target_snils={:?}, {} outcomes)", self.target_snils, self.outcomes.len())
+    }
+}
+
+/// Records of every list on an admission list page
+#[pyfunction]
+#[pyo3(signature = (html, source = "pasted"))]
+fn scrape_html(html: &str, source: &str) -> PyResult<Vec<Record>> {
+    let programs = AdmissionScraper::new()
+        .parse_html_content(html, source)
+        .map_err(|e| PyValueError::new_err(error_chain(&e)))?;
+    Ok(programs
+        .into_iter()
+        .flat_map(|(program_info, records)| {
+            sources::deduplicate_records_by_snils(records)
+                .into_iter()
+                .map(move |record| Record { program_name: program_info.name.clone(), record })
+        })
+        .collect())
+}
+
+/// Simulate admission over `records` with the settings of `config`, the contents of a
+/// config.toml with defaults for what it leaves out
+#[pyfunction]
+#[pyo3(signature = (records, config = "", target_snils = None))]
+fn analyze(records: Vec<Record>, config: &str, target_snils: Option<String>) -> PyResult<Analysis> {
+    let error = |e: crate::Error| PyValueError::new_err(error_chain(&e));
+    let mut config = Config::parse(config).map_err(|e| error(e.into()))?;
+    if let Some(target_snils) = target_snils {
+        config.target_snils = target_snils;
+    }
+
+    // Back into lists, in the order their first records come
+    let mut all_program_records: Vec<(String, Vec<StudentRecord>)> = Vec::new();
+    for Record { program_name, record } in records {
+        match all_program_records.iter_mut().find(|(name, _)| *name == program_name) {
+            Some((_, list)) => list.push(record),
+            None => all_program_records.push((program_name, vec![record])),
+        }
+    }
+
+    let identities = IdentityResolver::new(config.identity.as_ref()).map_err(|e| error(e.into()))?;
+    let scoring = ScoringRules::new(config.scoring.as_deref()).map_err(|e| error(e.into()))?;
+    let prepared = prepare_records(&config, &identities, &scoring, all_program_records).map_err(error)?;
+    let analyzed = analyze_prepared(&config, prepared, &BTreeSet::new(), |analyzer, all_program_records| {
+        analyzer.analyze_all_programs(all_program_records)
+    });
+    Ok(Analysis {
+        target_snils: analyzed.target_snils,
+        outcomes: analyzed.target_outcomes.into_iter().map(Outcome::from).collect(),
+        admitted: analyzed
+            .analysis
+            .final_admission_results
+            .into_iter()
+            .map(|(program_key, admitted)| (program_key.to_string(), admitted))
+            .collect(),
+    })
+}
+
+#[pymodule]
+fn abitur_analyzer(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Record>()?;
+    module.add_class::<Outcome>()?;
+    module.add_class::<Analysis>()?;
+    module.add_function(wrap_pyfunction!(scrape_html, module)?)?;
+    module.add_function(wrap_pyfunction!(analyze, module)?)?;
+    Ok(())
+}
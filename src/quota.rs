@@ -0,0 +1,64 @@
+use crate::analyzer::AdmissionAnalysis;
+use crate::models::{FundingSource, ProgramKey};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Places of a program's quotas the simulation leaves unfilled, going to its budget competition
+#[derive(Debug, Clone, Serialize)]
+pub struct QuotaTransfer {
+    // Budget competition receiving the places
+    pub program_key: ProgramKey,
+    // Targeted and special quota competitions with unfilled places
+    pub quotas: Vec<ProgramKey>,
+    pub places: u32,
+}
+
+/// Unfilled quota places of `analysis` by the budget competition of the same program and
+/// study form they go to; quotas of programs without a budget competition keep them
+pub fn unfilled(analysis: &AdmissionAnalysis) -> Vec<QuotaTransfer> {
+    let mut transfers: BTreeMap<ProgramKey, QuotaTransfer> = BTreeMap::new();
+    for popularity in analysis.program_popularities.iter().filter(|popularity| popularity.funding_source.is_quota()) {
+        let admitted = analysis.final_admission_results.get(&popularity.program_key).map_or(0, Vec::len);
+        let places = popularity.available_places.saturating_sub(admitted as u32);
+        let budget = ProgramKey {
+            funding: FundingSource::Budget,
+            ..popularity.program_key.clone()
+        };
+        if places == 0 || !analysis.program_popularities.iter().any(|other| other.program_key == budget) {
+            continue;
+        }
+        let transfer = transfers.entry(budget.clone()).or_insert_with(|| QuotaTransfer {
+            program_key: budget,
+            quotas: Vec::new(),
+            places: 0,
+        });
+        transfer.quotas.push(popularity.program_key.clone());
+        transfer.places += places;
+    }
+    transfers.into_values().collect()
+}
+
+/// `seat_overrides` with the transfers applied: budget competitions get the quota places,
+/// quotas keep only the places the simulation filled
+pub fn seat_overrides(
+    analysis: &AdmissionAnalysis,
+    transfers: &[QuotaTransfer],
+    seat_overrides: &BTreeMap<String, u32>,
+) -> BTreeMap<String, u32> {
+    let places = |program_key: &ProgramKey| {
+        analysis
+            .program_popularities
+            .iter()
+            .find(|popularity| popularity.program_key == *program_key)
+            .map_or(0, |popularity| popularity.available_places)
+    };
+    let mut seat_overrides = seat_overrides.clone();
+    for transfer in transfers {
+        seat_overrides.insert(transfer.program_key.to_string(), places(&transfer.program_key) + transfer.places);
+        for quota in &transfer.quotas {
+            let admitted = analysis.final_admission_results.get(quota).map_or(0, Vec::len);
+            seat_overrides.insert(quota.to_string(), admitted as u32);
+        }
+    }
+    seat_overrides
+}
@@ -0,0 +1,90 @@
+use crate::models::{ProgramKey, Provenance, StudentRecord};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+/// How a published rank disagrees with the scores
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RankIssue {
+    // Another application to the competition has the same rank
+    Duplicate,
+    // Listed in another place than its score gives
+    OutOfOrder,
+    // The rank before it is missing, e.g. after a withdrawal
+    Gap,
+}
+
+/// An application whose published rank does not fit its computed one
+#[derive(Debug, Clone, Serialize)]
+pub struct RankDiscrepancy {
+    pub program_key: ProgramKey,
+    pub snils: String,
+    pub published_rank: u32,
+    pub computed_rank: u32,
+    pub issue: RankIssue,
+    // Where the application was read from
+    pub provenance: Option<Provenance>,
+}
+
+/// Rank every competition by score again, set `computed_rank` and compare it with the list
+///
+/// Privileged applicants come first, applications without a score last and equal scores
+/// keep their published order. Run before lists are merged, as each list numbers its own
+/// applications.
+pub fn recompute(all_program_records: &mut [(String, Vec<StudentRecord>)]) -> Vec<RankDiscrepancy> {
+    let mut discrepancies = Vec::new();
+    for (program_name, records) in all_program_records.iter_mut() {
+        let mut competitions: BTreeMap<ProgramKey, Vec<usize>> = BTreeMap::new();
+        for (index, record) in records.iter().enumerate() {
+            competitions.entry(ProgramKey::of(program_name, record)).or_default().push(index);
+        }
+
+        for (program_key, mut listed) in competitions {
+            listed.sort_by_key(|&index| records[index].rank);
+            let mut rank_counts: HashMap<u32, usize> = HashMap::new();
+            for &index in &listed {
+                *rank_counts.entry(records[index].rank).or_default() += 1;
+            }
+            let mut scored = listed.clone();
+            scored.sort_by(|&a, &b| by_score(&records[a], &records[b]));
+
+            for (position, &index) in scored.iter().enumerate() {
+                let computed_rank = position as u32 + 1;
+                records[index].computed_rank = Some(computed_rank);
+
+                let listed_position = listed.iter().position(|&other| other == index).unwrap_or(position);
+                let previous_rank = listed_position.checked_sub(1).map_or(0, |previous| records[listed[previous]].rank);
+                let record = &records[index];
+                let issue = if rank_counts[&record.rank] > 1 {
+                    RankIssue::Duplicate
+                } else if listed_position != position {
+                    RankIssue::OutOfOrder
+                } else if record.rank > previous_rank + 1 {
+                    RankIssue::Gap
+                } else {
+                    continue;
+                };
+                discrepancies.push(RankDiscrepancy {
+                    program_key: program_key.clone(),
+                    snils: record.snils.clone(),
+                    published_rank: record.rank,
+                    computed_rank,
+                    issue,
+                    provenance: record.provenance.clone(),
+                });
+            }
+        }
+    }
+    discrepancies
+}
+
+/// Privileged first, then higher scores, then applications without a score
+fn by_score(a: &StudentRecord, b: &StudentRecord) -> Ordering {
+    b.privileged.cmp(&a.privileged).then_with(|| match (a.score, b.score) {
+        (Some(a), Some(b)) => b.total_cmp(&a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    })
+}
@@ -0,0 +1,106 @@
+use crate::analyzer::TargetOutcome;
+use crate::confidence::Confidence;
+use crate::models::{FundingSource, ProgramKey, StudentRecord};
+use crate::quota::QuotaTransfer;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// One application in a program list, as every report shows it
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportRow {
+    pub record: StudentRecord,
+    // Takes part in the simulation: original handed in, or consent without originals_only
+    pub eager: bool,
+    pub admitted: bool,
+    // 1-based position in the admission order if admitted
+    pub admission_position: Option<usize>,
+    pub target: bool,
+}
+
+/// A program-funding combination with its simulated cutoff, rows and the target's outcome
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramReport {
+    pub program_key: ProgramKey,
+    pub program_name: String,
+    // Empty with a single institution
+    pub institution: String,
+    pub funding_source: FundingSource,
+    pub available_places: u32,
+    // Places the list shows when seat_overrides changed `available_places`
+    pub published_places: Option<u32>,
+    pub total_eager_applicants: usize,
+    // Lowest score among admitted applicants, 0 if none were admitted
+    pub cutoff_score: f64,
+    // Eager applicants with exactly the cutoff score, admitted or not
+    pub cutoff_ties: usize,
+    pub projected_cutoff_score: Option<f64>,
+    // Unfilled quota places the combination receives on campaign.quota_transfer
+    pub quota_places_returned: Option<u32>,
+    // Cutoff once unfilled quota places have gone to the budget competitions
+    pub transfer_cutoff_score: Option<f64>,
+    pub admitted_count: usize,
+    // Every application to the combination, best rank first
+    pub rows: Vec<ReportRow>,
+    pub target: TargetOutcome,
+}
+
+impl ProgramReport {
+    pub fn admitted_rows(&self) -> impl Iterator<Item = &ReportRow> {
+        self.rows.iter().filter(|row| row.admitted)
+    }
+
+    pub fn target_row(&self) -> Option<&ReportRow> {
+        self.rows.iter().find(|row| row.target)
+    }
+}
+
+/// Everything the reports show, derived once from the simulation
+///
+/// Writers render from it instead of looking records and cutoffs up themselves, so every
+/// file shows the same numbers.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportModel {
+    pub target_snils: String,
+    // In popularity order
+    pub programs: Vec<ProgramReport>,
+}
+
+impl ReportModel {
+    pub fn program(&self, program_key: &ProgramKey) -> Option<&ProgramReport> {
+        self.programs.iter().find(|program| program.program_key == *program_key)
+    }
+
+    /// Take the cutoffs of `projected`, simulated with consent-only applicants enrolled too
+    pub fn set_projected(&mut self, projected: &ReportModel) {
+        for program in &mut self.programs {
+            program.projected_cutoff_score = projected.program(&program.program_key).map(|projected| projected.cutoff_score);
+            program.target.projected_cutoff_score = program.projected_cutoff_score;
+        }
+    }
+
+    /// Take the cutoffs of `transferred`, simulated with the unfilled quota places of
+    /// `transfers` moved to the budget competitions
+    pub fn set_quota_transfer(&mut self, transferred: &ReportModel, transfers: &[QuotaTransfer]) {
+        for program in &mut self.programs {
+            program.quota_places_returned = transfers
+                .iter()
+                .find(|transfer| transfer.program_key == program.program_key)
+                .map(|transfer| transfer.places);
+            program.transfer_cutoff_score = transferred.program(&program.program_key).map(|transferred| transferred.cutoff_score);
+            program.target.quota_places_returned = program.quota_places_returned;
+            program.target.transfer_cutoff_score = program.transfer_cutoff_score;
+        }
+    }
+
+    /// Attach the confidence assessed for each combination to the target's outcome there
+    pub fn set_confidence(&mut self, confidences: &BTreeMap<ProgramKey, Confidence>) {
+        for program in &mut self.programs {
+            program.target.confidence = confidences.get(&program.program_key).cloned();
+        }
+    }
+
+    /// The target's outcome in every combination, in popularity order
+    pub fn target_outcomes(&self) -> Vec<TargetOutcome> {
+        self.programs.iter().map(|program| program.target.clone()).collect()
+    }
+}
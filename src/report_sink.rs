@@ -0,0 +1,231 @@
+use crate::{analyzer, atomic_file, campaign, charts, cli, competitors, consistency, csv_output, csv_report, heatmap, html_report, markdown_report, models, pdf_report, program_aliases, program_dirs, storage, strategies, templates, xlsx_report, AnalyzedRun};
+use anyhow::Result;
+use models::OutputFormat;
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Destination of one output format, written from the analyzed run
+pub trait ReportSink: Sync {
+    fn write(&self, analyzed: &AnalyzedRun, output_dir: &str) -> Result<()>;
+
+    /// Whether the sink only writes its own files, so it can run alongside the others
+    fn independent(&self) -> bool {
+        true
+    }
+}
+
+/// Sinks of the formats in `settings`, in the order dependent sinks have to run
+pub fn sinks<'a>(settings: &'a cli::RunSettings, templates: &'a templates::ReportTemplates) -> Vec<Box<dyn ReportSink + 'a>> {
+    let formats = &settings.output_formats;
+    let mut sinks: Vec<Box<dyn ReportSink + 'a>> = Vec::new();
+    if formats.contains(&OutputFormat::Csv) {
+        sinks.push(Box::new(CsvSink { csv_dialect: &settings.csv_dialect, templates }));
+    }
+    if formats.contains(&OutputFormat::Json) {
+        sinks.push(Box::new(JsonSink));
+    }
+    if formats.contains(&OutputFormat::Html) {
+        sinks.push(Box::new(HtmlSink));
+    }
+    if formats.contains(&OutputFormat::Xlsx) {
+        sinks.push(Box::new(XlsxSink));
+    }
+    if formats.contains(&OutputFormat::Markdown) {
+        sinks.push(Box::new(MarkdownSink { templates }));
+    }
+    if formats.contains(&OutputFormat::Pdf) {
+        sinks.push(Box::new(PdfSink { font_path: settings.config.pdf_font.as_deref() }));
+    }
+    if formats.contains(&OutputFormat::Heatmap) {
+        sinks.push(Box::new(HeatmapSink { csv_dialect: &settings.csv_dialect }));
+    }
+    // Charts read the history the database has just been given
+    if formats.contains(&OutputFormat::Sqlite) {
+        sinks.push(Box::new(SqliteSink { database_path: settings.database_path() }));
+    }
+    if formats.contains(&OutputFormat::Charts) {
+        sinks.push(Box::new(ChartsSink { database_path: settings.database_path() }));
+    }
+    sinks
+}
+
+/// Write independent sinks in parallel, then the others in order
+pub fn write_all(sinks: &[Box<dyn ReportSink + '_>], analyzed: &AnalyzedRun, output_dir: &str) -> Result<()> {
+    sinks
+        .par_iter()
+        .filter(|sink| sink.independent())
+        .try_for_each(|sink| sink.write(analyzed, output_dir))?;
+    for sink in sinks.iter().filter(|sink| !sink.independent()) {
+        sink.write(analyzed, output_dir)?;
+    }
+    Ok(())
+}
+
+/// CSV tables and the text reports rendered from templates
+pub struct CsvSink<'a> {
+    csv_dialect: &'a csv_output::CsvDialect,
+    templates: &'a templates::ReportTemplates,
+}
+
+impl ReportSink for CsvSink<'_> {
+    fn write(&self, analyzed: &AnalyzedRun, output_dir: &str) -> Result<()> {
+        csv_report::generate_csv_reports(self.csv_dialect, self.templates, analyzed, output_dir)
+    }
+}
+
+/// Complete analysis as a single analysis.json for other tools
+pub struct JsonSink;
+
+impl ReportSink for JsonSink {
+    fn write(&self, analyzed: &AnalyzedRun, output_dir: &str) -> Result<()> {
+        #[derive(serde::Serialize)]
+        struct JsonReport<'a> {
+            target_snils: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            campaign: Option<&'a campaign::CampaignStatus>,
+            #[serde(skip_serializing_if = "<[_]>::is_empty")]
+            institutions: &'a [models::InstitutionOptions],
+            #[serde(flatten)]
+            analysis: &'a analyzer::AdmissionAnalysis,
+            target_outcomes: &'a [analyzer::TargetOutcome],
+            #[serde(skip_serializing_if = "Option::is_none")]
+            best_option: Option<&'a analyzer::TargetOutcome>,
+            admission_distances: &'a [analyzer::AdmissionDistance],
+            competitors: &'a [competitors::Competitor],
+            boundary: &'a [competitors::BoundaryApplicant],
+            strategies: &'a [strategies::Strategy],
+            flows: &'a [strategies::Flow],
+            inconsistencies: &'a [consistency::Inconsistency],
+            #[serde(skip_serializing_if = "<[_]>::is_empty")]
+            program_merges: &'a [program_aliases::ProgramMerge],
+        }
+
+        let report = JsonReport {
+            target_snils: &analyzed.target_snils,
+            campaign: analyzed.campaign.as_ref(),
+            institutions: &analyzed.institutions,
+            analysis: &analyzed.analysis,
+            target_outcomes: &analyzed.target_outcomes,
+            best_option: analyzer::best_option(&analyzed.target_outcomes),
+            admission_distances: &analyzed.admission_distances,
+            competitors: &analyzed.competitors,
+            boundary: &analyzed.boundary,
+            strategies: &analyzed.strategies,
+            flows: &analyzed.flows,
+            inconsistencies: &analyzed.inconsistencies,
+            program_merges: &analyzed.program_merges,
+        };
+
+        let content = serde_json::to_string_pretty(&report)?;
+        atomic_file::write(Path::new(output_dir).join("analysis.json"), content)?;
+        let sankey = strategies::sankey_json(&analyzed.flows, &t!("flows.no_seat"));
+        atomic_file::write(Path::new(output_dir).join("applicant_flows.json"), serde_json::to_string_pretty(&sankey)?)?;
+        Ok(())
+    }
+}
+
+pub struct HtmlSink;
+
+impl ReportSink for HtmlSink {
+    fn write(&self, analyzed: &AnalyzedRun, output_dir: &str) -> Result<()> {
+        html_report::generate_html_report(
+            &analyzed.target_snils,
+            analyzed.campaign.as_ref(),
+            &analyzed.report,
+            &analyzed.target_outcomes,
+            &analyzed.flows,
+            output_dir,
+        )
+    }
+}
+
+pub struct XlsxSink;
+
+impl ReportSink for XlsxSink {
+    fn write(&self, analyzed: &AnalyzedRun, output_dir: &str) -> Result<()> {
+        xlsx_report::generate_xlsx_report(&analyzed.report, &analyzed.target_outcomes, output_dir)
+    }
+}
+
+pub struct MarkdownSink<'a> {
+    templates: &'a templates::ReportTemplates,
+}
+
+impl ReportSink for MarkdownSink<'_> {
+    fn write(&self, analyzed: &AnalyzedRun, output_dir: &str) -> Result<()> {
+        markdown_report::generate_markdown_summary(
+            self.templates,
+            &analyzed.target_snils,
+            analyzed.campaign.as_ref(),
+            &analyzed.target_outcomes,
+            output_dir,
+        )
+    }
+}
+
+/// One printable page with the verdict, for discussing the options offline
+pub struct PdfSink<'a> {
+    font_path: Option<&'a str>,
+}
+
+impl ReportSink for PdfSink<'_> {
+    fn write(&self, analyzed: &AnalyzedRun, output_dir: &str) -> Result<()> {
+        pdf_report::generate_pdf_verdict(
+            &analyzed.target_snils,
+            analyzed.campaign.as_ref(),
+            &analyzed.target_outcomes,
+            &analyzed.all_program_records,
+            self.font_path,
+            output_dir,
+        )
+    }
+}
+
+/// Programs against places, eager applicants, cutoffs and seats not yet claimed by originals
+pub struct HeatmapSink<'a> {
+    csv_dialect: &'a csv_output::CsvDialect,
+}
+
+impl ReportSink for HeatmapSink<'_> {
+    fn write(&self, analyzed: &AnalyzedRun, output_dir: &str) -> Result<()> {
+        heatmap::generate_seat_heatmap(self.csv_dialect, &analyzed.report, output_dir)
+    }
+}
+
+/// Run history in the SQLite database, shared by all runs
+pub struct SqliteSink {
+    database_path: PathBuf,
+}
+
+impl ReportSink for SqliteSink {
+    fn write(&self, analyzed: &AnalyzedRun, _output_dir: &str) -> Result<()> {
+        storage::Storage::open(&self.database_path)?.save_run(
+            &analyzed.target_snils,
+            &analyzed.analysis,
+            &analyzed.report,
+            &analyzed.target_outcomes,
+            &analyzed.all_program_records,
+        )?;
+        Ok(())
+    }
+
+    fn independent(&self) -> bool {
+        false
+    }
+}
+
+/// Histograms and the cutoff trend from the run history
+pub struct ChartsSink {
+    database_path: PathBuf,
+}
+
+impl ReportSink for ChartsSink {
+    fn write(&self, analyzed: &AnalyzedRun, output_dir: &str) -> Result<()> {
+        let program_dirs = program_dirs::ProgramDirectories::of(analyzed);
+        charts::generate_charts(&analyzed.report, &program_dirs, output_dir, &self.database_path)
+    }
+
+    fn independent(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,71 @@
+use crate::atomic_file;
+use crate::error::StorageError;
+use crate::http_cache::CachedPage;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File in the output root with the pages of a scrape that has not finished
+pub const PROGRESS_FILE: &str = "scrape_progress.json";
+
+/// A page fetched by the scrape and when
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchedPage {
+    pub fetched_at: DateTime<Local>,
+    pub page: CachedPage,
+}
+
+/// Pages fetched so far, saved after each one so an interrupted scrape can resume
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScrapeProgress {
+    #[serde(skip)]
+    path: PathBuf,
+    pages: BTreeMap<String, FetchedPage>,
+}
+
+impl ScrapeProgress {
+    /// A new scrape in `output_root`, forgetting the pages of an unfinished one
+    pub fn start(output_root: &Path) -> Self {
+        let path = output_root.join(PROGRESS_FILE);
+        let _ = fs::remove_file(&path);
+        Self { path, pages: BTreeMap::new() }
+    }
+
+    /// The unfinished scrape in `output_root`, empty if there is none or it cannot be read
+    pub fn resume(output_root: &Path) -> Self {
+        let path = output_root.join(PROGRESS_FILE);
+        let pages = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<ScrapeProgress>(&content).ok())
+            .map(|progress| progress.pages)
+            .unwrap_or_default();
+        Self { path, pages }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// The page fetched from `url` before the scrape was interrupted
+    pub fn get(&self, url: &str) -> Option<&FetchedPage> {
+        self.pages.get(url)
+    }
+
+    /// Keep the page just fetched from `url` and save the progress
+    pub fn record(&mut self, url: &str, page: &CachedPage) -> Result<(), StorageError> {
+        self.pages.insert(url.to_string(), FetchedPage { fetched_at: Local::now(), page: page.clone() });
+        let content = serde_json::to_string(self)?;
+        atomic_file::write(&self.path, content).map_err(|cause| StorageError::Write { path: self.path.display().to_string(), cause })
+    }
+
+    /// The scrape got every page, nothing is left to resume
+    pub fn finish(self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
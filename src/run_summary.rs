@@ -0,0 +1,113 @@
+use crate::atomic_file;
+use crate::checksums::SourceChecksums;
+use crate::discovery::DiscoveredLists;
+use crate::events::EventLog;
+use crate::http_cache::HttpCache;
+use crate::layout::SourceLayouts;
+use crate::resume::ScrapeProgress;
+use crate::models::{normalize_snils, StudentRecord};
+use crate::error::StorageError;
+use chrono::{DateTime, Local};
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// Statistics of a single run, printed at the end and written to run_summary.txt
+#[derive(Default)]
+pub struct RunSummary {
+    pub sources_processed: usize,
+    pub sources_failed: usize,
+    pub programs_found: usize,
+    pub total_applications: usize,
+    pub duplicates_removed: usize,
+    pub withdrawn: usize,
+    // Checksum of every loaded source
+    pub checksums: SourceChecksums,
+    // Pages of the last fetch going in, of this one coming out; conditional requests are made for those in it
+    pub http_cache: HttpCache,
+    // List links of every index page, those of the last run going in
+    pub discovered: DiscoveredLists,
+    // Table columns of every source, those of the last run going in
+    pub layouts: SourceLayouts,
+    // Sources whose columns differ from those of the last run
+    pub layout_changes: BTreeSet<String>,
+    // Pages fetched so far when the scrape can be resumed, those of the interrupted one going in
+    pub scrape_progress: Option<ScrapeProgress>,
+    // Written to events.jsonl next to run_summary.txt
+    pub events: EventLog,
+    unique_applicants: HashSet<String>,
+    data_from: Option<DateTime<Local>>,
+    data_to: Option<DateTime<Local>>,
+    phases: Vec<(String, Duration)>,
+}
+
+impl RunSummary {
+    /// Account for a successfully parsed source and the time its data was taken
+    pub fn add_source(&mut self, data_time: DateTime<Local>) {
+        self.sources_processed += 1;
+        self.data_from = Some(self.data_from.map_or(data_time, |from| from.min(data_time)));
+        self.data_to = Some(self.data_to.map_or(data_time, |to| to.max(data_time)));
+    }
+
+    /// Account for a parsed program list after deduplication
+    pub fn add_program(&mut self, records: &[StudentRecord], duplicates_removed: usize) {
+        self.programs_found += 1;
+        self.duplicates_removed += duplicates_removed;
+        self.total_applications += records.len();
+        self.withdrawn += records.iter().filter(|record| record.withdrawn).count();
+        for record in records {
+            self.unique_applicants.insert(normalize_snils(&record.snils));
+        }
+    }
+
+    /// Record the wall-clock time of a phase started at `started`
+    pub fn finish_phase(&mut self, name: String, started: Instant) {
+        self.phases.push((name, started.elapsed()));
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let time_format = "%Y-%m-%d %H:%M:%S";
+        let data_range = match (self.data_from, self.data_to) {
+            (Some(from), Some(to)) => t!("summary.data_range_value", from.format(time_format), to.format(time_format)),
+            _ => "-".to_string(),
+        };
+
+        let mut lines = vec![
+            t!("summary.sources", self.sources_processed, self.sources_failed),
+            t!("summary.programs", self.programs_found),
+            t!("summary.applicants", self.total_applications, self.unique_applicants.len()),
+            t!("summary.duplicates", self.duplicates_removed),
+            t!("summary.withdrawn", self.withdrawn),
+            t!("summary.data_range", data_range),
+            t!("summary.phases"),
+        ];
+        for (name, duration) in &self.phases {
+            lines.push(format!("   {}: {:.2}s", name, duration.as_secs_f64()));
+        }
+        lines
+    }
+
+    /// Print the summary to the console
+    pub fn print(&self) {
+        info!("{}", t!("summary.title"));
+        for line in self.lines() {
+            info!("{}", line);
+        }
+    }
+
+    /// Print the summary and write it to run_summary.txt
+    pub fn report(&self, output_dir: &str) -> Result<(), StorageError> {
+        self.print();
+        let title = t!("summary.title");
+        let lines = self.lines();
+
+        let mut content = format!("{}\n{}\n\n", title, "=".repeat(title.chars().count()));
+        for line in &lines {
+            content.push_str(line);
+            content.push('\n');
+        }
+        let path = Path::new(output_dir).join("run_summary.txt");
+        atomic_file::write(&path, content).map_err(|cause| StorageError::Write { path: path.display().to_string(), cause })
+    }
+}
@@ -0,0 +1,137 @@
+use crate::models::Config;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use chrono_tz::Tz;
+use clap::ArgMatches;
+use std::str::FromStr;
+
+const DEFAULT_INTERVAL_MINUTES: u64 = 30;
+
+/// When watch mode runs the pipeline
+pub enum Schedule {
+    // Minutes between the end of one run and the start of the next
+    Interval(u64),
+    // Cron times in the given time zone, system time zone if `None`
+    Cron {
+        expression: String,
+        schedule: Box<cron::Schedule>,
+        timezone: Option<Tz>,
+    },
+}
+
+impl Schedule {
+    /// `--interval`, then `--schedule`, then the `[schedule]` table, then every 30 minutes
+    pub fn from_matches(config: &Config, matches: &ArgMatches) -> Result<Self> {
+        if let Some(minutes) = matches.get_one::<u64>("interval") {
+            return Ok(Self::Interval(*minutes));
+        }
+        let timezone = matches
+            .get_one::<String>("timezone")
+            .map(String::as_str)
+            .or_else(|| config.schedule.as_ref().and_then(|options| options.timezone.as_deref()));
+        match (matches.get_one::<String>("schedule"), &config.schedule) {
+            (Some(expression), _) => Self::cron(expression, timezone),
+            (None, Some(options)) => Self::cron(&options.cron, timezone),
+            (None, None) => Ok(Self::Interval(DEFAULT_INTERVAL_MINUTES)),
+        }
+    }
+
+    /// Parse a five-field cron expression, or a six/seven-field one starting with seconds
+    pub fn cron(expression: &str, timezone: Option<&str>) -> Result<Self> {
+        let full_expression = if expression.split_whitespace().count() == 5 {
+            format!("0 {}", expression)
+        } else {
+            expression.to_string()
+        };
+        let schedule = cron::Schedule::from_str(&full_expression)
+            .with_context(|| format!("Invalid cron schedule: {}", expression))?;
+        let timezone = timezone
+            .map(|name| name.parse::<Tz>().map_err(|_| anyhow!("Unknown time zone: {}", name)))
+            .transpose()?;
+        Ok(Self::Cron {
+            expression: expression.to_string(),
+            schedule: Box::new(schedule),
+            timezone,
+        })
+    }
+
+    /// Start message for the log
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Interval(minutes) => t!("watch.started", minutes),
+            Self::Cron { expression, timezone, .. } => t!(
+                "watch.started_schedule",
+                expression,
+                timezone.map(|tz| tz.to_string()).unwrap_or_else(|| t!("watch.local_time"))
+            ),
+        }
+    }
+
+    /// Interval schedules run right away, cron schedules wait for their first time
+    pub fn first_run(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Interval(_) => Some(now),
+            Self::Cron { .. } => self.next_run(now),
+        }
+    }
+
+    /// Start of the next run after a run finished at `now`; `None` if the schedule has no more times
+    pub fn next_run(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Interval(minutes) => Some(now + chrono::Duration::minutes(*minutes as i64)),
+            Self::Cron { schedule, timezone: Some(tz), .. } => {
+                schedule.after(&now.with_timezone(tz)).next().map(|time| time.with_timezone(&Utc))
+            }
+            Self::Cron { schedule, timezone: None, .. } => {
+                schedule.after(&now.with_timezone(&Local)).next().map(|time| time.with_timezone(&Utc))
+            }
+        }
+    }
+
+    /// Time in the schedule's time zone for log messages
+    pub fn format_time(&self, time: DateTime<Utc>) -> String {
+        match self {
+            Self::Cron { timezone: Some(tz), .. } => time.with_timezone(tz).format("%Y-%m-%d %H:%M %Z").to_string(),
+            _ => time.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+        }
+    }
+}
+
+/// Stop request from Ctrl-C or, on Unix, SIGTERM from systemd or docker
+///
+/// Handlers are installed on creation, so a signal arriving during a run is not lost.
+pub struct Shutdown {
+    #[cfg(unix)]
+    interrupt: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl Shutdown {
+    #[cfg(unix)]
+    pub fn new() -> Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+        Ok(Self {
+            interrupt: signal(SignalKind::interrupt())?,
+            terminate: signal(SignalKind::terminate())?,
+        })
+    }
+
+    #[cfg(not(unix))]
+    pub fn new() -> Result<Self> {
+        Ok(Self {})
+    }
+
+    #[cfg(unix)]
+    pub async fn recv(&mut self) {
+        tokio::select! {
+            _ = self.interrupt.recv() => {}
+            _ = self.terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub async fn recv(&mut self) {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
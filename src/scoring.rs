@@ -0,0 +1,178 @@
+use crate::error::ConfigError;
+use crate::models::{parse_score, Rounding, ScoreStrategyKind, ScoringRule, StudentRecord};
+use std::sync::Arc;
+
+/// How a program scores its applicants
+///
+/// Each program seats its applicants in the simulation by this score, and its cutoff and
+/// average score are taken from it, so every program is judged the way it actually ranks.
+/// Scores of programs with different strategies are never compared with each other.
+pub trait ScoreStrategy: Send + Sync {
+    /// Score of the application without individual achievements, `None` if the list does not give it
+    fn score(&self, record: &StudentRecord) -> Option<f64>;
+}
+
+/// Average certificate score, as printed in the list
+pub struct AverageScore;
+
+impl ScoreStrategy for AverageScore {
+    fn score(&self, record: &StudentRecord) -> Option<f64> {
+        parse_score(&record.average_score)
+    }
+}
+
+/// Sum of the subject or entrance test scores
+pub struct SubjectSum;
+
+impl ScoreStrategy for SubjectSum {
+    fn score(&self, record: &StudentRecord) -> Option<f64> {
+        subject_sum(record)
+    }
+}
+
+/// Average score and subject sum, each multiplied by its weight
+pub struct Weighted {
+    pub average_weight: f64,
+    pub subjects_weight: f64,
+}
+
+impl ScoreStrategy for Weighted {
+    fn score(&self, record: &StudentRecord) -> Option<f64> {
+        Some(self.average_weight * AverageScore.score(record)? + self.subjects_weight * subject_sum(record)?)
+    }
+}
+
+/// Sum of the subject scores, printed separated by spaces or semicolons, e.g. "5 4 5"
+pub fn subject_sum(record: &StudentRecord) -> Option<f64> {
+    let scores: Option<Vec<f64>> = record
+        .subject_scores
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|part| !part.is_empty())
+        .map(parse_score)
+        .collect();
+    scores.filter(|scores| !scores.is_empty()).map(|scores| scores.iter().sum())
+}
+
+/// Conversion of a strategy's score to the scale other programs are scored on
+///
+/// The score is scaled first, then rounded, then held between the floor and the cap.
+#[derive(Debug, Clone, Default)]
+pub struct ScoreTransform {
+    // (from low, from high) and (to low, to high)
+    pub scale: Option<([f64; 2], [f64; 2])>,
+    pub round: Option<(u32, Rounding)>,
+    pub floor: Option<f64>,
+    pub cap: Option<f64>,
+}
+
+impl ScoreTransform {
+    fn new(rule: &ScoringRule) -> Result<Self, ConfigError> {
+        let invalid = |problem: &str| ConfigError::Invalid(format!("Scoring rule for \"{}\": {}", rule.programs, problem));
+        let scale = match (rule.scale_from, rule.scale_to) {
+            (None, None) => None,
+            (Some(from), Some(to)) => {
+                if from.iter().chain(&to).any(|bound| !bound.is_finite()) || from[0] == from[1] {
+                    return Err(invalid("scale_from must be two different numbers and scale_to two numbers"));
+                }
+                Some((from, to))
+            }
+            _ => return Err(invalid("scale_from and scale_to must be set together")),
+        };
+        if let (Some(floor), Some(cap)) = (rule.floor, rule.cap) {
+            if floor > cap {
+                return Err(invalid("floor must not be above cap"));
+            }
+        }
+        Ok(Self {
+            scale,
+            round: rule.round_to.map(|decimals| (decimals, rule.rounding.unwrap_or_default())),
+            floor: rule.floor,
+            cap: rule.cap,
+        })
+    }
+
+    pub fn apply(&self, score: f64) -> f64 {
+        let mut score = match self.scale {
+            Some((from, to)) => to[0] + (score - from[0]) * (to[1] - to[0]) / (from[1] - from[0]),
+            None => score,
+        };
+        if let Some((decimals, rounding)) = self.round {
+            let factor = 10f64.powi(decimals as i32);
+            let scaled = score * factor;
+            // Undo the float error of the scaling, so 4.35 * 10 rounds as 43.5 and not 43.4999…
+            let scaled = (scaled * 1e9).round() / 1e9;
+            score = match rounding {
+                Rounding::Nearest => scaled.round(),
+                Rounding::Down => scaled.floor(),
+                Rounding::Up => scaled.ceil(),
+            } / factor;
+        }
+        if let Some(floor) = self.floor {
+            score = score.max(floor);
+        }
+        if let Some(cap) = self.cap {
+            score = score.min(cap);
+        }
+        score
+    }
+}
+
+/// Strategies and score conversions of the `[[scoring]]` rules, looked up by program name
+#[derive(Clone, Default)]
+pub struct ScoringRules {
+    // Lowercase program name fragment, its strategy and conversion, in configuration order
+    rules: Vec<(String, Arc<dyn ScoreStrategy>, ScoreTransform)>,
+}
+
+impl ScoringRules {
+    pub fn new(rules: Option<&[ScoringRule]>) -> Result<Self, ConfigError> {
+        let mut scoring = Self::default();
+        for rule in rules.unwrap_or_default() {
+            let strategy: Arc<dyn ScoreStrategy> = match rule.strategy {
+                ScoreStrategyKind::Average => Arc::new(AverageScore),
+                ScoreStrategyKind::SubjectSum => Arc::new(SubjectSum),
+                ScoreStrategyKind::Weighted => {
+                    let average_weight = rule.average_weight.unwrap_or(1.0);
+                    let subjects_weight = rule.subjects_weight.unwrap_or(1.0);
+                    if [average_weight, subjects_weight].iter().any(|weight| !weight.is_finite() || *weight < 0.0) {
+                        return Err(ConfigError::Invalid(format!(
+                            "Scoring weights of \"{}\" must be non-negative numbers",
+                            rule.programs
+                        )));
+                    }
+                    Arc::new(Weighted { average_weight, subjects_weight })
+                }
+            };
+            scoring.rules.push((rule.programs.to_lowercase(), strategy, ScoreTransform::new(rule)?));
+        }
+        Ok(scoring)
+    }
+
+    /// Strategy and conversion of the first rule whose fragment is in `program_name`, `None`
+    /// if no rule matches
+    pub fn strategy(&self, program_name: &str) -> Option<(&dyn ScoreStrategy, &ScoreTransform)> {
+        let program_name = program_name.to_lowercase();
+        self.rules
+            .iter()
+            .find(|(fragment, _, _)| program_name.contains(fragment.as_str()))
+            .map(|(_, strategy, transform)| (strategy.as_ref(), transform))
+    }
+
+    /// Rescore the records of programs with a rule, returning how many program lists that was
+    ///
+    /// Programs without one keep the score they were loaded with. Achievement points already
+    /// counted stay counted, added to the converted score.
+    pub fn apply(&self, all_program_records: &mut [(String, Vec<StudentRecord>)]) -> usize {
+        let mut rescored = 0;
+        for (program_name, records) in all_program_records.iter_mut() {
+            let Some((strategy, transform)) = self.strategy(program_name) else {
+                continue;
+            };
+            for record in records.iter_mut() {
+                record.score = strategy.score(record).map(|score| transform.apply(score) + record.bonus);
+            }
+            rescored += 1;
+        }
+        rescored
+    }
+}
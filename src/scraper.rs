@@ -1,43 +1,222 @@
-use crate::models::{ProgramInfo, StudentRecord};
-use anyhow::{Context, Result};
+use crate::models::{parse_score, FundingSource, ProgramInfo, Provenance, RowMerge, StudentRecord, StudyForm};
+use crate::error::{ParseError, Result, ScrapeError};
+use crate::privileged::PRIVILEGED_MARKERS;
+use encoding_rs::{Encoding, UTF_8};
+use lol_html::{element, send, text, AsciiCompatibleEncoding};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
 use regex::Regex;
 use scraper::{Html, Selector};
-use std::fs;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing::{debug, warn};
+#[cfg(feature = "network")]
+use crate::{http_cache::CachedPage, models::IndexPageOptions};
+#[cfg(feature = "network")]
+use flate2::write::{DeflateDecoder, GzDecoder, ZlibDecoder};
+#[cfg(feature = "network")]
+use reqwest::{header, StatusCode};
+#[cfg(feature = "network")]
+use std::{io::{self, Write}, path::PathBuf, sync::atomic::{AtomicUsize, Ordering}};
+#[cfg(feature = "network")]
+use tracing::info;
 
+/// Pages larger than this are parsed as a stream instead of being loaded into a DOM
+pub const DEFAULT_STREAMING_THRESHOLD_MB: u64 = 16;
+
+/// Responses larger than this, after decompression, are refused
+pub const DEFAULT_MAX_RESPONSE_MB: u64 = 256;
+
+// Lower-case text of a row that marks the application as withdrawn
+const WITHDRAWN_MARKERS: &[&str] = &["отозван", "отзыв заявления"];
+
+// Lower-case text of a section row returning to the competition the program header names
+const GENERAL_SECTION_MARKERS: &[&str] = &["общий конкурс", "общие места", "основные места", "на общих основаниях"];
+
+// Lower-case text of a row summing up the rows above it
+const SUBTOTAL_MARKERS: &[&str] = &["итого", "всего"];
+
+/// Parses applicant lists from saved HTML pages or URLs
+#[derive(Clone)]
 pub struct AdmissionScraper {
+    #[cfg(feature = "network")]
     client: reqwest::Client,
+    streaming_threshold: u64,
+    // 0 for no limit
+    #[cfg(feature = "network")]
+    max_response: u64,
+    row_merge: RowMerge,
+}
+
+impl Default for AdmissionScraper {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AdmissionScraper {
     pub fn new() -> Self {
         Self {
+            #[cfg(feature = "network")]
             client: reqwest::Client::new(),
+            streaming_threshold: DEFAULT_STREAMING_THRESHOLD_MB * 1024 * 1024,
+            #[cfg(feature = "network")]
+            max_response: DEFAULT_MAX_RESPONSE_MB * 1024 * 1024,
+            row_merge: RowMerge::Off,
         }
     }
 
+    /// Join the rows of one applicant as `row_merge` says before reading them
+    pub fn with_row_merge(mut self, row_merge: RowMerge) -> Self {
+        self.row_merge = row_merge;
+        self
+    }
+
+    /// Stream pages larger than `megabytes` instead of loading them into a DOM
+    pub fn with_streaming_threshold_mb(mut self, megabytes: u64) -> Self {
+        self.streaming_threshold = megabytes * 1024 * 1024;
+        self
+    }
+
+    #[cfg(feature = "network")]
+    /// Refuse responses larger than `megabytes` once decompressed, 0 for no limit
+    pub fn with_max_response_mb(mut self, megabytes: u64) -> Self {
+        self.max_response = megabytes * 1024 * 1024;
+        self
+    }
+
+    /// Parse all program lists found in a local HTML file
     pub fn scrape_file(&self, file_path: &str) -> Result<Vec<(ProgramInfo, Vec<StudentRecord>)>> {
-        let content = fs::read_to_string(file_path)
-            .with_context(|| format!("Failed to read file: {}", file_path))?;
+        let size = fs::metadata(file_path).map_err(|cause| read_error(file_path, cause))?.len();
+        if size > self.streaming_threshold {
+            debug!("{}", t!("scraper.streaming", file_path));
+            return self.stream_file(Path::new(file_path), UTF_8, file_path);
+        }
+
+        let content = fs::read_to_string(file_path).map_err(|cause| read_error(file_path, cause))?;
+
+        self.parse_html_content(&content, file_path)
+    }
+
+    /// Parse a large file chunk by chunk; memory grows with the rows found, not the page size
+    ///
+    /// `source` names the page in records and errors, the URL for a spooled response.
+    fn stream_file(&self, path: &Path, encoding: &'static Encoding, source: &str) -> Result<Vec<(ProgramInfo, Vec<StudentRecord>)>> {
+        let file_path = path.display().to_string();
+        let mut file = File::open(path).map_err(|cause| read_error(&file_path, cause))?;
+        let page = Mutex::new(StreamedPage::default());
+        let mut rewriter = page_rewriter(&page, encoding);
+        let mut buffer = vec![0; STREAM_CHUNK_SIZE];
+        loop {
+            let read = file.read(&mut buffer).map_err(|cause| read_error(&file_path, cause))?;
+            if read == 0 {
+                break;
+            }
+            rewriter.write(&buffer[..read]).map_err(|e| ParseError::new(source, e))?;
+        }
+        rewriter.end().map_err(|e| ParseError::new(source, e))?;
 
-        self.parse_html_content(&content, Some(file_path))
+        Ok(self.programs_from_stream(page.into_inner().unwrap(), source))
     }
 
+    #[cfg(feature = "network")]
+    /// Fetch a page and parse all program lists found in it
     pub async fn scrape_url(&self, url: &str) -> Result<Vec<(ProgramInfo, Vec<StudentRecord>)>> {
-        println!("🌐 Fetching data from: {}", url);
-        
-        let response = self.client
+        Ok(self.scrape_url_cached(url, None).await?.programs)
+    }
+
+    #[cfg(feature = "network")]
+    /// Fetch a page unless it is unchanged since `cached` was fetched
+    ///
+    /// The request carries `If-None-Match` and `If-Modified-Since` from `cached`; when the
+    /// server answers 304 Not Modified the cached lists are returned without parsing anything.
+    pub async fn scrape_url_cached(&self, url: &str, cached: Option<&CachedPage>) -> Result<CachedPage> {
+        info!("{}", t!("scraper.fetching", url));
+
+        let mut request = self
+            .client
+            .get(url)
+            .header(header::ACCEPT_ENCODING, ACCEPTED_ENCODINGS)
+            .timeout(std::time::Duration::from_secs(30));
+        if let Some(cached) = cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = request.send().await.map_err(|cause| fetch_error(url, cause))?;
+
+        if let (StatusCode::NOT_MODIFIED, Some(cached)) = (response.status(), cached) {
+            info!("{}", t!("scraper.not_modified", url));
+            return Ok(cached.clone());
+        }
+        if !response.status().is_success() {
+            return Err(ScrapeError::Status { url: url.to_string(), status: response.status() }.into());
+        }
+
+        let validator = |name| response.headers().get(name).and_then(|value| value.to_str().ok()).map(str::to_string);
+        let etag = validator(header::ETAG);
+        let last_modified = validator(header::LAST_MODIFIED);
+        let programs = self.read_response(url, response).await?;
+        Ok(CachedPage { etag, last_modified, programs })
+    }
+
+    #[cfg(feature = "network")]
+    /// Fetch an index page and return the absolute URLs of its links that lead to program lists
+    pub async fn discover_links(&self, index_page: &IndexPageOptions) -> Result<Vec<String>> {
+        let url = index_page.url.as_str();
+        info!("{}", t!("scraper.fetching", url));
+        let response = self
+            .client
             .get(url)
+            .header(header::ACCEPT_ENCODING, ACCEPTED_ENCODINGS)
             .timeout(std::time::Duration::from_secs(30))
             .send()
             .await
-            .with_context(|| format!("Failed to fetch URL: {}", url))?;
-
+            .map_err(|cause| fetch_error(url, cause))?;
         if !response.status().is_success() {
-            return Err(anyhow::anyhow!("HTTP request failed with status: {}", response.status()));
+            return Err(ScrapeError::Status { url: url.to_string(), status: response.status() }.into());
+        }
+        // Relative links resolve against the page after redirects
+        let base = response.url().clone();
+        let encoding = response_encoding(&response);
+        let content = match self.read_body(url, response).await? {
+            Body::Memory(body) => encoding.decode(&body).0.into_owned(),
+            Body::Spooled(file) => encoding.decode(&fs::read(&file.path).map_err(|cause| read_error(&file.path.display().to_string(), cause))?).0.into_owned(),
+        };
+
+        let document = Html::parse_document(&content);
+        let link_selector = Selector::parse("a[href]").unwrap();
+        let mut links: Vec<String> = Vec::new();
+        for link in document.select(&link_selector) {
+            let Some(mut target) = link.value().attr("href").and_then(|href| base.join(href.trim()).ok()) else {
+                continue;
+            };
+            target.set_fragment(None);
+            let target = target.to_string();
+            let is_page = target.starts_with("http://") || target.starts_with("https://");
+            if is_page && target != base.as_str() && index_page.matches_link(&target) && !links.contains(&target) {
+                links.push(target);
+            }
         }
+        Ok(links)
+    }
 
-        let content = response.text().await
-            .with_context(|| format!("Failed to read response body from: {}", url))?;
+    #[cfg(feature = "network")]
+    /// Parse the body of a successful response
+    async fn read_response(&self, url: &str, response: reqwest::Response) -> Result<Vec<(ProgramInfo, Vec<StudentRecord>)>> {
+        let encoding = response_encoding(&response);
+        let body = match self.read_body(url, response).await? {
+            Body::Memory(body) => body,
+            Body::Spooled(file) => {
+                debug!("{}", t!("scraper.streaming", url));
+                return self.stream_file(&file.path, encoding, url);
+            }
+        };
+        let (content, _, _) = encoding.decode(&body);
 
         // Look for the data-wrap div specifically
         let document = Html::parse_document(&content);
@@ -46,30 +225,122 @@ impl AdmissionScraper {
         if let Some(data_wrap) = document.select(&data_wrap_selector).next() {
             // Create a new document from just the data-wrap content
             let data_wrap_html = data_wrap.html();
-            println!("   ✅ Found data-wrap section ({} chars)", data_wrap_html.len());
-            self.parse_html_content(&data_wrap_html, Some(url))
+            debug!("{}", t!("scraper.data_wrap_found", data_wrap_html.len()));
+            self.parse_html_content(&data_wrap_html, url)
         } else {
-            println!("   ⚠️  No data-wrap section found, parsing entire document");
-            self.parse_html_content(&content, Some(url))
+            debug!("{}", t!("scraper.data_wrap_missing"));
+            self.parse_html_content(&content, url)
         }
     }
 
-    fn parse_html_content(&self, content: &str, source: Option<&str>) -> Result<Vec<(ProgramInfo, Vec<StudentRecord>)>> {
+    #[cfg(feature = "network")]
+    /// Download and decompress a response body, in memory up to the streaming threshold and
+    /// into a temporary file beyond it
+    ///
+    /// A body over the response limit, cut short or not decompressing is an error rather
+    /// than a page with some of its lists missing.
+    async fn read_body(&self, url: &str, mut response: reqwest::Response) -> Result<Body> {
+        let too_large = || ScrapeError::TooLarge { url: url.to_string(), limit_mb: self.max_response / 1024 / 1024 };
+        let declared_length = response.content_length();
+        if self.max_response > 0 && declared_length.is_some_and(|length| length > self.max_response) {
+            return Err(too_large().into());
+        }
+        let content_encoding = response
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("identity")
+            .trim()
+            .to_lowercase();
+        let mut decoder = BodyDecoder::new(&content_encoding)
+            .ok_or_else(|| ScrapeError::Encoding { url: url.to_string(), encoding: content_encoding.clone() })?;
+        let damaged = |cause| ScrapeError::Damaged { url: url.to_string(), cause };
+
+        let mut received = 0;
+        let mut body = Body::Memory(Vec::new());
+        loop {
+            let decoded = match response.chunk().await.map_err(|cause| fetch_error(url, cause))? {
+                Some(chunk) => {
+                    received += chunk.len() as u64;
+                    decoder.decode(&chunk).map_err(damaged)?
+                }
+                None => {
+                    let rest = std::mem::replace(&mut decoder, BodyDecoder::Identity).finish().map_err(damaged)?;
+                    if rest.is_empty() {
+                        break;
+                    }
+                    rest
+                }
+            };
+            body.append(&decoded)?;
+            if self.max_response > 0 && body.len() > self.max_response {
+                return Err(too_large().into());
+            }
+            if let Body::Memory(content) = &body {
+                if content.len() as u64 > self.streaming_threshold {
+                    body = Body::Spooled(SpooledFile::create(content)?);
+                }
+            }
+        }
+        // Compressed bodies are counted before decompression, as the header gives them
+        if declared_length.is_some_and(|length| length != received) {
+            return Err(damaged(io::Error::from(io::ErrorKind::UnexpectedEof)).into());
+        }
+        Ok(body)
+    }
+
+    /// Pair the streamed program headers with their tables like the document path does
+    fn programs_from_stream(&self, page: StreamedPage, source: &str) -> Vec<(ProgramInfo, Vec<StudentRecord>)> {
+        let mut programs = Vec::new();
+        let scraped_at = Local::now();
+        for (table_index, (header, table)) in page.headers.iter().zip(&page.tables).enumerate() {
+            let program_name = decode_entities(&header.name).trim().to_string();
+            if !program_name.starts_with("ОП СПО") {
+                continue;
+            }
+            let paragraphs: Vec<String> = header.paragraphs.iter().map(|paragraph| decode_entities(paragraph)).collect();
+            let mut program_info = program_info_from_text(&program_name, &paragraphs);
+
+            let header_rows: Vec<Vec<String>> = table
+                .header_rows
+                .iter()
+                .map(|headers| headers.iter().map(|header| decode_entities(header)).collect())
+                .collect();
+            let optional_columns = OptionalColumns::find(&header_rows);
+            program_info.columns = columns(&header_rows, table.rows.iter().map(|row| row.cells.len()).max().unwrap_or(0));
+            let rows = table.rows.iter().filter(|row| !row.cells.is_empty()).map(|row| TableRow {
+                cells: row.cells.iter().map(|cell| decode_entities(cell)).collect(),
+                ..*row
+            });
+            let records =
+                self.records_from_rows(rows, &optional_columns, &program_info, |index| provenance(source, table_index, index, scraped_at));
+            if !records.is_empty() {
+                programs.push((program_info, records));
+            }
+        }
+
+        if programs.is_empty() {
+            warn!("{}", t!("scraper.no_programs", source));
+        }
+        programs
+    }
+
+    /// Parse the lists of a page already in memory, `source` naming it in records and errors
+    pub fn parse_html_content(&self, content: &str, source: &str) -> Result<Vec<(ProgramInfo, Vec<StudentRecord>)>> {
         let document = Html::parse_document(content);
         
-        let programs = self.extract_all_programs(&document)?;
+        let programs = self.extract_all_programs(&document, source)?;
         
-        if let Some(src) = source {
-            if programs.is_empty() {
-                println!("   ⚠️  Warning: No programs found in {}", src);
-            }
+        if programs.is_empty() {
+            warn!("{}", t!("scraper.no_programs", source));
         }
 
         Ok(programs)
     }
 
-    fn extract_all_programs(&self, document: &Html) -> Result<Vec<(ProgramInfo, Vec<StudentRecord>)>> {
+    fn extract_all_programs(&self, document: &Html, source: &str) -> Result<Vec<(ProgramInfo, Vec<StudentRecord>)>> {
         let mut programs = Vec::new();
+        let scraped_at = Local::now();
         
         // Find all program name elements
         let strong_selector = Selector::parse("p > strong").unwrap();
@@ -89,10 +360,10 @@ impl AdmissionScraper {
             {
                 // Convert back to ElementRef for the div
                 let program_div = scraper::ElementRef::wrap(program_parent).unwrap();
-                let program_info = self.extract_program_info_from_div(program_div, &program_name)?;
+                let mut program_info = self.extract_program_info_from_div(program_div, &program_name)?;
                 
                 // Find the table that follows this program info
-                let table_records = self.extract_records_for_program(document, i, &program_info)?;
+                let table_records = self.extract_records_for_program(document, i, &mut program_info, source, scraped_at)?;
                 
                 if !table_records.is_empty() {
                     programs.push((program_info, table_records));
@@ -111,16 +382,16 @@ impl AdmissionScraper {
         let funding_source = funding_regex
             .captures(&div_html)
             .and_then(|caps| caps.get(1))
-            .map(|m| m.as_str().to_string())
-            .unwrap_or_else(|| "Unknown".to_string());
+            .map(|m| FundingSource::parse(m.as_str()))
+            .unwrap_or_else(|| FundingSource::Other("Unknown".to_string()));
 
         // Extract study form
         let form_regex = Regex::new(r"Форма обучения:\s*<i>([^<]+)</i>").unwrap();
         let study_form = form_regex
             .captures(&div_html)
             .and_then(|caps| caps.get(1))
-            .map(|m| m.as_str().to_string())
-            .unwrap_or_else(|| "Unknown".to_string());
+            .map(|m| StudyForm::parse(m.as_str()))
+            .unwrap_or_else(|| StudyForm::Other("Unknown".to_string()));
 
         // Extract available places
         let places_regex = Regex::new(r"Количество мест:\s*<i>(\d+)</i>").unwrap();
@@ -132,9 +403,11 @@ impl AdmissionScraper {
 
         Ok(ProgramInfo {
             name: program_name.to_string(),
+            institution: String::new(),
             funding_source,
             study_form,
             available_places,
+            columns: Vec::new(),
         })
     }
 
@@ -142,7 +415,9 @@ impl AdmissionScraper {
         &self,
         document: &Html,
         program_index: usize,
-        program_info: &ProgramInfo,
+        program_info: &mut ProgramInfo,
+        source: &str,
+        scraped_at: DateTime<Local>,
     ) -> Result<Vec<StudentRecord>> {
         // Find all tables in the document
         let table_selector = Selector::parse("table.table-bordered").unwrap();
@@ -155,58 +430,125 @@ impl AdmissionScraper {
             return Ok(Vec::new());
         };
         
-        let row_selector = Selector::parse("tbody tr.srt").unwrap();
-        let mut records = Vec::new();
+        let row_selector = Selector::parse("tbody tr").unwrap();
+        let cell_selector = Selector::parse("td").unwrap();
+        let header_rows = self.header_rows(table);
+        let optional_columns = OptionalColumns::find(&header_rows);
+        let rows: Vec<TableRow> = table
+            .select(&row_selector)
+            .map(|row| TableRow {
+                cells: row.select(&cell_selector).map(|cell| cell.text().collect()).collect(),
+                struck: self.is_struck(&row),
+                listed: row.value().has_class("srt", scraper::CaseSensitivity::CaseSensitive),
+            })
+            .filter(|row| !row.cells.is_empty())
+            .collect();
+        let widest_row = rows.iter().filter(|row| row.listed).map(|row| row.cells.len()).max().unwrap_or(0);
+        program_info.columns = columns(&header_rows, widest_row);
 
-        for row in table.select(&row_selector) {
-            let cells: Vec<_> = row.select(&Selector::parse("td").unwrap()).collect();
-            
-            if cells.len() < 8 {
-                continue; // Skip incomplete rows
-            }
-
-            // Extract data from each cell
-            let rank = cells[0]
-                .text()
-                .collect::<String>()
-                .trim()
-                .parse::<u32>()
-                .unwrap_or(0);
-
-            let snils = self.extract_snils(&cells[2]);
-            let priority = self.extract_priority(&cells[3]);
-            let consent = cells[4].text().collect::<String>().trim().to_string();
-            let document_type = cells[5].text().collect::<String>().trim().to_string();
-            let average_score = cells[6].text().collect::<String>().trim().to_string();
-            let subject_scores = cells[7].text().collect::<String>().trim().to_string();
-            let psychological_test = if cells.len() > 8 {
-                cells[8].text().collect::<String>().trim().to_string()
-            } else {
-                "-".to_string()
-            };
+        Ok(self.records_from_rows(rows.into_iter(), &optional_columns, program_info, |index| {
+            provenance(source, program_index, index, scraped_at)
+        }))
+    }
 
-            records.push(StudentRecord {
-                rank,
-                snils,
-                priority,
-                consent,
-                document_type,
-                average_score,
-                subject_scores,
-                psychological_test,
-                program_name: program_info.name.clone(),
-                funding_source: program_info.funding_source.clone(),
-                study_form: program_info.study_form.clone(),
-                available_places: program_info.available_places,
-            });
+    /// Records of the list rows of a table in order, `provenance` telling where the n-th row is
+    ///
+    /// Section rows such as "Особая квота" give the rows after them their funding, and their
+    /// places if they state them; subtotals and other rows of a merged cell are left out.
+    fn records_from_rows(
+        &self,
+        rows: impl Iterator<Item = TableRow>,
+        optional_columns: &OptionalColumns,
+        program_info: &ProgramInfo,
+        provenance: impl Fn(usize) -> Provenance,
+    ) -> Vec<StudentRecord> {
+        let mut section = program_info.clone();
+        let mut records = Vec::new();
+        for (index, row) in merge_rows(rows, self.row_merge) {
+            match separator_row(&row.cells) {
+                Some(SeparatorRow::Section { funding, places }) => {
+                    section.funding_source = funding.unwrap_or_else(|| program_info.funding_source.clone());
+                    section.available_places = places.unwrap_or(program_info.available_places);
+                    debug!("{}", t!("scraper.section", program_info.name, index + 1, section.funding_source, section.available_places));
+                }
+                Some(SeparatorRow::Subtotal | SeparatorRow::Text) => debug!("{}", t!("scraper.separator_skipped", program_info.name, index + 1)),
+                // Other rows without the list's class are layout, not applicants
+                None if !row.listed => {}
+                None => {
+                    let record = self.record_from_cells(&row.cells, row.struck, optional_columns, &section);
+                    records.extend(skip_invalid_row(record, &section, provenance(index)));
+                }
+            }
+        }
+        records
+    }
+
+    /// Build a record from the text of a row's cells, what is wrong with incomplete rows
+    fn record_from_cells(
+        &self,
+        cells: &[String],
+        struck: bool,
+        optional_columns: &OptionalColumns,
+        program_info: &ProgramInfo,
+    ) -> Result<StudentRecord, String> {
+        if cells.len() < 8 {
+            return Err(format!("{} cells instead of at least 8", cells.len()));
         }
 
-        Ok(records)
+        // Extract data from each cell
+        let rank = cells[0].trim().parse::<u32>().unwrap_or(0);
+        let snils = self.extract_snils(&cells[2]);
+        let priority = self.extract_priority(&cells[3]);
+        let consent = cells[4].trim().to_string();
+        let document_type = cells[5].trim().to_string();
+        let average_score = cells[6].trim().to_string();
+        let subject_scores = cells[7].trim().to_string();
+        let optional_cell =
+            |column: Option<usize>| column.and_then(|column| cells.get(column)).map(|cell| cell.trim().to_string());
+        let achievements = optional_cell(optional_columns.achievements).unwrap_or_default();
+        let submitted_at = optional_cell(optional_columns.submitted_at).and_then(|cell| parse_submission_time(&cell));
+        let consent_at = optional_cell(optional_columns.consent_at).and_then(|cell| parse_submission_time(&cell));
+        let original_at = optional_cell(optional_columns.original_at).and_then(|cell| parse_submission_time(&cell));
+        let application_id = optional_cell(optional_columns.application_id).filter(|cell| !cell.is_empty() && cell != "-");
+        let psychological_test = if cells.len() > 8 && !optional_columns.contains(8) {
+            cells[8].trim().to_string()
+        } else {
+            "-".to_string()
+        };
+        let text = cells.concat().to_lowercase();
+        let withdrawn = struck || WITHDRAWN_MARKERS.iter().any(|marker| text.contains(marker));
+        let privileged = PRIVILEGED_MARKERS.iter().any(|marker| text.contains(marker));
+
+        Ok(StudentRecord {
+            rank,
+            snils,
+            priority,
+            consent,
+            document_type,
+            score: parse_score(&average_score),
+            average_score,
+            achievements,
+            bonus: 0.0,
+            subject_scores,
+            psychological_test,
+            program_name: program_info.name.clone(),
+            institution: program_info.institution.clone(),
+            funding_source: program_info.funding_source.clone(),
+            study_form: program_info.study_form.clone(),
+            available_places: program_info.available_places,
+            withdrawn,
+            privileged,
+            computed_rank: None,
+            provenance: None,
+            submitted_at,
+            consent_at,
+            original_at,
+            recent_consent: false,
+            application_id,
+        })
     }
 
-    fn extract_snils(&self, cell: &scraper::ElementRef) -> String {
-        let full_text = cell.text().collect::<String>();
-        
+    fn extract_snils(&self, full_text: &str) -> String {
         // Try to find SNILS in the text
         // It could be in the first line or after "СНИЛС: "
         for line in full_text.lines() {
@@ -235,11 +577,568 @@ impl AdmissionScraper {
             .unwrap_or_else(|| "Unknown".to_string())
     }
 
-    fn extract_priority(&self, cell: &scraper::ElementRef) -> u32 {
-        cell.text()
-            .collect::<String>()
-            .trim()
-            .parse::<u32>()
-            .unwrap_or(0)
+    /// Text of the header cells of every table row that has them
+    fn header_rows(&self, table: &scraper::ElementRef) -> Vec<Vec<String>> {
+        let row_selector = Selector::parse("tr").unwrap();
+        let header_selector = Selector::parse("th").unwrap();
+        table
+            .select(&row_selector)
+            .map(|row| row.select(&header_selector).map(|header| header.text().collect()).collect::<Vec<String>>())
+            .filter(|headers| !headers.is_empty())
+            .collect()
+    }
+
+    /// Whether the row or anything in it is struck through
+    fn is_struck(&self, row: &scraper::ElementRef) -> bool {
+        let struck_selector = Selector::parse("s, del, strike, [style*='line-through']").unwrap();
+        let row_struck = row.value().attr("style").is_some_and(|style| style.contains("line-through"));
+        row_struck || row.select(&struck_selector).next().is_some()
+    }
+
+    fn extract_priority(&self, text: &str) -> u32 {
+        text.trim().parse::<u32>().unwrap_or(0)
+    }
+}
+
+/// Rows of a table with those of one applicant joined as `row_merge` says, the cells of the
+/// later rows following those of the first, each with the index of its first row
+fn merge_rows(rows: impl Iterator<Item = TableRow>, row_merge: RowMerge) -> Vec<(usize, TableRow)> {
+    let mut merged: Vec<(usize, TableRow)> = Vec::new();
+    // The last row is an applicant that may still take the next row
+    let mut open = false;
+    for (index, row) in rows.enumerate() {
+        let joins = open
+            && match row_merge {
+                RowMerge::Off => false,
+                RowMerge::Pairs => true,
+                RowMerge::Continuation => {
+                    !numbered(&row.cells)
+                        && !matches!(separator_row(&row.cells), Some(SeparatorRow::Section { .. } | SeparatorRow::Subtotal))
+                }
+            };
+        match merged.last_mut() {
+            Some((_, last)) if joins => {
+                last.cells.extend(row.cells);
+                last.struck |= row.struck;
+                last.listed |= row.listed;
+                // A pair is complete with its second row
+                open = row_merge == RowMerge::Continuation;
+            }
+            _ => {
+                // Layout rows never start an applicant
+                open = (row.listed || numbered(&row.cells)) && separator_row(&row.cells).is_none();
+                merged.push((index, row));
+            }
+        }
+    }
+    merged
+}
+
+/// The first cell is the number of the row
+fn numbered(cells: &[String]) -> bool {
+    cells.first().is_some_and(|first| first.trim().parse::<u32>().is_ok())
+}
+
+/// A row between the applicants of a list
+enum SeparatorRow {
+    /// The rows after it compete for other places, those of the program header without a funding
+    Section { funding: Option<FundingSource>, places: Option<u32> },
+    /// A subtotal
+    Subtotal,
+    /// Other text of a merged cell
+    Text,
+}
+
+/// What a row is if it is not an applicant: one that is not numbered and has text in at most
+/// two cells, as a merged cell with an optional count gives
+fn separator_row(cells: &[String]) -> Option<SeparatorRow> {
+    let filled: Vec<&str> = cells.iter().map(|cell| cell.trim()).filter(|cell| !cell.is_empty()).collect();
+    if filled.first().is_some_and(|first| first.parse::<u32>().is_ok()) || filled.len() > 2 {
+        return None;
+    }
+    let text = filled.join(" ").to_lowercase();
+    if SUBTOTAL_MARKERS.iter().any(|marker| text.contains(marker)) {
+        return Some(SeparatorRow::Subtotal);
+    }
+    let places = Regex::new(r"(\d+)\s*мест|мест\S*\s*[:\-–—]?\s*(\d+)")
+        .unwrap()
+        .captures(&text)
+        .and_then(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .and_then(|count| count.as_str().parse().ok());
+    match FundingSource::parse(&text) {
+        FundingSource::Other(_) if GENERAL_SECTION_MARKERS.iter().any(|marker| text.contains(marker)) => {
+            Some(SeparatorRow::Section { funding: None, places })
+        }
+        FundingSource::Other(_) => Some(SeparatorRow::Text),
+        funding => Some(SeparatorRow::Section { funding: Some(funding), places }),
+    }
+}
+
+/// Columns only some lists have, found by their headers
+struct OptionalColumns {
+    achievements: Option<usize>,
+    submitted_at: Option<usize>,
+    consent_at: Option<usize>,
+    original_at: Option<usize>,
+    application_id: Option<usize>,
+}
+
+impl OptionalColumns {
+    fn find(header_rows: &[Vec<String>]) -> Self {
+        let column = |matches: fn(&str) -> bool| {
+            header_rows
+                .iter()
+                .find_map(|headers| headers.iter().position(|header| matches(header.to_lowercase().trim())))
+        };
+        Self {
+            achievements: column(|text| text.contains("достижени") || text == "ид"),
+            // "Дата подачи согласия" is the date of the consent, not of the application
+            submitted_at: column(|text| {
+                (text.contains("дата подачи") || text.contains("время подачи") || text.contains("дата и время"))
+                    && !text.contains("согласи")
+                    && !text.contains("оригинал")
+            }),
+            consent_at: column(|text| (text.contains("дата") || text.contains("время")) && text.contains("согласи")),
+            original_at: column(|text| (text.contains("дата") || text.contains("время")) && text.contains("оригинал")),
+            application_id: column(|text| {
+                ["номер заявления", "№ заявления", "id заявления", "идентификатор заявления"]
+                    .iter()
+                    .any(|label| text.contains(label))
+            }),
+        }
+    }
+
+    /// Whether `index` is one of these columns
+    fn contains(&self, index: usize) -> bool {
+        [self.achievements, self.submitted_at, self.consent_at, self.original_at, self.application_id].contains(&Some(index))
+    }
+}
+
+/// Submission time as lists print it, e.g. "01.08.2026 14:35:10" or "2026-08-01"
+fn parse_submission_time(text: &str) -> Option<NaiveDateTime> {
+    const DATE_TIME_FORMATS: [&str; 5] = ["%d.%m.%Y %H:%M:%S", "%d.%m.%Y %H:%M", "%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"];
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    DATE_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(&text, format).ok())
+        .or_else(|| {
+            ["%d.%m.%Y", "%Y-%m-%d"]
+                .iter()
+                .find_map(|format| NaiveDate::parse_from_str(&text, format).ok())
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+}
+
+/// Header cells of a table in reading order, whitespace collapsed; a table without headers
+/// has its columns named by position, up to the cells of its widest row
+fn columns(header_rows: &[Vec<String>], widest_row: usize) -> Vec<String> {
+    let headers: Vec<String> = header_rows
+        .iter()
+        .flatten()
+        .map(|header| header.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|header| !header.is_empty())
+        .collect();
+    if !headers.is_empty() {
+        return headers;
+    }
+    (1..=widest_row).map(|position| format!("column {}", position)).collect()
+}
+
+fn read_error(path: &str, cause: std::io::Error) -> ScrapeError {
+    ScrapeError::Read { path: path.to_string(), cause }
+}
+
+#[cfg(feature = "network")]
+fn fetch_error(url: &str, cause: reqwest::Error) -> ScrapeError {
+    ScrapeError::Fetch { url: url.to_string(), cause }
+}
+
+/// Where the row at `row_index` of the table at `table_index` came from
+fn provenance(source: &str, table_index: usize, row_index: usize, scraped_at: DateTime<Local>) -> Provenance {
+    Provenance {
+        source: source.to_string(),
+        table: table_index + 1,
+        row: row_index + 1,
+        scraped_at,
+    }
+}
+
+/// Record of a row tagged with where it came from, `None` with a note in the debug log if it is incomplete
+fn skip_invalid_row(
+    record: Result<StudentRecord, String>,
+    program_info: &ProgramInfo,
+    provenance: Provenance,
+) -> Option<StudentRecord> {
+    match record {
+        Ok(mut record) => {
+            record.provenance = Some(provenance);
+            Some(record)
+        }
+        Err(message) => {
+            let error = ParseError::new(&provenance.source, message)
+                .in_program(&program_info.name)
+                .in_table(provenance.table)
+                .at_row(provenance.row);
+            debug!("{}", t!("scraper.row_skipped", error));
+            None
+        }
+    }
+}
+
+const STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+#[cfg(feature = "network")]
+/// Content encodings asked for and undone by [`BodyDecoder`]
+const ACCEPTED_ENCODINGS: &str = "gzip, deflate";
+
+#[cfg(feature = "network")]
+/// A downloaded response body
+enum Body {
+    Memory(Vec<u8>),
+    // Too large to keep in memory
+    Spooled(SpooledFile),
+}
+
+#[cfg(feature = "network")]
+impl Body {
+    fn append(&mut self, data: &[u8]) -> Result<()> {
+        match self {
+            Body::Memory(content) => content.extend_from_slice(data),
+            Body::Spooled(file) => file.append(data)?,
+        }
+        Ok(())
+    }
+
+    fn len(&self) -> u64 {
+        match self {
+            Body::Memory(content) => content.len() as u64,
+            Body::Spooled(file) => file.len,
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+/// A temporary file holding a large response body, removed when dropped
+struct SpooledFile {
+    path: PathBuf,
+    file: File,
+    len: u64,
+}
+
+#[cfg(feature = "network")]
+impl SpooledFile {
+    /// A new temporary file starting with `content`
+    fn create(content: &[u8]) -> Result<Self> {
+        // Unique within the process, as pages are fetched one at a time per scraper but a
+        // process may run several scrapers
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "abitur-analyzer-{}-{}.html",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = File::create(&path).map_err(|cause| ScrapeError::Spool { path: path.display().to_string(), cause })?;
+        let mut spooled = Self { path, file, len: 0 };
+        spooled.append(content)?;
+        Ok(spooled)
+    }
+
+    fn append(&mut self, data: &[u8]) -> Result<()> {
+        self.file
+            .write_all(data)
+            .map_err(|cause| ScrapeError::Spool { path: self.path.display().to_string(), cause })?;
+        self.len += data.len() as u64;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "network")]
+impl Drop for SpooledFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(feature = "network")]
+/// Undoes the Content-Encoding of a response chunk by chunk
+enum BodyDecoder {
+    Identity,
+    Gzip(GzDecoder<Vec<u8>>),
+    // "deflate" before its first byte tells whether it is zlib-wrapped, as the standard
+    // says, or raw, as some servers send it
+    Deflate,
+    Zlib(ZlibDecoder<Vec<u8>>),
+    RawDeflate(DeflateDecoder<Vec<u8>>),
+}
+
+#[cfg(feature = "network")]
+impl BodyDecoder {
+    /// Decoder of a Content-Encoding header value, None if it is not supported
+    fn new(content_encoding: &str) -> Option<Self> {
+        match content_encoding {
+            "" | "identity" => Some(BodyDecoder::Identity),
+            "gzip" | "x-gzip" => Some(BodyDecoder::Gzip(GzDecoder::new(Vec::new()))),
+            "deflate" => Some(BodyDecoder::Deflate),
+            _ => None,
+        }
+    }
+
+    /// Decompress the next chunk, returning what it yields
+    fn decode(&mut self, chunk: &[u8]) -> io::Result<Vec<u8>> {
+        if let BodyDecoder::Deflate = self {
+            // A zlib header starts with the compression method 8 in its low bits
+            *self = match chunk.first() {
+                Some(first) if first & 0x0F == 8 => BodyDecoder::Zlib(ZlibDecoder::new(Vec::new())),
+                Some(_) => BodyDecoder::RawDeflate(DeflateDecoder::new(Vec::new())),
+                None => return Ok(Vec::new()),
+            };
+        }
+        match self {
+            BodyDecoder::Identity | BodyDecoder::Deflate => Ok(chunk.to_vec()),
+            BodyDecoder::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            BodyDecoder::Zlib(decoder) => {
+                decoder.write_all(chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+            BodyDecoder::RawDeflate(decoder) => {
+                decoder.write_all(chunk)?;
+                Ok(std::mem::take(decoder.get_mut()))
+            }
+        }
+    }
+
+    /// The rest of the decompressed data, an error if the compressed stream is incomplete
+    fn finish(self) -> io::Result<Vec<u8>> {
+        match self {
+            BodyDecoder::Identity | BodyDecoder::Deflate => Ok(Vec::new()),
+            BodyDecoder::Gzip(decoder) => decoder.finish(),
+            BodyDecoder::Zlib(decoder) => decoder.finish(),
+            BodyDecoder::RawDeflate(decoder) => decoder.finish(),
+        }
+    }
+}
+
+/// Program headers and list rows collected while streaming a page
+#[derive(Default)]
+struct StreamedPage {
+    // One per `p > strong`; the n-th belongs to the n-th table, as in the document path
+    headers: Vec<StreamedHeader>,
+    tables: Vec<StreamedTable>,
+    // Between a program name and the next table, where its funding, form and places are
+    in_header: bool,
+}
+
+#[derive(Default)]
+struct StreamedHeader {
+    name: String,
+    paragraphs: Vec<String>,
+}
+
+#[derive(Default)]
+struct StreamedTable {
+    header_rows: Vec<Vec<String>>,
+    header_row_open: bool,
+    rows: Vec<TableRow>,
+}
+
+/// Text of the cells of a table row
+#[derive(Default)]
+struct TableRow {
+    cells: Vec<String>,
+    struck: bool,
+    // Has the class of applicant rows; section and subtotal rows often do not
+    listed: bool,
+}
+
+/// Rewriter that only collects program headers and list rows of the page into `page`
+///
+/// Text comes in chunks and with entities as written; it is decoded when the page is assembled.
+fn page_rewriter<'h>(page: &'h Mutex<StreamedPage>, encoding: &'static Encoding) -> send::HtmlRewriter<'h, fn(&[u8])> {
+    let mut handlers = vec![
+        element!("p > strong", move |_| {
+            let mut page = page.lock().unwrap();
+            page.headers.push(StreamedHeader::default());
+            page.in_header = true;
+            Ok(())
+        }),
+        text!("p > strong", move |chunk| {
+            if let Some(header) = page.lock().unwrap().headers.last_mut() {
+                header.name.push_str(chunk.as_str());
+            }
+            Ok(())
+        }),
+        element!("p", move |_| {
+            let mut page = page.lock().unwrap();
+            if page.in_header {
+                if let Some(header) = page.headers.last_mut() {
+                    header.paragraphs.push(String::new());
+                }
+            }
+            Ok(())
+        }),
+        text!("p", move |chunk| {
+            let mut page = page.lock().unwrap();
+            if page.in_header {
+                if let Some(paragraph) = page.headers.last_mut().and_then(|header| header.paragraphs.last_mut()) {
+                    paragraph.push_str(chunk.as_str());
+                }
+            }
+            Ok(())
+        }),
+        element!("table.table-bordered", move |_| {
+            let mut page = page.lock().unwrap();
+            page.tables.push(StreamedTable::default());
+            page.in_header = false;
+            Ok(())
+        }),
+        element!("table.table-bordered tr", move |row| {
+            let struck = row.get_attribute("style").is_some_and(|style| style.contains("line-through"));
+            let listed = row.get_attribute("class").is_some_and(|class| class.split_whitespace().any(|class| class == "srt"));
+            if let Some(table) = page.lock().unwrap().tables.last_mut() {
+                table.header_row_open = true;
+                table.rows.push(TableRow { cells: Vec::new(), struck, listed });
+            }
+            Ok(())
+        }),
+        element!("table.table-bordered th", move |_| {
+            if let Some(table) = page.lock().unwrap().tables.last_mut() {
+                if table.header_row_open || table.header_rows.is_empty() {
+                    table.header_rows.push(Vec::new());
+                    table.header_row_open = false;
+                }
+                if let Some(headers) = table.header_rows.last_mut() {
+                    headers.push(String::new());
+                }
+            }
+            Ok(())
+        }),
+        text!("table.table-bordered th", move |chunk| {
+            let mut page = page.lock().unwrap();
+            let header = page
+                .tables
+                .last_mut()
+                .and_then(|table| table.header_rows.last_mut())
+                .and_then(|headers| headers.last_mut());
+            if let Some(header) = header {
+                header.push_str(chunk.as_str());
+            }
+            Ok(())
+        }),
+        // No `tbody` here: unlike the document parser, the stream does not insert a missing one
+        element!("table.table-bordered td", move |_| {
+            if let Some(row) = page.lock().unwrap().tables.last_mut().and_then(|table| table.rows.last_mut()) {
+                row.cells.push(String::new());
+            }
+            Ok(())
+        }),
+        text!("table.table-bordered td", move |chunk| {
+            let mut page = page.lock().unwrap();
+            let cell = page
+                .tables
+                .last_mut()
+                .and_then(|table| table.rows.last_mut())
+                .and_then(|row| row.cells.last_mut());
+            if let Some(cell) = cell {
+                cell.push_str(chunk.as_str());
+            }
+            Ok(())
+        }),
+    ];
+    for struck in ["s", "del", "strike", "[style*='line-through']"] {
+        handlers.push(element!(format!("table.table-bordered tr {}", struck), move |_| {
+            if let Some(row) = page.lock().unwrap().tables.last_mut().and_then(|table| table.rows.last_mut()) {
+                row.struck = true;
+            }
+            Ok(())
+        }));
+    }
+
+    send::HtmlRewriter::new(
+        send::Settings {
+            element_content_handlers: handlers,
+            encoding: AsciiCompatibleEncoding::new(encoding).unwrap_or_else(AsciiCompatibleEncoding::utf_8),
+            adjust_charset_on_meta_tag: true,
+            ..send::Settings::new_send()
+        },
+        (|_: &[u8]| {}) as fn(&[u8]),
+    )
+}
+
+#[cfg(feature = "network")]
+/// Encoding from the Content-Type charset, UTF-8 if there is none
+fn response_encoding(response: &reqwest::Response) -> &'static Encoding {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|content_type| content_type.split(';').find_map(|part| part.trim().strip_prefix("charset=")))
+        .and_then(|label| Encoding::for_label(label.trim_matches('"').as_bytes()))
+        .unwrap_or(UTF_8)
+}
+
+/// Funding, study form and places from the text of a program header, as in its HTML
+fn program_info_from_text(program_name: &str, paragraphs: &[String]) -> ProgramInfo {
+    let value = |label: &str| {
+        paragraphs
+            .iter()
+            .find_map(|paragraph| paragraph.split_once(label))
+            .map(|(_, value)| value.trim().to_string())
+    };
+    ProgramInfo {
+        name: program_name.to_string(),
+        institution: String::new(),
+        funding_source: value("Источник финансирования:")
+            .map(|funding| FundingSource::parse(&funding))
+            .unwrap_or_else(|| FundingSource::Other("Unknown".to_string())),
+        study_form: value("Форма обучения:")
+            .map(|form| StudyForm::parse(&form))
+            .unwrap_or_else(|| StudyForm::Other("Unknown".to_string())),
+        available_places: value("Количество мест:")
+            .and_then(|places| places.split_whitespace().next().and_then(|places| places.parse().ok()))
+            .unwrap_or(0),
+        columns: Vec::new(),
+    }
+}
+
+/// Replace the character references lists use with the characters, as the document parser does
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let reference = rest.find(';').filter(|end| *end <= 10).map(|end| &rest[1..end]);
+        let character = reference.and_then(|reference| match reference {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => reference
+                .strip_prefix("#x")
+                .or_else(|| reference.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| reference.strip_prefix('#').and_then(|decimal| decimal.parse().ok()))
+                .and_then(char::from_u32),
+        });
+        match (reference, character) {
+            (Some(reference), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[reference.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
     }
+    decoded.push_str(rest);
+    decoded
 }
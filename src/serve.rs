@@ -0,0 +1,345 @@
+use crate::cli::RunSettings;
+use crate::html_report::{self, escape};
+use crate::analyzer::{AdmissionDistance, TargetOutcome};
+use crate::models::{normalize_snils, FundingSource, ProgramKey, StudentRecord};
+use crate::report_model::{ProgramReport, ReportRow};
+use crate::snapshot::Snapshot;
+use crate::{analyze_records, charts, i18n, load_snapshot, run_summary, AnalyzedRun};
+use anyhow::Result;
+use axum::extract::{DefaultBodyLimit, Path as UrlPath, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::Mutex;
+use tracing::info;
+
+/// Dashboard state: settings and the analysis of the latest snapshot, redone when it changes
+pub(crate) struct Dashboard {
+    pub(crate) settings: RunSettings,
+    latest: Mutex<Option<(PathBuf, SystemTime, Arc<AnalyzedRun>)>>,
+}
+
+// Snapshots of a whole campaign are larger than axum's default 2 MB body limit
+pub(crate) const SNAPSHOT_BODY_LIMIT: usize = 64 * 1024 * 1024;
+
+type HandlerResult<T> = Result<T, (StatusCode, String)>;
+
+fn internal_error(e: anyhow::Error) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}", e))
+}
+
+impl Dashboard {
+    pub(crate) fn new(settings: RunSettings) -> Self {
+        Self {
+            settings,
+            latest: Mutex::new(None),
+        }
+    }
+
+    /// Analysis of the latest run's snapshot, reloaded after a new run finished
+    pub(crate) async fn analysis(&self) -> Result<(Arc<AnalyzedRun>, SystemTime)> {
+        let snapshot_path = self.settings.snapshot_path(None);
+        let modified = std::fs::metadata(&snapshot_path)?.modified()?;
+
+        let mut latest = self.latest.lock().await;
+        if let Some((path, cached_modified, analyzed)) = latest.as_ref() {
+            if *path == snapshot_path && *cached_modified == modified {
+                return Ok((analyzed.clone(), modified));
+            }
+        }
+        let analyzed = Arc::new(self.analyze_for(self.settings.target_snils.clone(), load_snapshot(&snapshot_path)?).await?);
+        *latest = Some((snapshot_path, modified, analyzed.clone()));
+        Ok((analyzed, modified))
+    }
+
+    /// Simulate admission for any target on a worker thread so other requests are still served
+    pub(crate) async fn analyze_for(
+        &self,
+        target_snils: String,
+        all_program_records: Vec<(String, Vec<StudentRecord>)>,
+    ) -> Result<AnalyzedRun> {
+        let settings = RunSettings {
+            target_snils,
+            ..self.settings.clone()
+        };
+        tokio::task::spawn_blocking(move || {
+            analyze_records(&settings, all_program_records, &mut run_summary::RunSummary::default())
+        })
+        .await?
+    }
+
+    /// Analysis of the latest run's snapshot for `snils`; only the configured target is cached,
+    /// anyone else is simulated on request
+    pub(crate) async fn status_of(&self, snils: &str) -> Result<Arc<AnalyzedRun>> {
+        let (latest, _) = self.analysis().await?;
+        if normalize_snils(snils) == normalize_snils(&self.settings.target_snils) {
+            return Ok(latest);
+        }
+        let records = load_snapshot(&self.settings.snapshot_path(None))?;
+        Ok(Arc::new(self.analyze_for(snils.to_string(), records).await?))
+    }
+}
+
+/// Serve the dashboard for the latest run until Ctrl-C
+pub async fn serve(settings: RunSettings, bind: &str) -> Result<()> {
+    let dashboard = Arc::new(Dashboard::new(settings));
+    let app = Router::new()
+        .route("/", get(index))
+        .route("/programs/{key}", get(program))
+        .route("/history.svg", get(history))
+        .route("/programs", get(api_programs))
+        .route("/programs/{key}/admitted", get(api_admitted))
+        .route("/target/{snils}/status", get(api_target_status))
+        .route("/analyze", post(api_analyze).layer(DefaultBodyLimit::max(SNAPSHOT_BODY_LIMIT)))
+        .with_state(dashboard);
+
+    let listener = tokio::net::TcpListener::bind(bind).await?;
+    info!("{}", t!("serve.listening", listener.local_addr()?));
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    info!("{}", t!("serve.stopped"));
+    Ok(())
+}
+
+async fn index(State(dashboard): State<Arc<Dashboard>>) -> HandlerResult<Html<String>> {
+    let (analyzed, modified) = dashboard.analysis().await.map_err(internal_error)?;
+    let outcomes = &analyzed.target_outcomes;
+
+    let mut html = html_report::page_start(&t!("report.title", escape(&analyzed.target_snils)));
+    html.push_str(&format!(
+        "<p>{}</p>\n",
+        t!("serve.data_time", DateTime::<Local>::from(modified).format("%Y-%m-%d %H:%M"))
+    ));
+    html.push_str(&html_report::verdict(outcomes));
+    html.push_str(&format!("<h2>{}</h2>\n", t!("report.summary")));
+    html.push_str(&html_report::summary_table(outcomes));
+    html.push_str(&format!("<h2>{}</h2>\n", t!("report.cutoff_vs_target")));
+    html.push_str(&html_report::cutoff_chart_svg(outcomes));
+
+    // Program list with links to the applicant tables
+    html.push_str(&format!("<h2>{}</h2>\n<table class=\"sortable\">\n<thead><tr>", t!("serve.programs")));
+    for key in [
+        "report.program",
+        "report.funding",
+        "report.places",
+        "report.total_eager",
+        "report.per_place",
+        "report.cutoff_score",
+    ] {
+        html.push_str(&format!("<th>{}</th>", t!(key)));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+    for program in &analyzed.report.programs {
+        html.push_str(&format!(
+            "<tr><td><a href=\"/programs/{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}</td><td>{:.4}</td></tr>\n",
+            url_encode(&program.program_key.to_string()),
+            escape(&program.program_name),
+            escape(program.funding_source.as_str()),
+            i18n::places_label(program.available_places, program.published_places),
+            program.total_eager_applicants,
+            program.total_eager_applicants as f64 / program.available_places as f64,
+            program.cutoff_score,
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    html.push_str(&format!("<h2>{}</h2>\n", t!("chart.trend_title")));
+    if dashboard.settings.database_path().exists() {
+        html.push_str("<img src=\"/history.svg\" alt=\"\">\n");
+    } else {
+        html.push_str(&format!("<p>{}</p>\n", t!("serve.no_history")));
+    }
+
+    html.push_str(&html_report::page_end());
+    Ok(Html(html))
+}
+
+#[derive(Deserialize)]
+struct ProgramQuery {
+    // "all" (default), "eager" or "admitted"
+    filter: Option<String>,
+}
+
+async fn program(
+    State(dashboard): State<Arc<Dashboard>>,
+    UrlPath(key): UrlPath<String>,
+    Query(query): Query<ProgramQuery>,
+) -> HandlerResult<Html<String>> {
+    let (analyzed, _) = dashboard.analysis().await.map_err(internal_error)?;
+    let Some(program) = find_program(&analyzed, &key) else {
+        return Err((StatusCode::NOT_FOUND, t!("serve.program_not_found", key)));
+    };
+
+    let filter = query.filter.as_deref().unwrap_or("all");
+    let rows = program.rows.iter().filter(|row| match filter {
+        "eager" => row.eager,
+        "admitted" => row.admitted,
+        _ => true,
+    });
+
+    let mut html = html_report::page_start(&html_report::program_header(program));
+    html.push_str(&format!("<p><a href=\"/\">{}</a> · ", t!("serve.back")));
+    let links: Vec<String> = [("all", "serve.filter_all"), ("eager", "serve.filter_eager"), ("admitted", "serve.filter_admitted")]
+        .iter()
+        .map(|(value, label)| {
+            if *value == filter {
+                format!("<b>{}</b>", t!(label))
+            } else {
+                format!("<a href=\"?filter={}\">{}</a>", value, t!(label))
+            }
+        })
+        .collect();
+    html.push_str(&links.join(" | "));
+    html.push_str("</p>\n");
+    html.push_str(&html_report::program_table(rows));
+    html.push_str(&html_report::page_end());
+    Ok(Html(html))
+}
+
+/// Program-funding combination by the key used in URLs
+fn find_program<'a>(analyzed: &'a AnalyzedRun, key: &str) -> Option<&'a ProgramReport> {
+    analyzed.report.programs.iter().find(|program| program.program_key.to_string() == key)
+}
+
+async fn history(State(dashboard): State<Arc<Dashboard>>) -> HandlerResult<Response> {
+    match charts::cutoff_trend_svg(&dashboard.settings.database_path()).map_err(internal_error)? {
+        Some(svg) => Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response()),
+        None => Err((StatusCode::NOT_FOUND, t!("serve.no_history"))),
+    }
+}
+
+/// Program in the `GET /programs` list
+#[derive(Serialize)]
+struct ProgramSummary<'a> {
+    program_key: &'a ProgramKey,
+    program_name: &'a str,
+    funding_source: &'a FundingSource,
+    available_places: u32,
+    total_eager_applicants: usize,
+    admitted_count: usize,
+    cutoff_score: f64,
+    cutoff_ties: usize,
+}
+
+async fn api_programs(State(dashboard): State<Arc<Dashboard>>) -> HandlerResult<Response> {
+    let (analyzed, _) = dashboard.analysis().await.map_err(internal_error)?;
+    let programs: Vec<ProgramSummary> = analyzed
+        .report
+        .programs
+        .iter()
+        .map(|program| ProgramSummary {
+            program_key: &program.program_key,
+            program_name: &program.program_name,
+            funding_source: &program.funding_source,
+            available_places: program.available_places,
+            total_eager_applicants: program.total_eager_applicants,
+            admitted_count: program.admitted_count,
+            cutoff_score: program.cutoff_score,
+            cutoff_ties: program.cutoff_ties,
+        })
+        .collect();
+    Ok(Json(programs).into_response())
+}
+
+/// Admitted list of one program in `GET /programs/{key}/admitted`
+#[derive(Serialize)]
+struct AdmittedList<'a> {
+    program_key: &'a ProgramKey,
+    program_name: &'a str,
+    funding_source: &'a FundingSource,
+    available_places: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    published_places: Option<u32>,
+    // In admission order
+    admitted: Vec<&'a StudentRecord>,
+}
+
+async fn api_admitted(State(dashboard): State<Arc<Dashboard>>, UrlPath(key): UrlPath<String>) -> HandlerResult<Response> {
+    let (analyzed, _) = dashboard.analysis().await.map_err(internal_error)?;
+    let Some(program) = find_program(&analyzed, &key) else {
+        return Err((StatusCode::NOT_FOUND, t!("serve.program_not_found", key)));
+    };
+
+    let mut admitted: Vec<&ReportRow> = program.admitted_rows().collect();
+    admitted.sort_by_key(|row| row.admission_position);
+
+    Ok(Json(AdmittedList {
+        program_key: &program.program_key,
+        program_name: &program.program_name,
+        funding_source: &program.funding_source,
+        available_places: program.available_places,
+        published_places: program.published_places,
+        admitted: admitted.into_iter().map(|row| &row.record).collect(),
+    })
+    .into_response())
+}
+
+/// Simulation result for one applicant, same fields as in analysis.json
+#[derive(Serialize)]
+struct TargetStatus<'a> {
+    target_snils: &'a str,
+    target_outcomes: &'a [TargetOutcome],
+    admission_distances: &'a [AdmissionDistance],
+}
+
+impl<'a> From<&'a AnalyzedRun> for TargetStatus<'a> {
+    fn from(analyzed: &'a AnalyzedRun) -> Self {
+        Self {
+            target_snils: &analyzed.target_snils,
+            target_outcomes: &analyzed.target_outcomes,
+            admission_distances: &analyzed.admission_distances,
+        }
+    }
+}
+
+async fn api_target_status(
+    State(dashboard): State<Arc<Dashboard>>,
+    UrlPath(snils): UrlPath<String>,
+) -> HandlerResult<Response> {
+    let analyzed = dashboard.status_of(&snils).await.map_err(internal_error)?;
+
+    if analyzed.target_outcomes.iter().all(|outcome| outcome.priority.is_none()) {
+        return Err((StatusCode::NOT_FOUND, t!("serve.applicant_not_found", snils)));
+    }
+    Ok(Json(TargetStatus::from(analyzed.as_ref())).into_response())
+}
+
+#[derive(Deserialize)]
+struct AnalyzeQuery {
+    // Defaults to the configured target
+    snils: Option<String>,
+}
+
+/// Simulate admission on a posted snapshot without touching the run directories
+async fn api_analyze(
+    State(dashboard): State<Arc<Dashboard>>,
+    Query(query): Query<AnalyzeQuery>,
+    body: String,
+) -> HandlerResult<Response> {
+    let snapshot = Snapshot::parse(&body).map_err(|e| (StatusCode::BAD_REQUEST, format!("{:#}", e)))?;
+    let target_snils = query.snils.unwrap_or_else(|| dashboard.settings.target_snils.clone());
+    let analyzed = dashboard
+        .analyze_for(target_snils, snapshot.into_program_records())
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(TargetStatus::from(&analyzed)).into_response())
+}
+
+/// Percent-encode a path segment
+fn url_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
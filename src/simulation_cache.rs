@@ -0,0 +1,109 @@
+use crate::analyzer::{AdmissionAnalysis, AdmissionAnalyzer};
+use crate::atomic_file;
+use crate::checksums;
+use crate::error::StorageError;
+use crate::models::StudentRecord;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// File in the output root with the admission results of the last simulations
+pub const SIMULATION_CACHE_FILE: &str = "simulation_cache.json";
+
+/// Simulations kept, so alternating between a few inputs, as `compare` does, still hits
+const MAX_ENTRIES: usize = 4;
+
+/// Admission results of recent simulations, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SimulationCache {
+    entries: Vec<CachedSimulation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSimulation {
+    key: String,
+    // Program key -> admitted SNILSes in admission order
+    admitted: BTreeMap<String, Vec<String>>,
+}
+
+/// SHA-256 of everything the simulation depends on: the records, the simulation settings
+/// and the version of the analyzer, whose algorithm may change between releases
+pub fn key(analyzer: &AdmissionAnalyzer, all_program_records: &[(String, Vec<StudentRecord>)]) -> String {
+    let mut hasher = Sha256::new();
+    let settings = (
+        env!("CARGO_PKG_VERSION"),
+        analyzer.target_snils,
+        analyzer.originals_only,
+        analyzer.cutoff_ties,
+        &analyzer.tie_breakers,
+        &analyzer.seat_overrides,
+    );
+    // Serializing plain data into memory cannot fail
+    hasher.update(serde_json::to_vec(&settings).unwrap_or_default());
+    for (program_name, records) in all_program_records {
+        hasher.update(serde_json::to_vec(program_name).unwrap_or_default());
+        checksums::hash_records(&mut hasher, records);
+        // Set after parsing and not serialized: the score scoring rules and achievements give,
+        // and consents too recent to count
+        let simulated: Vec<(Option<f64>, bool)> = records.iter().map(|record| (record.score, record.recent_consent)).collect();
+        hasher.update(serde_json::to_vec(&simulated).unwrap_or_default());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+impl SimulationCache {
+    /// Simulations saved in `output_root`, none if there are none or they cannot be read
+    pub fn load(output_root: &Path) -> Self {
+        fs::read_to_string(output_root.join(SIMULATION_CACHE_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, output_root: &Path) -> Result<(), StorageError> {
+        let path = output_root.join(SIMULATION_CACHE_FILE);
+        let content = serde_json::to_string(self)?;
+        atomic_file::write(&path, content).map_err(|cause| StorageError::Write { path: path.display().to_string(), cause })
+    }
+
+    /// The analysis simulated under `key`, `None` if it is not cached
+    ///
+    /// Only the admission results are kept; program popularities are recomputed, as they
+    /// take a single pass over the records.
+    pub fn analysis(
+        &self,
+        key: &str,
+        analyzer: &AdmissionAnalyzer,
+        all_program_records: &[(String, Vec<StudentRecord>)],
+    ) -> Option<AdmissionAnalysis> {
+        let cached = self.entries.iter().find(|entry| entry.key == key)?;
+        let program_popularities = analyzer.program_popularities(all_program_records);
+        let final_admission_results = program_popularities
+            .iter()
+            .map(|popularity| {
+                let admitted = cached.admitted.get(&popularity.program_key.to_string())?;
+                Some((popularity.program_key.clone(), admitted.clone()))
+            })
+            .collect::<Option<_>>()?;
+        Some(AdmissionAnalysis {
+            program_popularities,
+            final_admission_results,
+        })
+    }
+
+    /// Keep the admission results of `analysis` under `key`, forgetting the oldest beyond
+    /// the last few
+    pub fn insert(&mut self, key: String, analysis: &AdmissionAnalysis) {
+        self.entries.retain(|entry| entry.key != key);
+        let admitted = analysis
+            .final_admission_results
+            .iter()
+            .map(|(program_key, admitted)| (program_key.to_string(), admitted.clone()))
+            .collect();
+        self.entries.push(CachedSimulation { key, admitted });
+        let excess = self.entries.len().saturating_sub(MAX_ENTRIES);
+        self.entries.drain(..excess);
+    }
+}
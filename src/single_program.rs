@@ -0,0 +1,140 @@
+use crate::analyzer::AdmissionAnalyzer;
+use crate::models::{normalize_snils, ApplicantApplication, FundingSource, ProgramKey, StudentRecord};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The target's chance of a seat in one program-funding combination of the estimated program
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramEstimate {
+    pub program_key: ProgramKey,
+    pub funding_source: FundingSource,
+    pub available_places: u32,
+    pub eager_applicants: usize,
+    pub priority: Option<u32>, // None if target did not apply
+    pub position: Option<usize>, // 1-based position among eager applicants, target included
+    // Applicants ahead of the target expected to take their seat here rather than leave
+    pub expected_ahead: f64,
+    // Probability that fewer applicants ahead stay than there are places
+    pub seat_chance: f64,
+    pub status: String,
+}
+
+/// Estimate the target's seat in every competition of `program_name` without simulating the
+/// other programs
+///
+/// Each applicant ahead of the target leaves for a program they rank higher with the chance
+/// of a seat there, 1 within its places and places / position beyond them, and stays
+/// otherwise. The chance of a seat is that of fewer applicants staying than there are places.
+/// Much faster than [`AdmissionAnalyzer::analyze_all_programs`] but an approximation: who
+/// takes the seats of the other programs is not known.
+pub fn estimate(
+    analyzer: &AdmissionAnalyzer,
+    all_program_records: &[(String, Vec<StudentRecord>)],
+    program_name: &str,
+) -> Vec<ProgramEstimate> {
+    let normalized_target = normalize_snils(analyzer.target_snils);
+
+    // Eager applications of each competition in the order the simulation seats them
+    let mut competitions: HashMap<ProgramKey, Vec<(&StudentRecord, ApplicantApplication)>> = HashMap::new();
+    // (institution, applicant) -> priority and competition of each eager application
+    let mut applications: HashMap<(&str, String), Vec<(u32, ProgramKey)>> = HashMap::new();
+    for (name, records) in all_program_records {
+        for record in records.iter().filter(|record| analyzer.is_eager(record)) {
+            let program_key = ProgramKey::of(name, record);
+            applications
+                .entry((record.institution.as_str(), normalize_snils(&record.snils)))
+                .or_default()
+                .push((record.priority, program_key.clone()));
+            competitions.entry(program_key).or_default().push((record, analyzer.application(name, record)));
+        }
+    }
+    let places = |program_key: &ProgramKey, record: &StudentRecord| {
+        analyzer.seat_overrides.get(&program_key.to_string()).copied().unwrap_or(record.available_places)
+    };
+    // (competition, applicant) -> chance of a seat there by position alone
+    let mut seat_chances: HashMap<(&ProgramKey, String), f64> = HashMap::new();
+    for records in competitions.values_mut() {
+        records.sort_by(|(_, a), (_, b)| analyzer.admission_order(a, b));
+    }
+    for (program_key, records) in &competitions {
+        let available_places = f64::from(places(program_key, records[0].0));
+        for (index, (record, _)) in records.iter().enumerate() {
+            let chance = (available_places / (index + 1) as f64).min(1.0);
+            seat_chances.insert((program_key, normalize_snils(&record.snils)), chance);
+        }
+    }
+
+    let mut estimates: Vec<ProgramEstimate> = Vec::new();
+    for (name, records) in all_program_records.iter().filter(|(name, _)| name == program_name) {
+        for record in records {
+            let program_key = ProgramKey::of(name, record);
+            if estimates.iter().any(|estimate| estimate.program_key == program_key) {
+                continue;
+            }
+            let target_record = records
+                .iter()
+                .find(|record| program_key.matches(name, record) && normalize_snils(&record.snils) == normalized_target);
+            let eager = competitions.get(&program_key).map(Vec::as_slice).unwrap_or_default();
+            let available_places = places(&program_key, record);
+
+            // Chance of each applicant ahead of the target to take a seat here
+            let staying: Vec<f64> = match target_record {
+                Some(target_record) => {
+                    let target_application = analyzer.application(name, target_record);
+                    eager
+                        .iter()
+                        .filter(|(applicant, _)| normalize_snils(&applicant.snils) != normalized_target)
+                        .filter(|(_, application)| analyzer.admission_order(application, &target_application).is_lt())
+                        .map(|(applicant, _)| {
+                            let snils = normalize_snils(&applicant.snils);
+                            applications
+                                .get(&(applicant.institution.as_str(), snils.clone()))
+                                .into_iter()
+                                .flatten()
+                                .filter(|(priority, _)| *priority < applicant.priority)
+                                .map(|(_, other)| 1.0 - seat_chances.get(&(other, snils.clone())).copied().unwrap_or_default())
+                                .product()
+                        })
+                        .collect()
+                }
+                None => Vec::new(),
+            };
+            let chance = fewer_than(&staying, available_places as usize);
+            let status = match target_record {
+                None => "Not_Applied",
+                Some(_) if chance >= 0.5 => "Admitted",
+                Some(_) => "Not_Admitted",
+            };
+            estimates.push(ProgramEstimate {
+                funding_source: program_key.funding.clone(),
+                available_places,
+                eager_applicants: eager.len(),
+                priority: target_record.map(|record| record.priority),
+                position: target_record.map(|_| staying.len() + 1),
+                expected_ahead: staying.iter().sum(),
+                seat_chance: if target_record.is_some() { chance } else { 0.0 },
+                status: status.to_string(),
+                program_key,
+            });
+        }
+    }
+    estimates.sort_by(|a, b| a.program_key.cmp(&b.program_key));
+    estimates
+}
+
+/// Probability that fewer than `places` of independent events with chances `chances` happen
+fn fewer_than(chances: &[f64], places: usize) -> f64 {
+    // distribution[k]: probability that exactly k of the events so far happened, k < places
+    let mut distribution = vec![0.0; places];
+    let Some(first) = distribution.first_mut() else {
+        return 0.0;
+    };
+    *first = 1.0;
+    for chance in chances {
+        for k in (0..places).rev() {
+            let below = if k > 0 { distribution[k - 1] } else { 0.0 };
+            distribution[k] = distribution[k] * (1.0 - chance) + below * chance;
+        }
+    }
+    distribution.iter().sum()
+}
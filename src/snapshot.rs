@@ -0,0 +1,160 @@
+use crate::atomic_file;
+use crate::models::{parse_score, ProgramInfo, StudentRecord};
+use crate::error::{ParseError, Result, ScrapeError, StorageError};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// File name of the parsed data inside a run directory
+pub const SNAPSHOT_FILE: &str = "snapshot.json";
+
+/// Version of the snapshot format written by this build; bumped on incompatible changes
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Parsed applicant lists saved between scraping and analysis
+///
+/// This is the exchange format between pipeline stages: snapshots can be archived and
+/// re-analyzed later, and lists produced by other scrapers can be analyzed as long as
+/// they follow it (see "Snapshot Format" in the README).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub format_version: u32,
+    // Program that produced the snapshot, informational only
+    #[serde(default)]
+    pub generator: String,
+    pub created_at: DateTime<Local>,
+    pub programs: Vec<ProgramList>,
+}
+
+/// Applicant list of one program and funding source, ordered by rank
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgramList {
+    pub program: ProgramInfo,
+    pub records: Vec<StudentRecord>,
+}
+
+impl Snapshot {
+    pub fn new(all_program_records: &[(String, Vec<StudentRecord>)]) -> Self {
+        let mut programs: Vec<ProgramList> = Vec::new();
+        for (program_name, records) in all_program_records {
+            // A parsed list may mix funding sources and study forms; each gets its own program header
+            for record in records {
+                match programs.iter_mut().find(|list| {
+                    list.program.name == *program_name
+                        && list.program.funding_source == record.funding_source
+                        && list.program.study_form == record.study_form
+                }) {
+                    Some(list) => list.records.push(record.clone()),
+                    None => programs.push(ProgramList {
+                        program: ProgramInfo {
+                            name: program_name.clone(),
+                            institution: record.institution.clone(),
+                            funding_source: record.funding_source.clone(),
+                            study_form: record.study_form.clone(),
+                            available_places: record.available_places,
+                            columns: Vec::new(),
+                        },
+                        records: vec![record.clone()],
+                    }),
+                }
+            }
+        }
+
+        Self {
+            format_version: FORMAT_VERSION,
+            generator: format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+            created_at: Local::now(),
+            programs,
+        }
+    }
+
+    /// Read a snapshot file, refusing files without a version or from a newer format
+    pub fn load(path: &Path) -> Result<Self> {
+        let input = path.display().to_string();
+        let content = fs::read_to_string(path).map_err(|cause| ScrapeError::Read { path: input.clone(), cause })?;
+        Ok(Self::parse_input(&content, &input)?)
+    }
+
+    /// Parse snapshot JSON, refusing documents without a version or from a newer format
+    pub fn parse(content: &str) -> Result<Self, ParseError> {
+        Self::parse_input(content, "snapshot")
+    }
+
+    fn parse_input(content: &str, input: &str) -> Result<Self, ParseError> {
+        let value: serde_json::Value = serde_json::from_str(content).map_err(|e| ParseError::new(input, e))?;
+
+        let version = value
+            .get("format_version")
+            .and_then(|version| version.as_u64())
+            .ok_or_else(|| ParseError::new(input, "Not a snapshot (no format_version)"))?;
+        if version > FORMAT_VERSION as u64 {
+            return Err(ParseError::new(
+                input,
+                format!("Snapshot has format version {}, this build supports up to {}", version, FORMAT_VERSION),
+            ));
+        }
+
+        let snapshot: Snapshot = serde_json::from_value(value).map_err(|e| ParseError::new(input, e))?;
+        snapshot.validate(input)?;
+        Ok(snapshot)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), StorageError> {
+        atomic_file::write(path, serde_json::to_string_pretty(self)?).map_err(|cause| StorageError::Write {
+            path: path.display().to_string(),
+            cause,
+        })
+    }
+
+    fn validate(&self, input: &str) -> Result<(), ParseError> {
+        for list in &self.programs {
+            if list.program.name.trim().is_empty() {
+                return Err(ParseError::new(input, "Program without a name"));
+            }
+            if let Some(index) = list.records.iter().position(|record| record.snils.trim().is_empty()) {
+                return Err(ParseError::new(input, "Record without SNILS")
+                    .in_program(&list.program.name)
+                    .at_row(index + 1));
+            }
+        }
+        Ok(())
+    }
+
+    /// Records in the `(program name, records)` shape used by the analyzer
+    ///
+    /// Scores are parsed here. Program, funding, study form and places missing in a record
+    /// are taken from the program header, so third-party lists only need them once per program.
+    pub fn into_program_records(self) -> Vec<(String, Vec<StudentRecord>)> {
+        self.programs
+            .into_iter()
+            .map(|list| {
+                let program = list.program;
+                let records = list
+                    .records
+                    .into_iter()
+                    .map(|mut record| {
+                        record.score = parse_score(&record.average_score);
+                        if record.program_name.is_empty() {
+                            record.program_name = program.name.clone();
+                        }
+                        if record.institution.is_empty() {
+                            record.institution = program.institution.clone();
+                        }
+                        if record.funding_source.is_empty() {
+                            record.funding_source = program.funding_source.clone();
+                        }
+                        if record.study_form.is_empty() {
+                            record.study_form = program.study_form.clone();
+                        }
+                        if record.available_places == 0 {
+                            record.available_places = program.available_places;
+                        }
+                        record
+                    })
+                    .collect();
+                (program.name, records)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,450 @@
+use crate::models::{normalize_snils, Config, FailurePolicy, FundingScope, StudentRecord};
+use crate::run_summary::RunSummary;
+use crate::error::SimulationError;
+use chrono::{Local, NaiveDateTime};
+use std::collections::HashSet;
+#[cfg(any(feature = "network", feature = "ffi", feature = "python"))]
+use std::collections::HashMap;
+#[cfg(feature = "network")]
+use crate::{checksums, discovery, events::Event, layout, progress};
+#[cfg(feature = "network")]
+use crate::models::{DataSourceMode, IndexPageOptions, InstitutionOptions, ProgramInfo};
+#[cfg(feature = "network")]
+use crate::scraper::{AdmissionScraper, DEFAULT_MAX_RESPONSE_MB, DEFAULT_STREAMING_THRESHOLD_MB};
+#[cfg(feature = "network")]
+use crate::error::{Result, ScrapeError};
+#[cfg(feature = "network")]
+use chrono::DateTime;
+#[cfg(feature = "network")]
+use std::{fs, path::{Path, PathBuf}};
+#[cfg(feature = "network")]
+use tracing::{debug, error, info, warn};
+
+/// Check the sources counted in `summary` against the configured `failure_policy`
+///
+/// Unless `require_sources` asks for more, a run needs one loaded source if any was tried.
+pub fn check_failure_policy(config: &Config, summary: &RunSummary) -> Result<(), SimulationError> {
+    let attempted = summary.sources_processed + summary.sources_failed;
+    let required = match config.failure_policy.unwrap_or_default() {
+        FailurePolicy::RequireSources => config.required_sources.unwrap_or(1),
+        FailurePolicy::Continue | FailurePolicy::FailFast => attempted.min(1),
+    };
+    if summary.sources_processed < required {
+        return Err(SimulationError::TooFewSources {
+            loaded: summary.sources_processed,
+            failed: summary.sources_failed,
+            required,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(feature = "network")]
+/// Parse every configured source into `(program name, records)` lists, deduplicated by SNILS
+///
+/// Sources that fail are logged and counted in `summary`; with failure_policy = "fail_fast"
+/// the first one aborts with a [`SimulationError`] instead. URLs in `summary.http_cache` are
+/// fetched with conditional requests, and fetched pages replace them there. The lists linked
+/// from `index_pages` are fetched after `internet_urls`. With `summary.scrape_progress` set,
+/// the URLs it holds are taken from there instead and every page fetched is added to it.
+pub async fn load_program_records(
+    config: &Config,
+    data_source_mode: &DataSourceMode,
+    summary: &mut RunSummary,
+) -> Result<Vec<(String, Vec<StudentRecord>)>> {
+    let scraper = AdmissionScraper::new()
+        .with_streaming_threshold_mb(config.streaming_threshold_mb.unwrap_or(DEFAULT_STREAMING_THRESHOLD_MB))
+        .with_max_response_mb(config.max_response_mb.unwrap_or(DEFAULT_MAX_RESPONSE_MB));
+    let fail_fast = config.failure_policy == Some(FailurePolicy::FailFast);
+    let conditional_requests = config.conditional_requests.unwrap_or(true);
+    let institutions = config.institutions.as_deref().unwrap_or_default();
+    // An institution's own row_merge wins over that of the configuration
+    let row_merge = |institution: Option<&InstitutionOptions>| {
+        institution.and_then(|institution| institution.row_merge).or(config.row_merge).unwrap_or_default()
+    };
+    let mut all_program_records = Vec::new();
+    
+    // Process local files if configured
+    if matches!(data_source_mode, DataSourceMode::Local | DataSourceMode::Both) {
+        if let Some(data_dir) = &config.data_directory {
+            info!("{}", t!("run.processing_local", data_dir));
+            
+            if Path::new(data_dir).exists() {
+                // Pages right in the directory, then those of each institution's subdirectory
+                let mut html_files = html_files_in(Path::new(data_dir))?
+                    .into_iter()
+                    .map(|path| (path, None, row_merge(None)))
+                    .collect::<Vec<_>>();
+                let read_error = |cause| ScrapeError::Read { path: data_dir.clone(), cause };
+                let mut institution_dirs = Vec::new();
+                for entry in fs::read_dir(data_dir).map_err(read_error)? {
+                    let path = entry.map_err(read_error)?.path();
+                    if path.is_dir() {
+                        institution_dirs.push(path);
+                    }
+                }
+                institution_dirs.sort();
+                for dir in institution_dirs {
+                    let dir_name = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+                    let configured = institutions.iter().find(|institution| institution.directory() == dir_name);
+                    let institution = configured.map_or(dir_name, |institution| institution.name.clone());
+                    let dir_row_merge = row_merge(configured);
+                    html_files.extend(
+                        html_files_in(&dir)?.into_iter().map(|path| (path, Some(institution.clone()), dir_row_merge)),
+                    );
+                }
+
+                let files_bar = progress::bar(html_files.len(), &t!("progress.files"));
+                for (path, institution, row_merge) in html_files {
+                    files_bar.set_message(path.file_name().unwrap().to_string_lossy().to_string());
+                    info!("{}", t!("run.processing_file", format!("{:?}", path.file_name().unwrap())));
+                    
+                    match scraper.clone().with_row_merge(row_merge).scrape_file(path.to_str().unwrap()) {
+                        Ok(programs) => {
+                            let modified = fs::metadata(&path)
+                                .and_then(|metadata| metadata.modified())
+                                .map(DateTime::<Local>::from)
+                                .unwrap_or_else(|_| Local::now());
+                            summary.add_source(modified);
+                            summary.events.push(Event::SourceFetched {
+                                source: path.display().to_string(),
+                                data_time: modified,
+                                programs: programs.len(),
+                            });
+                            summary.checksums.insert(path.display().to_string(), checksums::of_programs(&programs));
+                            check_layout(summary, &path.display().to_string(), &programs);
+                            for (mut program_info, mut records) in programs {
+                                if let Some(institution) = &institution {
+                                    program_info.set_institution(&mut records, institution);
+                                }
+                                let original_count = records.len();
+                                info!("{}", t!("run.found_applicants", original_count, program_info.name));
+                                
+                                // Deduplicate records by SNILS within this program
+                                let deduplicated_records = deduplicate_records_by_snils(records);
+                                let duplicates_removed = original_count - deduplicated_records.len();
+                                if duplicates_removed > 0 {
+                                    debug!("{}", t!("run.duplicates_removed", duplicates_removed));
+                                }
+                                summary.add_program(&deduplicated_records, duplicates_removed);
+                                summary.events.push(Event::ProgramParsed {
+                                    source: path.display().to_string(),
+                                    program_name: program_info.name.clone(),
+                                    institution: institution.clone().unwrap_or_default(),
+                                    applications: deduplicated_records.len(),
+                                    duplicates_removed,
+                                });
+                                all_program_records.push((program_info.name, deduplicated_records));
+                            }
+                        }
+                        Err(e) => {
+                            summary.sources_failed += 1;
+                            error!("{}", t!("run.file_error", e));
+                            if fail_fast {
+                                files_bar.finish_and_clear();
+                                return Err(SimulationError::Aborted {
+                                    source_name: path.display().to_string(),
+                                    cause: Box::new(e),
+                                }
+                                .into());
+                            }
+                        }
+                    }
+                    files_bar.inc(1);
+                }
+                files_bar.finish_and_clear();
+            } else {
+                warn!("{}", t!("run.missing_data_dir", data_dir));
+            }
+        }
+    }
+    
+    // Process internet URLs if configured
+    if matches!(data_source_mode, DataSourceMode::Internet | DataSourceMode::Both) {
+        let mut urls = config.internet_urls.clone().unwrap_or_default();
+        for index_page in config.index_pages.as_deref().unwrap_or_default() {
+            for link in discover_lists(&scraper, index_page, fail_fast, summary).await? {
+                if !urls.contains(&link) {
+                    urls.push(link);
+                }
+            }
+        }
+        // Pages no longer fetched, e.g. lists gone from an index page, are not kept
+        summary.http_cache.retain(|url, _| urls.contains(url));
+        if !urls.is_empty() {
+            info!("{}", t!("run.processing_internet", urls.len()));
+            
+            let urls_bar = progress::bar(urls.len(), &t!("progress.urls"));
+            for url in &urls {
+                urls_bar.set_message(url.clone());
+                let institution = institutions.iter().find(|institution| institution.matches_url(url));
+                let resumed = summary.scrape_progress.as_ref().and_then(|progress| progress.get(url)).cloned();
+                let fetched = match resumed {
+                    Some(resumed) => {
+                        info!("{}", t!("resume.reused", url));
+                        Ok((resumed.page, resumed.fetched_at))
+                    }
+                    None => {
+                        let cached = summary.http_cache.get(url).filter(|cached| cached.has_validators());
+                        let fetched = scraper.clone().with_row_merge(row_merge(institution)).scrape_url_cached(url, cached).await;
+                        if let (Ok(page), Some(progress)) = (&fetched, summary.scrape_progress.as_mut()) {
+                            if let Err(e) = progress.record(url, page) {
+                                warn!("{}", t!("resume.save_failed", e));
+                            }
+                        }
+                        fetched.map(|page| (page, Local::now()))
+                    }
+                };
+                match fetched {
+                    Ok((page, fetched_at)) => {
+                        if conditional_requests && page.has_validators() {
+                            summary.http_cache.insert(url.clone(), page.clone());
+                        } else {
+                            summary.http_cache.remove(url);
+                        }
+                        let programs = page.programs;
+                        summary.add_source(fetched_at);
+                        summary.events.push(Event::SourceFetched {
+                            source: url.clone(),
+                            data_time: fetched_at,
+                            programs: programs.len(),
+                        });
+                        summary.checksums.insert(url.clone(), checksums::of_programs(&programs));
+                        check_layout(summary, url, &programs);
+                        for (mut program_info, mut records) in programs {
+                            if let Some(institution) = institution {
+                                program_info.set_institution(&mut records, &institution.name);
+                            }
+                            let original_count = records.len();
+                            info!("{}", t!("run.found_applicants", original_count, program_info.name));
+                            
+                            // Deduplicate records by SNILS within this program
+                            let deduplicated_records = deduplicate_records_by_snils(records);
+                            let duplicates_removed = original_count - deduplicated_records.len();
+                            if duplicates_removed > 0 {
+                                debug!("{}", t!("run.duplicates_removed", duplicates_removed));
+                            }
+                            summary.add_program(&deduplicated_records, duplicates_removed);
+                            summary.events.push(Event::ProgramParsed {
+                                source: url.clone(),
+                                program_name: program_info.name.clone(),
+                                institution: institution.map(|institution| institution.name.clone()).unwrap_or_default(),
+                                applications: deduplicated_records.len(),
+                                duplicates_removed,
+                            });
+
+                            all_program_records.push((program_info.name, deduplicated_records));
+                        }
+                    }
+                    Err(e) => {
+                        summary.sources_failed += 1;
+                        error!("{}", t!("run.url_error", url, e));
+                        if fail_fast {
+                            urls_bar.finish_and_clear();
+                            return Err(SimulationError::Aborted { source_name: url.clone(), cause: Box::new(e) }.into());
+                        }
+                    }
+                }
+                urls_bar.inc(1);
+            }
+            urls_bar.finish_and_clear();
+        } else {
+            warn!("{}", t!("run.no_urls"));
+        }
+    }
+
+    Ok(all_program_records)
+}
+
+#[cfg(feature = "network")]
+/// Warn when the columns of a source's tables differ from those in `summary.layouts`, then
+/// keep the new ones there
+fn check_layout(summary: &mut RunSummary, source: &str, programs: &[(ProgramInfo, Vec<StudentRecord>)]) {
+    let current = layout::of_programs(programs);
+    if let Some(baseline) = summary.layouts.get(source) {
+        let changes = layout::diff(baseline, &current);
+        if !changes.is_empty() {
+            warn!("{}", t!("layout.changed", source, changes.join("; ")));
+            summary.layout_changes.insert(source.to_string());
+        }
+    }
+    summary.layouts.insert(source.to_string(), current);
+}
+
+#[cfg(feature = "network")]
+/// List links on an index page, reporting those that appeared or disappeared since they were
+/// last recorded in `summary.discovered`
+///
+/// A page that cannot be read counts as a failed source and the links recorded last time are
+/// used instead.
+async fn discover_lists(
+    scraper: &AdmissionScraper,
+    index_page: &IndexPageOptions,
+    fail_fast: bool,
+    summary: &mut RunSummary,
+) -> Result<Vec<String>> {
+    let previous = summary.discovered.get(&index_page.url).cloned();
+    match scraper.discover_links(index_page).await {
+        Ok(links) => {
+            info!("{}", t!("discovery.found", links.len(), index_page.url));
+            if let Some(previous) = &previous {
+                let (appeared, disappeared) = discovery::changes(previous, &links);
+                for url in appeared {
+                    warn!("{}", t!("discovery.appeared", url));
+                }
+                for url in disappeared {
+                    warn!("{}", t!("discovery.disappeared", url));
+                }
+            }
+            summary.discovered.insert(index_page.url.clone(), links.clone());
+            Ok(links)
+        }
+        Err(e) => {
+            summary.sources_failed += 1;
+            let previous = previous.unwrap_or_default();
+            error!("{}", t!("discovery.failed", index_page.url, e, previous.len()));
+            if fail_fast {
+                return Err(SimulationError::Aborted { source_name: index_page.url.clone(), cause: Box::new(e) }.into());
+            }
+            Ok(previous)
+        }
+    }
+}
+
+#[cfg(feature = "network")]
+/// HTML pages directly in `dir`, sorted by name
+fn html_files_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    let read_error = |cause| ScrapeError::Read { path: dir.display().to_string(), cause };
+    let mut html_files = Vec::new();
+    for entry in fs::read_dir(dir).map_err(read_error)? {
+        let path = entry.map_err(read_error)?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("html") {
+            html_files.push(path);
+        }
+    }
+    html_files.sort();
+    Ok(html_files)
+}
+
+/// Deduplicate records by SNILS within each program, keeping the best record for each SNILS
+/// Priority: Not withdrawn > Original document (Да) > Consent (Да) > Priority number (lower is better)
+/// Drop withdrawn applications from every list before the simulation, returns how many were dropped
+pub fn remove_withdrawn(all_program_records: &mut [(String, Vec<StudentRecord>)]) -> usize {
+    let mut removed = 0;
+    for (_, records) in all_program_records.iter_mut() {
+        let before = records.len();
+        records.retain(|record| !record.withdrawn);
+        removed += before - records.len();
+    }
+    removed
+}
+
+/// Drop applications submitted after `until`, returns how many were dropped; those without a
+/// submission time are kept
+pub fn remove_submitted_after(all_program_records: &mut [(String, Vec<StudentRecord>)], until: NaiveDateTime) -> usize {
+    let mut removed = 0;
+    for (_, records) in all_program_records.iter_mut() {
+        let before = records.len();
+        records.retain(|record| record.submitted_at.is_none_or(|submitted_at| submitted_at <= until));
+        removed += before - records.len();
+    }
+    removed
+}
+
+/// Drop applications to lists outside `scope`, returns how many were dropped
+pub fn remove_other_funding(all_program_records: &mut Vec<(String, Vec<StudentRecord>)>, scope: FundingScope) -> usize {
+    let mut removed = 0;
+    for (_, records) in all_program_records.iter_mut() {
+        let before = records.len();
+        records.retain(|record| scope.includes(&record.funding_source));
+        removed += before - records.len();
+    }
+    all_program_records.retain(|(_, records)| !records.is_empty());
+    removed
+}
+
+/// Stop counting consents given less than `days` days before their list was read, likely to
+/// be withdrawn yet; returns how many were discounted. Those without a date still count
+pub fn discount_recent_consents(all_program_records: &mut [(String, Vec<StudentRecord>)], days: u32) -> usize {
+    let now = Local::now();
+    let mut discounted = 0;
+    for record in all_program_records.iter_mut().flat_map(|(_, records)| records.iter_mut()) {
+        let Some(consent_at) = record.consent_at else {
+            continue;
+        };
+        let read_at = record.provenance.as_ref().map_or(now, |provenance| provenance.scraped_at).naive_local();
+        if record.has_consent() && read_at - consent_at < chrono::Duration::days(i64::from(days)) {
+            record.recent_consent = true;
+            discounted += 1;
+        }
+    }
+    discounted
+}
+
+/// Drop every application of the applicants in `excluded`, given as normalized identifiers,
+/// returns how many were dropped
+pub fn remove_excluded(all_program_records: &mut [(String, Vec<StudentRecord>)], excluded: &HashSet<String>) -> usize {
+    let mut removed = 0;
+    for (_, records) in all_program_records.iter_mut() {
+        let before = records.len();
+        records.retain(|record| !excluded.contains(&normalize_snils(&record.snils)));
+        removed += before - records.len();
+    }
+    removed
+}
+
+#[cfg(any(feature = "network", feature = "ffi", feature = "python"))]
+pub(crate) fn deduplicate_records_by_snils(records: Vec<StudentRecord>) -> Vec<StudentRecord> {
+    let mut best_records: HashMap<String, StudentRecord> = HashMap::new();
+    
+    for record in records {
+        let normalized_snils = normalize_snils(&record.snils);
+        
+        match best_records.get(&normalized_snils) {
+            None => {
+                // First occurrence of this SNILS
+                best_records.insert(normalized_snils, record);
+            }
+            Some(existing) => {
+                // Compare and keep the better record
+                let record_is_better = is_record_better(&record, existing);
+                if record_is_better {
+                    best_records.insert(normalized_snils, record);
+                }
+            }
+        }
+    }
+    
+    let mut result: Vec<StudentRecord> = best_records.into_values().collect();
+    // Sort by rank to maintain original order, SNILS between equal ranks
+    result.sort_by(|a, b| a.rank.cmp(&b.rank).then_with(|| a.snils.cmp(&b.snils)));
+    result
+}
+
+/// Determine if record1 is better than record2 for the same SNILS
+/// Priority: Not withdrawn > Original document (Да) > Consent (Да) > Priority number (lower is better)
+#[cfg(any(feature = "network", feature = "ffi", feature = "python"))]
+fn is_record_better(record1: &StudentRecord, record2: &StudentRecord) -> bool {
+    if record1.withdrawn != record2.withdrawn {
+        return !record1.withdrawn;
+    }
+
+    // First priority: Original document
+    let r1_has_doc = record1.has_original_document();
+    let r2_has_doc = record2.has_original_document();
+    
+    if r1_has_doc != r2_has_doc {
+        return r1_has_doc; // Prefer the one with original document
+    }
+    
+    // Second priority: Consent
+    let r1_has_consent = record1.has_consent();
+    let r2_has_consent = record2.has_consent();
+    
+    if r1_has_consent != r2_has_consent {
+        return r1_has_consent; // Prefer the one with consent
+    }
+    
+    // Third priority: Lower priority number (1 is better than 2)
+    record1.priority < record2.priority
+}
@@ -0,0 +1,361 @@
+use crate::analyzer::{AdmissionAnalysis, TargetOutcome};
+use crate::models::{normalize_snils, FundingSource, ProgramKey, StudentRecord, StudyForm};
+use crate::report_model::ReportModel;
+use crate::snapshot::Snapshot;
+use crate::error::{Result, StorageError};
+use chrono::{DateTime, Local, NaiveDateTime};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// File name of the history database inside the output root
+pub const DATABASE_FILE: &str = "analysis.db";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    target_snils TEXT NOT NULL,
+    snapshot TEXT
+);
+CREATE TABLE IF NOT EXISTS programs (
+    id INTEGER PRIMARY KEY,
+    program_key TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    funding_source TEXT NOT NULL,
+    study_form TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS applicants (
+    id INTEGER PRIMARY KEY,
+    normalized_snils TEXT NOT NULL UNIQUE
+);
+CREATE TABLE IF NOT EXISTS program_stats (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    program_id INTEGER NOT NULL REFERENCES programs(id),
+    available_places INTEGER NOT NULL,
+    top_candidates_average_priority REAL NOT NULL,
+    average_score REAL NOT NULL,
+    total_eager_applicants INTEGER NOT NULL,
+    cutoff_score REAL NOT NULL,
+    PRIMARY KEY (run_id, program_id)
+);
+CREATE TABLE IF NOT EXISTS applications (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    program_id INTEGER NOT NULL REFERENCES programs(id),
+    applicant_id INTEGER NOT NULL REFERENCES applicants(id),
+    rank INTEGER NOT NULL,
+    snils TEXT NOT NULL,
+    priority INTEGER NOT NULL,
+    consent TEXT NOT NULL,
+    document_type TEXT NOT NULL,
+    average_score TEXT NOT NULL,
+    score REAL,
+    subject_scores TEXT NOT NULL,
+    psychological_test TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS admissions (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    program_id INTEGER NOT NULL REFERENCES programs(id),
+    applicant_id INTEGER NOT NULL REFERENCES applicants(id),
+    position INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS target_outcomes (
+    run_id INTEGER NOT NULL REFERENCES runs(id),
+    program_id INTEGER NOT NULL REFERENCES programs(id),
+    priority INTEGER,
+    target_score REAL,
+    cutoff_score REAL NOT NULL,
+    position_in_admitted INTEGER,
+    status TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS applications_run_program ON applications(run_id, program_id);
+CREATE INDEX IF NOT EXISTS admissions_run_program ON admissions(run_id, program_id);
+";
+
+/// Run stored in the history database
+#[derive(Debug, Clone, Serialize)]
+pub struct StoredRun {
+    pub id: i64,
+    pub created_at: DateTime<Local>,
+    pub target_snils: String,
+    pub programs: usize,
+    // Runs stored by older versions have results but no parsed lists
+    pub has_snapshot: bool,
+}
+
+/// Cutoff and competition of one program in one stored run
+#[derive(Debug, Clone, Serialize)]
+pub struct CutoffPoint {
+    pub run_id: i64,
+    pub created_at: DateTime<Local>,
+    pub available_places: u32,
+    pub total_eager_applicants: usize,
+    pub cutoff_score: f64,
+}
+
+/// Cutoff history of one program over all stored runs
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgramHistory {
+    pub program_key: ProgramKey,
+    pub program_name: String,
+    pub funding_source: FundingSource,
+    pub points: Vec<CutoffPoint>,
+}
+
+/// Campaign history: parsed lists and simulation results of every run, keyed by run time
+///
+/// Backed by SQLite, so the tables can also be queried with any SQLite tool.
+pub struct Storage {
+    connection: Connection,
+}
+
+impl Storage {
+    /// Open or create the database, upgrading tables written by older versions
+    pub fn open(path: &Path) -> Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        let has_snapshot_column = connection
+            .prepare("SELECT 1 FROM pragma_table_info('runs') WHERE name = 'snapshot'")?
+            .exists([])?;
+        if !has_snapshot_column {
+            connection.execute("ALTER TABLE runs ADD COLUMN snapshot TEXT", [])?;
+        }
+        // Keys written before the study form was part of them
+        connection.execute(
+            "UPDATE programs SET program_key = name || '_' || funding_source || '_' || study_form
+             WHERE study_form <> '' AND program_key = name || '_' || funding_source",
+            [],
+        )?;
+        Ok(Self { connection })
+    }
+
+    /// Append parsed records and simulation results of a run, returns the run id
+    pub fn save_run(
+        &mut self,
+        target_snils: &str,
+        analysis: &AdmissionAnalysis,
+        report: &ReportModel,
+        target_outcomes: &[TargetOutcome],
+        all_program_records: &[(String, Vec<StudentRecord>)],
+    ) -> Result<i64> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO runs (target_snils, snapshot) VALUES (?1, ?2)",
+            params![target_snils, serde_json::to_string(&Snapshot::new(all_program_records)).map_err(StorageError::from)?],
+        )?;
+        let run_id = transaction.last_insert_rowid();
+
+        let mut program_ids: HashMap<ProgramKey, i64> = HashMap::new();
+        let mut applicant_ids: HashMap<String, i64> = HashMap::new();
+
+        for (program_name, records) in all_program_records {
+            for record in records {
+                let program_key = ProgramKey::of(program_name, record);
+                let program_id = match program_ids.get(&program_key) {
+                    Some(id) => *id,
+                    None => {
+                        transaction.execute(
+                            "INSERT OR IGNORE INTO programs (program_key, name, funding_source, study_form) VALUES (?1, ?2, ?3, ?4)",
+                            params![program_key.to_string(), program_name, record.funding_source.as_str(), record.study_form.as_str()],
+                        )?;
+                        let id: i64 = transaction.query_row(
+                            "SELECT id FROM programs WHERE program_key = ?1",
+                            params![program_key.to_string()],
+                            |row| row.get(0),
+                        )?;
+                        program_ids.insert(program_key, id);
+                        id
+                    }
+                };
+                let applicant_id = applicant_id(&transaction, &mut applicant_ids, &record.snils)?;
+
+                transaction.execute(
+                    "INSERT INTO applications (run_id, program_id, applicant_id, rank, snils, priority, consent, document_type, average_score, score, subject_scores, psychological_test)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                    params![
+                        run_id,
+                        program_id,
+                        applicant_id,
+                        record.rank,
+                        record.snils,
+                        record.priority,
+                        record.consent,
+                        record.document_type,
+                        record.average_score,
+                        record.score,
+                        record.subject_scores,
+                        record.psychological_test,
+                    ],
+                )?;
+            }
+        }
+
+        for popularity in &analysis.program_popularities {
+            let Some(&program_id) = program_ids.get(&popularity.program_key) else {
+                continue;
+            };
+            transaction.execute(
+                "INSERT INTO program_stats (run_id, program_id, available_places, top_candidates_average_priority, average_score, total_eager_applicants, cutoff_score)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    run_id,
+                    program_id,
+                    popularity.available_places,
+                    popularity.top_candidates_average_priority,
+                    popularity.average_score,
+                    popularity.total_eager_applicants as i64,
+                    report.program(&popularity.program_key).map_or(0.0, |program| program.cutoff_score),
+                ],
+            )?;
+
+            if let Some(admitted) = analysis.final_admission_results.get(&popularity.program_key) {
+                for (position, snils) in admitted.iter().enumerate() {
+                    let applicant_id = applicant_id(&transaction, &mut applicant_ids, snils)?;
+                    transaction.execute(
+                        "INSERT INTO admissions (run_id, program_id, applicant_id, position) VALUES (?1, ?2, ?3, ?4)",
+                        params![run_id, program_id, applicant_id, position as i64 + 1],
+                    )?;
+                }
+            }
+        }
+
+        for outcome in target_outcomes {
+            let Some(&program_id) = program_ids.get(&outcome.program_key) else {
+                continue;
+            };
+            transaction.execute(
+                "INSERT INTO target_outcomes (run_id, program_id, priority, target_score, cutoff_score, position_in_admitted, status)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    run_id,
+                    program_id,
+                    outcome.priority,
+                    outcome.target_score,
+                    outcome.cutoff_score,
+                    outcome.position_in_admitted.map(|p| p as i64),
+                    outcome.status,
+                ],
+            )?;
+        }
+
+        transaction.commit()?;
+        Ok(run_id)
+    }
+
+    /// All stored runs, oldest first
+    pub fn runs(&self) -> Result<Vec<StoredRun>> {
+        let mut statement = self.connection.prepare(
+            "SELECT r.id, r.created_at, r.target_snils, r.snapshot IS NOT NULL,
+                    (SELECT COUNT(*) FROM program_stats s WHERE s.run_id = r.id)
+             FROM runs r ORDER BY r.id",
+        )?;
+        let runs = statement
+            .query_map([], |row| {
+                Ok(StoredRun {
+                    id: row.get(0)?,
+                    created_at: parse_timestamp(&row.get::<_, String>(1)?),
+                    target_snils: row.get(2)?,
+                    has_snapshot: row.get(3)?,
+                    programs: row.get::<_, i64>(4)? as usize,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(runs)
+    }
+
+    /// Cutoff of one program in every stored run, oldest first
+    pub fn cutoff_history(&self, program_key: &ProgramKey) -> Result<Vec<CutoffPoint>> {
+        let mut statement = self.connection.prepare(
+            "SELECT s.run_id, r.created_at, s.available_places, s.total_eager_applicants, s.cutoff_score
+             FROM program_stats s
+             JOIN runs r ON r.id = s.run_id
+             JOIN programs p ON p.id = s.program_id
+             WHERE p.program_key = ?1
+             ORDER BY s.run_id",
+        )?;
+        let points = statement
+            .query_map(params![program_key.to_string()], |row| {
+                Ok(CutoffPoint {
+                    run_id: row.get(0)?,
+                    created_at: parse_timestamp(&row.get::<_, String>(1)?),
+                    available_places: row.get(2)?,
+                    total_eager_applicants: row.get::<_, i64>(3)? as usize,
+                    cutoff_score: row.get(4)?,
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(points)
+    }
+
+    /// Cutoff history of every stored program, ordered by program name and funding
+    pub fn program_histories(&self) -> Result<Vec<ProgramHistory>> {
+        let programs: Vec<ProgramKey> = self
+            .connection
+            .prepare("SELECT name, funding_source, study_form FROM programs ORDER BY name, funding_source, study_form")?
+            .query_map([], |row| {
+                Ok(ProgramKey {
+                    name: row.get(0)?,
+                    funding: FundingSource::parse(&row.get::<_, String>(1)?),
+                    study_form: StudyForm::parse(&row.get::<_, String>(2)?),
+                })
+            })?
+            .collect::<rusqlite::Result<_>>()?;
+
+        programs
+            .into_iter()
+            .map(|program_key| {
+                Ok(ProgramHistory {
+                    points: self.cutoff_history(&program_key)?,
+                    program_name: program_key.name.clone(),
+                    funding_source: program_key.funding.clone(),
+                    program_key,
+                })
+            })
+            .collect()
+    }
+
+    /// Parsed lists of a stored run, for analyzing or comparing it again
+    pub fn load_snapshot(&self, run_id: i64) -> Result<Snapshot> {
+        let snapshot: Option<Option<String>> = self
+            .connection
+            .query_row("SELECT snapshot FROM runs WHERE id = ?1", params![run_id], |row| row.get(0))
+            .optional()?;
+        match snapshot {
+            None => Err(StorageError::NoRun(run_id).into()),
+            Some(None) => Err(StorageError::NoSnapshot(run_id).into()),
+            Some(Some(content)) => Ok(Snapshot::parse(&content)?),
+        }
+    }
+}
+
+/// SQLite `datetime('now')` is UTC without a zone
+fn parse_timestamp(text: &str) -> DateTime<Local> {
+    NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+        .map(|time| time.and_utc().with_timezone(&Local))
+        .unwrap_or_default()
+}
+
+/// Look up or create the applicant row for a SNILS
+fn applicant_id(
+    transaction: &rusqlite::Transaction,
+    cache: &mut HashMap<String, i64>,
+    snils: &str,
+) -> Result<i64> {
+    let normalized_snils = normalize_snils(snils);
+    if let Some(id) = cache.get(&normalized_snils) {
+        return Ok(*id);
+    }
+
+    transaction.execute(
+        "INSERT OR IGNORE INTO applicants (normalized_snils) VALUES (?1)",
+        params![normalized_snils],
+    )?;
+    let id: i64 = transaction.query_row(
+        "SELECT id FROM applicants WHERE normalized_snils = ?1",
+        params![normalized_snils],
+        |row| row.get(0),
+    )?;
+    cache.insert(normalized_snils, id);
+    Ok(id)
+}
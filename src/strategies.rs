@@ -0,0 +1,153 @@
+use crate::models::{normalize_snils, ProgramKey};
+use crate::report_model::ReportModel;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Eager applicants who applied to the same programs in the same order of priorities
+#[derive(Debug, Clone, Serialize)]
+pub struct Strategy {
+    // Most wanted first
+    pub programs: Vec<ProgramKey>,
+    pub applicants: usize,
+    // Of those applicants, seated by the simulation in each program; the rest get no seat
+    pub seated: BTreeMap<ProgramKey, usize>,
+    // The target applied this way
+    pub target: bool,
+}
+
+impl Strategy {
+    /// Programs in priority order, as written in reports
+    pub fn describe(&self) -> String {
+        self.programs.iter().map(ToString::to_string).collect::<Vec<_>>().join(" > ")
+    }
+
+    pub fn unseated(&self) -> usize {
+        self.applicants - self.seated.values().sum::<usize>()
+    }
+}
+
+/// Application strategies of the applicants taking part in the simulation, most common first
+///
+/// An applicant's strategy is every program applied to, ordered by priority, eager or not
+/// there: a consent in one program is enough to compete for the others. The target's
+/// strategy is marked even if the target is not eager yet.
+pub fn cluster(report: &ReportModel) -> Vec<Strategy> {
+    // Applicant -> (priority, program), seat and whether eager anywhere
+    struct Applications<'a> {
+        programs: Vec<(u32, &'a ProgramKey)>,
+        seat: Option<&'a ProgramKey>,
+        eager: bool,
+        target: bool,
+    }
+    let mut applicants: HashMap<String, Applications> = HashMap::new();
+    for program in &report.programs {
+        for row in &program.rows {
+            let applications = applicants.entry(normalize_snils(&row.record.snils)).or_insert(Applications {
+                programs: Vec::new(),
+                seat: None,
+                eager: false,
+                target: false,
+            });
+            applications.programs.push((row.record.priority, &program.program_key));
+            applications.eager |= row.eager;
+            applications.target |= row.target;
+            if row.admitted {
+                applications.seat = Some(&program.program_key);
+            }
+        }
+    }
+
+    let mut strategies: BTreeMap<Vec<ProgramKey>, Strategy> = BTreeMap::new();
+    for mut applications in applicants.into_values().filter(|applications| applications.eager || applications.target) {
+        applications.programs.sort();
+        let programs: Vec<ProgramKey> = applications.programs.into_iter().map(|(_, program_key)| program_key.clone()).collect();
+        let strategy = strategies.entry(programs.clone()).or_insert_with(|| Strategy {
+            programs,
+            applicants: 0,
+            seated: BTreeMap::new(),
+            target: false,
+        });
+        strategy.target |= applications.target;
+        // The target only marks its strategy unless it takes part in the simulation
+        if applications.eager {
+            strategy.applicants += 1;
+            if let Some(seat) = applications.seat {
+                *strategy.seated.entry(seat.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    let mut strategies: Vec<Strategy> = strategies.into_values().collect();
+    // Stable, so equally common strategies stay in program order
+    strategies.sort_by_key(|strategy| std::cmp::Reverse(strategy.applicants));
+    strategies
+}
+
+/// Eager applicants moving from their first priority to the seat the simulation gives them
+#[derive(Debug, Clone, Serialize)]
+pub struct Flow {
+    pub source: ProgramKey,
+    // None without a seat
+    pub destination: Option<ProgramKey>,
+    pub applicants: usize,
+}
+
+/// Flows of the applicants of `strategies` from their first priority, largest first
+pub fn flows(strategies: &[Strategy]) -> Vec<Flow> {
+    let mut counts: BTreeMap<(&ProgramKey, Option<&ProgramKey>), usize> = BTreeMap::new();
+    for strategy in strategies {
+        let Some(source) = strategy.programs.first() else {
+            continue;
+        };
+        for (destination, applicants) in &strategy.seated {
+            *counts.entry((source, Some(destination))).or_default() += applicants;
+        }
+        if strategy.unseated() > 0 {
+            *counts.entry((source, None)).or_default() += strategy.unseated();
+        }
+    }
+    let mut flows: Vec<Flow> = counts
+        .into_iter()
+        .map(|((source, destination), applicants)| Flow {
+            source: source.clone(),
+            destination: destination.cloned(),
+            applicants,
+        })
+        .collect();
+    flows.sort_by_key(|flow| std::cmp::Reverse(flow.applicants));
+    flows
+}
+
+/// Flows as nodes and links by node index, the input of d3-sankey and similar libraries
+///
+/// First priorities and seats are separate nodes, so a program applicants stay in does not
+/// make a cycle.
+pub fn sankey_json(flows: &[Flow], no_seat: &str) -> serde_json::Value {
+    let mut sources: Vec<&ProgramKey> = Vec::new();
+    let mut destinations: Vec<Option<&ProgramKey>> = Vec::new();
+    for flow in flows {
+        if !sources.contains(&&flow.source) {
+            sources.push(&flow.source);
+        }
+        if !destinations.contains(&flow.destination.as_ref()) {
+            destinations.push(flow.destination.as_ref());
+        }
+    }
+    let mut nodes: Vec<serde_json::Value> = sources
+        .iter()
+        .map(|source| serde_json::json!({ "name": source.to_string(), "side": "first_priority" }))
+        .collect();
+    nodes.extend(destinations.iter().map(|destination| {
+        let name = destination.map_or_else(|| no_seat.to_string(), ToString::to_string);
+        serde_json::json!({ "name": name, "side": "seat" })
+    }));
+    let links: Vec<serde_json::Value> = flows
+        .iter()
+        .map(|flow| {
+            let source = sources.iter().position(|source| **source == flow.source).unwrap_or_default();
+            let target = destinations.iter().position(|destination| *destination == flow.destination.as_ref()).unwrap_or_default();
+            serde_json::json!({ "source": source, "target": sources.len() + target, "value": flow.applicants })
+        })
+        .collect();
+    serde_json::json!({ "nodes": nodes, "links": links })
+}
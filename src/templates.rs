@@ -0,0 +1,96 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tera::{Tera, Value};
+
+/// Built-in templates, used when no override exists in the templates directory
+const DEFAULT_TEMPLATES: [(&str, &str); 3] = [
+    ("program_popularity.txt.tera", include_str!("../templates/program_popularity.txt.tera")),
+    ("final_cutoff_analysis.txt.tera", include_str!("../templates/final_cutoff_analysis.txt.tera")),
+    ("summary.md.tera", include_str!("../templates/summary.md.tera")),
+];
+
+/// Text report templates with user overrides loaded from a directory
+pub struct ReportTemplates {
+    tera: Tera,
+}
+
+impl ReportTemplates {
+    /// Load built-in templates and replace them with same-named files from `templates_dir` if present
+    pub fn load(templates_dir: &str) -> Result<Self> {
+        let mut tera = Tera::default();
+
+        for (name, default_content) in DEFAULT_TEMPLATES {
+            let override_path = Path::new(templates_dir).join(name);
+            if override_path.exists() {
+                tracing::info!("{}", t!("config.custom_template", override_path.display()));
+                tera.add_template_file(&override_path, Some(name))
+                    .with_context(|| format!("Failed to load template: {}", override_path.display()))?;
+            } else {
+                tera.add_raw_template(name, default_content)?;
+            }
+        }
+
+        tera.register_filter("fixed", fixed_filter);
+        tera.register_filter("status_emoji", status_emoji_filter);
+        tera.register_filter("status_label", status_label_filter);
+        tera.register_function("t", translate_function);
+
+        Ok(Self { tera })
+    }
+
+    pub fn render<T: Serialize>(&self, name: &str, data: &T) -> Result<String> {
+        let context = tera::Context::from_serialize(data)?;
+        self.tera
+            .render(name, &context)
+            .with_context(|| format!("Failed to render template: {}", name))
+    }
+}
+
+/// Format a number with a fixed number of digits: `{{ score | fixed(digits=4) }}`
+fn fixed_filter(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let digits = args.get("digits").and_then(|d| d.as_u64()).unwrap_or(2) as usize;
+    match value.as_f64() {
+        Some(number) => Ok(Value::String(format!("{:.*}", digits, number))),
+        None => Ok(Value::String("N/A".to_string())),
+    }
+}
+
+/// Map an admission status to an emoji: `{{ status | status_emoji }}`
+fn status_emoji_filter(value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let emoji = match value.as_str().unwrap_or_default() {
+        "Admitted" => "✅",
+        "Admitted_ByScore_NotByPriority" => "🟡",
+        _ => "❌",
+    };
+    Ok(Value::String(emoji.to_string()))
+}
+
+/// Map an admission status code to a localized label: `{{ status | status_label }}`
+fn status_label_filter(value: &Value, _args: &HashMap<String, Value>) -> tera::Result<Value> {
+    Ok(Value::String(crate::i18n::status_label(value.as_str().unwrap_or_default())))
+}
+
+/// Localized message lookup: `{{ t(key="report.title", args=[target_snils]) }}`
+fn translate_function(args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let key = args
+        .get("key")
+        .and_then(|key| key.as_str())
+        .ok_or_else(|| tera::Error::msg("t() requires a `key` argument"))?;
+    let values: Vec<String> = args
+        .get("args")
+        .and_then(|values| values.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .map(|value| match value {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let display_values: Vec<&dyn std::fmt::Display> = values.iter().map(|v| v as &dyn std::fmt::Display).collect();
+    Ok(Value::String(crate::i18n::format_message(crate::i18n::translate(key), &display_values)))
+}
@@ -0,0 +1,45 @@
+use crate::analyzer::TargetOutcome;
+use serde::Serialize;
+
+/// Share of a program's places an admitted target has to be within to count as safe
+pub const SAFE_SHARE: f64 = 0.8;
+
+/// How sure the target can be of a seat in a program
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Chance {
+    // Admitted with a fifth of the places or more to spare
+    Safe,
+    // Admitted on the last places, or passing by score on a priority the target prefers less
+    Match,
+    // Not admitted in the simulation
+    Reach,
+}
+
+impl Chance {
+    /// Key of the label in the i18n messages
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            Chance::Safe => "chance.safe",
+            Chance::Match => "chance.match",
+            Chance::Reach => "chance.reach",
+        }
+    }
+}
+
+/// Class of a program the target applied to, None if the target did not apply
+pub fn classify(outcome: &TargetOutcome) -> Option<Chance> {
+    match outcome.status.as_str() {
+        "Admitted" => {
+            let position = outcome.position_in_admitted.unwrap_or(usize::MAX) as f64;
+            if position <= outcome.available_places as f64 * SAFE_SHARE {
+                Some(Chance::Safe)
+            } else {
+                Some(Chance::Match)
+            }
+        }
+        "Admitted_ByScore_NotByPriority" => Some(Chance::Match),
+        "Not_Applied" => None,
+        _ => Some(Chance::Reach),
+    }
+}
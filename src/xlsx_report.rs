@@ -0,0 +1,168 @@
+use crate::analyzer::TargetOutcome;
+use crate::atomic_file;
+use crate::confidence;
+use crate::i18n;
+use crate::models::parse_score;
+use crate::report_model::ReportModel;
+use anyhow::Result;
+use rust_xlsxwriter::{Color, ConditionalFormatFormula, Format, FormatBorder, Workbook, Worksheet};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Excel sheet names are limited to 31 characters
+const MAX_SHEET_NAME_LEN: usize = 31;
+
+/// Generate analysis.xlsx with a summary sheet and one sheet per program-funding combination
+pub fn generate_xlsx_report(report: &ReportModel, target_outcomes: &[TargetOutcome], output_dir: &str) -> Result<()> {
+    let header_format = Format::new().set_bold().set_background_color(Color::RGB(0xDDDDDD)).set_border(FormatBorder::Thin);
+    let admitted_format = Format::new().set_background_color(Color::RGB(0xDFF3DF));
+    let not_admitted_format = Format::new().set_background_color(Color::RGB(0xF8DDDD));
+    let target_format = Format::new().set_bold().set_background_color(Color::RGB(0xFFE9A8));
+    let score_format = Format::new().set_num_format("0.0000");
+
+    let mut workbook = Workbook::new();
+
+    // Summary sheet with target outcome per program
+    let summary = workbook.add_worksheet();
+    let summary_name = t!("report.summary");
+    summary.set_name(&summary_name)?;
+    let headers = [
+        t!("report.program"),
+        t!("report.funding"),
+        t!("report.priority"),
+        t!("report.places"),
+        t!("report.target_score"),
+        t!("report.cutoff_score"),
+        t!("report.position"),
+        t!("report.status"),
+        t!("confidence.header"),
+    ];
+    write_headers(summary, &headers, &header_format)?;
+
+    let applied: Vec<&TargetOutcome> = target_outcomes
+        .iter()
+        .filter(|outcome| outcome.status != "Not_Applied")
+        .collect();
+    for (i, outcome) in applied.iter().enumerate() {
+        let row = i as u32 + 1;
+        summary.write_string(row, 0, &outcome.program_name)?;
+        summary.write_string(row, 1, outcome.funding_source.as_str())?;
+        summary.write_number(row, 2, outcome.priority.unwrap_or(0))?;
+        match outcome.published_places {
+            Some(published_places) => summary.write_string(row, 3, i18n::places_label(outcome.available_places, Some(published_places)))?,
+            None => summary.write_number(row, 3, outcome.available_places)?,
+        };
+        summary.write_number_with_format(row, 4, outcome.target_score.unwrap_or(0.0), &score_format)?;
+        summary.write_number_with_format(row, 5, outcome.cutoff_score, &score_format)?;
+        let position = outcome
+            .position_in_admitted
+            .map(|p| t!("report.of", p, outcome.admitted_count))
+            .unwrap_or_else(|| "-".to_string());
+        summary.write_string(row, 6, &position)?;
+        summary.write_string(row, 7, i18n::status_label(&outcome.status))?;
+        summary.write_string(row, 8, confidence::label(outcome.confidence.as_ref()))?;
+    }
+    if !applied.is_empty() {
+        let last_row = applied.len() as u32;
+        let admitted_label = i18n::status_label("Admitted");
+        let admitted_rule = ConditionalFormatFormula::new()
+            .set_rule(format!("=$H2=\"{}\"", admitted_label).as_str())
+            .set_format(&admitted_format);
+        let not_admitted_rule = ConditionalFormatFormula::new()
+            .set_rule(format!("=$H2<>\"{}\"", admitted_label).as_str())
+            .set_format(&not_admitted_format);
+        summary.add_conditional_format(1, 0, last_row, 8, &admitted_rule)?;
+        summary.add_conditional_format(1, 0, last_row, 8, &not_admitted_rule)?;
+    }
+    summary.set_freeze_panes(1, 0)?;
+    summary.autofit();
+
+    // One sheet per program-funding combination in popularity order
+    let mut used_names: HashSet<String> = HashSet::from([summary_name.clone()]);
+    for program in &report.programs {
+        let sheet_name = unique_sheet_name(&program.program_name, program.funding_source.as_str(), &mut used_names);
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(&sheet_name)?;
+
+        let headers = [
+            t!("report.rank"),
+            t!("report.snils"),
+            t!("report.priority"),
+            t!("report.consent"),
+            t!("report.original"),
+            t!("report.score"),
+            t!("report.achievements"),
+            t!("report.admitted"),
+            t!("report.target"),
+        ];
+        write_headers(sheet, &headers, &header_format)?;
+
+        for (i, report_row) in program.rows.iter().enumerate() {
+            let row = i as u32 + 1;
+            let record = &report_row.record;
+            sheet.write_number(row, 0, record.rank)?;
+            sheet.write_string(row, 1, &record.snils)?;
+            sheet.write_number(row, 2, record.priority)?;
+            sheet.write_string(row, 3, &record.consent)?;
+            sheet.write_string(row, 4, &record.document_type)?;
+            match record.base_score() {
+                Some(score) => sheet.write_number_with_format(row, 5, score, &score_format)?,
+                None => sheet.write_string(row, 5, &record.average_score)?,
+            };
+            match parse_score(&record.achievements) {
+                Some(bonus) => sheet.write_number_with_format(row, 6, bonus, &score_format)?,
+                None => sheet.write_string(row, 6, &record.achievements)?,
+            };
+            sheet.write_string(row, 7, if report_row.admitted { "Да" } else { "Нет" })?;
+            sheet.write_string(row, 8, if report_row.target { "Да" } else { "Нет" })?;
+        }
+
+        if !program.rows.is_empty() {
+            let last_row = program.rows.len() as u32;
+            // Target rule is added first so it takes precedence over admitted coloring
+            let target_rule = ConditionalFormatFormula::new()
+                .set_rule("=$I2=\"Да\"")
+                .set_format(&target_format);
+            let admitted_rule = ConditionalFormatFormula::new()
+                .set_rule("=$H2=\"Да\"")
+                .set_format(&admitted_format);
+            sheet.add_conditional_format(1, 0, last_row, 8, &target_rule)?;
+            sheet.add_conditional_format(1, 0, last_row, 8, &admitted_rule)?;
+        }
+        sheet.set_freeze_panes(1, 0)?;
+        sheet.autofit();
+    }
+
+    atomic_file::write(Path::new(output_dir).join("analysis.xlsx"), workbook.save_to_buffer()?)?;
+    Ok(())
+}
+
+fn write_headers(sheet: &mut Worksheet, headers: &[String], format: &Format) -> Result<()> {
+    for (col, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, header, format)?;
+    }
+    Ok(())
+}
+
+/// Build a valid and unique sheet name like "Лечебное дело (Б)"
+fn unique_sheet_name(program_name: &str, funding_source: &str, used_names: &mut HashSet<String>) -> String {
+    let short_program = program_name.trim_start_matches("ОП СПО").trim();
+    let funding_letter: String = funding_source.chars().take(1).collect();
+    let suffix = format!(" ({})", funding_letter);
+
+    let sanitized: String = short_program
+        .chars()
+        .map(|c| if matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\') { '_' } else { c })
+        .collect();
+    let max_base = MAX_SHEET_NAME_LEN - suffix.chars().count() - 3;
+    let base: String = sanitized.chars().take(max_base).collect();
+
+    let mut name = format!("{}{}", base.trim(), suffix);
+    let mut counter = 1;
+    while used_names.contains(&name) {
+        counter += 1;
+        name = format!("{}{}{}", base.trim(), counter, suffix);
+    }
+    used_names.insert(name.clone());
+    name
+}
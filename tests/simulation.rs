@@ -0,0 +1,104 @@
+//! Deferred acceptance in `AdmissionAnalyzer::analyze_all_programs`: programs hold their
+//! best applicants so far and give a seat up to a better one, who may come later
+
+use abitur_analyzer::models::{FundingSource, StudentRecord, StudyForm};
+use abitur_analyzer::scoring::ScoringRules;
+use abitur_analyzer::{AdmissionAnalysis, AdmissionAnalyzer, Config};
+
+const TARGET: &str = "С25-00000";
+
+/// Eager budget application of `snils` to `program_name` with one place
+fn application(program_name: &str, snils: &str, priority: u32, average_score: &str, subject_scores: &str) -> StudentRecord {
+    StudentRecord {
+        rank: 0,
+        snils: snils.to_string(),
+        priority,
+        consent: "Да".to_string(),
+        document_type: "Да".to_string(),
+        average_score: average_score.to_string(),
+        score: average_score.parse().ok(),
+        achievements: String::new(),
+        bonus: 0.0,
+        subject_scores: subject_scores.to_string(),
+        psychological_test: String::new(),
+        program_name: program_name.to_string(),
+        institution: String::new(),
+        funding_source: FundingSource::Budget,
+        study_form: StudyForm::FullTime,
+        available_places: 1,
+        withdrawn: false,
+        privileged: false,
+        computed_rank: None,
+        provenance: None,
+        submitted_at: None,
+        consent_at: None,
+        original_at: None,
+        recent_consent: false,
+        application_id: None,
+    }
+}
+
+/// Lists of the applications, one per program in the order they first appear
+fn lists(applications: Vec<StudentRecord>) -> Vec<(String, Vec<StudentRecord>)> {
+    let mut lists: Vec<(String, Vec<StudentRecord>)> = Vec::new();
+    for record in applications {
+        match lists.iter_mut().find(|(name, _)| *name == record.program_name) {
+            Some((_, records)) => records.push(record),
+            None => lists.push((record.program_name.clone(), vec![record])),
+        }
+    }
+    lists
+}
+
+fn admitted<'a>(analysis: &'a AdmissionAnalysis, program_name: &str) -> &'a [String] {
+    analysis
+        .final_admission_results
+        .iter()
+        .find(|(program_key, _)| program_key.name == program_name)
+        .map(|(_, admitted)| admitted.as_slice())
+        .unwrap_or_default()
+}
+
+#[test]
+fn displaced_applicant_falls_through_to_next_priority() {
+    // С25-00001 is considered first and holds the place of Лечебное дело until С25-00002
+    // takes it, then goes on to Фармация and displaces С25-00003 there
+    let records = lists(vec![
+        application("Лечебное дело", "С25-00001", 1, "4.0", ""),
+        application("Фармация", "С25-00001", 2, "4.0", ""),
+        application("Лечебное дело", "С25-00002", 1, "4.5", ""),
+        application("Фармация", "С25-00003", 1, "3.0", ""),
+    ]);
+
+    let analysis = AdmissionAnalyzer::new(TARGET).analyze_all_programs(&records);
+
+    assert_eq!(admitted(&analysis, "Лечебное дело"), ["С25-00002"]);
+    assert_eq!(admitted(&analysis, "Фармация"), ["С25-00001"]);
+}
+
+#[test]
+fn programs_seat_by_their_own_strategy() {
+    let config = Config::parse(
+        r#"
+        [[scoring]]
+        programs = "Фармация"
+        strategy = "subject_sum"
+        "#,
+    )
+    .unwrap();
+    let scoring = ScoringRules::new(config.scoring.as_deref()).unwrap();
+    // С25-00001 has the best average but the lowest subject sum
+    let mut records = lists(vec![
+        application("Фармация", "С25-00001", 1, "5.0", "3 3 3"),
+        application("Лечебное дело", "С25-00001", 2, "5.0", "3 3 3"),
+        application("Фармация", "С25-00002", 1, "4.0", "5 5 5"),
+        application("Лечебное дело", "С25-00003", 1, "4.5", "5 5 5"),
+    ]);
+    scoring.apply(&mut records);
+
+    let analysis = AdmissionAnalyzer::new(TARGET).analyze_all_programs(&records);
+
+    // Фармация seats by the subject sum, Лечебное дело by the average score
+    assert_eq!(admitted(&analysis, "Фармация"), ["С25-00002"]);
+    assert_eq!(admitted(&analysis, "Лечебное дело"), ["С25-00001"]);
+}